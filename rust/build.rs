@@ -0,0 +1,76 @@
+// With the `ffi` feature, generate the C header for src/ffi.rs;
+// with `c-reference`, compile the C implementations; and turn on what
+// needs a newer compiler than the oldest one the library supports.
+fn main() {
+    // For `ilog asm`, which defaults to disassembling for the host and
+    // adds src/probe.rs with --cfg ilog_probe.
+    println!("cargo:rustc-env=TARGET={}", std::env::var("TARGET").unwrap());
+    println!("cargo:rustc-check-cfg=cfg(ilog_probe)");
+    // For the environment in `bench --report` and `bench --db`.
+    let version = rustc_version();
+    println!(
+        "cargo:rustc-env=ILOG_RUSTC_VERSION={}",
+        version.as_deref().unwrap_or("unknown")
+    );
+
+    // max_decimal_len needs trait bounds on a const fn, from 1.61.
+    println!("cargo:rustc-check-cfg=cfg(ilog_const_fn_bounds)");
+    if matches!(version.as_deref().and_then(minor), Some(minor) if minor >= 61) {
+        println!("cargo:rustc-cfg=ilog_const_fn_bounds");
+    }
+
+    // Const fns reading the tables as statics (src/statics.rs), from 1.83.
+    println!("cargo:rustc-check-cfg=cfg(ilog_const_statics)");
+    if matches!(version.as_deref().and_then(minor), Some(minor) if minor >= 83) {
+        println!("cargo:rustc-cfg=ilog_const_statics");
+    }
+
+    // The header comes from src/ffi.rs alone, so that nothing else in
+    // the crate, whatever the features, leaks into it. It goes to
+    // OUT_DIR; set ILOG_UPDATE_HEADER to rewrite the committed
+    // include/ilog.h as well, which is left alone otherwise since
+    // builds shouldn't touch the source directory.
+    #[cfg(feature = "ffi")]
+    {
+        let dir = std::env::var("CARGO_MANIFEST_DIR").unwrap();
+        let out = std::env::var("OUT_DIR").unwrap();
+        println!("cargo:rerun-if-changed=src/ffi.rs");
+        println!("cargo:rerun-if-changed=cbindgen.toml");
+        println!("cargo:rerun-if-env-changed=ILOG_UPDATE_HEADER");
+        let config = cbindgen::Config::from_file(format!("{dir}/cbindgen.toml")).unwrap();
+        let bindings = cbindgen::Builder::new()
+            .with_src(format!("{dir}/src/ffi.rs"))
+            .with_config(config)
+            .generate()
+            .expect("unable to generate C bindings");
+        bindings.write_to_file(format!("{out}/ilog.h"));
+        if std::env::var_os("ILOG_UPDATE_HEADER").is_some() {
+            bindings.write_to_file(format!("{dir}/include/ilog.h"));
+        }
+    }
+
+    #[cfg(feature = "c-reference")]
+    {
+        println!("cargo:rerun-if-changed=csrc/reference.c");
+        println!("cargo:rerun-if-changed=csrc/generated.c");
+        cc::Build::new()
+            .file("csrc/reference.c")
+            .file("csrc/generated.c")
+            .warnings(true)
+            .compile("ilog_reference");
+    }
+}
+
+// `rustc --version` of the compiler cargo is building with, e.g.
+// "rustc 1.61.0 (fe5b13d68 2022-05-18)".
+fn rustc_version() -> Option<String> {
+    let rustc = std::env::var_os("RUSTC")?;
+    let output = std::process::Command::new(rustc).arg("--version").output().ok()?;
+    let version = String::from_utf8(output.stdout).ok()?;
+    Some(version.trim().to_string())
+}
+
+// The minor version in one of those.
+fn minor(version: &str) -> Option<u32> {
+    version.split('.').nth(1)?.parse().ok()
+}
@@ -0,0 +1,27 @@
+/*
+ * A minimal --plugin for `ilog`: one function with the plugin ABI,
+ *
+ *     uint32_t SYMBOL(uint64_t x);
+ *
+ * returning floor(log10(x)) for x > 0. Build and run it with
+ *
+ *     cc -O2 -shared -fPIC -o libexample_plugin.so csrc/example_plugin.c
+ *     ilog test --plugin ./libexample_plugin.so:example_ladder --filter example_ladder
+ *     ilog --plugin ./libexample_plugin.so:example_ladder --filter example_ladder
+ *
+ * (with --u64 for the u64 test). This one is the ladder of comparisons
+ * from reference.c, widened to u64.
+ */
+
+#include <stdint.h>
+
+uint32_t example_ladder(uint64_t x) {
+    uint32_t log = 0;
+    for (uint64_t p = 10; x >= p; p *= 10) {
+        log++;
+        if (log == 19) {
+            break;
+        }
+    }
+    return log;
+}
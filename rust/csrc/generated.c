@@ -0,0 +1,45 @@
+/* floor(log10(x)) for nonzero x, from the ilog crate's guesses and tables.
+ * Generated by `ilog codegen c`; none of the functions handle x == 0. */
+
+#include <stdint.h>
+
+/* 10^(g + 1) - 1, the largest value with g + 1 digits. */
+static const uint32_t ILOG10_U32_THRESHOLDS[9] = {
+    9u, 99u, 999u, 9999u, 99999u,
+    999999u, 9999999u, 99999999u, 999999999u};
+
+/* 9/32 is just under log10(2). */
+uint32_t ilog10_u32_mul(uint32_t x) {
+    uint32_t guess = ((31 - __builtin_clz(x)) * 9) >> 5;
+    return guess + (x > ILOG10_U32_THRESHOLDS[guess]);
+}
+
+/* A 1 at each bit where the next power of two passes a power of ten;
+ * the leading zeros shift out the ones x doesn't reach. */
+uint32_t ilog10_u32_popcount(uint32_t x) {
+    uint32_t guess = __builtin_popcount(0x09124490u << __builtin_clz(x));
+    return guess + (x > ILOG10_U32_THRESHOLDS[guess]);
+}
+
+/* The guess for each ilog2(x). */
+uint32_t ilog10_u32_table(uint32_t x) {
+    static const uint8_t guesses[32] = {
+        0, 0, 0, 0, 1, 1, 1, 2, 2, 2, 3, 3, 3, 3, 4, 4,
+        4, 5, 5, 5, 6, 6, 6, 6, 7, 7, 7, 8, 8, 8, 8, 8};
+    uint32_t guess = guesses[31 - __builtin_clz(x)];
+    return guess + (x > ILOG10_U32_THRESHOLDS[guess]);
+}
+
+/* 19/64 is just under log10(2). */
+uint32_t ilog10_u64_mul(uint64_t x) {
+    static const uint64_t thresholds[19] = {
+        9ull, 99ull, 999ull,
+        9999ull, 99999ull, 999999ull,
+        9999999ull, 99999999ull, 999999999ull,
+        9999999999ull, 99999999999ull, 999999999999ull,
+        9999999999999ull, 99999999999999ull, 999999999999999ull,
+        9999999999999999ull, 99999999999999999ull, 999999999999999999ull,
+        9999999999999999999ull};
+    uint32_t guess = ((63 - __builtin_clzll(x)) * 19) >> 6;
+    return guess + (x > thresholds[guess]);
+}
@@ -0,0 +1,102 @@
+/* Classic C implementations of floor(log10(x)), compiled by build.rs
+ * with the `c-reference` feature so that the benchmark can put them
+ * side by side with the Rust versions. None of them handle x == 0.
+ *
+ * Each algorithm also gets its benchmark loops here in C, so that the
+ * timings compare the compilers' code for the whole loop rather than
+ * a Rust loop making a non-inlinable call per value. The run loops
+ * take values from safe Rust, which may be 0, so they call each one on
+ * x | 1: never 0, and with the same log as x for any nonzero x. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+/* Hacker's Delight, 2nd ed., section 11-4: guess from the leading
+ * zero count, then correct with the sign bit of a subtraction. */
+uint32_t ref_ilog10_hd(uint32_t x) {
+    static const uint32_t table2[11] = {
+        0, 9, 99, 999, 9999, 99999, 999999, 9999999, 99999999,
+        999999999, 0xFFFFFFFF};
+    uint32_t y = (19 * (31 - __builtin_clz(x))) >> 6;
+    return y + ((table2[y + 1] - x) >> 31);
+}
+
+/* Guess looked up by leading zero count, then one comparison. */
+uint32_t ref_ilog10_clz_table(uint32_t x) {
+    static const uint8_t guess[32] = {
+        9, 9, 8, 8, 8, 7, 7, 7, 6, 6, 6, 6, 5, 5, 5, 4,
+        4, 4, 3, 3, 3, 3, 2, 2, 2, 1, 1, 1, 0, 0, 0, 0};
+    static const uint32_t thresholds[10] = {
+        9, 99, 999, 9999, 99999, 999999, 9999999, 99999999,
+        999999999, 0xFFFFFFFF};
+    uint32_t y = guess[__builtin_clz(x)];
+    return y + (x > thresholds[y]);
+}
+
+/* The binary compare ladder found in libc-style integer formatters. */
+uint32_t ref_ilog10_ladder(uint32_t x) {
+    if (x >= 100000) {
+        if (x >= 10000000) {
+            if (x >= 1000000000) return 9;
+            if (x >= 100000000) return 8;
+            return 7;
+        }
+        if (x >= 1000000) return 6;
+        return 5;
+    }
+    if (x >= 100) {
+        if (x >= 10000) return 4;
+        if (x >= 1000) return 3;
+        return 2;
+    }
+    return x >= 10;
+}
+
+/* The u64 guess needs 19/64 rather than 9/32; a plain comparison
+ * replaces the subtraction trick, as the top thresholds exceed 2^63. */
+uint32_t ref_ilog10_u64_mul(uint64_t x) {
+    static const uint64_t thresholds[19] = {
+        9ull, 99ull, 999ull, 9999ull, 99999ull, 999999ull, 9999999ull,
+        99999999ull, 999999999ull, 9999999999ull, 99999999999ull,
+        999999999999ull, 9999999999999ull, 99999999999999ull,
+        999999999999999ull, 9999999999999999ull, 99999999999999999ull,
+        999999999999999999ull, 9999999999999999999ull};
+    uint32_t y = (19 * (63 - __builtin_clzll(x))) >> 6;
+    return y + (x > thresholds[y]);
+}
+
+/* Keeps a result alive without storing it, like std::hint::black_box. */
+#define BLACK_BOX(r) __asm__ volatile("" : : "r"(r))
+
+#define LOOPS(name, T)                                                  \
+    void name##_run(const T *values, size_t len, size_t passes) {       \
+        for (size_t p = 0; p < passes; p++) {                           \
+            for (size_t i = 0; i < len; i++) {                          \
+                uint32_t r = name(values[i] | 1);                       \
+                BLACK_BOX(r);                                           \
+            }                                                           \
+        }                                                               \
+    }                                                                   \
+    void name##_sweep(void) {                                           \
+        for (uint32_t i = 1;; i++) {                                    \
+            uint32_t r = name((T)i);                                    \
+            BLACK_BOX(r);                                               \
+            if (i == UINT32_MAX) break;                                 \
+        }                                                               \
+    }
+
+LOOPS(ref_ilog10_hd, uint32_t)
+LOOPS(ref_ilog10_clz_table, uint32_t)
+LOOPS(ref_ilog10_ladder, uint32_t)
+LOOPS(ref_ilog10_u64_mul, uint64_t)
+
+/* The output of `ilog codegen c`, in csrc/generated.c. */
+uint32_t ilog10_u32_mul(uint32_t x);
+uint32_t ilog10_u32_popcount(uint32_t x);
+uint32_t ilog10_u32_table(uint32_t x);
+uint32_t ilog10_u64_mul(uint64_t x);
+
+LOOPS(ilog10_u32_mul, uint32_t)
+LOOPS(ilog10_u32_popcount, uint32_t)
+LOOPS(ilog10_u32_table, uint32_t)
+LOOPS(ilog10_u64_mul, uint64_t)
@@ -0,0 +1,50 @@
+#ifndef ILOG_H
+#define ILOG_H
+
+/* Generated by cbindgen from src/ffi.rs; do not edit. */
+
+#include <stddef.h>
+#include <stdint.h>
+
+// floor(log10(x)), or `UINT32_MAX` for 0, where it is undefined.
+uint32_t ilog10_u32(uint32_t x);
+
+// floor(log10(x)), or `UINT32_MAX` for 0, where it is undefined.
+uint32_t ilog10_u64(uint64_t x);
+
+// Number of decimal digits in `x`, counting 0 as one digit.
+uint32_t digits_u32(uint32_t x);
+
+// Number of decimal digits in `x`, counting 0 as one digit.
+uint32_t digits_u64(uint64_t x);
+
+// `ilog10_u32` of each of the `len` values at `input`, written to `output`.
+//
+// # Safety
+//
+// `input` and `output` must each be valid for `len` elements and must
+// not overlap. Either may be null if `len` is 0.
+void ilog10_u32_slice(const uint32_t *input, uint32_t *output, size_t len);
+
+// `ilog10_u64` of each of the `len` values at `input`, written to `output`.
+//
+// # Safety
+//
+// As for `ilog10_u32_slice`.
+void ilog10_u64_slice(const uint64_t *input, uint32_t *output, size_t len);
+
+// `digits_u32` of each of the `len` values at `input`, written to `output`.
+//
+// # Safety
+//
+// As for `ilog10_u32_slice`.
+void digits_u32_slice(const uint32_t *input, uint32_t *output, size_t len);
+
+// `digits_u64` of each of the `len` values at `input`, written to `output`.
+//
+// # Safety
+//
+// As for `ilog10_u32_slice`.
+void digits_u64_slice(const uint64_t *input, uint32_t *output, size_t len);
+
+#endif  /* ILOG_H */
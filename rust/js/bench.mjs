@@ -0,0 +1,48 @@
+// Times the registered implementations under Node (V8). Build the
+// package as described in src/wasm.rs, then
+//
+//   node js/bench.mjs
+//
+// ILOG_PKG overrides the path to the generated ilog.js.
+// Each algorithm runs over the same random values; the best of five
+// runs is reported in nanoseconds per call.
+
+import { createRequire } from "node:module";
+
+const require = createRequire(import.meta.url);
+const ilog = require(process.env.ILOG_PKG ?? "../pkg/ilog.js");
+
+const COUNT = 1 << 16;
+const PASSES = 1024;
+
+function randomU32() {
+  const values = new Uint32Array(COUNT);
+  for (let i = 0; i < COUNT; i++) {
+    values[i] = Math.floor(Math.random() * 0xffffffff) + 1;
+  }
+  return values;
+}
+
+function randomU64() {
+  const values = new BigUint64Array(COUNT);
+  for (let i = 0; i < COUNT; i++) {
+    values[i] = (BigInt(Math.floor(Math.random() * 2 ** 32)) << 32n) | 1n;
+  }
+  return values;
+}
+
+for (const [width, values, run] of [
+  ["u32", randomU32(), ilog.run_u32],
+  ["u64", randomU64(), ilog.run_u64],
+]) {
+  for (const name of ilog.algorithms(width)) {
+    let best = Infinity;
+    for (let rep = 0; rep < 5; rep++) {
+      const start = performance.now();
+      run(name, values, PASSES);
+      best = Math.min(best, performance.now() - start);
+    }
+    const ns = (best * 1e6) / (COUNT * PASSES);
+    console.log(`${width}/${name}: ${ns.toFixed(3)} ns/op`);
+  }
+}
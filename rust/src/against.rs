@@ -0,0 +1,134 @@
+// `bench --against REF`: the same benchmark on this build and on
+// another revision's, for judging an algorithm change with one
+// command. The revision is checked out into a temporary git worktree
+// and built into a target directory of its own under target/against,
+// kept for the next comparison with it. The two binaries then take
+// turns running the benchmark, ROUNDS times each in alternating order
+// so that drift in clock speed or background load hits both alike,
+// and each result's best time on one is set against the other's.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use ilog::results::BenchResult;
+use serde_json::{Map, Value};
+
+use crate::matrix;
+use crate::report::Report;
+
+// Runs of each binary.
+const ROUNDS: usize = 3;
+
+// The options of this run that only concern its own output, and
+// whether each takes a value; the rest of the command line is passed
+// to both binaries.
+const OWN_OPTIONS: [(&str, bool); 7] = [
+    ("--against", true),
+    ("--output", true),
+    ("-o", true),
+    ("--format", true),
+    ("-f", true),
+    ("--append", false),
+    ("-a", false),
+];
+
+pub fn report(reference: &str) -> Result<Report, String> {
+    let args = matrix::forwarded(std::env::args().skip(1), &OWN_OPTIONS);
+    let (rev, base) = build(reference)?;
+    let this = std::env::current_exe().map_err(|e| format!("finding this binary: {e}"))?;
+    let sides = [(rev.as_str(), base.as_path()), ("this build", this.as_path())];
+    let mut best: [Vec<BenchResult>; 2] = [Vec::new(), Vec::new()];
+    for round in 0..ROUNDS {
+        for i in [round % 2, 1 - round % 2] {
+            let (label, binary) = sides[i];
+            eprintln!("Round {} of {ROUNDS}: {label}", round + 1);
+            for r in matrix::bench(binary, &args, label)?.rows {
+                match best[i].iter_mut().find(|b| same(b, &r)) {
+                    Some(b) if b.ns_per_op <= r.ns_per_op => {}
+                    Some(b) => *b = r,
+                    None => best[i].push(r),
+                }
+            }
+        }
+    }
+
+    let columns = [
+        "width",
+        "distribution",
+        "context",
+        "algorithm",
+        "base_ns_per_op",
+        "ns_per_op",
+        "change_percent",
+    ];
+    let mut report = Report::new(&format!("bench against {rev}"), &columns);
+    let [base_rows, rows] = &best;
+    for r in rows {
+        let base = base_rows.iter().find(|b| same(b, r)).map(|b| b.ns_per_op);
+        let mut row = Map::new();
+        row.insert("width".into(), r.width.name().into());
+        row.insert("distribution".into(), r.distribution.name().into());
+        row.insert("context".into(), r.context.name().into());
+        row.insert("algorithm".into(), r.algorithm.clone().into());
+        row.insert("base_ns_per_op".into(), base.into());
+        row.insert("ns_per_op".into(), r.ns_per_op.into());
+        row.insert("change_percent".into(), base.map(|b| (r.ns_per_op / b - 1.0) * 100.0).into());
+        report.push(&Value::Object(row));
+    }
+    Ok(report)
+}
+
+fn same(a: &BenchResult, b: &BenchResult) -> bool {
+    (a.width, a.distribution, a.context, &a.algorithm)
+        == (b.width, b.distribution, b.context, &b.algorithm)
+}
+
+// The short hash of `reference` and its `ilog` binary, built from a
+// worktree that is removed again afterwards.
+fn build(reference: &str) -> Result<(String, PathBuf), String> {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let rev = git(dir, &["rev-parse", "--short", &format!("{reference}^{{commit}}")])?;
+    // Where this crate is within the repository, e.g. `rust/`.
+    let prefix = git(dir, &["rev-parse", "--show-prefix"])?;
+    let worktree = std::env::temp_dir().join(format!("ilog-against-{rev}"));
+    let path = worktree.to_string_lossy().into_owned();
+    if worktree.exists() {
+        // Left over from an interrupted run.
+        let _ = git(dir, &["worktree", "remove", "--force", &path]);
+        let _ = std::fs::remove_dir_all(&worktree);
+    }
+    eprintln!("Building {reference} ({rev})");
+    git(dir, &["worktree", "add", "--detach", "--quiet", &path, &rev])?;
+    let target_dir = dir.join("target/against").join(&rev);
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let status = Command::new(cargo)
+        .arg("build")
+        .arg("--manifest-path")
+        .arg(worktree.join(&prefix).join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .args(["--release", "--quiet", "--bin", "ilog"])
+        .status();
+    let removed = git(dir, &["worktree", "remove", "--force", &path]);
+    let status = status.map_err(|e| format!("running cargo: {e}"))?;
+    if !status.success() {
+        return Err(format!("building {reference} failed"));
+    }
+    removed?;
+    Ok((rev, target_dir.join("release/ilog")))
+}
+
+// git's output, trimmed, or its error message.
+fn git(dir: &Path, args: &[&str]) -> Result<String, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .map_err(|e| format!("running git: {e}"))?;
+    if !output.status.success() {
+        let message = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git {}: {}", args.join(" "), message.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
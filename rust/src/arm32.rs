@@ -0,0 +1,31 @@
+//! u64 ilog10 for 32-bit ARM (ARMv7-A and the Thumb-2 Cortex-M
+//! cores), where a u64 lives in a register pair.
+//!
+//! `clz` is there, so for u32 `ilog10_mul` is already the sequence one
+//! would write by hand: `clz`, an `add` with a shifted operand for the
+//! multiply by 9, and `it hi` / `addhi` for the correction. What costs
+//! is u64: its clz becomes a compare and a conditional `clz` of each
+//! half, the threshold a two-word load, and the comparison a
+//! `subs` / `sbcs` pair, all of it paid even for the small values
+//! that most formatted integers are. `ilog10_u64_split` tests the high
+//! word first and hands anything that fits in 32 bits to the u32 code;
+//! past that, the guess comes from the high word alone.
+//!
+//! On `target_arch = "arm"` it is the u64 ilog10 behind `fast_ilog10`
+//! and the `ffi` wrappers (see `target` in lib.rs). It is plain Rust,
+//! so `ilog test --u64` checks it on any host; to check it on ARM
+//! itself, .cargo/config.toml has a QEMU runner for `cargo run
+//! --target armv7-unknown-linux-gnueabihf -- test --u64`.
+
+/// floor(log10(x)), one 32-bit half at a time. Panics if `x` is 0.
+#[inline]
+pub const fn ilog10_u64_split(x: u64) -> u32 {
+    let hi = (x >> 32) as u32;
+    if hi == 0 {
+        return crate::ilog10_mul(x as u32);
+    }
+    // ilog2(x) = 32 + ilog2(hi), so the guess is (32 + ilog2(hi)) * 19
+    // >> 6, at least 9: past the u32 part of the table.
+    let guess = (32 + crate::ilog2_u32(hi)).wrapping_mul(19) >> 6;
+    guess + (x > crate::u64_threshold(guess)) as u32
+}
@@ -0,0 +1,124 @@
+//! The multiply-based guess written out by hand in `asm!`, as the
+//! instruction sequence the Rust versions are hoping LLVM produces.
+//! In the benchmark these are the ceiling: a Rust variant that times
+//! the same as `asm` has nothing left to gain from codegen.
+//!
+//! x86-64: `bsr` for ilog2, `lea` + `shr` for the guess, then the
+//! comparison with the threshold feeds its carry straight into `adc`.
+//! aarch64: `clz`, then one `msub` folds the `31 - clz` into the
+//! multiply by 9, and `cinc` applies the comparison.
+//!
+//! None of these are defined for 0 (`bsr` leaves its destination
+//! undefined), hence `unsafe`.
+
+use core::arch::asm;
+
+// The tables the Rust versions read, where they are statics, so that
+// `Context::Pressure` flushes these too; `asm!` needs an address either
+// way.
+#[cfg(ilog_const_statics)]
+use crate::statics::TEN_THRESHOLDS as THRESHOLDS;
+#[cfg(all(ilog_const_statics, not(feature = "small-tables")))]
+use crate::statics::U64_THRESHOLDS;
+#[cfg(not(ilog_const_statics))]
+static THRESHOLDS: [u32; 9] = crate::TEN_THRESHOLDS;
+#[cfg(all(not(ilog_const_statics), not(feature = "small-tables")))]
+static U64_THRESHOLDS: [u64; 19] = crate::U64_THRESHOLDS;
+
+/// # Safety
+///
+/// `x` must be nonzero.
+#[cfg(target_arch = "x86_64")]
+#[inline]
+pub unsafe fn ilog10_u32(x: u32) -> u32 {
+    let r: u64;
+    asm!(
+        "bsr {r:e}, {x:e}",
+        "lea {r:e}, [{r} + {r}*8]",
+        "shr {r:e}, 5",
+        // CF = threshold < x, so adc adds one exactly when x > threshold.
+        "cmp dword ptr [{t} + {r}*4], {x:e}",
+        "adc {r:e}, 0",
+        x = in(reg) x,
+        t = in(reg) THRESHOLDS.as_ptr(),
+        r = out(reg) r,
+        options(pure, readonly, nostack),
+    );
+    r as u32
+}
+
+/// # Safety
+///
+/// `x` must be nonzero.
+#[cfg(all(target_arch = "x86_64", not(feature = "small-tables")))]
+#[inline]
+pub unsafe fn ilog10_u64(x: u64) -> u32 {
+    let r: u64;
+    asm!(
+        "bsr {r}, {x}",
+        // 19 * r as r + 2 * (9 * r).
+        "lea {n}, [{r} + {r}*8]",
+        "lea {r}, [{r} + {n}*2]",
+        "shr {r}, 6",
+        "cmp qword ptr [{t} + {r}*8], {x}",
+        "adc {r:e}, 0",
+        x = in(reg) x,
+        t = in(reg) U64_THRESHOLDS.as_ptr(),
+        n = out(reg) _,
+        r = out(reg) r,
+        options(pure, readonly, nostack),
+    );
+    r as u32
+}
+
+/// # Safety
+///
+/// `x` must be nonzero.
+#[cfg(target_arch = "aarch64")]
+#[inline]
+pub unsafe fn ilog10_u32(x: u32) -> u32 {
+    let r: u32;
+    asm!(
+        // 9 * (31 - clz) = 279 - 9 * clz
+        "clz {r:w}, {x:w}",
+        "msub {r:w}, {r:w}, {nine:w}, {c:w}",
+        "lsr {r:w}, {r:w}, #5",
+        "ldr {n:w}, [{t}, {r:x}, lsl #2]",
+        "cmp {x:w}, {n:w}",
+        "cinc {r:w}, {r:w}, hi",
+        x = in(reg) x,
+        t = in(reg) THRESHOLDS.as_ptr(),
+        nine = in(reg) 9u32,
+        c = in(reg) 279u32,
+        n = out(reg) _,
+        r = out(reg) r,
+        options(pure, readonly, nostack),
+    );
+    r
+}
+
+/// # Safety
+///
+/// `x` must be nonzero.
+#[cfg(all(target_arch = "aarch64", not(feature = "small-tables")))]
+#[inline]
+pub unsafe fn ilog10_u64(x: u64) -> u32 {
+    let r: u32;
+    asm!(
+        // 19 * (63 - clz) = 1197 - 19 * clz
+        "clz {r:x}, {x:x}",
+        "msub {r:w}, {r:w}, {nineteen:w}, {c:w}",
+        "lsr {r:w}, {r:w}, #6",
+        "ldr {n:x}, [{t}, {r:x}, lsl #3]",
+        "cmp {x:x}, {n:x}",
+        "cinc {r:w}, {r:w}, hi",
+        x = in(reg) x,
+        t = in(reg) U64_THRESHOLDS.as_ptr(),
+        nineteen = in(reg) 19u32,
+        c = in(reg) 1197u32,
+        n = out(reg) _,
+        r = out(reg) r,
+        options(pure, readonly, nostack),
+    );
+    r
+}
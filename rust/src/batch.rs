@@ -0,0 +1,233 @@
+//! ilog10 of a whole slice of u32s or u64s, for encoders that size many
+//! IDs or timestamps at once.
+//!
+//! The portable kernels take the values four or two at a time with
+//! `digits_u32x4` and `digits_u64x2`, which LLVM turns into vector code
+//! where the target has the instructions for it. With the `std`
+//! feature on x86-64 (and without `small-tables`), the slice functions
+//! check the CPU on each call (a cached load) and run one of:
+//!
+//! - AVX-512 (F and CD): 16 u32 or eight u64 lanes at a time with
+//!   `vplzcntd` or `vplzcntq` for the ilog2, shifts and adds for the
+//!   multiply by 9 or 19, a gather from `TEN_THRESHOLDS` or
+//!   `U64_THRESHOLDS` and a masked add for the correction;
+//! - AVX2: the portable kernel compiled for AVX2, where LLVM has to
+//!   emulate the leading-zero count;
+//! - otherwise the portable kernel as the crate was built.
+//!
+//! `u32_kernels` and `u64_kernels` list the ones this CPU can run, for
+//! the tests and benchmarks.
+
+/// A kernel's name and function.
+pub type Kernel<T> = (&'static str, fn(&[T], &mut [u32]));
+
+/// floor(log10(x)) of each value into `out`, with 0 giving 0 (the
+/// ilog10 of `x | 1`).
+///
+/// Panics if `values` and `out` differ in length.
+///
+/// ```
+/// let values = [0, 9, 10, 4_000_000_000];
+/// let mut out = [0; 4];
+/// ilog::batch::ilog10_u32_slice(&values, &mut out);
+/// assert_eq!(out, [0, 0, 1, 9]);
+/// ```
+#[inline]
+pub fn ilog10_u32_slice(values: &[u32], out: &mut [u32]) {
+    assert!(values.len() == out.len(), "values and out differ in length");
+    #[cfg(all(feature = "std", target_arch = "x86_64", not(feature = "small-tables")))]
+    {
+        if x86::has_avx512() {
+            // SAFETY: just checked.
+            return unsafe { x86::avx512_u32(values, out) };
+        }
+        if x86::has_avx2() {
+            // SAFETY: just checked.
+            return unsafe { x86::avx2_u32(values, out) };
+        }
+    }
+    portable_u32(values, out)
+}
+
+/// As `ilog10_u32_slice`.
+///
+/// ```
+/// let values = [0, 9, 10, 1_700_000_000_000, u64::MAX];
+/// let mut out = [0; 5];
+/// ilog::batch::ilog10_u64_slice(&values, &mut out);
+/// assert_eq!(out, [0, 0, 1, 12, 19]);
+/// ```
+#[inline]
+pub fn ilog10_u64_slice(values: &[u64], out: &mut [u32]) {
+    assert!(values.len() == out.len(), "values and out differ in length");
+    #[cfg(all(feature = "std", target_arch = "x86_64", not(feature = "small-tables")))]
+    {
+        if x86::has_avx512() {
+            // SAFETY: just checked.
+            return unsafe { x86::avx512_u64(values, out) };
+        }
+        if x86::has_avx2() {
+            // SAFETY: just checked.
+            return unsafe { x86::avx2_u64(values, out) };
+        }
+    }
+    portable_u64(values, out)
+}
+
+/// The kernels behind `ilog10_u32_slice` that this CPU can run, each
+/// with the same contract (though they may not check the lengths).
+pub fn u32_kernels() -> impl Iterator<Item = Kernel<u32>> {
+    let portable: Kernel<u32> = ("portable", portable_u32);
+    #[cfg(all(feature = "std", target_arch = "x86_64", not(feature = "small-tables")))]
+    let x86 = x86::u32_kernels();
+    #[cfg(not(all(feature = "std", target_arch = "x86_64", not(feature = "small-tables"))))]
+    let x86: [Option<Kernel<u32>>; 0] = [];
+    core::iter::once(portable).chain(x86.into_iter().flatten())
+}
+
+/// As `u32_kernels`, for `ilog10_u64_slice`.
+pub fn u64_kernels() -> impl Iterator<Item = Kernel<u64>> {
+    let portable: Kernel<u64> = ("portable", portable_u64);
+    #[cfg(all(feature = "std", target_arch = "x86_64", not(feature = "small-tables")))]
+    let x86 = x86::u64_kernels();
+    #[cfg(not(all(feature = "std", target_arch = "x86_64", not(feature = "small-tables"))))]
+    let x86: [Option<Kernel<u64>>; 0] = [];
+    core::iter::once(portable).chain(x86.into_iter().flatten())
+}
+
+// Four at a time with digits_u32x4, and the rest alone.
+#[inline(always)]
+fn portable_u32(values: &[u32], out: &mut [u32]) {
+    let n = values.len().min(out.len());
+    let (values, out) = (&values[..n], &mut out[..n]);
+    let mut quads = values.chunks_exact(4);
+    let mut outs = out.chunks_exact_mut(4);
+    for (x, o) in (&mut quads).zip(&mut outs) {
+        let digits = crate::digits_u32x4([x[0], x[1], x[2], x[3]]);
+        for (o, d) in o.iter_mut().zip(digits) {
+            *o = d - 1;
+        }
+    }
+    for (x, o) in quads.remainder().iter().zip(outs.into_remainder()) {
+        *o = crate::ilog10_mul(x | 1);
+    }
+}
+
+// Two at a time with digits_u64x2, and the odd one out alone.
+#[inline(always)]
+fn portable_u64(values: &[u64], out: &mut [u32]) {
+    let n = values.len().min(out.len());
+    let (values, out) = (&values[..n], &mut out[..n]);
+    let mut pairs = values.chunks_exact(2);
+    let mut outs = out.chunks_exact_mut(2);
+    for (x, o) in (&mut pairs).zip(&mut outs) {
+        let [a, b] = crate::digits_u64x2([x[0], x[1]]);
+        o[0] = a - 1;
+        o[1] = b - 1;
+    }
+    for (x, o) in pairs.remainder().iter().zip(outs.into_remainder()) {
+        *o = crate::ilog10_u64_mul(x | 1);
+    }
+}
+
+#[cfg(all(feature = "std", target_arch = "x86_64", not(feature = "small-tables")))]
+mod x86 {
+    use core::arch::x86_64::*;
+
+    use super::Kernel;
+
+    pub fn has_avx512() -> bool {
+        std::is_x86_feature_detected!("avx512f") && std::is_x86_feature_detected!("avx512cd")
+    }
+
+    pub fn has_avx2() -> bool {
+        std::is_x86_feature_detected!("avx2")
+    }
+
+    pub fn u32_kernels() -> [Option<Kernel<u32>>; 2] {
+        // SAFETY: each is only listed if the CPU has its features.
+        let wide: Kernel<u32> = ("avx512", |v, o| unsafe { avx512_u32(v, o) });
+        let narrow: Kernel<u32> = ("avx2", |v, o| unsafe { avx2_u32(v, o) });
+        [has_avx512().then_some(wide), has_avx2().then_some(narrow)]
+    }
+
+    pub fn u64_kernels() -> [Option<Kernel<u64>>; 2] {
+        // SAFETY: as for u32_kernels.
+        let wide: Kernel<u64> = ("avx512", |v, o| unsafe { avx512_u64(v, o) });
+        let narrow: Kernel<u64> = ("avx2", |v, o| unsafe { avx2_u64(v, o) });
+        [has_avx512().then_some(wide), has_avx2().then_some(narrow)]
+    }
+
+    // Safety: the CPU has AVX-512 F and CD.
+    #[target_feature(enable = "avx512f,avx512cd")]
+    pub unsafe fn avx512_u32(values: &[u32], out: &mut [u32]) {
+        let n = values.len().min(out.len());
+        let (values, out) = (&values[..n], &mut out[..n]);
+        let mut lanes = values.chunks_exact(16);
+        let mut outs = out.chunks_exact_mut(16);
+        let one = _mm512_set1_epi32(1);
+        let thirty_one = _mm512_set1_epi32(31);
+        for (x, o) in (&mut lanes).zip(&mut outs) {
+            // SAFETY: x and o are 16 u32s, unaligned loads and stores
+            // are allowed, and the gather indices are guesses, at most
+            // 31 * 9 >> 5 = 8, within TEN_THRESHOLDS.
+            unsafe {
+                let v = _mm512_or_si512(_mm512_loadu_si512(x.as_ptr().cast()), one);
+                let log2 = _mm512_sub_epi32(thirty_one, _mm512_lzcnt_epi32(v));
+                let times9 = _mm512_add_epi32(_mm512_slli_epi32::<3>(log2), log2);
+                let guess = _mm512_srli_epi32::<5>(times9);
+                let table = crate::statics::TEN_THRESHOLDS.as_ptr().cast();
+                let thresholds = _mm512_i32gather_epi32::<4>(guess, table);
+                let above = _mm512_cmpgt_epu32_mask(v, thresholds);
+                let ilog10 = _mm512_mask_add_epi32(guess, above, guess, one);
+                _mm512_storeu_si512(o.as_mut_ptr().cast(), ilog10);
+            }
+        }
+        super::portable_u32(lanes.remainder(), outs.into_remainder());
+    }
+
+    // Safety: the CPU has AVX-512 F and CD.
+    #[target_feature(enable = "avx512f,avx512cd")]
+    pub unsafe fn avx512_u64(values: &[u64], out: &mut [u32]) {
+        let n = values.len().min(out.len());
+        let (values, out) = (&values[..n], &mut out[..n]);
+        let mut lanes = values.chunks_exact(8);
+        let mut outs = out.chunks_exact_mut(8);
+        let one = _mm512_set1_epi64(1);
+        let sixty_three = _mm512_set1_epi64(63);
+        for (x, o) in (&mut lanes).zip(&mut outs) {
+            // SAFETY: x is 8 u64s and o 8 u32s, unaligned loads and
+            // stores are allowed, and the gather indices are guesses,
+            // at most 63 * 19 >> 6 = 18, within U64_THRESHOLDS.
+            unsafe {
+                let v = _mm512_or_si512(_mm512_loadu_si512(x.as_ptr().cast()), one);
+                let log2 = _mm512_sub_epi64(sixty_three, _mm512_lzcnt_epi64(v));
+                // log2 * 19 = log2 * 16 + log2 * 2 + log2, without the
+                // AVX-512 DQ multiply.
+                let times19 = _mm512_add_epi64(
+                    _mm512_add_epi64(_mm512_slli_epi64::<4>(log2), _mm512_add_epi64(log2, log2)),
+                    log2,
+                );
+                let guess = _mm512_srli_epi64::<6>(times19);
+                let table = crate::statics::U64_THRESHOLDS.as_ptr().cast();
+                let thresholds = _mm512_i64gather_epi64::<8>(guess, table);
+                let above = _mm512_cmpgt_epu64_mask(v, thresholds);
+                let ilog10 = _mm512_mask_add_epi64(guess, above, guess, one);
+                _mm256_storeu_si256(o.as_mut_ptr().cast(), _mm512_cvtepi64_epi32(ilog10));
+            }
+        }
+        super::portable_u64(lanes.remainder(), outs.into_remainder());
+    }
+
+    // Safety: the CPU has AVX2.
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn avx2_u32(values: &[u32], out: &mut [u32]) {
+        super::portable_u32(values, out)
+    }
+
+    // Safety: the CPU has AVX2.
+    #[target_feature(enable = "avx2")]
+    pub unsafe fn avx2_u64(values: &[u64], out: &mut [u32]) {
+        super::portable_u64(values, out)
+    }
+}
@@ -0,0 +1,782 @@
+//! Timing loops shared by the `ilog bench` command and the bindings.
+
+use std::sync::OnceLock;
+
+use rand::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::registry::Width;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Distribution {
+    /// Every value in 1..=u32::MAX, in order (as u64s for the u64 width)
+    Sweep,
+    /// Uniformly random bits, so almost every value has the maximum digit count
+    Uniform,
+    /// A uniformly random digit count, then a uniform value with that many digits
+    Digits,
+    /// The same values as `Digits`, sorted, so that branches on the
+    /// digit count are predictable
+    Sorted,
+    /// Every value in 1..=u32::MAX, as for `Sweep`, but in the order of
+    /// a fixed pseudo-random permutation
+    Permuted,
+    /// The mix of magnitudes an encoder of records sees: mostly small
+    /// counts and ids, some timestamps, a few full-width hashes
+    Mixed,
+    /// Digit counts in the proportions of a suite's `histogram`, such
+    /// as one taken from production; see `Histogram`
+    Histogram,
+}
+
+impl Distribution {
+    /// Those that need nothing but a name; `Histogram` also needs the
+    /// histogram.
+    pub const ALL: [Distribution; 6] = [
+        Distribution::Sweep,
+        Distribution::Uniform,
+        Distribution::Digits,
+        Distribution::Sorted,
+        Distribution::Permuted,
+        Distribution::Mixed,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Distribution::Sweep => "sweep",
+            Distribution::Uniform => "uniform",
+            Distribution::Digits => "digits",
+            Distribution::Sorted => "sorted",
+            Distribution::Permuted => "permuted",
+            Distribution::Mixed => "mixed",
+            Distribution::Histogram => "histogram",
+        }
+    }
+}
+
+/// Relative weights of ranges of digit counts, written
+/// `1-3:60,4-6:30,7+:10` for 60% of values with 1 to 3 digits, 30%
+/// with 4 to 6 and 10% with 7 or more. A single count is `5:20`. The
+/// weights need not add up to 100, and within a range the digit count
+/// and then the value are uniform, as for `Distribution::Digits`.
+///
+/// ```
+/// use ilog::bench::Histogram;
+///
+/// assert!("1-3:60,4-6:30,7+:10".parse::<Histogram>().is_ok());
+/// assert!("3-1:5".parse::<Histogram>().is_err());
+/// assert!("1-3:0".parse::<Histogram>().is_err());
+/// ```
+#[derive(Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(try_from = "String")]
+pub struct Histogram {
+    // (lowest digit count, highest or None for no limit, weight)
+    buckets: Vec<(u32, Option<u32>, u32)>,
+}
+
+impl std::str::FromStr for Histogram {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, String> {
+        let count = |s: &str| match s.parse::<u32>() {
+            Ok(n) if n > 0 => Ok(n),
+            _ => Err(format!("{s:?} is not a digit count")),
+        };
+        let mut buckets = Vec::new();
+        for bucket in spec.split(',') {
+            let (digits, weight) = bucket
+                .split_once(':')
+                .ok_or_else(|| format!("{bucket:?}: expected DIGITS:WEIGHT"))?;
+            let (lo, hi) = if let Some(lo) = digits.strip_suffix('+') {
+                (count(lo)?, None)
+            } else if let Some((lo, hi)) = digits.split_once('-') {
+                (count(lo)?, Some(count(hi)?))
+            } else {
+                (count(digits)?, Some(count(digits)?))
+            };
+            if hi.is_some_and(|hi| hi < lo) {
+                return Err(format!("{digits:?} is an empty range"));
+            }
+            let weight = match weight.parse::<u32>() {
+                Ok(w) if w > 0 => w,
+                _ => return Err(format!("{weight:?} is not a positive weight")),
+            };
+            buckets.push((lo, hi, weight));
+        }
+        Ok(Histogram { buckets })
+    }
+}
+
+impl TryFrom<String> for Histogram {
+    type Error = String;
+
+    fn try_from(spec: String) -> Result<Self, String> {
+        spec.parse()
+    }
+}
+
+// A uniform value with `digits` digits, no more than `max`.
+fn with_digits(rng: &mut impl Rng, digits: u32, max: u64) -> u64 {
+    let lo = 10u64.pow(digits - 1);
+    let hi = 10u64.checked_pow(digits).map_or(max, |p| (p - 1).min(max));
+    rng.random_range(lo..=hi)
+}
+
+/// A pseudo-random permutation of `0..2^bits`, for visiting every
+/// value of a range exactly once but in an order that neither the
+/// branch predictor nor the prefetcher can follow. Each step of the
+/// mixer (adding the seed, xoring in a right shift of itself, and
+/// multiplying by an odd constant, all mod 2^bits) can be undone, so
+/// `at` is a bijection; and as it needs only the index, a range can
+/// be split between threads by index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Permutation {
+    bits: u32,
+    seed: u64,
+}
+
+impl Permutation {
+    /// Panics unless `bits` is in 1..=63.
+    pub fn new(bits: u32, seed: u64) -> Self {
+        assert!((1..64).contains(&bits), "permutation of 2^{bits} values");
+        Permutation { bits, seed }
+    }
+
+    /// The number of values, 2^bits.
+    pub fn size(&self) -> u64 {
+        1 << self.bits
+    }
+
+    /// The value at position `i`, which must be below `size()`.
+    pub fn at(&self, i: u64) -> u64 {
+        let mask = self.size() - 1;
+        let shift = self.bits.div_ceil(2);
+        // The multipliers are splitmix64's.
+        let mut x = i.wrapping_add(self.seed) & mask;
+        x ^= x >> shift;
+        x = x.wrapping_mul(0xbf58476d1ce4e5b9) & mask;
+        x ^= x >> shift;
+        x = x.wrapping_mul(0x94d049bb133111eb) & mask;
+        x ^ (x >> shift)
+    }
+}
+
+/// The seed of `Distribution::Permuted`, fixed so that runs compare.
+const PERMUTED_SEED: u64 = 0;
+
+/// Random inputs are drawn into a buffer small enough to stay in L2
+/// and replayed, so the loop measures the ilog10 rather than memory.
+const RANDOM_VALUES: usize = 1 << 16;
+const RANDOM_PASSES: usize = 4096;
+
+/// The values a benchmark loop runs over.
+#[derive(Clone)]
+pub enum Input {
+    Sweep,
+    /// 1..=u32::MAX in the permutation's order
+    Permuted(Permutation),
+    /// Every `stride`th value of `Sweep`, or of `Permuted` with the
+    /// permutation: a sample of the same range for a shorter run
+    Strided {
+        permutation: Option<Permutation>,
+        stride: u64,
+    },
+    U32 { values: Vec<u32>, passes: usize },
+    U64 { values: Vec<u64>, passes: usize },
+}
+
+impl Input {
+    pub fn generate(width: Width, distribution: Distribution) -> Input {
+        let mut rng = rand::rng();
+        let draw = |max: u64| -> u64 {
+            match distribution {
+                Distribution::Sweep | Distribution::Permuted => unreachable!(),
+                Distribution::Histogram => panic!("use Input::from_histogram"),
+                Distribution::Uniform => rng.random_range(1..=max),
+                Distribution::Digits | Distribution::Sorted => {
+                    let digits = rng.random_range(1..=max.ilog10() + 1);
+                    with_digits(&mut rng, digits, max)
+                }
+                // 40% counts (1 to 3 digits), 30% ids (up to 7), 20%
+                // Unix timestamps (seconds, or milliseconds for u64) and
+                // 10% uniform, as hashes are.
+                Distribution::Mixed => match rng.random_range(0..10) {
+                    0..=3 => rng.random_range(1..=999),
+                    4..=6 => rng.random_range(1..=9_999_999),
+                    7 | 8 if max == u32::MAX as u64 => rng.random_range(1_500_000_000..=1_800_000_000),
+                    7 | 8 => rng.random_range(1_500_000_000_000..=1_800_000_000_000),
+                    _ => rng.random_range(1..=max),
+                },
+            }
+        };
+        let sorted = distribution == Distribution::Sorted;
+        match distribution {
+            Distribution::Sweep => Input::Sweep,
+            Distribution::Permuted => Input::Permuted(Permutation::new(32, PERMUTED_SEED)),
+            _ => Input::random(width, sorted, draw),
+        }
+    }
+
+    /// Random values whose digit counts follow `histogram`; an error if
+    /// one of its ranges has no digit count that fits `width`.
+    ///
+    /// ```
+    /// use ilog::bench::Input;
+    /// use ilog::registry::Width;
+    ///
+    /// let histogram = "2:3,20:1".parse().unwrap();
+    /// let Input::U64 { values, .. } = Input::from_histogram(Width::U64, &histogram).unwrap() else {
+    ///     unreachable!()
+    /// };
+    /// let short = values.iter().filter(|&&x| (10..100).contains(&x)).count();
+    /// assert_eq!(values.iter().filter(|&&x| x >= 10u64.pow(19)).count(), values.len() - short);
+    /// assert!((0.7..0.8).contains(&(short as f64 / values.len() as f64)));
+    ///
+    /// assert!(Input::from_histogram(Width::U32, &histogram).is_err());
+    /// ```
+    pub fn from_histogram(width: Width, histogram: &Histogram) -> Result<Input, String> {
+        let max = match width {
+            Width::U32 => u32::MAX as u64,
+            Width::U64 => u64::MAX,
+        };
+        let most = max.ilog10() + 1;
+        let mut buckets = Vec::new();
+        for &(lo, hi, weight) in &histogram.buckets {
+            if lo > most {
+                return Err(format!("no {} value has {lo} digits", width.name()));
+            }
+            buckets.push((lo, hi.unwrap_or(most).min(most), weight));
+        }
+        let total: u32 = buckets.iter().map(|b| b.2).sum();
+        let mut rng = rand::rng();
+        let draw = |max: u64| {
+            let mut pick = rng.random_range(0..total);
+            let (mut lo, mut hi) = (1, 1);
+            for &(first, last, weight) in &buckets {
+                if pick < weight {
+                    (lo, hi) = (first, last);
+                    break;
+                }
+                pick -= weight;
+            }
+            let digits = rng.random_range(lo..=hi);
+            with_digits(&mut rng, digits, max)
+        };
+        Ok(Input::random(width, false, draw))
+    }
+
+    // RANDOM_VALUES values from `draw`, which is given the largest value
+    // of the width.
+    fn random(width: Width, sorted: bool, mut draw: impl FnMut(u64) -> u64) -> Input {
+        match width {
+            Width::U32 => {
+                let mut values: Vec<u32> = (0..RANDOM_VALUES)
+                    .map(|_| draw(u32::MAX as u64) as u32)
+                    .collect();
+                if sorted {
+                    values.sort_unstable();
+                }
+                Input::U32 {
+                    values,
+                    passes: RANDOM_PASSES,
+                }
+            }
+            Width::U64 => {
+                let mut values: Vec<u64> = (0..RANDOM_VALUES).map(|_| draw(u64::MAX)).collect();
+                if sorted {
+                    values.sort_unstable();
+                }
+                Input::U64 {
+                    values,
+                    passes: RANDOM_PASSES,
+                }
+            }
+        }
+    }
+
+    pub fn ops(&self) -> u64 {
+        match self {
+            Input::Sweep | Input::Permuted(_) => u32::MAX as u64,
+            Input::Strided { stride, .. } => (u32::MAX as u64).div_ceil(*stride),
+            Input::U32 { values, passes } => (values.len() * passes) as u64,
+            Input::U64 { values, passes } => (values.len() * passes) as u64,
+        }
+    }
+
+    /// The same distribution cut down to about `ops` operations, or no
+    /// fewer than one value; never more than `self`.
+    pub fn scaled(&self, ops: u64) -> Input {
+        let ops = ops.max(1);
+        let stride = |stride: u64| (stride * (self.ops() / ops)).max(stride);
+        match self {
+            Input::Sweep => Input::Strided {
+                permutation: None,
+                stride: stride(1),
+            },
+            Input::Permuted(p) => Input::Strided {
+                permutation: Some(*p),
+                stride: stride(1),
+            },
+            Input::Strided { permutation, stride: s } => Input::Strided {
+                permutation: *permutation,
+                stride: stride(*s),
+            },
+            Input::U32 { values, passes } => {
+                let len = values.len().min(ops as usize);
+                Input::U32 {
+                    values: values[..len].to_vec(),
+                    passes: (ops as usize / len).clamp(1, *passes),
+                }
+            }
+            Input::U64 { values, passes } => {
+                let len = values.len().min(ops as usize);
+                Input::U64 {
+                    values: values[..len].to_vec(),
+                    passes: (ops as usize / len).clamp(1, *passes),
+                }
+            }
+        }
+    }
+}
+
+/// How each ilog10 is used inside the timed loop.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Context {
+    /// The bare call, its result passed straight to `black_box`
+    Standalone,
+    /// Inlined into an integer-to-string loop that sizes the output
+    /// with it, where it competes with the formatting for registers
+    Format,
+    /// As `Format`, but appending every value and a comma to one
+    /// output buffer of a megabyte or more, as a CSV or JSON encoder
+    /// does, so that the stores and the buffer's cache misses are
+    /// timed too
+    Serialize,
+    /// Each call after a load from a random line of a working set far
+    /// larger than the last-level cache, which waits for the call
+    /// before and which the prefetcher can't predict, and with the
+    /// lookup tables flushed from the cache, as if everything else an
+    /// application touches between two formatted integers had evicted
+    /// them. Only the loads are left where the tables can't be
+    /// flushed; see `flushes_tables`
+    Pressure,
+}
+
+impl Context {
+    pub fn name(self) -> &'static str {
+        match self {
+            Context::Standalone => "standalone",
+            Context::Format => "format",
+            Context::Serialize => "serialize",
+            Context::Pressure => "pressure",
+        }
+    }
+}
+
+fn each_u32(input: &Input, mut f: impl FnMut(u32)) {
+    match input {
+        Input::Sweep => {
+            for i in 1..=u32::MAX {
+                f(i);
+            }
+        }
+        Input::Permuted(p) => {
+            for i in 0..p.size() {
+                let x = p.at(i) as u32;
+                if x != 0 {
+                    f(x);
+                }
+            }
+        }
+        Input::Strided { permutation, stride } => {
+            let len = permutation.map_or(u32::MAX as u64, |p| p.size());
+            for i in (0..len).step_by(*stride as usize) {
+                let x = match permutation {
+                    Some(p) => p.at(i) as u32,
+                    None => (i + 1) as u32,
+                };
+                if x != 0 {
+                    f(x);
+                }
+            }
+        }
+        Input::U32 { values, passes } => {
+            for _ in 0..*passes {
+                for &x in values {
+                    f(x);
+                }
+            }
+        }
+        Input::U64 { .. } => unreachable!("u64 input for a u32 algorithm"),
+    }
+}
+
+fn each_u64(input: &Input, mut f: impl FnMut(u64)) {
+    match input {
+        Input::Sweep => {
+            for i in 1..=u32::MAX as u64 {
+                f(i);
+            }
+        }
+        Input::Permuted(p) => {
+            for i in 0..p.size() {
+                let x = p.at(i);
+                if x != 0 {
+                    f(x);
+                }
+            }
+        }
+        Input::Strided { permutation, stride } => {
+            let len = permutation.map_or(u32::MAX as u64, |p| p.size());
+            for i in (0..len).step_by(*stride as usize) {
+                let x = match permutation {
+                    Some(p) => p.at(i),
+                    None => i + 1,
+                };
+                if x != 0 {
+                    f(x);
+                }
+            }
+        }
+        Input::U64 { values, passes } => {
+            for _ in 0..*passes {
+                for &x in values {
+                    f(x);
+                }
+            }
+        }
+        Input::U32 { .. } => unreachable!("u32 input for a u64 algorithm"),
+    }
+}
+
+/// `f` of each value into `out`, for `Algorithm::eval_all`.
+#[inline(always)]
+pub fn eval_u32<F>(values: &[u32], out: &mut [u32], f: F)
+where
+    F: Fn(u32) -> u32,
+{
+    for (&x, o) in values.iter().zip(out) {
+        *o = f(x);
+    }
+}
+
+/// As `eval_u32`, each value widened.
+#[inline(always)]
+pub fn eval_u64<F>(values: &[u32], out: &mut [u32], f: F)
+where
+    F: Fn(u64) -> u32,
+{
+    eval_u32(values, out, |x| f(x as u64))
+}
+
+/// Calls `f` on every value of `input`.
+pub fn run_u32<F>(input: &Input, f: F)
+where
+    F: Fn(u32) -> u32,
+{
+    each_u32(input, |x| {
+        std::hint::black_box(f(x));
+    });
+}
+
+/// Calls `f` on every value of `input`.
+pub fn run_u64<F>(input: &Input, f: F)
+where
+    F: Fn(u64) -> u32,
+{
+    each_u64(input, |x| {
+        std::hint::black_box(f(x));
+    });
+}
+
+// Values per call of a slice implementation in `run_u64_slices`: enough
+// for the vector loop to dominate, few enough to stay in L1.
+const SLICE_LEN: usize = 256;
+
+/// Calls `f` on the values of `input` a slice of up to 256 at a time,
+/// as an encoder sizing a batch would.
+pub fn run_u32_slices<F>(input: &Input, f: F)
+where
+    F: Fn(&[u32], &mut [u32]),
+{
+    let mut values = [0u32; SLICE_LEN];
+    let mut out = [0u32; SLICE_LEN];
+    let mut len = 0;
+    each_u32(input, |x| {
+        values[len] = x;
+        len += 1;
+        if len == SLICE_LEN {
+            f(&values, &mut out);
+            std::hint::black_box(&out);
+            len = 0;
+        }
+    });
+    f(&values[..len], &mut out[..len]);
+    std::hint::black_box(&out);
+}
+
+/// As `run_u32_slices`.
+pub fn run_u64_slices<F>(input: &Input, f: F)
+where
+    F: Fn(&[u64], &mut [u32]),
+{
+    let mut values = [0u64; SLICE_LEN];
+    let mut out = [0u32; SLICE_LEN];
+    let mut len = 0;
+    each_u64(input, |x| {
+        values[len] = x;
+        len += 1;
+        if len == SLICE_LEN {
+            f(&values, &mut out);
+            std::hint::black_box(&out);
+            len = 0;
+        }
+    });
+    f(&values[..len], &mut out[..len]);
+    std::hint::black_box(&out);
+}
+
+/// Formats every value of `input` as decimal, using `f` for the digit
+/// count as `write_u64` does, so that the timing includes whatever the
+/// formatting loop costs `f` once they are inlined together.
+pub fn format_u32<F>(input: &Input, f: F)
+where
+    F: Fn(u32) -> u32,
+{
+    let mut buf = [0u8; 10];
+    each_u32(input, |mut x| {
+        let len = f(x) as usize + 1;
+        for b in buf[..len].iter_mut().rev() {
+            *b = b'0' + (x % 10) as u8;
+            x /= 10;
+        }
+        std::hint::black_box(&buf[..len]);
+    });
+}
+
+/// As `format_u32`.
+pub fn format_u64<F>(input: &Input, f: F)
+where
+    F: Fn(u64) -> u32,
+{
+    let mut buf = [0u8; 20];
+    each_u64(input, |mut x| {
+        let len = f(x) as usize + 1;
+        for b in buf[..len].iter_mut().rev() {
+            *b = b'0' + (x % 10) as u8;
+            x /= 10;
+        }
+        std::hint::black_box(&buf[..len]);
+    });
+}
+
+// Appends `x` and a comma at `out[pos..]`, `len` being its digit count,
+// and returns the position after them.
+#[inline(always)]
+fn append(out: &mut [u8], pos: usize, mut x: u64, len: usize) -> usize {
+    for b in out[pos..pos + len].iter_mut().rev() {
+        *b = b'0' + (x % 10) as u8;
+        x /= 10;
+    }
+    out[pos + len] = b',';
+    pos + len + 1
+}
+
+// Each value is appended to a buffer of this many entries of the
+// longest value and a comma, which is handed to `black_box` and
+// reused once full; random inputs are a single buffer each pass.
+const SERIALIZE_VALUES: usize = RANDOM_VALUES;
+
+/// Writes every value of `input` into one text buffer, comma
+/// separated, using `f` for the digit counts; see `Context::Serialize`.
+pub fn serialize_u32<F>(input: &Input, f: F)
+where
+    F: Fn(u32) -> u32,
+{
+    let mut out = vec![0u8; SERIALIZE_VALUES * 11];
+    let mut pos = 0;
+    each_u32(input, |x| {
+        if pos > out.len() - 11 {
+            std::hint::black_box(&out[..pos]);
+            pos = 0;
+        }
+        pos = append(&mut out, pos, x as u64, f(x) as usize + 1);
+    });
+    std::hint::black_box(&out[..pos]);
+}
+
+/// As `serialize_u32`.
+pub fn serialize_u64<F>(input: &Input, f: F)
+where
+    F: Fn(u64) -> u32,
+{
+    let mut out = vec![0u8; SERIALIZE_VALUES * 21];
+    let mut pos = 0;
+    each_u64(input, |x| {
+        if pos > out.len() - 21 {
+            std::hint::black_box(&out[..pos]);
+            pos = 0;
+        }
+        pos = append(&mut out, pos, x, f(x) as usize + 1);
+    });
+    std::hint::black_box(&out[..pos]);
+}
+
+// 2^22 u64s, 32 MiB: past the last-level cache of most machines the
+// benchmarks run on.
+const WORKING_SET_LEN: usize = 1 << 22;
+// u64s per 64-byte cache line.
+const LINE: usize = 8;
+
+/// Few enough calls per run for `Context::Pressure`, whose loads and
+/// flushes cost far more than the ilog10 each one sits beside, to be
+/// timed in about as long as the other contexts take.
+pub const PRESSURE_OPS: u64 = 1 << 21;
+
+// A random cycle through every cache line of the working set, each
+// line's first u64 holding the index of the next, so that each load
+// waits for the one before and the prefetcher can't guess which line
+// comes next. Writing every line also makes its pages really there
+// rather than all mapped to the one zero page. Built once, outside the
+// timed loops.
+fn working_set() -> &'static [u64] {
+    static SET: OnceLock<Vec<u64>> = OnceLock::new();
+    SET.get_or_init(|| {
+        let lines = WORKING_SET_LEN / LINE;
+        let mut order: Vec<usize> = (0..lines).collect();
+        order.shuffle(&mut rand::rng());
+        let mut set = vec![0u64; WORKING_SET_LEN];
+        for (i, &line) in order.iter().enumerate() {
+            set[line * LINE] = (order[(i + 1) % lines] * LINE) as u64;
+        }
+        set
+    })
+}
+
+// The address of every cache line of the lookup tables, which have one
+// address each where they are statics; see src/statics.rs.
+#[cfg(all(ilog_const_statics, any(target_arch = "x86_64", target_arch = "aarch64")))]
+fn table_lines() -> &'static [usize] {
+    static LINES: OnceLock<Vec<usize>> = OnceLock::new();
+    LINES.get_or_init(|| {
+        let mut lines: Vec<usize> = crate::statics::extents()
+            .flat_map(|(start, len)| ((start & !63)..start + len).step_by(64))
+            .collect();
+        lines.sort_unstable();
+        lines.dedup();
+        lines
+    })
+}
+
+#[cfg(not(all(ilog_const_statics, any(target_arch = "x86_64", target_arch = "aarch64"))))]
+fn table_lines() -> &'static [usize] {
+    &[]
+}
+
+// Whether this x86_64 has clflushopt, which evicts a list of lines
+// more than ten times as fast as clflush, which waits for each line
+// to go before starting on the next.
+#[cfg(target_arch = "x86_64")]
+fn has_clflushopt() -> bool {
+    static HAS: OnceLock<bool> = OnceLock::new();
+    // Leaf 7, where leaf 0 says there is one, bit 23 of ebx.
+    *HAS.get_or_init(|| {
+        use std::arch::x86_64::__cpuid_count;
+        __cpuid_count(0, 0).eax >= 7 && __cpuid_count(7, 0).ebx & (1 << 23) != 0
+    })
+}
+
+// Evicts each of `lines` from every level of the cache.
+#[cfg(target_arch = "x86_64")]
+#[inline(always)]
+fn flush(lines: &[usize]) {
+    use std::arch::x86_64::{_mm_clflush, _mm_mfence};
+    // SAFETY: clflush is part of SSE2, which every x86_64 has, the
+    // cpuid bit says whether there's clflushopt, and the lines are
+    // mapped.
+    unsafe {
+        if has_clflushopt() {
+            for &line in lines {
+                std::arch::asm!("clflushopt [{}]", in(reg) line, options(nostack, preserves_flags));
+            }
+        } else {
+            for &line in lines {
+                _mm_clflush(line as *const u8);
+            }
+        }
+        _mm_mfence();
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+#[inline(always)]
+fn flush(lines: &[usize]) {
+    // SAFETY: Linux lets user code clean and invalidate by address, and
+    // the lines are mapped.
+    unsafe {
+        for &line in lines {
+            std::arch::asm!("dc civac, {}", in(reg) line, options(nostack, preserves_flags));
+        }
+        std::arch::asm!("dsb ish", options(nostack, preserves_flags));
+    }
+}
+
+#[cfg(not(any(target_arch = "x86_64", target_arch = "aarch64")))]
+#[inline(always)]
+fn flush(_: &[usize]) {}
+
+/// Whether `Context::Pressure` can evict the lookup tables, which
+/// takes x86_64 or aarch64 and the tables as statics (Rust 1.83 on),
+/// rather than only chasing pointers through the working set.
+pub fn flushes_tables() -> bool {
+    !table_lines().is_empty()
+}
+
+/// Builds the working set of `Context::Pressure` and finds the tables,
+/// so that a run that uses them doesn't time that too.
+pub fn prepare(context: Context) {
+    if context == Context::Pressure {
+        working_set();
+        table_lines();
+        #[cfg(target_arch = "x86_64")]
+        has_clflushopt();
+    }
+}
+
+/// Calls `f` on every value of `input`, each call after a dependent
+/// load from a random line of the working set and with the lookup
+/// tables flushed from the cache; see `Context::Pressure`. `f` must
+/// return less than 2^31, as an ilog10 does.
+pub fn pressure_u32<F>(input: &Input, f: F)
+where
+    F: Fn(u32) -> u32,
+{
+    let (set, tables) = (working_set(), table_lines());
+    let mut at = 0;
+    each_u32(input, |x| {
+        at = set[at] as usize;
+        flush(tables);
+        // Each call waits for the load, and the next load for the call,
+        // through bits that are always zero but that the compiler can't
+        // know are.
+        let digits = std::hint::black_box(f(x | (at >> 63) as u32));
+        at |= (digits >> 31) as usize;
+    });
+}
+
+/// As `pressure_u32`.
+pub fn pressure_u64<F>(input: &Input, f: F)
+where
+    F: Fn(u64) -> u32,
+{
+    let (set, tables) = (working_set(), table_lines());
+    let mut at = 0;
+    each_u64(input, |x| {
+        at = set[at] as usize;
+        flush(tables);
+        let digits = std::hint::black_box(f(x | (at >> 63) as u64));
+        at |= (digits >> 31) as usize;
+    });
+}
@@ -0,0 +1,73 @@
+//! The u128 values worth testing an ilog10 on, for widths where no
+//! sweep or random sample comes near the thresholds: 2^128 values hold
+//! only 39 powers of ten. The values within `k` of each of these:
+//!
+//! - every power of ten, where the answer changes;
+//! - every power of two, where the ilog2 and so the guess changes;
+//! - every power of ten times 2, 4 and 8, once each for every ilog2
+//!   between two powers of ten;
+//! - 0 and `u128::MAX`.
+//!
+//! A guess-and-correct ilog10's answer changes only at the powers of
+//! ten and its guess only at the powers of two, so a comparison that
+//! is off by one shows up within a value or two of one of them. The
+//! values come
+//! in that order, with no duplicates removed, and without allocating,
+//! so that a test, a fuzzer's seed corpus or a bounded model checker's
+//! harness can each take them as they are, and narrower widths can
+//! filter them. A model checker can take `centre_u128` of a symbolic
+//! index below `CENTRES` instead, plus a symbolic offset.
+//!
+//! ```
+//! let values: Vec<u128> = ilog::boundary::near_thresholds_u128(1).collect();
+//! assert!(values.contains(&(10u128.pow(38) - 1)));
+//! assert!(values.contains(&((1 << 100) + 1)));
+//! assert!(values.contains(&(8_000 - 1)));
+//! assert!(values.contains(&u128::MAX));
+//! ```
+
+use core::ops::RangeInclusive;
+
+const TENS: usize = 39;
+const TWOS: usize = 128;
+// 2 * 10^38 still fits; 4 and 8 times it don't.
+const PRODUCTS: usize = 3 * TENS - 2;
+
+/// The number of values `centre_u128` takes, for a model checker's
+/// bound on its index.
+pub const CENTRES: usize = TENS + TWOS + PRODUCTS + 2;
+
+/// The `i`th threshold of `near_thresholds_u128`, for `i < CENTRES`:
+/// 10^i, then 2^i, then each 10^i times 2, 4 and 8, then 0 and
+/// `u128::MAX`.
+///
+/// Panics if `i >= CENTRES`.
+pub const fn centre_u128(i: usize) -> u128 {
+    if i < TENS {
+        10u128.pow(i as u32)
+    } else if i < TENS + TWOS {
+        1 << (i - TENS)
+    } else if i < TENS + TWOS + PRODUCTS {
+        let j = i - TENS - TWOS;
+        10u128.pow((j / 3) as u32) << (j % 3 + 1)
+    } else if i == CENTRES - 2 {
+        0
+    } else if i == CENTRES - 1 {
+        u128::MAX
+    } else {
+        panic!("index out of range")
+    }
+}
+
+const _: () = assert!(centre_u128(TENS + TWOS + PRODUCTS - 1) == 2 * 10u128.pow(38));
+
+/// The values within `k` of each of `centre_u128(0..CENTRES)`, in that
+/// order, clipped to the u128 range.
+pub fn near_thresholds_u128(k: u128) -> impl Iterator<Item = u128> + Clone {
+    (0..CENTRES).flat_map(move |i| around(centre_u128(i), k))
+}
+
+// centre - k to centre + k, clipped.
+fn around(centre: u128, k: u128) -> RangeInclusive<u128> {
+    centre.saturating_sub(k)..=centre.saturating_add(k)
+}
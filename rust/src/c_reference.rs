@@ -0,0 +1,148 @@
+//! The C implementations in `csrc/reference.c`, and those in
+//! `csrc/generated.c` that `ilog codegen c` writes, enabled by the
+//! `c-reference` feature and registered alongside the Rust ones.
+//!
+//! Standalone benchmarks call the C file's own loops, so the C
+//! compiler's code for the whole loop is what gets timed. In the
+//! formatting context the Rust loop calls them across the FFI
+//! boundary, since they can't be inlined into it.
+
+use crate::bench::{
+    eval_u32, eval_u64, format_u32, format_u64, pressure_u32, pressure_u64, run_u32, run_u64,
+    serialize_u32, serialize_u64, Input,
+};
+use crate::registry::{Algorithm, Width};
+
+extern "C" {
+    fn ref_ilog10_hd(x: u32) -> u32;
+    fn ref_ilog10_hd_run(values: *const u32, len: usize, passes: usize);
+    fn ref_ilog10_hd_sweep();
+    fn ref_ilog10_clz_table(x: u32) -> u32;
+    fn ref_ilog10_clz_table_run(values: *const u32, len: usize, passes: usize);
+    fn ref_ilog10_clz_table_sweep();
+    fn ref_ilog10_ladder(x: u32) -> u32;
+    fn ref_ilog10_ladder_run(values: *const u32, len: usize, passes: usize);
+    fn ref_ilog10_ladder_sweep();
+    fn ref_ilog10_u64_mul(x: u64) -> u32;
+    fn ref_ilog10_u64_mul_run(values: *const u64, len: usize, passes: usize);
+    fn ref_ilog10_u64_mul_sweep();
+    fn ilog10_u32_mul(x: u32) -> u32;
+    fn ilog10_u32_mul_run(values: *const u32, len: usize, passes: usize);
+    fn ilog10_u32_mul_sweep();
+    fn ilog10_u32_popcount(x: u32) -> u32;
+    fn ilog10_u32_popcount_run(values: *const u32, len: usize, passes: usize);
+    fn ilog10_u32_popcount_sweep();
+    fn ilog10_u32_table(x: u32) -> u32;
+    fn ilog10_u32_table_run(values: *const u32, len: usize, passes: usize);
+    fn ilog10_u32_table_sweep();
+    fn ilog10_u64_mul(x: u64) -> u32;
+    fn ilog10_u64_mul_run(values: *const u64, len: usize, passes: usize);
+    fn ilog10_u64_mul_sweep();
+}
+
+// None of the C functions handle 0, so, as in the registry, each is
+// given `x | 1`, and the C loops do the same.
+//
+// SAFETY (for all of the below): the C functions read exactly `len`
+// values from `values`, and are never passed 0.
+macro_rules! c_algorithm {
+    ($name:expr, U32, $f:ident, $run:ident, $sweep:ident) => {
+        Algorithm {
+            name: $name,
+            width: Width::U32,
+            eval: |x| unsafe { $f(x as u32 | 1) },
+            eval_all: |values, out| eval_u32(values, out, |x| unsafe { $f(x | 1) }),
+            run: |input| match input {
+                Input::Sweep => unsafe { $sweep() },
+                // The C file has no permutation or stride, so the Rust
+                // loop calls it.
+                Input::Permuted(_) | Input::Strided { .. } => run_u32(input, |x| unsafe { $f(x | 1) }),
+                Input::U32 { values, passes } => unsafe {
+                    $run(values.as_ptr(), values.len(), *passes)
+                },
+                Input::U64 { .. } => unreachable!("u64 input for a u32 algorithm"),
+            },
+            format: |input| format_u32(input, |x| unsafe { $f(x | 1) }),
+            serialize: |input| serialize_u32(input, |x| unsafe { $f(x | 1) }),
+            pressure: |input| pressure_u32(input, |x| unsafe { $f(x | 1) }),
+        }
+    };
+    ($name:expr, U64, $f:ident, $run:ident, $sweep:ident) => {
+        Algorithm {
+            name: $name,
+            width: Width::U64,
+            eval: |x| unsafe { $f(x | 1) },
+            eval_all: |values, out| eval_u64(values, out, |x| unsafe { $f(x | 1) }),
+            run: |input| match input {
+                Input::Sweep => unsafe { $sweep() },
+                Input::Permuted(_) | Input::Strided { .. } => run_u64(input, |x| unsafe { $f(x | 1) }),
+                Input::U64 { values, passes } => unsafe {
+                    $run(values.as_ptr(), values.len(), *passes)
+                },
+                Input::U32 { .. } => unreachable!("u32 input for a u64 algorithm"),
+            },
+            format: |input| format_u64(input, |x| unsafe { $f(x | 1) }),
+            serialize: |input| serialize_u64(input, |x| unsafe { $f(x | 1) }),
+            pressure: |input| pressure_u64(input, |x| unsafe { $f(x | 1) }),
+        }
+    };
+}
+
+pub const HD: Algorithm = c_algorithm!(
+    "c_hd",
+    U32,
+    ref_ilog10_hd,
+    ref_ilog10_hd_run,
+    ref_ilog10_hd_sweep
+);
+pub const CLZ_TABLE: Algorithm = c_algorithm!(
+    "c_clz_table",
+    U32,
+    ref_ilog10_clz_table,
+    ref_ilog10_clz_table_run,
+    ref_ilog10_clz_table_sweep
+);
+pub const LADDER: Algorithm = c_algorithm!(
+    "c_ladder",
+    U32,
+    ref_ilog10_ladder,
+    ref_ilog10_ladder_run,
+    ref_ilog10_ladder_sweep
+);
+pub const U64_MUL: Algorithm = c_algorithm!(
+    "c_mul",
+    U64,
+    ref_ilog10_u64_mul,
+    ref_ilog10_u64_mul_run,
+    ref_ilog10_u64_mul_sweep
+);
+
+// The output of `ilog codegen c`, csrc/generated.c.
+pub const GEN_MUL: Algorithm = c_algorithm!(
+    "c_gen_mul",
+    U32,
+    ilog10_u32_mul,
+    ilog10_u32_mul_run,
+    ilog10_u32_mul_sweep
+);
+pub const GEN_POPCOUNT: Algorithm = c_algorithm!(
+    "c_gen_popcount",
+    U32,
+    ilog10_u32_popcount,
+    ilog10_u32_popcount_run,
+    ilog10_u32_popcount_sweep
+);
+pub const GEN_TABLE: Algorithm = c_algorithm!(
+    "c_gen_table",
+    U32,
+    ilog10_u32_table,
+    ilog10_u32_table_run,
+    ilog10_u32_table_sweep
+);
+pub const GEN_U64_MUL: Algorithm = c_algorithm!(
+    "c_gen_mul",
+    U64,
+    ilog10_u64_mul,
+    ilog10_u64_mul_run,
+    ilog10_u64_mul_sweep
+);
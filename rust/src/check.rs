@@ -0,0 +1,271 @@
+// Differential checking of a candidate against a reference. Rather
+// than asserting (and panicking out of a rayon worker on the first
+// mismatch), every input is checked and the smallest failing input
+// is kept as a counterexample for the report.
+
+use std::cell::Cell;
+use std::time::Instant;
+
+use ilog::bench::Permutation;
+use ilog::results::{CheckResult, Counterexample, WorkerStats};
+use rayon::prelude::*;
+
+use crate::report::Report;
+
+thread_local! {
+    // Inputs checked on this thread and the seconds it took, since the
+    // last `workers`.
+    static WORK: Cell<(u64, f64)> = const { Cell::new((0, 0.0)) };
+}
+
+fn record(values: u64, seconds: f64) {
+    WORK.with(|work| {
+        let (v, s) = work.get();
+        work.set((v + values, s + seconds));
+    });
+}
+
+/// What each worker of the rayon pool has checked since the last call,
+/// over `wall_seconds` of the run. A worker far below the others in
+/// `values_per_second` is being throttled or sharing its core; workers
+/// well below a `busy_share` of 1 were waiting for work.
+pub fn workers(wall_seconds: f64) -> Vec<WorkerStats> {
+    let mut stats: Vec<WorkerStats> = rayon::broadcast(|ctx| {
+        let (values, busy_seconds) = WORK.with(Cell::take);
+        WorkerStats {
+            worker: ctx.index(),
+            values,
+            busy_seconds,
+            values_per_second: values as f64 / busy_seconds.max(f64::MIN_POSITIVE),
+            busy_share: busy_seconds / wall_seconds,
+        }
+    });
+    stats.sort_by_key(|s| s.worker);
+    stats
+}
+
+/// The `workers` report.
+pub fn workers_report(stats: &[WorkerStats]) -> Report {
+    let mut report = Report::new(
+        "test workers",
+        &["worker", "values", "busy_seconds", "values_per_second", "busy_share"],
+    );
+    for s in stats {
+        report.push(s);
+    }
+    report
+}
+
+pub fn check<I, C, R>(
+    name: &str,
+    algorithm: &str,
+    range: &str,
+    inputs: I,
+    candidate: C,
+    reference: R,
+) -> CheckResult
+where
+    I: ParallelIterator<Item = u64>,
+    C: Fn(u64) -> u32 + Sync,
+    R: Fn(u64) -> u32 + Sync,
+{
+    eprintln!("Testing {name}");
+    let start = Instant::now();
+    // Each run of the fold is one thread's, so its count and time are
+    // recorded for that thread as it ends.
+    let (checked, failures, first) = inputs
+        .fold(
+            || (Instant::now(), (0u64, 0u64, u64::MAX)),
+            |(started, (checked, failures, first)), x| {
+                let bad = candidate(x) != reference(x);
+                let first = if bad { first.min(x) } else { first };
+                (started, (checked + 1, failures + bad as u64, first))
+            },
+        )
+        .map(|(started, counts)| {
+            record(counts.0, started.elapsed().as_secs_f64());
+            counts
+        })
+        .reduce(
+            || (0, 0, u64::MAX),
+            |a, b| (a.0 + b.0, a.1 + b.1, a.2.min(b.2)),
+        );
+    let counterexample = (failures > 0).then(|| Counterexample {
+        input: first,
+        expected: reference(first),
+        actual: candidate(first),
+    });
+    CheckResult {
+        test: name.to_string(),
+        algorithm: algorithm.to_string(),
+        range: range.to_string(),
+        checked,
+        failures,
+        counterexample,
+        seconds: start.elapsed().as_secs_f64(),
+    }
+}
+
+// Values per block of a `sweep`: two 16 KiB buffers of results and one
+// of values, around L1.
+const BLOCK: usize = 1 << 12;
+
+/// As `check` against the ilog10 of every nonzero u32, in the order of
+/// `order` if there is one, a block at a time: `candidate` fills in
+/// the results for a block of values, so a vector kernel can take 8 or
+/// 16 at a time, and a vectorized reference checks them all at once.
+pub fn sweep<C>(
+    name: &str,
+    algorithm: &str,
+    range: &str,
+    order: Option<&Permutation>,
+    candidate: C,
+) -> CheckResult
+where
+    C: Fn(&[u32], &mut [u32]) + Sync,
+{
+    eprintln!("Testing {name}");
+    let start = Instant::now();
+    let blocks = (1u64 << 32) / BLOCK as u64;
+    let (checked, failures, first) = (0..blocks)
+        .into_par_iter()
+        .map(|b| {
+            let started = Instant::now();
+            let mut values = [0u32; BLOCK];
+            let indices = b * BLOCK as u64..(b + 1) * BLOCK as u64;
+            let mut zero = false;
+            for (v, i) in values.iter_mut().zip(indices) {
+                *v = order.map_or(i, |p| p.at(i)) as u32;
+                zero |= *v == 0;
+            }
+            // The one 0 goes to the front, to be left out.
+            if zero {
+                let z = values.iter().position(|&x| x == 0).unwrap();
+                values.swap(0, z);
+            }
+            let values = &values[zero as usize..];
+            let len = values.len();
+            let (mut actual, mut expected) = ([0u32; BLOCK], [0u32; BLOCK]);
+            // Split each block at a different point, so that over the
+            // blocks every residue mod 16 lands in every lane of a 4-,
+            // 8- or 16-lane kernel, and the kernels' tails get checked.
+            let split = (b % 16) as usize;
+            candidate(&values[..split], &mut actual[..split]);
+            candidate(&values[split..], &mut actual[split..len]);
+            reference_ilog10(values, &mut expected[..len]);
+            let (actual, expected) = (&actual[..len], &expected[..len]);
+            // All the lanes at once, and one at a time only on a failure.
+            let any = actual.iter().zip(expected).fold(0, |any, (a, e)| any | (a ^ e));
+            // The smallest failing input and what the candidate made
+            // of it there, in its lane: run alone it might take a
+            // different path.
+            let (mut failures, mut first) = (0, (u64::MAX, 0));
+            if any != 0 {
+                for (&x, (&a, e)) in values.iter().zip(actual.iter().zip(expected)) {
+                    if a != *e {
+                        failures += 1;
+                        first = first.min((x as u64, a));
+                    }
+                }
+            }
+            record(len as u64, started.elapsed().as_secs_f64());
+            (len as u64, failures, first)
+        })
+        .reduce(
+            || (0, 0, (u64::MAX, 0)),
+            |a, b| (a.0 + b.0, a.1 + b.1, a.2.min(b.2)),
+        );
+    let counterexample = (failures > 0).then(|| Counterexample {
+        input: first.0,
+        expected: first.0.ilog10(),
+        actual: first.1,
+    });
+    CheckResult {
+        test: name.to_string(),
+        algorithm: algorithm.to_string(),
+        range: range.to_string(),
+        checked,
+        failures,
+        counterexample,
+        seconds: start.elapsed().as_secs_f64(),
+    }
+}
+
+/// A scalar candidate for `sweep`.
+pub fn each<F>(f: F) -> impl Fn(&[u32], &mut [u32]) + Sync
+where
+    F: Fn(u32) -> u32 + Sync,
+{
+    move |values, out| {
+        for (&x, o) in values.iter().zip(out) {
+            *o = f(x);
+        }
+    }
+}
+
+// floor(log10(x)) of nonzero x by the definition, the number of powers
+// of ten it reaches. With no table or branch the loop vectorizes, and
+// it is compiled again for the widest vectors this CPU has.
+fn reference_ilog10(values: &[u32], out: &mut [u32]) {
+    #[cfg(target_arch = "x86_64")]
+    {
+        if is_x86_feature_detected!("avx512f") {
+            // SAFETY: just checked.
+            return unsafe { reference_avx512(values, out) };
+        }
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: just checked.
+            return unsafe { reference_avx2(values, out) };
+        }
+    }
+    ladder(values, out)
+}
+
+#[inline(always)]
+fn ladder(values: &[u32], out: &mut [u32]) {
+    const POWERS: [u32; 9] = [
+        10,
+        100,
+        1_000,
+        10_000,
+        100_000,
+        1_000_000,
+        10_000_000,
+        100_000_000,
+        1_000_000_000,
+    ];
+    for (&x, o) in values.iter().zip(out) {
+        *o = POWERS.iter().map(|&p| (x >= p) as u32).sum();
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx512f")]
+unsafe fn reference_avx512(values: &[u32], out: &mut [u32]) {
+    ladder(values, out)
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+unsafe fn reference_avx2(values: &[u32], out: &mut [u32]) {
+    ladder(values, out)
+}
+
+pub fn checks_report(checks: &[CheckResult]) -> Report {
+    let mut report = Report::new(
+        "test",
+        &[
+            "test",
+            "algorithm",
+            "range",
+            "checked",
+            "failures",
+            "counterexample",
+            "seconds",
+        ],
+    );
+    for c in checks {
+        report.push(c);
+    }
+    report
+}
@@ -0,0 +1,252 @@
+// Structural checks on the release codegen, for `ilog test --codegen`.
+// The comments in lib.rs make claims like "the table access is
+// unchecked" and "x | 1 ... without a branch"; these keep a refactor
+// or a compiler upgrade from quietly making them untrue.
+//
+// ilog2 panics on 0, so the functions built on it keep one branch to
+// that panic. It is predicted not taken and off the fast path, so it
+// is allowed; a branch anywhere else is not.
+
+use std::time::Instant;
+
+use ilog::results::CheckResult;
+
+use crate::disasm::{Function, Instruction, Kind};
+use crate::filter::Filter;
+
+struct Property {
+    description: &'static str,
+    /// Only checked on targets starting with this, if given
+    target: Option<&'static str>,
+    functions: &'static [&'static str],
+    /// The number of instructions breaking the property
+    violations: fn(&[Instruction]) -> usize,
+}
+
+const PROPERTIES: &[Property] = &[
+    Property {
+        description: "branch-free",
+        target: None,
+        functions: &[
+            "ilog10",
+            "ilog10_swar",
+            "ilog10_u64_swar",
+            "soft_clz::ilog10_debruijn",
+            "soft_clz::ilog10_u64_debruijn",
+            "soft_clz::ilog10_bytes",
+            "soft_clz::ilog10_u64_bytes",
+            "constant_time::ilog10_u32",
+            "constant_time::ilog10_u64",
+            "float::ilog10_f32",
+            "float::ilog10_f64",
+            "float::ilog10_u64_f64",
+            "digits_u32",
+            "digits_u64",
+            "digits_u32x4",
+            "digits_u64x2",
+            "digits_and_pow10_u32",
+            "digits_and_pow10_u64",
+            "leading_digit_u32",
+            "leading_digit_u64",
+            "leading_n_digits_u32",
+            "leading_n_digits_u64",
+            "si_scale_u32",
+            "si_scale_u64",
+            "asm::ilog10_u32",
+            "asm::ilog10_u64",
+        ],
+        violations: |code| {
+            code.iter()
+                .filter(|i| i.kind == Kind::ConditionalJump)
+                .count()
+        },
+    },
+    Property {
+        description: "branches only to the panic for 0",
+        target: None,
+        functions: &[
+            "ilog10_mul",
+            "ilog10_mul_alt",
+            "log10_table_table",
+            "ilog10_u64_mul",
+            "ilog10_mul_pow",
+            "ilog10_u64_mul_pow",
+            "ilog10_mul_pow4",
+            "ilog10_u64_mul_pow4",
+            "ilog10_mul_packed",
+            "ilog10_u64_mul_packed",
+            "ilog10_u64_mul20",
+            "ilog2_and_ilog10_u32",
+            "ilog2_and_ilog10_u64",
+            "ilog10_with_flag",
+            "ilog10_u64_with_flag",
+            "ilog1000_u32",
+            "ilog1000_u64",
+            "ilog10_product",
+            "lowering::ilog10_cmov",
+            "lowering::ilog10_sub_shift",
+        ],
+        violations: |code| {
+            let cold = code
+                .iter()
+                .find(|i| i.kind == Kind::Return)
+                .map_or(u64::MAX, |i| i.offset);
+            code.iter()
+                .filter(|i| i.kind == Kind::ConditionalJump)
+                .filter(|i| i.branch_target().is_none_or(|t| t <= cold))
+                .count()
+        },
+    },
+    Property {
+        description: "no bounds check",
+        target: None,
+        functions: &[
+            "ilog10",
+            "ilog10_mul",
+            "ilog10_mul_alt",
+            "log10_table_table",
+            "ilog10_u64_mul",
+            "ilog10_mul_pow4",
+            "ilog10_u64_mul_pow4",
+            "ilog10_mul_packed",
+            "ilog10_u64_mul_packed",
+            "ilog10_u64_mul20",
+            "arm32::ilog10_u64_split",
+        ],
+        violations: |code| {
+            code.iter()
+                .filter(|i| {
+                    i.target
+                        .as_deref()
+                        .is_some_and(|t| t.contains("panic_bounds_check"))
+                })
+                .count()
+        },
+    },
+    Property {
+        description: "no table loads",
+        target: None,
+        functions: &[
+            "ilog10_mul_pow",
+            "ilog10_u64_mul_pow",
+            "ilog10_swar",
+            "ilog10_u64_swar",
+        ],
+        // The panic's location is data too, but only the cold path
+        // after the return loads it.
+        violations: |code| {
+            code.iter()
+                .take_while(|i| i.kind != Kind::Return)
+                .filter(|i| i.data.is_some())
+                .count()
+        },
+    },
+    // What a load costs can depend on its address, so an address
+    // computed from the input could time the input. The stack (the
+    // barrier's store and load) and constants at fixed addresses are
+    // the same for every input.
+    Property {
+        description: "no loads but from the stack and constants",
+        target: None,
+        functions: &["constant_time::ilog10_u32", "constant_time::ilog10_u64"],
+        violations: input_addressed_loads,
+    },
+    Property {
+        description: "no multiply",
+        target: None,
+        functions: &["mcu::ilog10_ladder", "mcu::ilog10_u64_ladder"],
+        violations: |code| {
+            const MULTIPLIES: [&str; 7] = ["mul", "imul", "umul", "smul", "madd", "msub", "mla"];
+            code.iter()
+                .filter(|i| MULTIPLIES.iter().any(|m| i.text.starts_with(m)))
+                .count()
+        },
+    },
+    // ilog2_u32 in lib.rs swaps in the de Bruijn ilog2 where there is
+    // no clz; LLVM's own expansion takes these to 40 and more.
+    Property {
+        description: "at most 36 instructions on the fast path without Zbb",
+        target: Some("riscv"),
+        functions: &["ilog10_mul", "ilog10_u64_mul", "digits_u32", "digits_u64"],
+        violations: |code| {
+            code.iter()
+                .take_while(|i| i.kind != Kind::Return)
+                .count()
+                .saturating_sub(36)
+        },
+    },
+    // Of the code between returns, some must leave out the sbcs of a
+    // two-word compare: the path for values that fit in u32.
+    Property {
+        description: "a return path without a 64-bit compare",
+        target: Some("thumb"),
+        functions: &["arm32::ilog10_u64_split"],
+        violations: |code| {
+            let mut paths = code.split_inclusive(|i| i.kind == Kind::Return);
+            let narrow = paths.any(|path| {
+                path.last().is_some_and(|i| i.kind == Kind::Return)
+                    && !path.iter().any(|i| i.text.starts_with("sbc"))
+            });
+            (!narrow) as usize
+        },
+    },
+];
+
+// Loads and stores at addresses other than the stack's or a constant's,
+// on every target's syntax: `[rsp + 8]`, `[sp, #8]`, `[x9, w8, uxtw #2]`
+// and `8(sp)`. A register copied from the stack pointer (`mov r0, sp`)
+// counts as the stack, and a digit after a bracket is a vector lane,
+// `v0.s[1]`, not an address.
+fn input_addressed_loads(code: &[Instruction]) -> usize {
+    let mut stack = vec!["rsp", "esp", "sp"];
+    let mut count = 0;
+    for i in code {
+        if let Some(("mov", operands)) = i.text.split_once(' ') {
+            if let Some((to, "sp")) = operands.split_once(", ") {
+                stack.push(to);
+            }
+        }
+        let Some(address) = i.text.split_once(['[', '(']).map(|(_, a)| a) else {
+            continue;
+        };
+        let base = address.split([' ', ',', ']', ')']).next().unwrap_or("");
+        if i.data.is_none()
+            && !base.starts_with(|c: char| c.is_ascii_digit())
+            && !stack.contains(&base)
+        {
+            count += 1;
+        }
+    }
+    count
+}
+
+pub fn check_codegen(
+    functions: &[(&Function, Vec<Instruction>)],
+    target: &str,
+    filter: &Filter,
+) -> Vec<CheckResult> {
+    let mut checks = Vec::new();
+    for p in PROPERTIES {
+        if p.target.is_some_and(|t| !target.starts_with(t)) {
+            continue;
+        }
+        for (f, code) in functions {
+            if !p.functions.contains(&f.name)
+                || !filter.matches(&["codegen", f.name, f.algorithm.unwrap_or(f.name)])
+            {
+                continue;
+            }
+            let start = Instant::now();
+            checks.push(CheckResult {
+                test: "codegen".into(),
+                algorithm: f.name.into(),
+                range: format!("{}, {target}", p.description),
+                checked: code.len() as u64,
+                failures: (p.violations)(code) as u64,
+                counterexample: None,
+                seconds: start.elapsed().as_secs_f64(),
+            });
+        }
+    }
+    checks
+}
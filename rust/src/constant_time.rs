@@ -0,0 +1,76 @@
+//! ilog10 for values that must not leak through timing, such as the
+//! length of a secret in a constant-time encoder. Each is the same
+//! instruction sequence whatever the input: one carry out per power of
+//! ten, from adding `2^32 - 10^k` (`2^64 - 10^k` for u64) in a word
+//! twice as wide, summed. There is no ilog2 to start from, no table to
+//! index and no multiply, whose latency depends on the operands on
+//! some small cores. The input goes through a volatile read first, so
+//! that the compiler knows nothing of it to branch on.
+//!
+//! Like `ilog10_swar`, which does the same additions four at a time,
+//! these give 0 for 0. `ilog test --codegen` checks that they build
+//! without branches, or loads other than the barrier's and the
+//! constants', and
+//! `ilog timing` looks for a difference in their running time between
+//! fixed and random inputs.
+//!
+//! ```
+//! use ilog::constant_time;
+//!
+//! assert_eq!(constant_time::ilog10_u32(999), 2);
+//! assert_eq!(constant_time::ilog10_u64(10_000_000_000), 10);
+//! ```
+
+// 10^1 to 10^9, and 10^1 to 10^19.
+const POWERS_U32: [u64; 9] = {
+    let mut powers = [0; 9];
+    let mut p = 10;
+    let mut i = 0;
+    while i < 9 {
+        powers[i] = p;
+        p *= 10;
+        i += 1;
+    }
+    powers
+};
+
+const POWERS_U64: [u128; 19] = {
+    let mut powers = [0; 19];
+    let mut p = 10;
+    let mut i = 0;
+    while i < 19 {
+        powers[i] = p;
+        p *= 10;
+        i += 1;
+    }
+    powers
+};
+
+// `x`, unknown to the optimizer.
+#[inline(always)]
+fn barrier<T: Copy>(x: T) -> T {
+    // SAFETY: a read of a local through a reference to it.
+    unsafe { core::ptr::read_volatile(&x) }
+}
+
+/// floor(log10(x)), in the same instructions for every `x`; 0 gives 0.
+#[inline]
+pub fn ilog10_u32(x: u32) -> u32 {
+    let v = barrier(x) as u64;
+    let mut log = 0;
+    for p in POWERS_U32 {
+        log += ((v + (1 << 32) - p) >> 32) as u32;
+    }
+    log
+}
+
+/// As `ilog10_u32`, for u64.
+#[inline]
+pub fn ilog10_u64(x: u64) -> u32 {
+    let v = barrier(x) as u128;
+    let mut log = 0;
+    for p in POWERS_U64 {
+        log += ((v + (1 << 64) - p) >> 64) as u32;
+    }
+    log
+}
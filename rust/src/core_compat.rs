@@ -0,0 +1,88 @@
+//! The multiply-based versions in the shape of the integer log10 code
+//! in `core`, for trying out a change to the standard library here
+//! before sending it upstream.
+//!
+//! `u32` and `u64` mirror the private helpers in
+//! `core::num::int_log10`, which may assume a nonzero argument, so
+//! either can be pasted over its namesake as is. The remaining
+//! functions mirror the public methods built on them, including
+//! their handling of 0: `None` from the `checked_` forms, and the
+//! standard library's panic from the others. `ilog test` checks all
+//! of them against the real thing.
+
+// NonZeroU32 rather than the NonZero<u32> it now names, which is
+// newer than the `polyfill` feature's toolchains.
+use core::num::{NonZeroU32, NonZeroU64};
+
+/// The message `core` panics with for `0.ilog10()`.
+pub const PANIC_MESSAGE: &str = "argument of integer logarithm must be positive";
+
+/// As `core::num::int_log10::u32`: the caller guarantees `val > 0`.
+#[inline]
+pub const fn u32(val: u32) -> u32 {
+    crate::ilog10_mul(val)
+}
+
+/// As `core::num::int_log10::u64`: the caller guarantees `val > 0`.
+#[inline]
+pub const fn u64(val: u64) -> u32 {
+    crate::ilog10_u64_mul(val)
+}
+
+/// As `NonZero::<u32>::ilog10`.
+#[inline]
+pub const fn nonzero_ilog10_u32(x: NonZeroU32) -> u32 {
+    u32(x.get())
+}
+
+/// As `NonZero::<u64>::ilog10`.
+#[inline]
+pub const fn nonzero_ilog10_u64(x: NonZeroU64) -> u32 {
+    u64(x.get())
+}
+
+/// As `u32::checked_ilog10`.
+#[inline]
+pub const fn checked_ilog10_u32(x: u32) -> Option<u32> {
+    match NonZeroU32::new(x) {
+        Some(x) => Some(nonzero_ilog10_u32(x)),
+        None => None,
+    }
+}
+
+/// As `u64::checked_ilog10`.
+#[inline]
+pub const fn checked_ilog10_u64(x: u64) -> Option<u32> {
+    match NonZeroU64::new(x) {
+        Some(x) => Some(nonzero_ilog10_u64(x)),
+        None => None,
+    }
+}
+
+/// As `u32::ilog10`; panics if `x` is 0.
+#[inline]
+#[track_caller]
+pub const fn ilog10_u32(x: u32) -> u32 {
+    match checked_ilog10_u32(x) {
+        Some(log) => log,
+        None => panic_for_nonpositive_argument(),
+    }
+}
+
+/// As `u64::ilog10`; panics if `x` is 0.
+#[inline]
+#[track_caller]
+pub const fn ilog10_u64(x: u64) -> u32 {
+    match checked_ilog10_u64(x) {
+        Some(log) => log,
+        None => panic_for_nonpositive_argument(),
+    }
+}
+
+// Out of line and cold, as in core, to keep the panic machinery out of
+// the inlined fast path.
+#[cold]
+#[track_caller]
+const fn panic_for_nonpositive_argument() -> ! {
+    panic!("argument of integer logarithm must be positive")
+}
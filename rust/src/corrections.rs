@@ -0,0 +1,181 @@
+// How often the +1 correction fires, for `ilog corrections`. Most of
+// the implementations are a guess from the leading zeros followed by
+// the same correction, so what the branch predictor (for a branchy
+// build of the correction) or the port pressure (for a branch-free
+// one) sees is decided by the guess and the inputs alone, and can be
+// counted without timing anything.
+
+use ilog::bench::{Distribution, Input};
+use ilog::parts;
+use ilog::registry::Width;
+use ilog::results::CorrectionCount;
+
+use crate::filter::Filter;
+use crate::report::Report;
+
+/// A guess strategy and the registry entries built on it.
+struct Guess {
+    name: &'static str,
+    width: Width,
+    algorithms: &'static [&'static str],
+    guess: fn(u64) -> u32,
+    /// The correction the algorithms apply to it, and whether it fired
+    correct: fn(u32, u64) -> (u32, bool),
+}
+
+fn correct_u32(guess: u32, x: u64) -> (u32, bool) {
+    parts::correct_with_flag(guess, x as u32)
+}
+
+const GUESSES: &[Guess] = &[
+    Guess {
+        name: "mul",
+        width: Width::U32,
+        algorithms: &[
+            "mul",
+            "mul_alt",
+            "mul_pow",
+            "mul_loop",
+            "mul_pow4",
+            "mul_packed",
+            "asm",
+            "branch",
+            "cmov",
+            "sub_shift",
+        ],
+        guess: |x| parts::guess_ilog10_u32((x as u32).leading_zeros()),
+        correct: correct_u32,
+    },
+    Guess {
+        name: "popcount",
+        width: Width::U32,
+        algorithms: &["popcount"],
+        guess: |x| parts::guess_ilog10_u32_popcount((x as u32).leading_zeros()),
+        correct: correct_u32,
+    },
+    Guess {
+        name: "table",
+        width: Width::U32,
+        algorithms: &["table"],
+        // log10_table_table's own table goes on to 9 for the top two
+        // powers of two, where parts stops at 8 and corrects instead.
+        guess: |x| parts::guess_ilog10_u32_table((x as u32).leading_zeros()) + (x >= 1 << 30) as u32,
+        // Its threshold for 9 is u32::MAX, past which nothing is.
+        correct: |guess, x| match guess {
+            9 => (9, false),
+            _ => correct_u32(guess, x),
+        },
+    },
+    Guess {
+        name: "mul",
+        width: Width::U64,
+        algorithms: &["mul", "mul_pow", "mul_loop", "mul_pow4", "mul_packed", "asm"],
+        guess: |x| parts::guess_ilog10_u64(x.leading_zeros()),
+        correct: parts::correct_u64_with_flag,
+    },
+    Guess {
+        name: "mul_over",
+        width: Width::U64,
+        algorithms: &["mul20"],
+        guess: |x| parts::guess_ilog10_u64_over(x.leading_zeros()),
+        // The correction takes one off rather than adding one.
+        correct: |guess, x| {
+            let log = parts::correct_u64_down(guess, x);
+            (log, log != guess)
+        },
+    },
+];
+
+impl Guess {
+    fn qualified_name(&self) -> String {
+        format!("{}/{}", self.width.name(), self.name)
+    }
+
+    // Selected by its own name or that of any algorithm built on it.
+    fn wanted(&self, filter: &Filter) -> bool {
+        let mut names = vec![self.qualified_name()];
+        for a in self.algorithms {
+            names.push(a.to_string());
+            names.push(format!("{}/{a}", self.width.name()));
+        }
+        filter.matches(&names.iter().map(String::as_str).collect::<Vec<_>>())
+    }
+}
+
+// One pass over the values; the benchmark's repeated passes over the
+// same buffer would only multiply the counts.
+fn tally(guess: &Guess, distribution: Distribution) -> Vec<CorrectionCount> {
+    let mut values = [0u64; 20];
+    let mut corrected = [0u64; 20];
+    let mut flips = 0;
+    let mut previous = None;
+    let mut count = |x: u64| {
+        let g = (guess.guess)(x);
+        let (log, fired) = (guess.correct)(g, x);
+        assert!(log == x.ilog10(), "guess off by more than the correction");
+        values[g as usize] += 1;
+        corrected[g as usize] += fired as u64;
+        // Consecutive inputs disagreeing on the correction are what a
+        // branch on it would mispredict, give or take the history.
+        if previous.is_some_and(|p| p != fired) {
+            flips += 1;
+        }
+        previous = Some(fired);
+    };
+    match Input::generate(guess.width, distribution) {
+        Input::Sweep => (1..=u32::MAX as u64).for_each(&mut count),
+        Input::Permuted(p) => (0..p.size())
+            .map(|i| p.at(i))
+            .filter(|&x| x != 0)
+            .for_each(&mut count),
+        Input::Strided { .. } => unreachable!("generate doesn't stride"),
+        Input::U32 { values, .. } => values.into_iter().for_each(|x| count(x as u64)),
+        Input::U64 { values, .. } => values.into_iter().for_each(&mut count),
+    }
+    let total: u64 = values.iter().sum();
+    let record = |guess_value, values: u64, corrected: u64, flips| CorrectionCount {
+        guess: guess.qualified_name(),
+        distribution,
+        value: guess_value,
+        values,
+        share: values as f64 / total as f64,
+        corrected,
+        correction_rate: corrected as f64 / values as f64,
+        flips,
+    };
+    let mut rows: Vec<CorrectionCount> = (0..values.len())
+        .filter(|&g| values[g] > 0)
+        .map(|g| record(Some(g as u32), values[g], corrected[g], None))
+        .collect();
+    rows.push(record(None, total, corrected.iter().sum(), Some(flips)));
+    rows
+}
+
+pub fn report(distributions: &[Distribution], filter: &Filter) -> Result<Report, String> {
+    let guesses: Vec<&Guess> = GUESSES.iter().filter(|g| g.wanted(filter)).collect();
+    if guesses.is_empty() {
+        return Err("no guess strategy matches the filter".into());
+    }
+    let mut report = Report::new(
+        "corrections",
+        &[
+            "guess",
+            "distribution",
+            "value",
+            "values",
+            "share",
+            "corrected",
+            "correction_rate",
+            "flips",
+        ],
+    );
+    for guess in guesses {
+        for &distribution in distributions {
+            eprintln!("Counting {} over {}", guess.qualified_name(), distribution.name());
+            for row in tally(guess, distribution) {
+                report.push(&row);
+            }
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,139 @@
+// Standalone C for `ilog codegen c`: the guess-and-correct ilog10s as
+// dependency-free C99 (with the GCC and Clang builtins for the leading
+// zeros and popcount), for projects that can't take a Rust dependency.
+// The multipliers, masks and tables are the crate's own, written out
+// from the constants and checked against `parts` at compile time.
+//
+// The output for every algorithm is committed as csrc/generated.c,
+// which the `c-reference` feature compiles and registers as the c_gen_
+// algorithms, so that `ilog test` checks the C against the standard
+// library like any other implementation; `ilog test` also fails if the
+// committed file no longer matches what this module prints.
+
+use std::fmt::Write;
+
+use ilog::{parts, tables};
+
+/// The committed output for every algorithm.
+pub const GENERATED: &str = include_str!("../csrc/generated.c");
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Language {
+    C,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Algo {
+    /// ilog2(x) * 9 >> 5, as ilog10_mul
+    Mul,
+    /// The leading zeros shifting out a mask of powers of ten, as ilog10
+    Popcount,
+    /// A 32-entry guess table indexed by ilog2(x), as parts::guess_ilog10_u32_table
+    Table,
+    /// ilog2(x) * 19 >> 6, as ilog10_u64_mul
+    U64Mul,
+}
+
+impl Algo {
+    pub const ALL: [Algo; 4] = [Algo::Mul, Algo::Popcount, Algo::Table, Algo::U64Mul];
+}
+
+const U32_MUL: (u32, u32) = match tables::multiplier(10, 32) {
+    Some(m) => m,
+    None => panic!("no u32 multiplier"),
+};
+const U64_MUL: (u32, u32) = match tables::multiplier(10, 64) {
+    Some(m) => m,
+    None => panic!("no u64 multiplier"),
+};
+
+// The multipliers are the crate's guesses.
+const _: () = {
+    let mut lz = 0;
+    while lz < 64 {
+        if lz < 32 {
+            assert!(((31 - lz) * U32_MUL.0) >> U32_MUL.1 == parts::guess_ilog10_u32(lz));
+        }
+        assert!(((63 - lz) * U64_MUL.0) >> U64_MUL.1 == parts::guess_ilog10_u64(lz));
+        lz += 1;
+    }
+};
+
+pub fn emit(_: Language, algos: &[Algo]) -> String {
+    let u32_table = algos.iter().any(|&a| a != Algo::U64Mul);
+    let mut out = String::new();
+    let w = &mut out;
+    writeln!(w, "/* floor(log10(x)) for nonzero x, from the ilog crate's guesses and tables.").unwrap();
+    writeln!(w, " * Generated by `ilog codegen c`; none of the functions handle x == 0. */\n").unwrap();
+    writeln!(w, "#include <stdint.h>\n").unwrap();
+    if u32_table {
+        let thresholds: Vec<String> = ilog::TEN_THRESHOLDS.iter().map(|t| format!("{t}u")).collect();
+        writeln!(w, "/* 10^(g + 1) - 1, the largest value with g + 1 digits. */").unwrap();
+        writeln!(w, "static const uint32_t ILOG10_U32_THRESHOLDS[{}] = {{", thresholds.len()).unwrap();
+        writeln!(w, "{}}};\n", lines(&thresholds, 5)).unwrap();
+    }
+    for &algo in algos {
+        match algo {
+            Algo::Mul => {
+                writeln!(w, "/* {}/{} is just under log10(2). */", U32_MUL.0, 1 << U32_MUL.1).unwrap();
+                writeln!(w, "uint32_t ilog10_u32_mul(uint32_t x) {{").unwrap();
+                writeln!(
+                    w,
+                    "    uint32_t guess = ((31 - __builtin_clz(x)) * {}) >> {};",
+                    U32_MUL.0, U32_MUL.1
+                )
+                .unwrap();
+            }
+            Algo::Popcount => {
+                writeln!(w, "/* A 1 at each bit where the next power of two passes a power of ten;").unwrap();
+                writeln!(w, " * the leading zeros shift out the ones x doesn't reach. */").unwrap();
+                writeln!(w, "uint32_t ilog10_u32_popcount(uint32_t x) {{").unwrap();
+                writeln!(
+                    w,
+                    "    uint32_t guess = __builtin_popcount({:#010x}u << __builtin_clz(x));",
+                    crate::explain::popcount_mask()
+                )
+                .unwrap();
+            }
+            Algo::Table => {
+                let guesses: Vec<String> =
+                    (0..32).map(|ilog2| parts::guess_ilog10_u32_table(31 - ilog2).to_string()).collect();
+                writeln!(w, "/* The guess for each ilog2(x). */").unwrap();
+                writeln!(w, "uint32_t ilog10_u32_table(uint32_t x) {{").unwrap();
+                writeln!(w, "    static const uint8_t guesses[32] = {{").unwrap();
+                writeln!(w, "    {}}};", lines(&guesses, 16).replace('\n', "\n    ")).unwrap();
+                writeln!(w, "    uint32_t guess = guesses[31 - __builtin_clz(x)];").unwrap();
+            }
+            Algo::U64Mul => {
+                let thresholds: Vec<String> =
+                    ilog::U64_THRESHOLDS.iter().map(|t| format!("{t}ull")).collect();
+                writeln!(w, "/* {}/{} is just under log10(2). */", U64_MUL.0, 1 << U64_MUL.1).unwrap();
+                writeln!(w, "uint32_t ilog10_u64_mul(uint64_t x) {{").unwrap();
+                writeln!(w, "    static const uint64_t thresholds[{}] = {{", thresholds.len()).unwrap();
+                writeln!(w, "    {}}};", lines(&thresholds, 3).replace('\n', "\n    ")).unwrap();
+                writeln!(
+                    w,
+                    "    uint32_t guess = ((63 - __builtin_clzll(x)) * {}) >> {};",
+                    U64_MUL.0, U64_MUL.1
+                )
+                .unwrap();
+                writeln!(w, "    return guess + (x > thresholds[guess]);\n}}\n").unwrap();
+                continue;
+            }
+        }
+        writeln!(w, "    return guess + (x > ILOG10_U32_THRESHOLDS[guess]);\n}}\n").unwrap();
+    }
+    out.truncate(out.trim_end().len() + 1);
+    out
+}
+
+// `items`, `per_line` to an indented line, comma-separated.
+fn lines(items: &[String], per_line: usize) -> String {
+    items
+        .chunks(per_line)
+        .map(|line| format!("    {},", line.join(", ")))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim_end_matches(',')
+        .to_string()
+}
@@ -0,0 +1,166 @@
+// Writing CSV end to end, for `ilog csv`: random u64s written as rows
+// of `--fields` comma-separated values and a newline into a buffer of
+// about a megabyte, which is handed to `black_box` and reused when
+// full, as a writer flushing to a socket would. Three writers:
+//
+// - `write`: `write!` of each value, as most CSV writers do;
+// - `per_value`: each value's digit count from `digits_u64` as it
+//   comes, then its digits with `csv_row::write_digits`;
+// - `batch`: `csv_row::write`, which sizes a whole row with the batch
+//   kernels before writing any of it, with the same digit writer.
+//
+// So `per_value` against `write` is what sizing first buys over
+// formatting into a scratch buffer and copying, and `batch` against
+// `per_value` what the vector kernels buy over sizing one at a time.
+// The writers take turns, in a new order each round, and the best
+// round of each counts. All three are checked to write the same text
+// first.
+
+use std::hint::black_box;
+use std::io::Write;
+use std::time::Instant;
+
+use ilog::bench::{Distribution, Input};
+use ilog::csv_row;
+use ilog::registry::Width;
+use ilog::results::CsvThroughput;
+use rand::seq::SliceRandom;
+
+use crate::report::Report;
+
+// The buffer's size, and the most one u64 and a separator take.
+const BUFFER: usize = 1 << 20;
+const FIELD_MAX: usize = 21;
+
+// A writer: name, and what writes one row into the front of a buffer
+// with room for it and returns the length.
+type Writer = (&'static str, fn(&[u64], &mut [u8]) -> usize);
+
+const WRITERS: [Writer; 3] = [
+    ("write", write_fmt),
+    ("per_value", write_per_value),
+    ("batch", write_batch),
+];
+
+pub fn report(distribution: Distribution, fields: usize, samples: usize) -> Result<Report, String> {
+    if fields == 0 || samples == 0 {
+        return Err("--fields and --samples must be at least 1".into());
+    }
+    let input = Input::generate(Width::U64, distribution);
+    let Input::U64 { values, passes } = &input else {
+        return Err(format!("{} is not a random distribution", distribution.name()));
+    };
+
+    let expected = text(values, fields, WRITERS[0].1);
+    for (name, row) in &WRITERS[1..] {
+        if text(values, fields, *row) != expected {
+            return Err(format!("{name} wrote different text from {}", WRITERS[0].0));
+        }
+    }
+
+    let mut best = [f64::INFINITY; WRITERS.len()];
+    let mut bytes = 0;
+    let mut order: Vec<usize> = (0..WRITERS.len()).collect();
+    let mut rng = rand::rng();
+    for _ in 0..samples {
+        order.shuffle(&mut rng);
+        for &i in &order {
+            let start = Instant::now();
+            bytes = write_rows(values, *passes, fields, WRITERS[i].1);
+            best[i] = best[i].min(start.elapsed().as_nanos() as f64);
+        }
+    }
+
+    let ops = input.ops() as f64;
+    let mut report = Report::new(
+        "csv writing",
+        &[
+            "distribution",
+            "fields",
+            "writer",
+            "ns_per_value",
+            "bytes_per_value",
+            "mb_per_second",
+            "speedup",
+        ],
+    );
+    for ((name, _), &ns) in WRITERS.iter().zip(&best) {
+        report.push(&CsvThroughput {
+            distribution,
+            fields,
+            writer: name.to_string(),
+            ns_per_value: ns / ops,
+            bytes_per_value: bytes as f64 / ops,
+            mb_per_second: bytes as f64 / ns * 1e3,
+            speedup: best[0] / ns,
+        });
+    }
+    Ok(report)
+}
+
+// Every value, `passes` times over, written as rows with `row`; returns
+// the bytes written.
+fn write_rows(
+    values: &[u64],
+    passes: usize,
+    fields: usize,
+    row: fn(&[u64], &mut [u8]) -> usize,
+) -> usize {
+    let mut out = vec![0u8; BUFFER + fields * FIELD_MAX];
+    let mut pos = 0;
+    let mut written = 0;
+    for _ in 0..passes {
+        for values in values.chunks(fields) {
+            if pos > BUFFER {
+                black_box(&out[..pos]);
+                written += pos;
+                pos = 0;
+            }
+            pos += row(values, &mut out[pos..]);
+            out[pos] = b'\n';
+            pos += 1;
+        }
+    }
+    black_box(&out[..pos]);
+    written + pos
+}
+
+// The text `row` writes for every value once, to compare.
+fn text(values: &[u64], fields: usize, row: fn(&[u64], &mut [u8]) -> usize) -> Vec<u8> {
+    let mut text = Vec::new();
+    let mut out = vec![0u8; fields * FIELD_MAX];
+    for values in values.chunks(fields) {
+        let len = row(values, &mut out);
+        text.extend_from_slice(&out[..len]);
+        text.push(b'\n');
+    }
+    text
+}
+
+fn write_fmt(values: &[u64], out: &mut [u8]) -> usize {
+    let room = out.len();
+    let mut cursor = out;
+    for (i, x) in values.iter().enumerate() {
+        let written = if i == 0 { write!(cursor, "{x}") } else { write!(cursor, ",{x}") };
+        written.expect("the buffer has room for the row");
+    }
+    room - cursor.len()
+}
+
+fn write_per_value(values: &[u64], out: &mut [u8]) -> usize {
+    let mut pos = 0;
+    for &x in values {
+        if pos > 0 {
+            out[pos] = b',';
+            pos += 1;
+        }
+        let end = pos + ilog::digits_u64(x) as usize;
+        csv_row::write_digits(&mut out[pos..end], x);
+        pos = end;
+    }
+    pos
+}
+
+fn write_batch(values: &[u64], out: &mut [u8]) -> usize {
+    csv_row::write(values, b',', out)
+}
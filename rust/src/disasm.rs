@@ -0,0 +1,676 @@
+// Machine code for the library's functions as built for a given
+// target, for `ilog asm`. Small functions are inlined across crates
+// and never show up in the library's own object code, so the library
+// is rebuilt as a plain rlib with `--cfg ilog_probe`, which adds a
+// #[no_mangle] export wrapping each one (src/probe.rs), the same thing
+// one would paste into godbolt. The exports are then found in the
+// rlib and disassembled with capstone.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use capstone::prelude::*;
+use ilog::results::CodeSize;
+use object::read::archive::ArchiveFile;
+use object::{
+    Architecture, Object, ObjectSection, ObjectSymbol, RelocationTarget, SectionKind, SymbolKind,
+};
+
+/// The target `ilog` itself was built for.
+pub const HOST: &str = env!("TARGET");
+
+/// A library function and its export in src/probe.rs.
+pub struct Function {
+    pub name: &'static str,
+    /// The registry entry it backs, if any, e.g. `u32/mul`
+    pub algorithm: Option<&'static str>,
+    export: &'static str,
+}
+
+const fn function(name: &'static str, algorithm: &'static str, export: &'static str) -> Function {
+    Function {
+        name,
+        algorithm: Some(algorithm),
+        export,
+    }
+}
+
+pub const FUNCTIONS: &[Function] = &[
+    function("ilog10", "u32/popcount", "ilog_probe_ilog10"),
+    function("ilog10_mul", "u32/mul", "ilog_probe_ilog10_mul"),
+    function("ilog10_mul_alt", "u32/mul_alt", "ilog_probe_ilog10_mul_alt"),
+    function(
+        "log10_table_table",
+        "u32/table",
+        "ilog_probe_log10_table_table",
+    ),
+    function("ilog10_u32", "u32/stdlib", "ilog_probe_ilog10_u32"),
+    function("ilog10_u64_mul", "u64/mul", "ilog_probe_ilog10_u64_mul"),
+    function("ilog10_mul_pow", "u32/mul_pow", "ilog_probe_ilog10_mul_pow"),
+    function("ilog10_mul_loop", "u32/mul_loop", "ilog_probe_ilog10_mul_loop"),
+    function("ilog10_mul_pow4", "u32/mul_pow4", "ilog_probe_ilog10_mul_pow4"),
+    function("ilog10_mul_packed", "u32/mul_packed", "ilog_probe_ilog10_mul_packed"),
+    function(
+        "ilog10_u64_mul_pow",
+        "u64/mul_pow",
+        "ilog_probe_ilog10_u64_mul_pow",
+    ),
+    function(
+        "ilog10_u64_mul_loop",
+        "u64/mul_loop",
+        "ilog_probe_ilog10_u64_mul_loop",
+    ),
+    function(
+        "ilog10_u64_mul_pow4",
+        "u64/mul_pow4",
+        "ilog_probe_ilog10_u64_mul_pow4",
+    ),
+    function(
+        "ilog10_u64_mul_packed",
+        "u64/mul_packed",
+        "ilog_probe_ilog10_u64_mul_packed",
+    ),
+    function("ilog10_u64_mul20", "u64/mul20", "ilog_probe_ilog10_u64_mul20"),
+    function("ilog10_swar", "u32/swar", "ilog_probe_ilog10_swar"),
+    function("ilog10_u64_swar", "u64/swar", "ilog_probe_ilog10_u64_swar"),
+    function(
+        "soft_clz::ilog10_debruijn",
+        "u32/debruijn",
+        "ilog_probe_soft_clz_ilog10_debruijn",
+    ),
+    function(
+        "soft_clz::ilog10_u64_debruijn",
+        "u64/debruijn",
+        "ilog_probe_soft_clz_ilog10_u64_debruijn",
+    ),
+    function(
+        "soft_clz::ilog10_bytes",
+        "u32/bytes",
+        "ilog_probe_soft_clz_ilog10_bytes",
+    ),
+    function(
+        "soft_clz::ilog10_u64_bytes",
+        "u64/bytes",
+        "ilog_probe_soft_clz_ilog10_u64_bytes",
+    ),
+    function(
+        "constant_time::ilog10_u32",
+        "u32/constant_time",
+        "ilog_probe_constant_time_ilog10_u32",
+    ),
+    function(
+        "constant_time::ilog10_u64",
+        "u64/constant_time",
+        "ilog_probe_constant_time_ilog10_u64",
+    ),
+    function("float::ilog10_f32", "u32/f32", "ilog_probe_float_ilog10_f32"),
+    function("float::ilog10_f64", "u32/f64", "ilog_probe_float_ilog10_f64"),
+    function(
+        "float::ilog10_u64_f64",
+        "u64/f64",
+        "ilog_probe_float_ilog10_u64_f64",
+    ),
+    function(
+        "arm32::ilog10_u64_split",
+        "u64/split",
+        "ilog_probe_arm32_ilog10_u64_split",
+    ),
+    function("mcu::ilog10_ladder", "u32/ladder", "ilog_probe_mcu_ilog10_ladder"),
+    function(
+        "mcu::ilog10_u64_ladder",
+        "u64/ladder",
+        "ilog_probe_mcu_ilog10_u64_ladder",
+    ),
+    function("asm::ilog10_u32", "u32/asm", "ilog_probe_asm_ilog10_u32"),
+    function("asm::ilog10_u64", "u64/asm", "ilog_probe_asm_ilog10_u64"),
+    function(
+        "lowering::ilog10_branch",
+        "u32/branch",
+        "ilog_probe_lowering_branch",
+    ),
+    function(
+        "lowering::ilog10_cmov",
+        "u32/cmov",
+        "ilog_probe_lowering_cmov",
+    ),
+    function(
+        "lowering::ilog10_sub_shift",
+        "u32/sub_shift",
+        "ilog_probe_lowering_sub_shift",
+    ),
+    Function {
+        algorithm: None,
+        ..function("digits_u32", "", "ilog_probe_digits_u32")
+    },
+    Function {
+        algorithm: None,
+        ..function("digits_u64", "", "ilog_probe_digits_u64")
+    },
+    Function {
+        algorithm: None,
+        ..function("ilog2_and_ilog10_u32", "", "ilog_probe_ilog2_and_ilog10_u32")
+    },
+    Function {
+        algorithm: None,
+        ..function("ilog2_and_ilog10_u64", "", "ilog_probe_ilog2_and_ilog10_u64")
+    },
+    Function {
+        algorithm: None,
+        ..function("ilog10_with_flag", "", "ilog_probe_ilog10_with_flag")
+    },
+    Function {
+        algorithm: None,
+        ..function("ilog10_u64_with_flag", "", "ilog_probe_ilog10_u64_with_flag")
+    },
+    Function {
+        algorithm: None,
+        ..function("digits_and_pow10_u32", "", "ilog_probe_digits_and_pow10_u32")
+    },
+    Function {
+        algorithm: None,
+        ..function("digits_and_pow10_u64", "", "ilog_probe_digits_and_pow10_u64")
+    },
+    Function {
+        algorithm: None,
+        ..function("leading_digit_u32", "", "ilog_probe_leading_digit_u32")
+    },
+    Function {
+        algorithm: None,
+        ..function("leading_digit_u64", "", "ilog_probe_leading_digit_u64")
+    },
+    Function {
+        algorithm: None,
+        ..function("ilog10_product", "", "ilog_probe_ilog10_product")
+    },
+    Function {
+        algorithm: None,
+        ..function("leading_n_digits_u32", "", "ilog_probe_leading_n_digits_u32")
+    },
+    Function {
+        algorithm: None,
+        ..function("leading_n_digits_u64", "", "ilog_probe_leading_n_digits_u64")
+    },
+    Function {
+        algorithm: None,
+        ..function("round_sig_figs", "", "ilog_probe_round_sig_figs")
+    },
+    Function {
+        algorithm: None,
+        ..function("log10_with_rounding", "", "ilog_probe_log10_with_rounding")
+    },
+    Function {
+        algorithm: None,
+        ..function("nearest_power_of_ten", "", "ilog_probe_nearest_power_of_ten")
+    },
+    Function {
+        algorithm: None,
+        ..function(
+            "neg_ilog10_of_reciprocal",
+            "",
+            "ilog_probe_neg_ilog10_of_reciprocal",
+        )
+    },
+    Function {
+        algorithm: None,
+        ..function("ilog10_ratio", "", "ilog_probe_ilog10_ratio")
+    },
+    Function {
+        algorithm: None,
+        ..function("histogram::decade_bucket", "", "ilog_probe_decade_bucket")
+    },
+    Function {
+        algorithm: None,
+        ..function("ilog1000_u32", "", "ilog_probe_ilog1000_u32")
+    },
+    Function {
+        algorithm: None,
+        ..function("ilog1000_u64", "", "ilog_probe_ilog1000_u64")
+    },
+    Function {
+        algorithm: None,
+        ..function("si_scale_u32", "", "ilog_probe_si_scale_u32")
+    },
+    Function {
+        algorithm: None,
+        ..function("si_scale_u64", "", "ilog_probe_si_scale_u64")
+    },
+    Function {
+        algorithm: None,
+        ..function("to_decimal_sci", "", "ilog_probe_to_decimal_sci")
+    },
+    Function {
+        algorithm: None,
+        ..function("digits_in_radix", "", "ilog_probe_digits_in_radix")
+    },
+    Function {
+        algorithm: None,
+        ..function("total_decimal_len", "", "ilog_probe_total_decimal_len")
+    },
+    Function {
+        algorithm: None,
+        ..function(
+            "checked_encoded_len_with_prefix",
+            "",
+            "ilog_probe_checked_encoded_len_with_prefix"
+        )
+    },
+    Function {
+        algorithm: None,
+        ..function("digits_u32x4", "", "ilog_probe_digits_u32x4")
+    },
+    Function {
+        algorithm: None,
+        ..function("digits_u64x2", "", "ilog_probe_digits_u64x2")
+    },
+];
+
+impl Function {
+    /// Whether `name` is this function's name or its registry entry's.
+    pub fn is(&self, name: &str) -> bool {
+        self.name == name || self.algorithm == Some(name)
+    }
+}
+
+/// Splits a `--target` of the form `TRIPLE` or `TRIPLE:CPU`, where
+/// CPU is passed on as `-C target-cpu`, e.g.
+/// `x86_64-unknown-linux-gnu:x86-64-v3`.
+pub fn split_target(target: &str) -> (&str, Option<&str>) {
+    match target.split_once(':') {
+        Some((triple, cpu)) => (triple, Some(cpu)),
+        None => (target, None),
+    }
+}
+
+/// Builds the probe rlib for `target` and returns its path. Cargo's
+/// own output is passed through, so that a missing `rustup target`
+/// shows up as cargo reports it.
+pub fn build_probe(target: &str) -> Result<PathBuf, String> {
+    let (triple, cpu) = split_target(target);
+    // Fail before building for a target there is no disassembler for.
+    if !triple.starts_with("wasm32") {
+        capstone(triple)?;
+    }
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    // A separate target directory (per CPU, as the flags differ), so
+    // that the extra cfg does not invalidate the regular builds.
+    let target_dir = dir.join("target/asm").join(cpu.unwrap_or("default"));
+    let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+    let mut command = Command::new(cargo);
+    command
+        .arg("rustc")
+        .arg("--manifest-path")
+        .arg(dir.join("Cargo.toml"))
+        .arg("--target-dir")
+        .arg(&target_dir)
+        .args(["--release", "--quiet", "--lib", "--crate-type", "rlib"])
+        .args(["--no-default-features", "--target", triple])
+        .args(["--", "--cfg", "ilog_probe"]);
+    if let Some(cpu) = cpu {
+        command.arg(format!("-Ctarget-cpu={cpu}"));
+    }
+    let status = command
+        .status()
+        .map_err(|e| format!("running cargo: {e}"))?;
+    if !status.success() {
+        return Err(format!("building the library for {target} failed"));
+    }
+    Ok(target_dir.join(format!("{triple}/release/libilog.rlib")))
+}
+
+/// One function's machine code as found in the object file.
+pub struct Symbol {
+    pub bytes: Vec<u8>,
+    pub relocations: Vec<Relocation>,
+}
+
+pub struct Relocation {
+    /// Offset into the function's bytes
+    pub offset: u64,
+    /// The demangled name of the symbol referred to
+    pub target: String,
+    /// The size of the target, if it is data rather than code
+    pub data: Option<u64>,
+}
+
+/// Finds the probe exports for `functions` in the rlib at `path`;
+/// `None` for a function not built for this target.
+pub fn extract(path: &Path, functions: &[&Function]) -> Result<Vec<Option<Symbol>>, String> {
+    let err = |e: object::Error| format!("{}: {e}", path.display());
+    let data = std::fs::read(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let archive = ArchiveFile::parse(&*data).map_err(err)?;
+    let mut found: Vec<Option<Symbol>> = functions.iter().map(|_| None).collect();
+    for member in archive.members() {
+        let member = member.map_err(err)?;
+        // Skip the metadata and anything else that is not an object.
+        let Ok(file) = object::File::parse(member.data(&*data).map_err(err)?) else {
+            continue;
+        };
+        for sym in file.symbols() {
+            let Ok(name) = sym.name() else { continue };
+            // Mach-O prefixes C symbol names with an underscore.
+            let name = name
+                .strip_prefix('_')
+                .filter(|n| n.starts_with("ilog_probe_"))
+                .unwrap_or(name);
+            let Some(i) = functions.iter().position(|f| f.export == name) else {
+                continue;
+            };
+            let Some(section) = sym
+                .section_index()
+                .and_then(|i| file.section_by_index(i).ok())
+            else {
+                continue;
+            };
+            let contents = section.data().map_err(err)?;
+            // Thumb function symbols have the low bit set.
+            let thumb = file.architecture() == Architecture::Arm;
+            let start = (sym.address() & !(thumb as u64)) - section.address();
+            // Mach-O symbols have no size, so run to the next symbol
+            // in the section or its end.
+            let end = match sym.size() {
+                0 => file
+                    .symbols()
+                    .filter(|s| s.section_index() == sym.section_index())
+                    .map(|s| s.address() - section.address())
+                    .filter(|&a| a > start)
+                    .min()
+                    .unwrap_or(contents.len() as u64),
+                size => start + size,
+            };
+            let relocations = section
+                .relocations()
+                .filter(|(offset, _)| (start..end).contains(offset))
+                .map(|(offset, r)| {
+                    let (target, data) = match r.target() {
+                        RelocationTarget::Symbol(s) => match file.symbol_by_index(s) {
+                            Ok(s) => {
+                                let section = s
+                                    .section_index()
+                                    .and_then(|i| file.section_by_index(i).ok());
+                                // Section symbols are unnamed; the section
+                                // name usually says which constant it is.
+                                let name = match s.name() {
+                                    Ok("") => section
+                                        .as_ref()
+                                        .and_then(|s| s.name().ok().map(String::from))
+                                        .unwrap_or_else(|| "?".into()),
+                                    Ok(n) => format!("{:#}", rustc_demangle::demangle(n)),
+                                    Err(_) => "?".into(),
+                                };
+                                // A section symbol, or a Mach-O one, has
+                                // no size of its own.
+                                let data = section.filter(|_| s.kind() != SymbolKind::Text).map(
+                                    |section| match s.size() {
+                                        0 => section.size(),
+                                        size => size,
+                                    },
+                                );
+                                (name, data)
+                            }
+                            Err(_) => ("?".into(), None),
+                        },
+                        RelocationTarget::Section(s) => match file.section_by_index(s) {
+                            Ok(s) => (
+                                s.name().map_or_else(|_| "?".into(), String::from),
+                                (s.kind() != SectionKind::Text).then(|| s.size()),
+                            ),
+                            Err(_) => ("?".into(), None),
+                        },
+                        _ => ("?".into(), None),
+                    };
+                    Relocation {
+                        offset: offset - start,
+                        target,
+                        data,
+                    }
+                })
+                .collect();
+            found[i] = Some(Symbol {
+                bytes: contents[start as usize..end as usize].to_vec(),
+                relocations,
+            });
+        }
+    }
+    Ok(found)
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Kind {
+    Other,
+    Jump,
+    ConditionalJump,
+    Call,
+    Return,
+}
+
+pub struct Instruction {
+    pub offset: u64,
+    pub bytes: Vec<u8>,
+    pub text: String,
+    pub kind: Kind,
+    /// What any relocation in the instruction refers to
+    pub target: Option<String>,
+    /// The size of `target`, if it is data rather than code
+    pub data: Option<u64>,
+}
+
+impl Instruction {
+    /// The offset a branch goes to, if it is a direct branch within
+    /// the function (always the last operand, printed as `0x1b` or
+    /// `#0x1b`). None for wasm, which branches to enclosing blocks.
+    pub fn branch_target(&self) -> Option<u64> {
+        let operand = self.text.rsplit([' ', ',']).next()?.trim_start_matches('#');
+        u64::from_str_radix(operand.strip_prefix("0x")?, 16).ok()
+    }
+}
+
+fn capstone(target: &str) -> Result<Capstone, String> {
+    let arch = target.split('-').next().unwrap_or(target);
+    let cs = match arch {
+        "x86_64" => Capstone::new()
+            .x86()
+            .mode(arch::x86::ArchMode::Mode64)
+            .syntax(arch::x86::ArchSyntax::Intel)
+            .build(),
+        "i686" | "i586" => Capstone::new()
+            .x86()
+            .mode(arch::x86::ArchMode::Mode32)
+            .syntax(arch::x86::ArchSyntax::Intel)
+            .build(),
+        "aarch64" => Capstone::new()
+            .arm64()
+            .mode(arch::arm64::ArchMode::Arm)
+            .build(),
+        "riscv64gc" | "riscv64imac" => Capstone::new()
+            .riscv()
+            .mode(arch::riscv::ArchMode::RiscV64)
+            .extra_mode([arch::riscv::ArchExtraMode::RiscVC].into_iter())
+            .build(),
+        _ if arch.starts_with("thumb") => Capstone::new()
+            .arm()
+            .mode(arch::arm::ArchMode::Thumb)
+            .extra_mode([arch::arm::ArchExtraMode::MClass].into_iter())
+            .build(),
+        _ => return Err(format!("no disassembler for {target}")),
+    };
+    cs.map_err(|e| format!("capstone: {e}"))
+}
+
+// Classified by mnemonic rather than capstone's instruction groups,
+// which are not filled in consistently across architectures.
+fn kind(arch: &str, mnemonic: &str, operands: &str) -> Kind {
+    let m = mnemonic.trim_start_matches("c.");
+    match arch {
+        "x86_64" | "i686" | "i586" => match m {
+            "jmp" => Kind::Jump,
+            "call" => Kind::Call,
+            "ret" => Kind::Return,
+            _ if m.starts_with('j') => Kind::ConditionalJump,
+            _ => Kind::Other,
+        },
+        "aarch64" => match m {
+            "b" | "br" => Kind::Jump,
+            "bl" | "blr" => Kind::Call,
+            "ret" => Kind::Return,
+            "cbz" | "cbnz" | "tbz" | "tbnz" => Kind::ConditionalJump,
+            _ if m.starts_with("b.") => Kind::ConditionalJump,
+            _ => Kind::Other,
+        },
+        // Capstone prints the return as `c.jr ra` rather than `ret`.
+        _ if arch.starts_with("riscv") => match m {
+            "jr" if operands == "ra" => Kind::Return,
+            "j" | "jr" => Kind::Jump,
+            "jal" | "jalr" | "call" | "tail" => Kind::Call,
+            "ret" => Kind::Return,
+            _ if m.starts_with('b') => Kind::ConditionalJump,
+            _ => Kind::Other,
+        },
+        // Thumb
+        _ => match m.trim_end_matches(".w").trim_end_matches(".n") {
+            "b" => Kind::Jump,
+            "bl" | "blx" => Kind::Call,
+            "bx" => Kind::Return,
+            // Functions that saved lr return by popping it into pc.
+            "pop" if operands.contains("pc") => Kind::Return,
+            "cbz" | "cbnz" => Kind::ConditionalJump,
+            m if m.len() == 3 && m.starts_with('b') && ARM_CONDITIONS.contains(&&m[1..]) => {
+                Kind::ConditionalJump
+            }
+            _ => Kind::Other,
+        },
+    }
+}
+
+const ARM_CONDITIONS: [&str; 16] = [
+    "eq", "ne", "cs", "hs", "cc", "lo", "mi", "pl", "vs", "vc", "hi", "ls", "ge", "lt", "gt", "le",
+];
+
+pub fn disassemble(target: &str, symbol: &Symbol) -> Result<Vec<Instruction>, String> {
+    let (triple, _) = split_target(target);
+    let arch = triple.split('-').next().unwrap_or(triple);
+    let relocation = |range: std::ops::Range<u64>| {
+        symbol
+            .relocations
+            .iter()
+            .find(|r| range.contains(&r.offset))
+    };
+    if arch == "wasm32" {
+        return disassemble_wasm(symbol, relocation);
+    }
+    let cs = capstone(triple)?;
+    let insns = cs
+        .disasm_all(&symbol.bytes, 0)
+        .map_err(|e| format!("capstone: {e}"))?;
+    Ok(insns
+        .iter()
+        .map(|i| {
+            let mnemonic = i.mnemonic().unwrap_or("?");
+            Instruction {
+                offset: i.address(),
+                bytes: i.bytes().to_vec(),
+                text: format!("{mnemonic} {}", i.op_str().unwrap_or(""))
+                    .trim_end()
+                    .to_string(),
+                kind: kind(arch, mnemonic, i.op_str().unwrap_or("")),
+                target: relocation(i.address()..i.address() + i.len() as u64)
+                    .map(|r| r.target.clone()),
+                data: relocation(i.address()..i.address() + i.len() as u64)
+                    .and_then(|r| r.data),
+            }
+        })
+        .collect())
+}
+
+// Capstone has no WebAssembly support, but the function bodies are
+// easy to walk with wasmparser. The local declarations at the start
+// of a body are not instructions and are left out.
+fn disassemble_wasm<'a>(
+    symbol: &Symbol,
+    relocation: impl Fn(std::ops::Range<u64>) -> Option<&'a Relocation>,
+) -> Result<Vec<Instruction>, String> {
+    use wasmparser::{BinaryReader, FunctionBody, Operator};
+
+    let err = |e: wasmparser::BinaryReaderError| format!("wasm: {e}");
+    let body = FunctionBody::new(BinaryReader::new(&symbol.bytes, 0));
+    let mut reader = body.get_operators_reader().map_err(err)?;
+    let mut instructions = Vec::new();
+    while !reader.eof() {
+        let start = reader.original_position();
+        let op = reader.read().map_err(err)?;
+        let end = reader.original_position();
+        let kind = match op {
+            Operator::Br { .. } => Kind::Jump,
+            Operator::BrIf { .. } | Operator::BrTable { .. } | Operator::If { .. } => {
+                Kind::ConditionalJump
+            }
+            Operator::Call { .. } | Operator::CallIndirect { .. } => Kind::Call,
+            Operator::Return => Kind::Return,
+            _ => Kind::Other,
+        };
+        instructions.push(Instruction {
+            offset: start,
+            bytes: symbol.bytes[start as usize..end as usize].to_vec(),
+            text: format!("{op:?}"),
+            kind,
+            target: relocation(start..end).map(|r| r.target.clone()),
+            data: relocation(start..end).and_then(|r| r.data),
+        });
+    }
+    Ok(instructions)
+}
+
+/// The `ilog size` record for a function.
+pub fn code_size(f: &Function, target: &str, instructions: &[Instruction]) -> CodeSize {
+    let count = |kind| instructions.iter().filter(|i| i.kind == kind).count() as u64;
+    let fast_path = instructions
+        .iter()
+        .position(|i| i.kind == Kind::Return)
+        .map_or(instructions.len(), |i| i + 1);
+    // Each table once, however many instructions load from it.
+    let mut data: Vec<_> = instructions[..fast_path]
+        .iter()
+        .filter_map(|i| Some((i.target.as_deref()?, i.data?)))
+        .collect();
+    data.sort_unstable();
+    data.dedup();
+    CodeSize {
+        function: f.name.to_string(),
+        algorithm: f.algorithm.map(String::from),
+        target: target.to_string(),
+        instructions: instructions.len() as u64,
+        fast_path: fast_path as u64,
+        bytes: instructions.iter().map(|i| i.bytes.len() as u64).sum(),
+        // The wasm relocations are not read, so there is nothing to go on.
+        data_bytes: (!target.starts_with("wasm32")).then(|| data.iter().map(|(_, size)| size).sum()),
+        conditional_branches: count(Kind::ConditionalJump),
+        calls: count(Kind::Call),
+    }
+}
+
+/// The listing printed by `ilog asm`.
+pub fn annotate(name: &str, target: &str, instructions: &[Instruction]) -> String {
+    let bytes: usize = instructions.iter().map(|i| i.bytes.len()).sum();
+    let mut out = format!(
+        "{name} ({target}: {} instructions, {bytes} bytes)\n",
+        instructions.len()
+    );
+    for i in instructions {
+        let hex: Vec<String> = i.bytes.iter().map(|b| format!("{b:02x}")).collect();
+        let mut notes = Vec::new();
+        match i.kind {
+            Kind::ConditionalJump => notes.push("conditional branch".to_string()),
+            Kind::Call => notes.push("call".to_string()),
+            _ => {}
+        }
+        if let Some(t) = &i.target {
+            notes.push(t.clone());
+        }
+        let line = format!("{:6x}:  {:<30} {}", i.offset, hex.join(" "), i.text);
+        if notes.is_empty() {
+            out += &format!("{line}\n");
+        } else {
+            out += &format!("{line:<72} ; {}\n", notes.join(", "));
+        }
+    }
+    out
+}
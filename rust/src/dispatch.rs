@@ -0,0 +1,308 @@
+//! A u32 ilog10 chosen at run time for the CPU, enabled by the
+//! `dispatch` feature, for binaries built once for a mixed fleet.
+//!
+//! The benchmarks put the popcount guess (`ilog10`) ahead on AMD Zen,
+//! where `popcnt` is as cheap as an add, and the multiply guess
+//! (`ilog10_mul`) ahead everywhere else. On x86-64 the first call
+//! reads the vendor and family from CPUID and caches the choice; after
+//! that each call is a load and a well-predicted branch in front of
+//! the chosen version. Every other target gets `ilog10_mul`.
+//!
+//! With the feature on, this is what backs `fast_ilog10` and the `ffi`
+//! wrappers for u32, unless a `prefer-*` feature fixes the choice at
+//! build time (see `target` in lib.rs).
+//!
+//! `ilog10_u32_mul` and `ilog10_u64_mul` dispatch the same way between
+//! two builds of one function instead (see `Build`): `ilog10_mul` and
+//! `ilog10_u64_mul` as the crate was compiled, which on baseline x86-64
+//! takes the ilog2 with `bsr`, and a copy compiled for `lzcnt`. The
+//! copy can't be inlined into a caller built without `lzcnt`, so every
+//! dispatched call is a real call; `ilog multiversion` measures what
+//! that and the check cost next to the builds inlined.
+
+use core::num::{NonZeroU32, NonZeroU64};
+use core::sync::atomic::{AtomicU8, Ordering};
+
+/// The versions there is a choice between.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Choice {
+    /// `ilog10_mul`, for any CPU.
+    Mul,
+    /// `ilog10`, compiled for `popcnt` and `lzcnt`.
+    Popcount,
+}
+
+impl Choice {
+    pub const ALL: [Choice; 2] = [Choice::Mul, Choice::Popcount];
+
+    /// The registry name of the version.
+    pub fn name(self) -> &'static str {
+        match self {
+            Choice::Mul => "mul",
+            Choice::Popcount => "popcount",
+        }
+    }
+
+    /// Whether this CPU can run it.
+    #[inline]
+    pub fn supported(self) -> bool {
+        match self {
+            Choice::Mul => true,
+            Choice::Popcount => cpu() & POPCNT != 0,
+        }
+    }
+
+    /// floor(log10(x)) by this version. Panics if the CPU can't run it.
+    pub fn ilog10(self, x: NonZeroU32) -> u32 {
+        assert!(self.supported(), "{} needs popcnt and lzcnt", self.name());
+        // SAFETY: just checked.
+        unsafe { self.ilog10_unchecked(x) }
+    }
+
+    // Safety: `self.supported()`.
+    #[inline]
+    unsafe fn ilog10_unchecked(self, x: NonZeroU32) -> u32 {
+        match self {
+            Choice::Mul => crate::ilog10_mul(x.get()),
+            Choice::Popcount => cpu::ilog10_popcnt(x),
+        }
+    }
+}
+
+/// The builds of `ilog10_mul` and `ilog10_u64_mul` there is a choice
+/// between.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Build {
+    /// As the crate was compiled, for any CPU.
+    Baseline,
+    /// Compiled for `lzcnt`.
+    Lzcnt,
+}
+
+impl Build {
+    pub const ALL: [Build; 2] = [Build::Baseline, Build::Lzcnt];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Build::Baseline => "baseline",
+            Build::Lzcnt => "lzcnt",
+        }
+    }
+
+    /// Whether this CPU can run it.
+    #[inline]
+    pub fn supported(self) -> bool {
+        match self {
+            Build::Baseline => true,
+            Build::Lzcnt => cpu() & LZCNT != 0,
+        }
+    }
+
+    /// `ilog10_mul(x)` by this build. Panics if the CPU can't run it.
+    pub fn ilog10_u32(self, x: NonZeroU32) -> u32 {
+        assert!(self.supported(), "{} needs lzcnt", self.name());
+        // SAFETY: just checked.
+        unsafe { self.ilog10_u32_unchecked(x) }
+    }
+
+    /// `ilog10_u64_mul(x)` by this build. Panics if the CPU can't run it.
+    pub fn ilog10_u64(self, x: NonZeroU64) -> u32 {
+        assert!(self.supported(), "{} needs lzcnt", self.name());
+        // SAFETY: just checked.
+        unsafe { self.ilog10_u64_unchecked(x) }
+    }
+
+    /// As `ilog10_u32`, without the check, for timing the call alone.
+    ///
+    /// # Safety
+    ///
+    /// `self.supported()`.
+    #[inline]
+    pub unsafe fn ilog10_u32_unchecked(self, x: NonZeroU32) -> u32 {
+        match self {
+            Build::Baseline => crate::ilog10_mul(x.get()),
+            Build::Lzcnt => cpu::ilog10_mul_lzcnt(x),
+        }
+    }
+
+    /// As `ilog10_u64`, without the check.
+    ///
+    /// # Safety
+    ///
+    /// `self.supported()`.
+    #[inline]
+    pub unsafe fn ilog10_u64_unchecked(self, x: NonZeroU64) -> u32 {
+        match self {
+            Build::Baseline => crate::ilog10_u64_mul(x.get()),
+            Build::Lzcnt => cpu::ilog10_u64_mul_lzcnt(x),
+        }
+    }
+}
+
+// What the first call found out about the CPU: DETECTED, and then
+// POPCNT for popcnt and lzcnt, ZEN for an AMD Zen and LZCNT for lzcnt
+// on its own.
+static CPU: AtomicU8 = AtomicU8::new(0);
+const DETECTED: u8 = 1;
+const POPCNT: u8 = 2;
+const ZEN: u8 = 4;
+const LZCNT: u8 = 8;
+
+#[inline]
+fn cpu() -> u8 {
+    match CPU.load(Ordering::Relaxed) {
+        0 => detect(),
+        bits => bits,
+    }
+}
+
+#[cold]
+fn detect() -> u8 {
+    let bits = DETECTED
+        | if cpu::has_popcnt_and_lzcnt() { POPCNT } else { 0 }
+        | if cpu::is_zen() { ZEN } else { 0 }
+        | if cpu::has_lzcnt() { LZCNT } else { 0 };
+    // Racing first calls all store the same value.
+    CPU.store(bits, Ordering::Relaxed);
+    bits
+}
+
+/// The version this CPU gets, detected on the first call.
+#[inline]
+pub fn choice() -> Choice {
+    if cpu() & (POPCNT | ZEN) == POPCNT | ZEN {
+        Choice::Popcount
+    } else {
+        Choice::Mul
+    }
+}
+
+/// floor(log10(x)) by the version `choice` picks for this CPU.
+#[inline]
+pub fn ilog10_u32(x: NonZeroU32) -> u32 {
+    // SAFETY: choice() only picks a supported version.
+    unsafe { choice().ilog10_unchecked(x) }
+}
+
+/// The build this CPU gets: `lzcnt` where it has the instruction.
+#[inline]
+pub fn build() -> Build {
+    if cpu() & LZCNT != 0 {
+        Build::Lzcnt
+    } else {
+        Build::Baseline
+    }
+}
+
+/// `ilog10_mul(x)` by the build `build` picks for this CPU.
+#[inline]
+pub fn ilog10_u32_mul(x: NonZeroU32) -> u32 {
+    // SAFETY: build() only picks a supported build.
+    unsafe { build().ilog10_u32_unchecked(x) }
+}
+
+/// `ilog10_u64_mul(x)` by the build `build` picks for this CPU.
+#[inline]
+pub fn ilog10_u64_mul(x: NonZeroU64) -> u32 {
+    // SAFETY: as for ilog10_u32_mul.
+    unsafe { build().ilog10_u64_unchecked(x) }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod cpu {
+    use core::arch::x86_64::{CpuidResult, __cpuid};
+    use core::num::{NonZeroU32, NonZeroU64};
+
+    fn cpuid(leaf: u32) -> CpuidResult {
+        // SAFETY: every x86-64 CPU has CPUID, and unknown leaves read
+        // as zeros or the highest basic leaf. (Safe itself on newer
+        // toolchains.)
+        #[allow(unused_unsafe)]
+        unsafe {
+            __cpuid(leaf)
+        }
+    }
+
+    // AMD family 17h (Zen, Zen 2) and later, and Hygon's Zen-based
+    // Dhyana.
+    pub fn is_zen() -> bool {
+        let vendor = cpuid(0);
+        let name = [vendor.ebx, vendor.edx, vendor.ecx];
+        let words = |s: &[u8; 12]| {
+            [0, 4, 8].map(|i| u32::from_le_bytes([s[i], s[i + 1], s[i + 2], s[i + 3]]))
+        };
+        if name != words(b"AuthenticAMD") && name != words(b"HygonGenuine") {
+            return false;
+        }
+        let eax = cpuid(1).eax;
+        let base = (eax >> 8) & 0xf;
+        let family = if base == 0xf {
+            base + ((eax >> 20) & 0xff)
+        } else {
+            base
+        };
+        family >= 0x17
+    }
+
+    pub fn has_popcnt_and_lzcnt() -> bool {
+        cpuid(1).ecx & (1 << 23) != 0 && has_lzcnt()
+    }
+
+    pub fn has_lzcnt() -> bool {
+        let extended = cpuid(0x8000_0000).eax >= 0x8000_0001;
+        extended && cpuid(0x8000_0001).ecx & (1 << 5) != 0
+    }
+
+    // Safety: the CPU has popcnt and lzcnt.
+    #[target_feature(enable = "popcnt,lzcnt")]
+    pub unsafe fn ilog10_popcnt(x: NonZeroU32) -> u32 {
+        crate::ilog10(x.get())
+    }
+
+    // Safety: the CPU has lzcnt.
+    //
+    // ilog10_mul's guess, (31 - lz) * 9 >> 5, rearranged: LLVM turns
+    // 31 - lzcnt back into bsr, lzcnt or not, and bsr waits on its
+    // destination register, which in a loop often holds the previous
+    // result.
+    #[target_feature(enable = "lzcnt")]
+    pub unsafe fn ilog10_mul_lzcnt(x: NonZeroU32) -> u32 {
+        let guess = (279 - 9 * x.leading_zeros()) >> 5;
+        crate::parts::correct(guess, x.get())
+    }
+
+    // Safety: the CPU has lzcnt.
+    #[target_feature(enable = "lzcnt")]
+    pub unsafe fn ilog10_u64_mul_lzcnt(x: NonZeroU64) -> u32 {
+        crate::ilog10_u64_mul(x.get())
+    }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+mod cpu {
+    use core::num::{NonZeroU32, NonZeroU64};
+
+    pub fn is_zen() -> bool {
+        false
+    }
+
+    pub fn has_popcnt_and_lzcnt() -> bool {
+        false
+    }
+
+    pub unsafe fn ilog10_popcnt(_: NonZeroU32) -> u32 {
+        unreachable!("popcount is never supported off x86-64")
+    }
+
+    pub fn has_lzcnt() -> bool {
+        false
+    }
+
+    pub unsafe fn ilog10_mul_lzcnt(_: NonZeroU32) -> u32 {
+        unreachable!("lzcnt is never supported off x86-64")
+    }
+
+    pub unsafe fn ilog10_u64_mul_lzcnt(_: NonZeroU64) -> u32 {
+        unreachable!("lzcnt is never supported off x86-64")
+    }
+}
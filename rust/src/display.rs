@@ -0,0 +1,100 @@
+// `DisplayU64` and `DisplayI64` against the standard library's own
+// `Display` for u64 and i64, for `ilog display`: the same random values
+// formatted with `write!` into a `String` of about a megabyte, which is
+// handed to `black_box` and cleared when full, as `to_string` or a
+// logger would. The i64s are the u64s halved, every other one negated,
+// so that both signs come up. Each takes turns with the other, in a new
+// order each round, and the best round of each counts.
+
+use std::fmt::{Display, Write};
+use std::hint::black_box;
+use std::time::Instant;
+
+use ilog::bench::{Distribution, Input};
+use ilog::registry::Width;
+use ilog::results::DisplayThroughput;
+use ilog::{DisplayI64, DisplayU64};
+use rand::seq::SliceRandom;
+
+use crate::report::Report;
+
+const BUFFER: usize = 1 << 20;
+
+// One row: the integer type, the formatter, and the timed loop.
+type Row<'a> = (&'static str, &'static str, Box<dyn Fn() + 'a>);
+
+pub fn report(distribution: Distribution, samples: usize) -> Result<Report, String> {
+    if samples == 0 {
+        return Err("--samples must be at least 1".into());
+    }
+    // Enough for a second or so a round.
+    let input = Input::generate(Width::U64, distribution).scaled(1 << 24);
+    let Input::U64 { values, passes } = &input else {
+        return Err(format!("{} is not a random distribution", distribution.name()));
+    };
+    let passes = *passes;
+    let signed: Vec<i64> = values
+        .iter()
+        .map(|&x| match (x >> 1) as i64 {
+            half if x & 1 == 0 => half,
+            half => -half,
+        })
+        .collect();
+    let signed = &signed;
+
+    let rows: Vec<Row> = vec![
+        ("u64", "std", Box::new(move || format_all(values, passes, |x| x))),
+        ("u64", "ilog", Box::new(move || format_all(values, passes, DisplayU64))),
+        ("i64", "std", Box::new(move || format_all(signed, passes, |x| x))),
+        ("i64", "ilog", Box::new(move || format_all(signed, passes, DisplayI64))),
+    ];
+    let mut best = vec![f64::INFINITY; rows.len()];
+    let mut order: Vec<usize> = (0..rows.len()).collect();
+    let mut rng = rand::rng();
+    for _ in 0..samples {
+        order.shuffle(&mut rng);
+        for &i in &order {
+            let start = Instant::now();
+            (rows[i].2)();
+            best[i] = best[i].min(start.elapsed().as_nanos() as f64);
+        }
+    }
+
+    let ops = input.ops() as f64;
+    let mut report = Report::new(
+        "display",
+        &["integer", "distribution", "formatter", "ns_per_value", "speedup"],
+    );
+    for ((integer, formatter, _), &ns) in rows.iter().zip(&best) {
+        let std = rows
+            .iter()
+            .zip(&best)
+            .find(|((i, f, _), _)| i == integer && *f == "std")
+            .map_or(ns, |(_, &ns)| ns);
+        report.push(&DisplayThroughput {
+            integer: integer.to_string(),
+            distribution,
+            formatter: formatter.to_string(),
+            ns_per_value: ns / ops,
+            speedup: std / ns,
+        });
+    }
+    Ok(report)
+}
+
+// Every value, `passes` times over, written as `display` gives it, each
+// followed by a newline.
+#[inline(always)]
+fn format_all<T: Copy, D: Display>(values: &[T], passes: usize, display: impl Fn(T) -> D) {
+    let mut out = String::with_capacity(BUFFER + 32);
+    for _ in 0..passes {
+        for &x in values {
+            if out.len() > BUFFER {
+                black_box(out.as_str());
+                out.clear();
+            }
+            writeln!(out, "{}", display(x)).unwrap();
+        }
+    }
+    black_box(out.as_str());
+}
@@ -0,0 +1,73 @@
+// Package energy from the Linux powercap interface, for the
+// joules_per_billion_ops column of `ilog bench`. Each CPU package is a
+// top-level `intel-rapl:N` zone (AMD's RAPL shows up under the same
+// name) whose `energy_uj` counts microjoules and wraps at
+// `max_energy_range_uj`.
+//
+// What it measures is the whole package, idle cores and uncore
+// included, so it means most on an otherwise quiet machine, where
+// that part is the same for every algorithm. The counters are often
+// readable only by root; without them the column is empty.
+
+use std::path::PathBuf;
+
+const POWERCAP: &str = "/sys/class/powercap";
+
+struct Zone {
+    energy: PathBuf,
+    range: u64,
+}
+
+/// The package zones, opened once per benchmark.
+pub struct Meter {
+    zones: Vec<Zone>,
+}
+
+/// The counters of each zone at one moment.
+pub struct Reading(Vec<u64>);
+
+impl Meter {
+    /// None if there are no package zones or they can't be read.
+    pub fn open() -> Option<Meter> {
+        let mut zones = Vec::new();
+        for entry in std::fs::read_dir(POWERCAP).ok()?.flatten() {
+            let Ok(name) = entry.file_name().into_string() else {
+                continue;
+            };
+            // intel-rapl:0 is a package, intel-rapl:0:1 one of its parts.
+            match name.strip_prefix("intel-rapl:") {
+                Some(n) if !n.contains(':') => {}
+                _ => continue,
+            }
+            let path = entry.path();
+            let range = read_u64(&path.join("max_energy_range_uj"))?;
+            zones.push(Zone {
+                energy: path.join("energy_uj"),
+                range,
+            });
+        }
+        let meter = Meter { zones };
+        (!meter.zones.is_empty() && meter.read().is_some()).then_some(meter)
+    }
+
+    pub fn read(&self) -> Option<Reading> {
+        let counters = self.zones.iter().map(|z| read_u64(&z.energy));
+        counters.collect::<Option<_>>().map(Reading)
+    }
+
+    /// Joules used between two readings, allowing for one wrap of each
+    /// counter.
+    pub fn joules(&self, before: &Reading, after: &Reading) -> f64 {
+        let uj: u64 = self
+            .zones
+            .iter()
+            .zip(before.0.iter().zip(&after.0))
+            .map(|(zone, (&b, &a))| if a >= b { a - b } else { a + zone.range - b })
+            .sum();
+        uj as f64 / 1e6
+    }
+}
+
+fn read_u64(path: &std::path::Path) -> Option<u64> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
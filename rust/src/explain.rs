@@ -0,0 +1,147 @@
+// `ilog explain VALUE`: the guess-and-correct algorithms worked
+// through by hand for one input. Each guess strategy's rows give the
+// leading-zero count, the guess with the multiply, mask or table
+// arithmetic written out, the threshold the guess picks from the table,
+// and whether the correction adds one; then every registered algorithm
+// of the width gives its answer, checked against the standard
+// library's. As a report, the walk-through can be diffed between
+// inputs or fed to a script as JSON.
+
+use ilog::parts;
+use ilog::registry::{self, Width};
+use ilog::results::ExplainStep;
+
+use crate::report::Report;
+
+/// A guess strategy, and its guess from `lz` with the arithmetic spelled out.
+struct Guess {
+    name: &'static str,
+    width: Width,
+    explain: fn(u32) -> (u32, String),
+}
+
+const GUESSES: &[Guess] = &[
+    Guess {
+        name: "mul",
+        width: Width::U32,
+        explain: |lz| {
+            let ilog2 = 31 - lz;
+            let guess = parts::guess_ilog10_u32(lz);
+            let detail = format!(
+                "(31 - {lz}) * 9 >> 5 = {} >> 5, as 9/32 = 0.28125 is just under log10(2) = 0.30103",
+                ilog2 * 9
+            );
+            (guess, detail)
+        },
+    },
+    Guess {
+        name: "popcount",
+        width: Width::U32,
+        explain: |lz| {
+            let mask = popcount_mask();
+            let guess = parts::guess_ilog10_u32_popcount(lz);
+            let detail = format!(
+                "({mask:#034b} << {lz}).count_ones() = {:#034b}.count_ones(), the mask having \
+                 a 1 at each bit where the next power of two passes a power of ten",
+                mask << lz
+            );
+            (guess, detail)
+        },
+    },
+    Guess {
+        name: "table",
+        width: Width::U32,
+        explain: |lz| {
+            let guess = parts::guess_ilog10_u32_table(lz);
+            (guess, format!("GUESS_TABLE[31 - {lz}] = GUESS_TABLE[{}], one byte per ilog2", 31 - lz))
+        },
+    },
+    Guess {
+        name: "mul",
+        width: Width::U64,
+        explain: |lz| {
+            let ilog2 = 63 - lz;
+            let guess = parts::guess_ilog10_u64(lz);
+            let detail = format!(
+                "(63 - {lz}) * 19 >> 6 = {} >> 6, as 19/64 = 0.296875 is just under log10(2) = 0.30103",
+                ilog2 * 19
+            );
+            (guess, detail)
+        },
+    },
+];
+
+pub fn report(width: Width, x: u64) -> Result<Report, String> {
+    if x == 0 {
+        return Err("0 has no ilog10".into());
+    }
+    if width == Width::U32 && x > u32::MAX as u64 {
+        return Err(format!("{x} does not fit a u32; add --u64"));
+    }
+    let bits = match width {
+        Width::U32 => 32,
+        Width::U64 => 64,
+    };
+    let lz = x.leading_zeros() - (64 - bits);
+    let expected = x.ilog10();
+
+    let mut report = Report::new(&format!("explain {x}"), &["algorithm", "step", "value", "detail"]);
+    let mut step = |algorithm: String, step: &str, value: u64, detail: String| {
+        report.push(&ExplainStep {
+            algorithm,
+            step: step.to_string(),
+            value,
+            detail,
+        })
+    };
+    for guess in GUESSES.iter().filter(|g| g.width == width) {
+        let name = format!("{}/{}", width.name(), guess.name);
+        let digits = bits - lz;
+        step(
+            name.clone(),
+            "leading_zeros",
+            lz as u64,
+            format!("{x:#b} has {digits} significant bits, so ilog2 = {} - {lz} = {}", bits - 1, digits - 1),
+        );
+        let (g, detail) = (guess.explain)(lz);
+        step(name.clone(), "guess", g as u64, detail);
+        let (threshold, table) = match width {
+            Width::U32 => (ilog::TEN_THRESHOLDS[g as usize] as u64, "TEN_THRESHOLDS"),
+            Width::U64 => (ilog::U64_THRESHOLDS[g as usize], "U64_THRESHOLDS"),
+        };
+        step(
+            name.clone(),
+            "threshold",
+            threshold,
+            format!("{table}[{g}] = 10^{} - 1, the largest number of {} digits", g + 1, g + 1),
+        );
+        let (result, detail) = if x > threshold {
+            (g + 1, format!("{x} > {threshold}, so the guess was one short: {g} + 1"))
+        } else {
+            (g, format!("{x} <= {threshold}, so the guess {g} stands"))
+        };
+        step(name, "correct", result as u64, detail);
+    }
+    for a in registry::of_width(width) {
+        let result = (a.eval)(x);
+        let detail = if result == expected {
+            "agrees with the standard library".to_string()
+        } else {
+            format!("DISAGREES with the standard library's {expected}")
+        };
+        step(a.qualified_name(), "result", result as u64, detail);
+    }
+    Ok(report)
+}
+
+/// The mask the popcount guess shifts by `lz`, read back off the guess:
+/// the guess counts the mask's 1s from bit 0 to bit 31 - lz, so bit b is
+/// set where the guess for lz = 31 - b is one more than for lz = 32 - b.
+pub fn popcount_mask() -> u32 {
+    (0..32)
+        .filter(|&b| {
+            let below = if b == 0 { 0 } else { parts::guess_ilog10_u32_popcount(32 - b) };
+            parts::guess_ilog10_u32_popcount(31 - b) > below
+        })
+        .fold(0, |mask, b| mask | 1 << b)
+}
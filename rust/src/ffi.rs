@@ -0,0 +1,100 @@
+//! C ABI wrappers, enabled by the `ffi` feature.
+//!
+//! The prototypes are in `include/ilog.h`, generated from this file
+//! alone by cbindgen; a build with the feature writes a fresh copy to
+//! `OUT_DIR`, and rewrites the committed one if `ILOG_UPDATE_HEADER` is
+//! set.
+//! `cargo rustc --release --lib --features ffi --crate-type
+//! staticlib,cdylib` leaves a static and a shared library in
+//! `target/release` to link against from C or C++; for firmware, see
+//! the `embedded` profile in Cargo.toml. The scalar
+//! functions use the multiply-based guess, which is the best choice on
+//! most CPUs, unless a `prefer-*` feature picks another (see `target`
+//! in lib.rs).
+
+use core::slice;
+
+/// floor(log10(x)), or `UINT32_MAX` for 0, where it is undefined.
+#[no_mangle]
+pub extern "C" fn ilog10_u32(x: u32) -> u32 {
+    if x == 0 {
+        return u32::MAX;
+    }
+    crate::target::ilog10_u32(x)
+}
+
+/// floor(log10(x)), or `UINT32_MAX` for 0, where it is undefined.
+#[no_mangle]
+pub extern "C" fn ilog10_u64(x: u64) -> u32 {
+    if x == 0 {
+        return u32::MAX;
+    }
+    crate::target::ilog10_u64(x)
+}
+
+/// Number of decimal digits in `x`, counting 0 as one digit.
+#[no_mangle]
+pub extern "C" fn digits_u32(x: u32) -> u32 {
+    crate::digits_u32(x)
+}
+
+/// Number of decimal digits in `x`, counting 0 as one digit.
+#[no_mangle]
+pub extern "C" fn digits_u64(x: u64) -> u32 {
+    crate::digits_u64(x)
+}
+
+// The slice variants are written out rather than generated by a
+// macro because cbindgen does not expand macros.
+
+unsafe fn map_slice<T: Copy>(input: *const T, output: *mut u32, len: usize, f: impl Fn(T) -> u32) {
+    if len == 0 {
+        return;
+    }
+    let input = slice::from_raw_parts(input, len);
+    let output = slice::from_raw_parts_mut(output, len);
+    for (o, &x) in output.iter_mut().zip(input) {
+        *o = f(x);
+    }
+}
+
+/// `ilog10_u32` of each of the `len` values at `input`, written to `output`.
+///
+/// # Safety
+///
+/// `input` and `output` must each be valid for `len` elements and must
+/// not overlap. Either may be null if `len` is 0.
+#[no_mangle]
+pub unsafe extern "C" fn ilog10_u32_slice(input: *const u32, output: *mut u32, len: usize) {
+    map_slice(input, output, len, |x| ilog10_u32(x))
+}
+
+/// `ilog10_u64` of each of the `len` values at `input`, written to `output`.
+///
+/// # Safety
+///
+/// As for `ilog10_u32_slice`.
+#[no_mangle]
+pub unsafe extern "C" fn ilog10_u64_slice(input: *const u64, output: *mut u32, len: usize) {
+    map_slice(input, output, len, |x| ilog10_u64(x))
+}
+
+/// `digits_u32` of each of the `len` values at `input`, written to `output`.
+///
+/// # Safety
+///
+/// As for `ilog10_u32_slice`.
+#[no_mangle]
+pub unsafe extern "C" fn digits_u32_slice(input: *const u32, output: *mut u32, len: usize) {
+    map_slice(input, output, len, |x| digits_u32(x))
+}
+
+/// `digits_u64` of each of the `len` values at `input`, written to `output`.
+///
+/// # Safety
+///
+/// As for `ilog10_u32_slice`.
+#[no_mangle]
+pub unsafe extern "C" fn digits_u64_slice(input: *const u64, output: *mut u32, len: usize) {
+    map_slice(input, output, len, |x| digits_u64(x))
+}
@@ -0,0 +1,62 @@
+// Name filters for `--filter`, in the spirit of `cargo test <filter>`:
+// a plain word matches as a substring, a pattern with glob
+// metacharacters (`*mul*`, `u64/*`) must match the whole name, and
+// with `--regex` every pattern is a regular expression instead.
+
+use regex::Regex;
+
+enum Matcher {
+    Substring(String),
+    Glob(glob::Pattern),
+    Regex(Regex),
+}
+
+impl Matcher {
+    fn matches(&self, name: &str) -> bool {
+        match self {
+            Matcher::Substring(s) => name.contains(s.as_str()),
+            Matcher::Glob(p) => p.matches(name),
+            Matcher::Regex(r) => r.is_match(name),
+        }
+    }
+}
+
+/// Matches if any of its patterns match; an empty filter matches
+/// everything.
+#[derive(Default)]
+pub struct Filter {
+    matchers: Vec<Matcher>,
+}
+
+impl Filter {
+    pub fn new(patterns: &[String], regex: bool) -> Result<Filter, String> {
+        let matchers = patterns
+            .iter()
+            .map(|p| {
+                if regex {
+                    Regex::new(p).map(Matcher::Regex).map_err(|e| e.to_string())
+                } else if p.contains(['*', '?', '[']) {
+                    glob::Pattern::new(p)
+                        .map(Matcher::Glob)
+                        .map_err(|e| format!("{p:?}: {e}"))
+                } else {
+                    Ok(Matcher::Substring(p.clone()))
+                }
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Filter { matchers })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.matchers.is_empty()
+    }
+
+    /// Whether any pattern matches any of the names an item goes by.
+    pub fn matches(&self, names: &[&str]) -> bool {
+        self.is_empty()
+            || self
+                .matchers
+                .iter()
+                .any(|m| names.iter().any(|n| m.matches(n)))
+    }
+}
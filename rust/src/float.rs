@@ -0,0 +1,83 @@
+//! ilog2 from a float's exponent, with no count-leading-zeros at all:
+//! convert the integer to f32 or f64, shift the biased exponent out of
+//! the bits and take off the bias. The conversion is one instruction
+//! wherever there is an FPU (`cvtsi2sd` on x86-64, `fcvt.d.wu` on
+//! RISC-V), which makes this the cheap ilog2 on cores that have floats
+//! but no clz. (x86-64 before AVX-512 has no unsigned u64 to f64, and
+//! makes do with five instructions.)
+//!
+//! The catch is rounding. An f32 holds 24 significant bits and an f64
+//! 53, so past 2^24 (2^53) the conversion rounds to nearest, and the
+//! values just under each power of two round up to it, exponent and
+//! all:
+//!
+//! ```
+//! let x = (1u32 << 25) - 1;
+//! assert_eq!(x as f32, (1u32 << 25) as f32);
+//! assert_eq!(ilog::float::ilog2_f32_u32(x), 24);
+//! ```
+//!
+//! `ilog2_f32_u32` and `ilog2_f64_u64` take that back with a compare
+//! against the power of two. The ilog10s don't need to: the guess from
+//! one exponent too many is still at most the ilog10, as no power of ten
+//! is among the few values that round up, and the correction puts it
+//! right as usual. All they must do is keep the guess in range, since
+//! the top 128 u32s round to 2^32 as f32 and the top 1024 u64s to 2^64.
+//! `ilog test` checks every u32 and, for u64, both sides of every point
+//! where the rounding changes.
+//!
+//! As with `soft_clz`, these don't panic on 0: the ilog10 is 0.
+
+// The biased exponent of a positive f32 or f64, which is ilog2 of the
+// value it holds.
+#[inline]
+fn exponent_f32(x: u32) -> u32 {
+    ((x as f32).to_bits() >> 23) - 127
+}
+
+#[inline]
+fn exponent_f64(x: u64) -> u32 {
+    ((x as f64).to_bits() >> 52) as u32 - 1023
+}
+
+/// floor(log2(x)) from the exponent of `x as f32`. Panics if `x` is 0.
+#[inline]
+pub fn ilog2_f32_u32(x: u32) -> u32 {
+    assert!(x != 0, "argument of integer logarithm must be positive");
+    let e = exponent_f32(x);
+    // Up to 32, for the values that round up.
+    e - ((x as u64) < 1 << e) as u32
+}
+
+/// floor(log2(x)) from the exponent of `x as f64`. Panics if `x` is 0.
+#[inline]
+pub fn ilog2_f64_u64(x: u64) -> u32 {
+    assert!(x != 0, "argument of integer logarithm must be positive");
+    let e = exponent_f64(x);
+    // Up to 64, for the values that round up.
+    e - ((x as u128) < 1 << e) as u32
+}
+
+/// `ilog10_mul` with the exponent of `x as f32` for the ilog2, rounding
+/// and all.
+#[inline]
+pub fn ilog10_f32(x: u32) -> u32 {
+    let log2 = exponent_f32(x | 1).min(31);
+    crate::parts::correct(log2.wrapping_mul(9) >> 5, x)
+}
+
+/// `ilog10_mul` with the exponent of `x as f64`, which holds any u32
+/// exactly.
+#[inline]
+pub fn ilog10_f64(x: u32) -> u32 {
+    let log2 = exponent_f64(x as u64 | 1).min(31);
+    crate::parts::correct(log2.wrapping_mul(9) >> 5, x)
+}
+
+/// `ilog10_u64_mul` with the exponent of `x as f64` for the ilog2,
+/// rounding and all.
+#[inline]
+pub fn ilog10_u64_f64(x: u64) -> u32 {
+    let log2 = exponent_f64(x | 1).min(63);
+    crate::parts::correct_u64(log2.wrapping_mul(19) >> 6, x)
+}
@@ -0,0 +1,48 @@
+//! `fast_ilog10` for any `num_traits::PrimInt`, enabled by the
+//! `num-traits` feature, for code that is already generic over it.
+//!
+//! The width is a constant for each `T`, so after inlining only the
+//! call to the crate's u32 (8 to 32 bits) or u64 (64 bits) ilog10
+//! for the target is left: the multiply guess, or on 32-bit ARM and on
+//! microcontrollers one of the versions in `arm32` and `mcu`, or for
+//! u32 whichever a `prefer-*` feature asks for. 128-bit values above
+//! `u64::MAX` take a division by 10^20 first.
+
+use num_traits::PrimInt;
+
+/// floor(log10(x)), as `x.ilog10()` for the primitive type `T`.
+///
+/// Panics, with the standard library's message, if `x` is 0 or
+/// negative.
+///
+/// ```
+/// use ilog::fast_ilog10;
+///
+/// assert_eq!(fast_ilog10(99u8), 1);
+/// assert_eq!(fast_ilog10(1_000_000i32), 6);
+/// assert_eq!(fast_ilog10(u128::MAX), 38);
+/// ```
+#[inline]
+#[track_caller]
+pub fn fast_ilog10<T: PrimInt>(x: T) -> u32 {
+    if x <= T::zero() {
+        panic!("{}", crate::core_compat::PANIC_MESSAGE);
+    }
+    // x is positive, so it fits the unsigned type of its width, and
+    // the conversions below can't fail.
+    if core::mem::size_of::<T>() <= 4 {
+        return crate::target::ilog10_u32(x.to_u32().unwrap());
+    }
+    if let Some(x) = x.to_u64() {
+        return crate::target::ilog10_u64(x);
+    }
+    // Only a 128-bit x gets here, and it is at least 2^64 > 10^19.
+    const E20: u128 = 100_000_000_000_000_000_000;
+    let x = x.to_u128().unwrap();
+    if x < E20 {
+        19
+    } else {
+        // u128::MAX / 10^20 < 2^62
+        20 + crate::ilog10_u64_mul((x / E20) as u64)
+    }
+}
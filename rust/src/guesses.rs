@@ -0,0 +1,151 @@
+// Each half of a guess-and-correct ilog10 timed on its own, for `ilog
+// guesses`. Where one algorithm beats another on one CPU and loses on
+// the next, the difference is usually in the guess (popcount is slow
+// on some cores, the multiply on others) or in the correction's table
+// load, and timing the whole function can't say which. Here the
+// leading zeros are counted ahead of time for the guess, and the
+// guesses made ahead of time for the correction, so that each loop
+// times one step; the whole function is timed over the same values for
+// scale. The steps overlap in the pipeline when run together, so their
+// times needn't add up to the whole.
+//
+// The correction loop's guesses come from memory, so unlike in the
+// real functions the compiler can't drop its bounds check on the
+// table; that adds a compare and a never-taken branch.
+
+use std::time::Instant;
+
+use ilog::bench::{Distribution, Input};
+use ilog::parts;
+use ilog::registry::{self, Width};
+use ilog::results::GuessTiming;
+
+use crate::filter::Filter;
+use crate::report::Report;
+
+// Best of this many runs of each loop.
+const REPETITIONS: usize = 3;
+
+/// A guess strategy, its monomorphized timing loops and the registry
+/// entry it is the guess of.
+struct Guess {
+    name: &'static str,
+    width: Width,
+    algorithm: &'static str,
+    guess: fn(u32) -> u32,
+    time_guess: fn(&[u32], usize) -> u128,
+    time_correct: fn(&[(u32, u64)], usize) -> u128,
+}
+
+macro_rules! guess {
+    ($name:expr, $width:expr, $algorithm:expr, $guess:expr, $correct:expr) => {
+        Guess {
+            name: $name,
+            width: $width,
+            algorithm: $algorithm,
+            guess: $guess,
+            time_guess: |lz, passes| time(lz, passes, $guess),
+            time_correct: |pairs, passes| time(pairs, passes, |(g, x)| $correct(g, x)),
+        }
+    };
+}
+
+const GUESSES: &[Guess] = &[
+    guess!("mul", Width::U32, "mul", parts::guess_ilog10_u32, |g, x| parts::correct(g, x as u32)),
+    guess!("popcount", Width::U32, "popcount", parts::guess_ilog10_u32_popcount, |g, x| {
+        parts::correct(g, x as u32)
+    }),
+    guess!("table", Width::U32, "table", parts::guess_ilog10_u32_table, |g, x| {
+        parts::correct(g, x as u32)
+    }),
+    guess!("mul", Width::U64, "mul", parts::guess_ilog10_u64, parts::correct_u64),
+    guess!("mul_over", Width::U64, "mul20", parts::guess_ilog10_u64_over, parts::correct_u64_down),
+];
+
+impl Guess {
+    fn qualified_name(&self) -> String {
+        format!("{}/{}", self.width.name(), self.name)
+    }
+}
+
+// Microseconds for `f` of every value, `passes` times over.
+#[inline(always)]
+fn time<T: Copy, F: Fn(T) -> u32>(values: &[T], passes: usize, f: F) -> u128 {
+    let start = Instant::now();
+    for _ in 0..passes {
+        for &x in values {
+            std::hint::black_box(f(x));
+        }
+    }
+    start.elapsed().as_micros()
+}
+
+fn best(mut run: impl FnMut() -> u128) -> u128 {
+    (0..REPETITIONS).map(|_| run()).min().unwrap()
+}
+
+fn steps(guess: &Guess, distribution: Distribution) -> Result<Vec<GuessTiming>, String> {
+    let input = Input::generate(guess.width, distribution);
+    let (values, passes): (Vec<u64>, usize) = match &input {
+        Input::U32 { values, passes } => (values.iter().map(|&x| x as u64).collect(), *passes),
+        Input::U64 { values, passes } => (values.clone(), *passes),
+        Input::Sweep | Input::Permuted(_) | Input::Strided { .. } => {
+            return Err(format!("{} is not a random distribution", distribution.name()));
+        }
+    };
+    let lz: Vec<u32> = match guess.width {
+        // Counted as the u32 functions count them, from bit 31.
+        Width::U32 => values.iter().map(|&x| (x as u32).leading_zeros()).collect(),
+        Width::U64 => values.iter().map(|&x| x.leading_zeros()).collect(),
+    };
+    let pairs: Vec<(u32, u64)> = lz.iter().zip(&values).map(|(&z, &x)| ((guess.guess)(z), x)).collect();
+    let algorithm = registry::of_width(guess.width)
+        .find(|a| a.name == guess.algorithm)
+        .expect("guess of an unregistered algorithm");
+
+    let leading_zeros = match guess.width {
+        Width::U32 => best(|| time(&values, passes, |x| (x as u32).leading_zeros())),
+        Width::U64 => best(|| time(&values, passes, u64::leading_zeros)),
+    };
+    let timings = [
+        ("leading_zeros", leading_zeros),
+        ("guess", best(|| (guess.time_guess)(&lz, passes))),
+        ("correct", best(|| (guess.time_correct)(&pairs, passes))),
+        ("whole", best(|| algorithm.time(&input))),
+    ];
+    let ns = |us: u128| us as f64 * 1000.0 / input.ops() as f64;
+    let whole = ns(timings[3].1);
+    Ok(timings
+        .into_iter()
+        .map(|(step, us)| GuessTiming {
+            guess: guess.qualified_name(),
+            distribution,
+            step: step.to_string(),
+            ns_per_op: ns(us),
+            of_whole: ns(us) / whole,
+        })
+        .collect())
+}
+
+pub fn report(distributions: &[Distribution], filter: &Filter) -> Result<Report, String> {
+    let guesses: Vec<&Guess> = GUESSES
+        .iter()
+        .filter(|g| filter.matches(&[&g.qualified_name(), g.algorithm]))
+        .collect();
+    if guesses.is_empty() {
+        return Err("no guess strategy matches the filter".into());
+    }
+    let mut report = Report::new(
+        "guesses",
+        &["guess", "distribution", "step", "ns_per_op", "of_whole"],
+    );
+    for guess in guesses {
+        for &distribution in distributions {
+            eprintln!("Timing {} over {}", guess.qualified_name(), distribution.name());
+            for row in steps(guess, distribution)? {
+                report.push(&row);
+            }
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,76 @@
+//! Branch hints on the two branches that the forum thread keeps asking
+//! about, enabled by the `nightly` feature, as `core::hint::likely`
+//! and `unlikely` are not stable yet.
+//!
+//! - The `val >= 100_000` split of `ilog10_u32` (`stdlib`):
+//!   `ilog10_u32_large` hints that most values are at least 100000,
+//!   as uniform u32s are, and `ilog10_u32_small` that most are below,
+//!   as counts and lengths are.
+//! - The correction of `ilog10_mul`, which LLVM otherwise makes
+//!   branch-free. Written as an `if` with a hint it can come out as a
+//!   branch: `ilog10_mul_exact` expects the guess to be right, as it
+//!   mostly is for inputs spread evenly over the digit counts, and
+//!   `ilog10_mul_corrected` expects the +1, as for most uniform u32s
+//!   (see `ilog corrections`).
+//!
+//! A hint doesn't teach the predictor anything; what it changes is
+//! the layout of the fast path, and whether LLVM branches at all.
+//! `cargo +nightly run --release --features nightly -- bench --suite
+//! hints` times them next to the unhinted versions over shuffled and
+//! sorted inputs.
+
+use core::hint::{likely, unlikely};
+
+/// `ilog10_u32`, expecting `val >= 100_000`.
+#[inline]
+pub const fn ilog10_u32_large(mut val: u32) -> u32 {
+    let mut log = 0;
+    if likely(val >= 100_000) {
+        val /= 100_000;
+        log += 5;
+    }
+    log + crate::less_than_5(val)
+}
+
+/// `ilog10_u32`, expecting `val < 100_000`.
+#[inline]
+pub const fn ilog10_u32_small(mut val: u32) -> u32 {
+    let mut log = 0;
+    if unlikely(val >= 100_000) {
+        val /= 100_000;
+        log += 5;
+    }
+    log + crate::less_than_5(val)
+}
+
+#[inline]
+const fn guess_and_threshold(x: u32) -> (u32, u32) {
+    let guess = crate::ilog2_u32(x).wrapping_mul(9) >> 5;
+    if guess >= 9 {
+        // SAFETY: ilog2 is at most 31, and 31 * 9 >> 5 is 8.
+        unsafe { crate::unchecked::unreachable() }
+    }
+    (guess, crate::statics::TEN_THRESHOLDS[guess as usize])
+}
+
+/// `ilog10_mul`, expecting the guess to need no correction.
+#[inline]
+pub const fn ilog10_mul_exact(x: u32) -> u32 {
+    let (guess, ttg) = guess_and_threshold(x);
+    if unlikely(x > ttg) {
+        guess + 1
+    } else {
+        guess
+    }
+}
+
+/// `ilog10_mul`, expecting the guess to be one short.
+#[inline]
+pub const fn ilog10_mul_corrected(x: u32) -> u32 {
+    let (guess, ttg) = guess_and_threshold(x);
+    if likely(x > ttg) {
+        guess + 1
+    } else {
+        guess
+    }
+}
@@ -0,0 +1,210 @@
+//! Counts by order of magnitude, as latency and size histograms keep
+//! them: one bucket per decade, from 0 to 9 up to 10^19 and over. A
+//! value's bucket is its ilog10, and `record_slice` finds those for a
+//! whole block of values at once with `batch::ilog10_u64_slice`.
+//! `Spacing` gives the bounds and bucket indices of the finer 1, 2, 5
+//! spacing, for histograms kept elsewhere.
+//!
+//! ```
+//! use ilog::histogram::DecadeHistogram;
+//!
+//! let mut latencies_ns = DecadeHistogram::new();
+//! latencies_ns.record_slice(&[850, 1_200, 4_700, 31_000, 2_500_000]);
+//! assert_eq!(latencies_ns.count(3), 2);
+//! assert_eq!(DecadeHistogram::range(3), 1_000..=9_999);
+//! assert_eq!(latencies_ns.total(), 5);
+//! ```
+
+use core::ops::RangeInclusive;
+
+use crate::batch;
+
+/// The number of decades a u64 spans.
+pub const DECADES: usize = 20;
+
+/// The decade `x` falls in, floor(log10(x)), with 0 in the first along
+/// with 1 to 9.
+#[inline]
+pub const fn decade_bucket(x: u64) -> u32 {
+    crate::digits_u64(x) - 1
+}
+
+/// A count of values per decade.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DecadeHistogram {
+    counts: [u64; DECADES],
+}
+
+impl DecadeHistogram {
+    /// An empty histogram.
+    pub const fn new() -> Self {
+        DecadeHistogram {
+            counts: [0; DECADES],
+        }
+    }
+
+    /// Counts `x` in its decade.
+    #[inline]
+    pub fn record(&mut self, x: u64) {
+        self.counts[decade_bucket(x) as usize] += 1;
+    }
+
+    /// Counts every value of `values`; their decades are found a block
+    /// at a time, in vector registers where the CPU has them.
+    pub fn record_slice(&mut self, values: &[u64]) {
+        let mut decades = [0u32; 256];
+        for block in values.chunks(256) {
+            let decades = &mut decades[..block.len()];
+            batch::ilog10_u64_slice(block, decades);
+            for &d in decades.iter() {
+                self.counts[d as usize] += 1;
+            }
+        }
+    }
+
+    /// The count in `decade`. Panics unless `decade` is below `DECADES`.
+    pub const fn count(&self, decade: u32) -> u64 {
+        self.counts[decade as usize]
+    }
+
+    /// Every decade's count, from the first.
+    pub const fn counts(&self) -> &[u64; DECADES] {
+        &self.counts
+    }
+
+    /// The number of values counted.
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Adds the counts of `other`, as when combining per-thread
+    /// histograms.
+    pub fn merge(&mut self, other: &DecadeHistogram) {
+        for (count, &more) in self.counts.iter_mut().zip(&other.counts) {
+            *count += more;
+        }
+    }
+
+    /// The values `decade` holds: 10^decade to 10^(decade + 1) - 1, with
+    /// 0 in the first and the last running to `u64::MAX`. Panics unless
+    /// `decade` is below `DECADES`.
+    pub const fn range(decade: u32) -> RangeInclusive<u64> {
+        let low = match decade {
+            0 => 0,
+            _ => crate::statics::U64_THRESHOLDS[decade as usize - 1] + 1,
+        };
+        let high = match decade {
+            19 => u64::MAX,
+            _ => crate::statics::U64_THRESHOLDS[decade as usize],
+        };
+        RangeInclusive::new(low, high)
+    }
+}
+
+impl Extend<u64> for DecadeHistogram {
+    fn extend<I: IntoIterator<Item = u64>>(&mut self, values: I) {
+        for x in values {
+            self.record(x);
+        }
+    }
+}
+
+/// How `Spacing::bounds` spaces bucket bounds: one per decade, or at 1,
+/// 2 and 5 times each power of ten, the steps most metrics libraries
+/// use for latency buckets. All in integers, so the bounds are exact
+/// and the same everywhere.
+///
+/// ```
+/// use ilog::histogram::Spacing;
+///
+/// let bounds: Vec<u64> = Spacing::OneTwoFive.bounds(1_000).collect();
+/// assert_eq!(bounds, [1, 2, 5, 10, 20, 50, 100, 200, 500, 1_000]);
+/// assert_eq!(Spacing::OneTwoFive.bucket(350), 7);
+/// assert_eq!(Spacing::Decades.bounds(1_000).last(), Some(1_000));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Spacing {
+    /// 1, 10, 100, ...
+    Decades,
+    /// 1, 2, 5, 10, 20, 50, ...
+    OneTwoFive,
+}
+
+impl Spacing {
+    // The multiples of each power of ten that are bounds.
+    const fn multiples(self) -> &'static [u64] {
+        match self {
+            Spacing::Decades => &[1],
+            Spacing::OneTwoFive => &[1, 2, 5],
+        }
+    }
+
+    /// Bound `i`, counting from 1 as bound 0, or `None` past `u64::MAX`
+    /// (from bound 20, or 58 for `OneTwoFive`).
+    pub const fn bound(self, i: u32) -> Option<u64> {
+        let multiples = self.multiples();
+        let n = multiples.len() as u32;
+        let decade = i / n;
+        if decade > 19 {
+            return None;
+        }
+        crate::pow10(decade).checked_mul(multiples[(i % n) as usize])
+    }
+
+    /// The bucket `x` falls in: the greatest `i` with `bound(i) <= x`,
+    /// so that bucket `i` runs from bound `i` up to bound `i + 1`, and 0
+    /// along with 1 in the first.
+    #[inline]
+    pub const fn bucket(self, x: u64) -> u32 {
+        let decade = decade_bucket(x);
+        match self {
+            Spacing::Decades => decade,
+            Spacing::OneTwoFive => {
+                let lead = crate::leading_digit_u64(x);
+                3 * decade + (lead >= 2) as u32 + (lead >= 5) as u32
+            }
+        }
+    }
+
+    /// Every bound from 1 up to `max`, in order, and none for 0. Bucket
+    /// `bounds(max).len() - 1` is the one `max` falls in.
+    pub const fn bounds(self, max: u64) -> Bounds {
+        let end = match max {
+            0 => 0,
+            _ => self.bucket(max) + 1,
+        };
+        Bounds {
+            spacing: self,
+            next: 0,
+            end,
+        }
+    }
+}
+
+/// The bounds from `Spacing::bounds`.
+#[derive(Clone, Debug)]
+pub struct Bounds {
+    spacing: Spacing,
+    next: u32,
+    end: u32,
+}
+
+impl Iterator for Bounds {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        if self.next == self.end {
+            return None;
+        }
+        let bound = self.spacing.bound(self.next);
+        self.next += 1;
+        bound
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.end - self.next) as usize;
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Bounds {}
@@ -0,0 +1,213 @@
+// Benchmark runs kept in a SQLite database, for `bench --db` and `ilog
+// history`: each run is appended with the environment `bench
+// --report` shows (machine, CPU, compiler, build), so that a trend
+// across compiler versions or machines is one query away instead of
+// spread over saved markdown tables.
+//
+// SQLite comes from rusqlite, with the library bundled, so that `--db`
+// works the same on a machine with no SQLite of its own.
+//
+// The schema is two tables: `runs`, a row per run with an `id` and
+// the columns of RUN_COLUMNS, and `results`, a row per result with
+// the columns of RESULT_COLUMNS and `run`, the id of its run.
+
+use std::path::Path;
+
+use serde_json::{Map, Value};
+
+use ilog::results::BenchResult;
+
+use crate::filter::Filter;
+use crate::report::Report;
+
+/// The columns of `runs` besides its `id`, each with the
+/// `html::environment` entry it is filled from.
+const RUN_COLUMNS: [(&str, &str); 11] = [
+    ("timestamp", "Date"),
+    ("machine", "Host"),
+    ("cpu", "CPU"),
+    ("logical_cpus", "Logical CPUs"),
+    ("target", "Target"),
+    ("os", "OS"),
+    ("version", "ilog version"),
+    ("rustc", "Compiler"),
+    ("profile", "Build"),
+    ("dispatch", "Dispatch choice"),
+    ("command", "Command"),
+];
+
+/// The columns of `results`, all fields of `BenchResult`.
+const RESULT_COLUMNS: [(&str, &str); 14] = [
+    ("width", "TEXT"),
+    ("distribution", "TEXT"),
+    ("context", "TEXT"),
+    ("algorithm", "TEXT"),
+    ("ops", "INTEGER"),
+    ("best_us", "INTEGER"),
+    ("median_us", "INTEGER"),
+    ("positions", "TEXT"),
+    ("ns_per_op", "REAL"),
+    ("overhead_ns", "REAL"),
+    ("stddev_ns", "REAL"),
+    ("rse", "REAL"),
+    ("target_met", "INTEGER"),
+    ("joules_per_billion_ops", "REAL"),
+];
+
+/// Appends a run of `results` to the database at `path`, creating it
+/// if need be.
+pub fn record(path: &Path, results: &[BenchResult]) -> Result<(), String> {
+    let db = Db::open(path)?;
+    db.execute("BEGIN", &[])?;
+    let environment = crate::html::environment();
+    let values: Vec<Value> = RUN_COLUMNS
+        .iter()
+        .map(|(_, key)| {
+            let entry = environment.iter().find(|(k, _)| k == key);
+            entry.map_or(Value::Null, |(_, v)| v.clone().into())
+        })
+        .collect();
+    db.execute(&insert("runs", RUN_COLUMNS.iter().map(|(c, _)| *c)), &values)?;
+    let run = db.last_insert_rowid();
+    let columns = RESULT_COLUMNS.iter().map(|(c, _)| *c).chain(["run"]);
+    let insert_result = insert("results", columns);
+    for r in results {
+        let Ok(Value::Object(mut fields)) = serde_json::to_value(r) else {
+            unreachable!("BenchResult serializes as an object");
+        };
+        let mut values: Vec<Value> =
+            RESULT_COLUMNS.iter().map(|(c, _)| fields.remove(*c).unwrap_or(Value::Null)).collect();
+        // A field added to BenchResult but not here would otherwise be
+        // dropped from every run without a word.
+        if let Some(field) = fields.keys().next() {
+            return Err(format!("BenchResult's {field} has no column in the results table"));
+        }
+        values.push(run.into());
+        db.execute(&insert_result, &values)?;
+    }
+    db.execute("COMMIT", &[])?;
+    Ok(())
+}
+
+/// Every stored result of the algorithms `filter` selects, optionally
+/// on one machine only, oldest first within each machine, width,
+/// distribution, context and algorithm, with its ratio to the run
+/// before.
+pub fn report(path: &Path, machine: Option<&str>, filter: &Filter) -> Result<Report, String> {
+    if !path.exists() {
+        return Err(format!("{}: no such database", path.display()));
+    }
+    let db = Db::open(path)?;
+    let rows = db.execute(
+        "SELECT runs.machine, width, distribution, context, algorithm, runs.timestamp,
+                runs.rustc, runs.version, ns_per_op
+         FROM results JOIN runs ON results.run = runs.id
+         WHERE ?1 IS NULL OR runs.machine = ?1
+         ORDER BY runs.machine, width, distribution, context, algorithm, runs.timestamp, runs.id",
+        &[machine.into()],
+    )?;
+    let columns = [
+        "machine",
+        "width",
+        "distribution",
+        "context",
+        "algorithm",
+        "timestamp",
+        "rustc",
+        "version",
+        "ns_per_op",
+        "vs_previous",
+    ];
+    let mut report = Report::new("history", &columns);
+    let mut previous: Option<(Vec<Value>, f64)> = None;
+    for row in rows {
+        let (width, algorithm) = (row[1].as_str().unwrap_or(""), row[4].as_str().unwrap_or(""));
+        if !filter.matches(&[algorithm, &format!("{width}/{algorithm}")]) {
+            continue;
+        }
+        let key = row[..5].to_vec();
+        let ns = row[8].as_f64();
+        let vs_previous = match (&previous, ns) {
+            (Some((k, before)), Some(ns)) if *k == key => Some(ns / before),
+            _ => None,
+        };
+        previous = ns.map(|ns| (key, ns));
+        let mut object: Map<String, Value> = columns.iter().map(|c| c.to_string()).zip(row).collect();
+        object.insert("vs_previous".into(), vs_previous.into());
+        report.push(&Value::Object(object));
+    }
+    Ok(report)
+}
+
+// INSERT INTO `table` (columns) VALUES (?1, ?2, ...).
+fn insert<'a>(table: &str, columns: impl Iterator<Item = &'a str>) -> String {
+    let columns: Vec<&str> = columns.collect();
+    let params: Vec<String> = (1..=columns.len()).map(|i| format!("?{i}")).collect();
+    format!("INSERT INTO {table} ({}) VALUES ({})", columns.join(", "), params.join(", "))
+}
+
+// An open database with the tables created.
+struct Db(rusqlite::Connection);
+
+impl Db {
+    fn open(path: &Path) -> Result<Db, String> {
+        let db = rusqlite::Connection::open(path).map_err(|e| format!("{}: {e}", path.display()))?;
+        let db = Db(db);
+        let runs: Vec<String> = std::iter::once("id INTEGER PRIMARY KEY".to_string())
+            .chain(RUN_COLUMNS.iter().map(|(c, _)| format!("{c} TEXT")))
+            .collect();
+        let results: Vec<String> = RESULT_COLUMNS
+            .iter()
+            .map(|(c, t)| format!("{c} {t}"))
+            .chain(["run INTEGER NOT NULL REFERENCES runs(id)".to_string()])
+            .collect();
+        db.execute(&format!("CREATE TABLE IF NOT EXISTS runs ({})", runs.join(", ")), &[])?;
+        db.execute(&format!("CREATE TABLE IF NOT EXISTS results ({})", results.join(", ")), &[])?;
+        // A database from before a column was added gets it, empty for
+        // the runs already there.
+        let existing = db.execute("SELECT name FROM pragma_table_info('results')", &[])?;
+        for (column, kind) in RESULT_COLUMNS {
+            if !existing.iter().any(|row| row[0].as_str() == Some(column)) {
+                db.execute(&format!("ALTER TABLE results ADD COLUMN {column} {kind}"), &[])?;
+            }
+        }
+        Ok(db)
+    }
+
+    fn last_insert_rowid(&self) -> i64 {
+        self.0.last_insert_rowid()
+    }
+
+    // Runs one statement with `params` bound to ?1, ?2, ...; the rows
+    // it returns.
+    fn execute(&self, sql: &str, params: &[Value]) -> Result<Vec<Vec<Value>>, String> {
+        use rusqlite::types::{Value as Sql, ValueRef};
+        let failed = |e: rusqlite::Error| format!("SQLite: {e}");
+        let params = params.iter().map(|param| match param {
+            Value::Null => Sql::Null,
+            Value::Number(n) => match n.as_i64() {
+                Some(n) => Sql::Integer(n),
+                None => Sql::Real(n.as_f64().unwrap_or(f64::NAN)),
+            },
+            Value::Bool(b) => Sql::Integer(*b as i64),
+            Value::String(text) => Sql::Text(text.clone()),
+            v => Sql::Text(v.to_string()),
+        });
+        let mut statement = self.0.prepare(sql).map_err(failed)?;
+        let columns = statement.column_count();
+        let mut rows = statement.query(rusqlite::params_from_iter(params)).map_err(failed)?;
+        let mut out = Vec::new();
+        while let Some(row) = rows.next().map_err(failed)? {
+            let row = (0..columns)
+                .map(|c| match row.get_ref(c) {
+                    Ok(ValueRef::Integer(n)) => n.into(),
+                    Ok(ValueRef::Real(x)) => x.into(),
+                    Ok(ValueRef::Text(text)) => String::from_utf8_lossy(text).into_owned().into(),
+                    _ => Value::Null,
+                })
+                .collect();
+            out.push(row);
+        }
+        Ok(out)
+    }
+}
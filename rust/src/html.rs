@@ -0,0 +1,130 @@
+// A single-file HTML report of an `ilog bench` run, for `bench --report`:
+// the machine and build it ran on, the bar charts from `plot`, every
+// distribution's algorithms ranked against the fastest, and the full
+// table. Everything is inline, so the one file can be archived or
+// mailed as it is.
+
+use std::fmt::Write;
+use std::time::SystemTime;
+
+use ilog::results::BenchResult;
+
+use crate::plot::{self, escape};
+use crate::report::Report;
+
+const STYLE: &str = "body { font-family: sans-serif; max-width: 60em; margin: 2em auto; }
+table { border-collapse: collapse; margin-bottom: 1.5em; }
+th, td { border: 1px solid #ccc; padding: 0.2em 0.6em; text-align: left; }
+td.number { text-align: right; font-variant-numeric: tabular-nums; }
+caption { text-align: left; font-weight: bold; padding-bottom: 0.3em; }";
+
+/// The page for `report`, a `bench` report.
+pub fn page(report: &Report) -> String {
+    let results: Vec<BenchResult> = report.records();
+    let mut out = String::new();
+    let w = &mut out;
+    writeln!(w, "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">").unwrap();
+    writeln!(w, "<title>ilog {}</title>\n<style>\n{STYLE}\n</style>\n</head>\n<body>", report.title)
+        .unwrap();
+    writeln!(w, "<h1>ilog {}</h1>", report.title).unwrap();
+
+    writeln!(w, "<h2>Environment</h2>\n<table>").unwrap();
+    for (key, value) in environment() {
+        writeln!(w, "<tr><th>{key}</th><td>{}</td></tr>", escape(&value)).unwrap();
+    }
+    writeln!(w, "</table>").unwrap();
+
+    writeln!(w, "<h2>Charts</h2>\n{}", plot::svg(&results)).unwrap();
+
+    writeln!(w, "<h2>By distribution</h2>").unwrap();
+    let mut groups: Vec<(String, Vec<&BenchResult>)> = Vec::new();
+    for r in &results {
+        let key = format!("{} {} ({})", r.width.name(), r.distribution.name(), r.context.name());
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, rows)) => rows.push(r),
+            None => groups.push((key, vec![r])),
+        }
+    }
+    for (key, mut rows) in groups {
+        rows.sort_by(|a, b| a.ns_per_op.total_cmp(&b.ns_per_op));
+        let fastest = rows[0].ns_per_op;
+        writeln!(w, "<table>\n<caption>{key}</caption>").unwrap();
+        writeln!(w, "<tr><th>algorithm</th><th>ns per op</th><th>vs fastest</th></tr>").unwrap();
+        for r in rows {
+            writeln!(
+                w,
+                "<tr><td>{}</td><td class=\"number\">{:.3}</td><td class=\"number\">{:.2}x</td></tr>",
+                escape(&r.algorithm),
+                r.ns_per_op,
+                r.ns_per_op / fastest
+            )
+            .unwrap();
+        }
+        writeln!(w, "</table>").unwrap();
+    }
+
+    writeln!(w, "<h2>All results</h2>\n<table>\n<tr>").unwrap();
+    for column in &report.columns {
+        write!(w, "<th>{column}</th>").unwrap();
+    }
+    writeln!(w, "</tr>").unwrap();
+    for row in &report.rows {
+        write!(w, "<tr>").unwrap();
+        for value in row {
+            match value {
+                serde_json::Value::Number(n) => write!(w, "<td class=\"number\">{n}</td>"),
+                serde_json::Value::String(s) => write!(w, "<td>{}</td>", escape(s)),
+                serde_json::Value::Null => write!(w, "<td></td>"),
+                v => write!(w, "<td>{}</td>", escape(&v.to_string())),
+            }
+            .unwrap();
+        }
+        writeln!(w, "</tr>").unwrap();
+    }
+    writeln!(w, "</table>\n</body>\n</html>").unwrap();
+    out
+}
+
+/// What the numbers depend on, beyond the code: the machine, the
+/// build, and how `ilog` was run.
+pub fn environment() -> Vec<(&'static str, String)> {
+    let timestamp = humantime::format_rfc3339_seconds(SystemTime::now()).to_string();
+    let cpus = std::thread::available_parallelism().map_or("unknown".into(), |n| n.to_string());
+    let profile = if cfg!(debug_assertions) { "debug" } else { "release" };
+    vec![
+        ("Date", timestamp),
+        ("Command", std::env::args().collect::<Vec<_>>().join(" ")),
+        ("Host", host_name().unwrap_or_else(|| "unknown".into())),
+        ("CPU", cpu_model().unwrap_or_else(|| "unknown".into())),
+        ("Logical CPUs", cpus),
+        ("Target", crate::disasm::HOST.to_string()),
+        ("OS", std::env::consts::OS.to_string()),
+        ("ilog version", env!("CARGO_PKG_VERSION").to_string()),
+        ("Build", profile.to_string()),
+        ("Compiler", env!("ILOG_RUSTC_VERSION").to_string()),
+        ("Dispatch choice", ilog::dispatch::choice().name().to_string()),
+    ]
+}
+
+#[cfg(unix)]
+fn host_name() -> Option<String> {
+    let mut name = [0u8; 256];
+    // SAFETY: gethostname writes at most the length it is given.
+    if unsafe { libc::gethostname(name.as_mut_ptr().cast(), name.len()) } != 0 {
+        return None;
+    }
+    let len = name.iter().position(|&b| b == 0)?;
+    Some(String::from_utf8_lossy(&name[..len]).into_owned())
+}
+
+#[cfg(not(unix))]
+fn host_name() -> Option<String> {
+    std::env::var("COMPUTERNAME").ok()
+}
+
+// The model name from /proc/cpuinfo, where there is one.
+fn cpu_model() -> Option<String> {
+    let info = std::fs::read_to_string("/proc/cpuinfo").ok()?;
+    let line = info.lines().find(|l| l.starts_with("model name"))?;
+    Some(line.split_once(':')?.1.trim().to_string())
+}
@@ -0,0 +1,132 @@
+//! Byte counts and rates for people to read: "1.21 GB", "3.4 M/s",
+//! "512 KiB". Three significant figures and a prefix, found with
+//! `round_sig_figs` and `ilog1000_u64` in decimal and from the ilog2
+//! in binary, and written with `write_u64`; `Display` writes straight
+//! to the formatter, so it needs no allocation.
+//!
+//! ```
+//! use ilog::humanize::{self, Base};
+//!
+//! assert_eq!(humanize::bytes(1_213_000_000, Base::Decimal).to_string(), "1.21 GB");
+//! assert_eq!(humanize::bytes(524_288, Base::Binary).to_string(), "512 KiB");
+//! assert_eq!(humanize::with_unit(3_400_000, Base::Decimal, "/s").to_string(), "3.4 M/s");
+//! assert_eq!(humanize::bytes(999, Base::Decimal).to_string(), "999 B");
+//! ```
+
+use core::fmt;
+
+use crate::Rounding;
+
+/// Which powers the prefixes stand for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Base {
+    /// Powers of 1000: k, M, G, T, P, E.
+    Decimal,
+    /// Powers of 1024: Ki, Mi, Gi, Ti, Pi, Ei.
+    Binary,
+}
+
+impl Base {
+    /// The prefix of the `k`th power, `k` up to 6.
+    pub fn prefix(self, k: u32) -> &'static str {
+        let prefixes = match self {
+            Base::Decimal => ["", "k", "M", "G", "T", "P", "E"],
+            Base::Binary => ["", "Ki", "Mi", "Gi", "Ti", "Pi", "Ei"],
+        };
+        prefixes[k as usize]
+    }
+}
+
+/// A value ready to print, from `bytes` or `with_unit`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Humanized {
+    // The scaled value in hundredths, to three significant figures.
+    hundredths: u64,
+    k: u32,
+    base: Base,
+    unit: &'static str,
+}
+
+impl Humanized {
+    /// The scaled value in hundredths: 121 for 1.21 GB.
+    pub fn hundredths(&self) -> u64 {
+        self.hundredths
+    }
+
+    /// The power of 1000 or 1024 it is scaled by: 3 for 1.21 GB.
+    pub fn power(&self) -> u32 {
+        self.k
+    }
+}
+
+/// `x` bytes, as "1.21 GB" or "1.13 GiB".
+pub fn bytes(x: u64, base: Base) -> Humanized {
+    with_unit(x, base, "B")
+}
+
+/// `x` of `unit`, as "3.4 M/s" for `unit` "/s". Below the first
+/// prefix the number is `x` itself, as in "999 /s".
+pub fn with_unit(x: u64, base: Base, unit: &'static str) -> Humanized {
+    let (hundredths, k) = match base {
+        Base::Decimal => {
+            // Rounding first, so that 999_999 rounds up into the next
+            // prefix as 1 M rather than 1000 k. No u64 rounds past
+            // u64::MAX to three figures.
+            let rounded = crate::checked_round_sig_figs(x, 3, Rounding::HalfUp).unwrap_or(x);
+            let k = crate::ilog1000_u64(rounded | 1);
+            if k == 0 {
+                (rounded * 100, 0)
+            } else {
+                // Exact: the three figures are at 10^(3k - 2) or above.
+                (rounded / 10u64.pow(3 * k - 2), k)
+            }
+        }
+        Base::Binary => {
+            let k = (63 - (x | 1).leading_zeros()) / 10;
+            if k == 0 {
+                (x * 100, 0)
+            } else {
+                // x / 2^shift to three figures in one rounding, from the
+                // digits of its whole part: 1 to 3 leave 2 to 0
+                // decimals, and 4 (1000 to 1023) rounds to tens.
+                let shift = 10 * k;
+                let digits = crate::digits_u64(x >> shift);
+                let scaled = if digits == 4 {
+                    ((x >> shift) + 5) / 10 * 1000
+                } else {
+                    let decimals = 10u128.pow(3 - digits);
+                    let n = (x as u128 * decimals + (1 << (shift - 1))) >> shift;
+                    n as u64 * 10u64.pow(digits - 1)
+                };
+                (scaled, k)
+            }
+        }
+    };
+    Humanized {
+        hundredths,
+        k,
+        base,
+        unit,
+    }
+}
+
+impl fmt::Display for Humanized {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut buf = [0; 24];
+        let mut len = crate::write_u64(self.hundredths / 100, &mut buf);
+        let fraction = self.hundredths % 100;
+        let (tenths, hundredths) = (fraction / 10, fraction % 10);
+        if fraction != 0 {
+            buf[len] = b'.';
+            buf[len + 1] = b'0' + tenths as u8;
+            len += 2;
+        }
+        if hundredths != 0 {
+            buf[len] = b'0' + hundredths as u8;
+            len += 1;
+        }
+        // Only ASCII digits and '.' were written.
+        let number = core::str::from_utf8(&buf[..len]).unwrap();
+        write!(f, "{number} {}{}", self.base.prefix(self.k), self.unit)
+    }
+}
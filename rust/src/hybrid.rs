@@ -0,0 +1,71 @@
+// P-cores against E-cores for `bench --per-core-type`. On a hybrid CPU
+// the two kinds of core differ in more than clock speed: the E-cores
+// have fewer and different execution ports, so whether the popcount or
+// the multiply guess wins can flip between them, and a run that the
+// scheduler moves between the two averages them. Each kind is timed on
+// its own, pinned to its first CPU, and the results are joined row by
+// row.
+//
+// Linux only: a hybrid Intel CPU lists the CPUs of each kind in
+// /sys/devices/cpu_core/cpus and /sys/devices/cpu_atom/cpus. (macOS has
+// no way to pin a thread to a core.)
+
+use serde_json::{Map, Value};
+
+use ilog::results::BenchResult;
+
+use crate::report::Report;
+use crate::smt;
+
+/// The kinds of core, named as in the report columns, and the sysfs
+/// directory listing each one's CPUs.
+const CORE_TYPES: [(&str, &str); 2] = [("p_core", "cpu_core"), ("e_core", "cpu_atom")];
+
+/// The first CPU of each kind of core, in the order of CORE_TYPES.
+pub fn first_cpus() -> Result<Vec<usize>, String> {
+    CORE_TYPES
+        .iter()
+        .map(|(_, dir)| {
+            let list = std::fs::read_to_string(format!("/sys/devices/{dir}/cpus"))
+                .map_err(|_| format!("not a hybrid CPU: no /sys/devices/{dir}"))?;
+            smt::parse_cpu_list(list.trim())
+                .and_then(|cpus| cpus.first().copied())
+                .ok_or_else(|| format!("can't read the CPUs of /sys/devices/{dir}"))
+        })
+        .collect()
+}
+
+/// Runs `benchmark` pinned to each kind of core in turn, and reports
+/// each row's ns per op on both side by side.
+pub fn report(benchmark: impl Fn() -> Result<Report, String>) -> Result<Report, String> {
+    let mut per_type = Vec::new();
+    for cpu in first_cpus()? {
+        // Unpinned at the end of each kind, so that the run is back on
+        // every CPU it had once the loop is done.
+        let _pinned = smt::pin(cpu)?;
+        per_type.push(benchmark()?.records::<BenchResult>());
+    }
+    let mut columns = vec!["width", "distribution", "context", "algorithm"];
+    let ns_columns: Vec<String> =
+        CORE_TYPES.iter().map(|(name, _)| format!("{name}_ns_per_op")).collect();
+    columns.extend(ns_columns.iter().map(String::as_str));
+    columns.push("e_over_p");
+    let mut report = Report::new("core types", &columns);
+    for r in &per_type[0] {
+        let same = |o: &&BenchResult| {
+            (o.width, o.distribution, o.context, &o.algorithm)
+                == (r.width, r.distribution, r.context, &r.algorithm)
+        };
+        let e = per_type[1].iter().find(same).map(|o| o.ns_per_op);
+        let mut row = Map::new();
+        row.insert("width".into(), r.width.name().into());
+        row.insert("distribution".into(), r.distribution.name().into());
+        row.insert("context".into(), r.context.name().into());
+        row.insert("algorithm".into(), r.algorithm.clone().into());
+        row.insert(ns_columns[0].clone(), r.ns_per_op.into());
+        row.insert(ns_columns[1].clone(), e.into());
+        row.insert("e_over_p".into(), e.map(|e| e / r.ns_per_op).into());
+        report.push(&Value::Object(row));
+    }
+    Ok(report)
+}
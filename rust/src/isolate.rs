@@ -0,0 +1,82 @@
+// `bench --isolate`: each algorithm benchmarked in a process of its
+// own. In one process the candidates share more than the CPU: where
+// the linker put each one's loop relative to the others, the branch
+// predictor's and the caches' memory of whichever ran before, the
+// allocator's state when each input was built. Any of these can move a
+// 2 ns result by a tenth. Here this binary runs itself once per
+// algorithm, with the same command line narrowed to that algorithm and
+// its report sent back as JSON over the stdout pipe, and the rows are
+// put back together in the order one process would have given them.
+
+use std::time::Duration;
+
+use ilog::results::BenchResult;
+
+use crate::filter::Filter;
+use crate::matrix;
+use crate::report::Report;
+use crate::suite::{self, Suite};
+
+/// The hidden `bench` option naming the one algorithm a child process
+/// runs, by its qualified name.
+pub const ISOLATED: &str = "--isolated";
+
+// The options that only concern the parent's own output or that it
+// hands out itself, and whether each takes a value; the rest of the
+// command line is passed to every child.
+const OWN_OPTIONS: [(&str, bool); 12] = [
+    ("--isolate", false),
+    ("--output", true),
+    ("-o", true),
+    ("--format", true),
+    ("-f", true),
+    ("--append", false),
+    ("-a", false),
+    ("--plot", true),
+    ("--report", true),
+    ("--db", true),
+    ("--fail-if-slower-than", true),
+    ("--max-time", true),
+];
+
+/// Times `suite` as `suite::benchmark` does, each algorithm in a child
+/// process given an even share of `max_time`.
+pub fn benchmark(suite: &Suite, filter: &Filter, max_time: Option<Duration>) -> Result<Report, String> {
+    let mut names = Vec::new();
+    for &width in &suite.widths {
+        names.extend(suite::select(suite, width, filter)?.iter().map(|a| a.qualified_name()));
+    }
+    let mut args = matrix::forwarded(std::env::args().skip(1), &OWN_OPTIONS);
+    if let Some(max_time) = max_time {
+        let share = max_time.as_micros() / names.len().max(1) as u128;
+        args.extend(["--max-time".to_string(), format!("{share}us")]);
+    }
+    let this = std::env::current_exe().map_err(|e| format!("finding this binary: {e}"))?;
+    let mut rows: Vec<BenchResult> = Vec::new();
+    for (i, name) in names.iter().enumerate() {
+        eprintln!("Process {} of {}: {name}", i + 1, names.len());
+        let mut args = args.clone();
+        args.extend([ISOLATED.to_string(), name.clone()]);
+        rows.extend(matrix::bench(&this, &args, name)?.rows);
+    }
+    // By width, distribution and context as the suite lists them, and
+    // within those in registry order, which the children went in.
+    let position = |r: &BenchResult| {
+        (
+            suite.widths.iter().position(|&w| w == r.width),
+            suite.distributions.iter().position(|&d| d == r.distribution),
+            suite.contexts.iter().position(|&c| c == r.context),
+        )
+    };
+    rows.sort_by_key(position);
+    let mut report = Report::new("benchmark", &suite::COLUMNS);
+    for r in &rows {
+        report.push(r);
+    }
+    Ok(report)
+}
+
+/// The filter of a child process: `name` and nothing else.
+pub fn only(name: &str) -> Filter {
+    Filter::new(&[format!("^{}$", regex::escape(name))], true).expect("an escaped name is a valid regex")
+}
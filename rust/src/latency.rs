@@ -0,0 +1,451 @@
+// A latency model for `ilog latency`, alongside `mca` but needing no
+// LLVM: each instruction on a function's fast path (as for `mca`,
+// everything up to the first return) is looked up in a table of
+// latencies per microarchitecture, and the dependencies between them
+// are followed from the arguments to the returned register. The
+// longest chain is the predicted latency of one call whose result is
+// waited on.
+//
+// That chain is where the popcount and multiply guesses part ways. On
+// the Intel cores `lzcnt` and `popcnt` take three cycles and a shift by
+// `cl` two, so `ilog10` (built with popcnt, e.g. for
+// `x86_64-unknown-linux-gnu:x86-64-v3`) has the longer chain; on Zen
+// all three take one cycle, while `bsr` is slower and a `lea` with a
+// scaled index takes two, and the popcount guess comes out ahead.
+//
+// The figures are uops.info's for the register forms, rounded to whole
+// cycles, with loads as L1 hits. Branches are taken as predicted,
+// stores as off the path, and instructions the table lacks as one
+// cycle, listed in `unmodelled`. Only x86 is covered.
+
+use std::collections::{BTreeSet, HashMap};
+
+use ilog::results::LatencyEstimate;
+
+use crate::disasm::{Function, Instruction, Kind};
+use crate::report::Report;
+
+/// A microarchitecture's latencies, in cycles, where they differ from
+/// one.
+struct Uarch {
+    name: &'static str,
+    /// Load to use on an L1 hit: with a base and displacement only,
+    /// and with an index.
+    load: [u32; 2],
+    /// `mov` between registers, 0 where it is eliminated at rename
+    mov: u32,
+    /// `lea` with a scaled index, as in [rax + rax*8]
+    lea_scaled: u32,
+    /// `lea` with base, index and displacement
+    lea_three: u32,
+    /// A shift or rotate by `cl`
+    shift_cl: u32,
+    imul: u32,
+    /// The widening `mul`, rdx:rax = rax * r
+    mul: u32,
+    /// And `bsf`
+    bsr: u32,
+    /// And `tzcnt`
+    lzcnt: u32,
+    popcnt: u32,
+    /// And `sbb`
+    adc: u32,
+    cmov: u32,
+}
+
+const UARCHES: [Uarch; 7] = [
+    Uarch {
+        name: "haswell",
+        load: [4, 5],
+        mov: 0,
+        lea_scaled: 1,
+        lea_three: 3,
+        shift_cl: 2,
+        imul: 3,
+        mul: 4,
+        bsr: 3,
+        lzcnt: 3,
+        popcnt: 3,
+        adc: 2,
+        cmov: 2,
+    },
+    Uarch {
+        name: "skylake",
+        load: [4, 5],
+        mov: 0,
+        lea_scaled: 1,
+        lea_three: 3,
+        shift_cl: 2,
+        imul: 3,
+        mul: 4,
+        bsr: 3,
+        lzcnt: 3,
+        popcnt: 3,
+        adc: 1,
+        cmov: 1,
+    },
+    // Move elimination is off in Ice Lake's microcode.
+    Uarch {
+        name: "icelake",
+        load: [5, 5],
+        mov: 1,
+        lea_scaled: 1,
+        lea_three: 1,
+        shift_cl: 2,
+        imul: 3,
+        mul: 4,
+        bsr: 3,
+        lzcnt: 3,
+        popcnt: 3,
+        adc: 1,
+        cmov: 1,
+    },
+    // The performance cores.
+    Uarch {
+        name: "alderlake",
+        load: [5, 5],
+        mov: 0,
+        lea_scaled: 1,
+        lea_three: 1,
+        shift_cl: 1,
+        imul: 3,
+        mul: 4,
+        bsr: 3,
+        lzcnt: 3,
+        popcnt: 3,
+        adc: 1,
+        cmov: 1,
+    },
+    Uarch {
+        name: "zen2",
+        load: [4, 4],
+        mov: 0,
+        lea_scaled: 2,
+        lea_three: 2,
+        shift_cl: 1,
+        imul: 3,
+        mul: 3,
+        bsr: 4,
+        lzcnt: 1,
+        popcnt: 1,
+        adc: 1,
+        cmov: 1,
+    },
+    Uarch {
+        name: "zen3",
+        load: [4, 4],
+        mov: 0,
+        lea_scaled: 2,
+        lea_three: 2,
+        shift_cl: 1,
+        imul: 3,
+        mul: 3,
+        bsr: 4,
+        lzcnt: 1,
+        popcnt: 1,
+        adc: 1,
+        cmov: 1,
+    },
+    Uarch {
+        name: "zen4",
+        load: [4, 4],
+        mov: 0,
+        lea_scaled: 2,
+        lea_three: 2,
+        shift_cl: 1,
+        imul: 3,
+        mul: 3,
+        bsr: 1,
+        lzcnt: 1,
+        popcnt: 1,
+        adc: 1,
+        cmov: 1,
+    },
+];
+
+/// The names `--uarch` accepts.
+pub fn uarch_names() -> Vec<&'static str> {
+    UARCHES.iter().map(|u| u.name).collect()
+}
+
+// Each register as its full-width name, so that a write to eax and a
+// read of rax meet.
+const GPRS: [[&str; 5]; 8] = [
+    ["rax", "eax", "ax", "al", "ah"],
+    ["rbx", "ebx", "bx", "bl", "bh"],
+    ["rcx", "ecx", "cx", "cl", "ch"],
+    ["rdx", "edx", "dx", "dl", "dh"],
+    ["rsi", "esi", "si", "sil", "sil"],
+    ["rdi", "edi", "di", "dil", "dil"],
+    ["rbp", "ebp", "bp", "bpl", "bpl"],
+    ["rsp", "esp", "sp", "spl", "spl"],
+];
+
+fn register(name: &str) -> Option<String> {
+    if let Some(row) = GPRS.iter().find(|row| row.contains(&name)) {
+        return Some(row[0].to_string());
+    }
+    let numbered = |prefix: &str| {
+        let n = name.strip_prefix(prefix)?;
+        n.parse::<u32>().ok().map(|n| format!("{prefix}{n}"))
+    };
+    // r8 to r15 and the mask registers, then r8d, r8w and r8b and so on
+    if let Some(r) = numbered("r").or_else(|| numbered("k")) {
+        return Some(r);
+    }
+    if let Some(n) = name.strip_prefix('r') {
+        let n = n.trim_end_matches(['d', 'w', 'b']);
+        if n.parse::<u32>().is_ok() {
+            return Some(format!("r{n}"));
+        }
+    }
+    ["xmm", "ymm", "zmm"]
+        .iter()
+        .find_map(|p| name.strip_prefix(p)?.parse::<u32>().ok())
+        .map(|n| format!("v{n}"))
+}
+
+struct Operand<'a> {
+    text: &'a str,
+    /// The register named, or those in the address
+    registers: Vec<String>,
+    memory: bool,
+}
+
+fn operand(text: &str) -> Operand<'_> {
+    match text.find('[') {
+        Some(start) => Operand {
+            text,
+            registers: text[start..]
+                .split(|c: char| !c.is_ascii_alphanumeric())
+                .filter_map(register)
+                .collect(),
+            memory: true,
+        },
+        None => Operand {
+            text,
+            registers: register(text).into_iter().collect(),
+            memory: false,
+        },
+    }
+}
+
+// What an instruction does to the dependency chains.
+struct Effect {
+    latency: u32,
+    /// Registers read directly, including "flags"
+    reads: Vec<String>,
+    /// The address registers of a load, and its latency
+    load: Option<(Vec<String>, u32)>,
+    writes: Vec<String>,
+}
+
+const FLAGS: &str = "flags";
+
+fn effect(
+    uarch: &Uarch,
+    mnemonic: &str,
+    operands: &[Operand],
+    unmodelled: &mut BTreeSet<String>,
+) -> Option<Effect> {
+    if matches!(mnemonic, "nop" | "endbr64" | "endbr32" | "push" | "pop") {
+        return None;
+    }
+    let first = operands.first();
+    let compare = matches!(mnemonic, "cmp" | "test" | "bt");
+    // Stores, and read-modify-writes of memory, end their chains.
+    if !compare && first.is_some_and(|o| o.memory) {
+        return None;
+    }
+    let indexed = |o: &Operand| o.text.contains('*') || o.registers.len() > 1;
+    let memory = operands.iter().find(|o| o.memory && mnemonic != "lea");
+    let load = memory.map(|o| (o.registers.clone(), uarch.load[indexed(o) as usize]));
+    let by = |o: &Operand| o.registers.clone();
+    // lea's address is its source, not a load.
+    let sources: Vec<String> = operands
+        .iter()
+        .skip(1)
+        .filter(|o| !o.memory || mnemonic == "lea")
+        .flat_map(by)
+        .collect();
+    let dest: Vec<String> = first.filter(|o| !o.memory).map(by).unwrap_or_default();
+    let same = operands.len() == 2 && operands[0].text == operands[1].text;
+
+    let condition = |prefix: &str| mnemonic.starts_with(prefix) && mnemonic.len() > prefix.len();
+    let shift = matches!(mnemonic, "shl" | "shr" | "sar" | "sal" | "rol" | "ror");
+    let latency = match mnemonic {
+        "mov" if memory.is_some() => 0,
+        "mov" | "movabs" if operands.get(1).is_some_and(|o| o.registers.is_empty()) => 0,
+        "mov" => uarch.mov,
+        "movzx" | "movsx" | "movsxd" if memory.is_some() => 0,
+        "lea" => {
+            let address = &operands[1];
+            let displaced = address.text.contains(" + 0x") || address.text.contains(" - 0x");
+            let scaled = address.text.contains('*');
+            if address.text.contains("rip") || address.registers.len() < 2 && !scaled {
+                1
+            } else if address.registers.len() + displaced as usize >= 3 {
+                uarch.lea_three
+            } else if scaled {
+                uarch.lea_scaled
+            } else {
+                1
+            }
+        }
+        _ if shift && operands.get(1).is_some_and(|o| o.text == "cl") => uarch.shift_cl,
+        "imul" => uarch.imul,
+        "mul" => uarch.mul,
+        "bsr" | "bsf" => uarch.bsr,
+        "lzcnt" | "tzcnt" => uarch.lzcnt,
+        "popcnt" => uarch.popcnt,
+        "adc" | "sbb" => uarch.adc,
+        _ if condition("cmov") => uarch.cmov,
+        _ if shift || condition("set") => 1,
+        "add" | "sub" | "and" | "or" | "xor" | "inc" | "dec" | "neg" | "not" | "cmp" | "test"
+        | "bt" | "movzx" | "movsx" | "movsxd" | "shlx" | "shrx" | "sarx" | "rorx" | "andn"
+        | "blsr" | "blsi" | "blsmsk" | "bzhi" => 1,
+        _ => {
+            unmodelled.insert(mnemonic.to_string());
+            1
+        }
+    };
+    let no_flags = matches!(
+        mnemonic,
+        "mov" | "movabs" | "movzx" | "movsx" | "movsxd" | "lea" | "not" | "shlx" | "shrx" | "sarx"
+            | "rorx"
+    ) || condition("cmov")
+        || condition("set");
+    let mut writes = if compare { Vec::new() } else { dest.clone() };
+    if !no_flags {
+        writes.push(FLAGS.to_string());
+    }
+    let reads_flags = matches!(mnemonic, "adc" | "sbb") || condition("cmov") || condition("set");
+
+    let mut reads = Vec::new();
+    // xor and sub of a register with itself depend on nothing; sbb only
+    // on the carry.
+    if !(same && matches!(mnemonic, "xor" | "sub" | "sbb" | "pxor" | "xorps")) {
+        reads.extend(sources);
+        // bsr and bsf leave the destination as it was for 0, so they
+        // wait on it as the two-operand arithmetic does.
+        let write_only = matches!(
+            mnemonic,
+            "mov" | "movabs" | "movzx" | "movsx" | "movsxd" | "lea" | "lzcnt" | "tzcnt"
+                | "popcnt"
+        ) || condition("set")
+            || operands.len() == 3;
+        if compare || !write_only {
+            reads.extend(dest.clone());
+        }
+    }
+    if reads_flags {
+        reads.push(FLAGS.to_string());
+    }
+    if matches!(mnemonic, "mul") || mnemonic == "imul" && operands.len() == 1 {
+        reads.push("rax".into());
+        writes.push("rax".into());
+        writes.push("rdx".into());
+    }
+    let latency = if same && matches!(mnemonic, "xor" | "sub") { 0 } else { latency };
+    Some(Effect { latency, reads, load, writes })
+}
+
+fn estimate(
+    f: &Function,
+    target: &str,
+    uarch: &Uarch,
+    instructions: &[Instruction],
+) -> LatencyEstimate {
+    // When each register's value is ready, and the chain behind it.
+    let mut ready: HashMap<String, (u32, Vec<String>)> = HashMap::new();
+    let mut unmodelled = BTreeSet::new();
+    let fast: Vec<&Instruction> =
+        instructions.iter().take_while(|i| i.kind != Kind::Return).collect();
+    for i in &fast {
+        if i.kind != Kind::Other {
+            continue;
+        }
+        let (mnemonic, operands) = i.text.split_once(' ').unwrap_or((&i.text, ""));
+        let operands: Vec<Operand> = match operands {
+            "" => Vec::new(),
+            o => o.split(", ").map(operand).collect(),
+        };
+        let Some(effect) = effect(uarch, mnemonic, &operands, &mut unmodelled) else {
+            continue;
+        };
+        let input = |r: &String| ready.get(r).cloned().unwrap_or((0, Vec::new()));
+        let latest = |registers: &[String]| {
+            registers.iter().map(input).max_by_key(|(t, _)| *t).unwrap_or_default()
+        };
+        let mut start = latest(&effect.reads);
+        let mut label = mnemonic.to_string();
+        if let Some((address, load)) = &effect.load {
+            let (t, path) = latest(address);
+            if t + load > start.0 {
+                start = (t + load, path);
+                label += "+load";
+            }
+        }
+        let (time, mut path) = start;
+        path.push(label);
+        for w in effect.writes {
+            ready.insert(w, (time + effect.latency, path.clone()));
+        }
+    }
+    let (latency_cycles, path) = ready.remove("rax").unwrap_or_default();
+    LatencyEstimate {
+        function: f.name.to_string(),
+        algorithm: f.algorithm.map(String::from),
+        target: target.to_string(),
+        uarch: uarch.name.to_string(),
+        instructions: fast.len() as u64,
+        latency_cycles,
+        critical_path: path.join(" > "),
+        unmodelled: unmodelled.into_iter().collect::<Vec<_>>().join(", "),
+    }
+}
+
+pub fn report(
+    functions: &[(&Function, Vec<Instruction>)],
+    target: &str,
+    uarches: &[String],
+) -> Result<Report, String> {
+    let arch = target.split('-').next().unwrap_or(target);
+    if !matches!(arch, "x86_64" | "i686" | "i586") {
+        return Err(format!("the latency tables cover x86 only, not {target}"));
+    }
+    let mut chosen = Vec::new();
+    for name in uarches {
+        match UARCHES.iter().find(|u| u.name == name) {
+            Some(u) => chosen.push(u),
+            None => {
+                return Err(format!(
+                    "unknown microarchitecture {name:?}; expected one of {}",
+                    uarch_names().join(", ")
+                ))
+            }
+        }
+    }
+    if chosen.is_empty() {
+        chosen = UARCHES.iter().collect();
+    }
+    let mut report = Report::new(
+        "latency model",
+        &[
+            "function",
+            "algorithm",
+            "target",
+            "uarch",
+            "instructions",
+            "latency_cycles",
+            "critical_path",
+            "unmodelled",
+        ],
+    );
+    for (f, instructions) in functions {
+        for uarch in &chosen {
+            report.push(&estimate(f, target, uarch, instructions));
+        }
+    }
+    Ok(report)
+}
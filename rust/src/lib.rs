@@ -0,0 +1,1879 @@
+//! Alternative ilog10 implementations for u32 and u64.
+//!
+//! The `ilog` binary in this package tests these exhaustively against
+//! the standard library and benchmarks them against each other. Each
+//! is in the module for its strategy, and in `prelude`, as
+//! `ilog10_<width>_<name>` after its name there: `u64/mul_pow` is
+//! `mul_shift::ilog10_u64_mul_pow`.
+//!
+//! Without the default `std` feature the library is `no_std`, leaving
+//! just the algorithms (and the `ffi` wrappers, if enabled) for
+//! embedded use. Adding the `polyfill` feature to that builds with
+//! toolchains back to 1.60, from before `ilog2` and `ilog10` were
+//! stable; see `polyfill`.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "nightly", feature(likely_unlikely))]
+
+pub mod arm32;
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+pub mod asm;
+pub mod batch;
+#[cfg(feature = "std")]
+pub mod bench;
+pub mod boundary;
+#[cfg(feature = "c-reference")]
+mod c_reference;
+pub mod constant_time;
+pub mod core_compat;
+pub mod csv_row;
+#[cfg(feature = "dispatch")]
+pub mod dispatch;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod float;
+#[cfg(feature = "num-traits")]
+mod generic;
+#[cfg(feature = "nightly")]
+pub mod hints;
+pub mod histogram;
+pub mod humanize;
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+pub mod lowering;
+pub mod mcu;
+#[cfg(feature = "no-panic")]
+pub mod no_panic;
+pub mod parts;
+#[cfg(feature = "polyfill")]
+pub mod polyfill;
+#[cfg(ilog_probe)]
+mod probe;
+#[cfg(feature = "python")]
+mod python;
+#[cfg(feature = "std")]
+pub mod registry;
+#[cfg(feature = "std")]
+pub mod results;
+pub mod soft_clz;
+mod statics;
+pub mod tables;
+mod unchecked;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+#[cfg(feature = "num-traits")]
+pub use generic::fast_ilog10;
+
+#[cfg(all(feature = "panic-handler", not(feature = "std")))]
+#[panic_handler]
+fn panic(_: &core::panic::PanicInfo) -> ! {
+    loop {}
+}
+
+// `x.ilog2()`, the guess's starting point, chosen by target:
+//
+// - RISC-V without Zbb (`cfg(not(target_feature = "zbb"))`) has no
+//   clz instruction, and a de Bruijn multiply is a fraction of the
+//   length of the expansion LLVM uses instead; see `soft_clz`. The
+//   byte table is a few instructions shorter still, but brings 256
+//   bytes of table along to every call site.
+// - Otherwise, with the `polyfill` feature, the same as ilog2 from
+//   leading_zeros, as ilog2 only arrived in 1.67.
+// - Otherwise ilog2 itself.
+//
+// Either way 0 panics.
+#[cfg(all(
+    any(target_arch = "riscv32", target_arch = "riscv64"),
+    not(target_feature = "zbb")
+))]
+#[inline]
+const fn ilog2_u32(x: u32) -> u32 {
+    if x == 0 {
+        panic!("argument of integer logarithm must be positive");
+    }
+    soft_clz::ilog2_debruijn_u32(x)
+}
+
+#[cfg(all(
+    any(target_arch = "riscv32", target_arch = "riscv64"),
+    not(target_feature = "zbb")
+))]
+#[inline]
+const fn ilog2_u64(x: u64) -> u32 {
+    if x == 0 {
+        panic!("argument of integer logarithm must be positive");
+    }
+    soft_clz::ilog2_debruijn_u64(x)
+}
+
+#[cfg(all(
+    feature = "polyfill",
+    not(all(
+        any(target_arch = "riscv32", target_arch = "riscv64"),
+        not(target_feature = "zbb")
+    ))
+))]
+#[inline]
+const fn ilog2_u32(x: u32) -> u32 {
+    if x == 0 {
+        panic!("argument of integer logarithm must be positive");
+    }
+    31 - x.leading_zeros()
+}
+
+#[cfg(all(
+    feature = "polyfill",
+    not(all(
+        any(target_arch = "riscv32", target_arch = "riscv64"),
+        not(target_feature = "zbb")
+    ))
+))]
+#[inline]
+const fn ilog2_u64(x: u64) -> u32 {
+    if x == 0 {
+        panic!("argument of integer logarithm must be positive");
+    }
+    63 - x.leading_zeros()
+}
+
+#[cfg(not(any(
+    feature = "polyfill",
+    all(
+        any(target_arch = "riscv32", target_arch = "riscv64"),
+        not(target_feature = "zbb")
+    )
+)))]
+#[inline]
+const fn ilog2_u32(x: u32) -> u32 {
+    x.ilog2()
+}
+
+#[cfg(not(any(
+    feature = "polyfill",
+    all(
+        any(target_arch = "riscv32", target_arch = "riscv64"),
+        not(target_feature = "zbb")
+    )
+)))]
+#[inline]
+const fn ilog2_u64(x: u64) -> u32 {
+    x.ilog2()
+}
+
+/// Reference version copied from Rust stdlib.
+#[inline]
+const fn less_than_5(val: u32) -> u32 {
+    // Similar to u8, when adding one of these constants to val,
+    // we get two possible bit patterns above the low 17 bits,
+    // depending on whether val is below or above the threshold.
+    const C1: u32 = 0b011_00000000000000000 - 10; // 393206
+    const C2: u32 = 0b100_00000000000000000 - 100; // 524188
+    const C3: u32 = 0b111_00000000000000000 - 1000; // 916504
+    const C4: u32 = 0b100_00000000000000000 - 10000; // 514288
+
+    // Value of top bits:
+    //                +c1  +c2  1&2  +c3  +c4  3&4   ^
+    //         0..=9  010  011  010  110  011  010  000 = 0
+    //       10..=99  011  011  011  110  011  010  001 = 1
+    //     100..=999  011  100  000  110  011  010  010 = 2
+    //   1000..=9999  011  100  000  111  011  011  011 = 3
+    // 10000..=99999  011  100  000  111  100  100  100 = 4
+    (((val + C1) & (val + C2)) ^ ((val + C3) & (val + C4))) >> 17
+}
+
+// less_than_5 works for any four thresholds t1 < t2 < t3 < t4 and
+// any width: with val < 2^W, the bits above W of val + (P << W) - t
+// are P - 1 below t and P from t on, and the P in the table above
+// (3, 4, 7, 4) count the thresholds reached whatever they are. So
+// doing the additions 3 bits wider than the input covers all of it,
+// with no division: u64 words for u32, and u128 (two words) for u64.
+#[inline]
+const fn count4_u32(val: u64, t: [u64; 4]) -> u32 {
+    let c1 = (0b011 << 32) - t[0];
+    let c2 = (0b100 << 32) - t[1];
+    let c3 = (0b111 << 32) - t[2];
+    let c4 = (0b100 << 32) - t[3];
+    ((((val + c1) & (val + c2)) ^ ((val + c3) & (val + c4))) >> 32) as u32
+}
+
+#[inline]
+const fn count4_u64(val: u128, t: [u128; 4]) -> u32 {
+    let c1 = (0b011 << 64) - t[0];
+    let c2 = (0b100 << 64) - t[1];
+    let c3 = (0b111 << 64) - t[2];
+    let c4 = (0b100 << 64) - t[3];
+    ((((val + c1) & (val + c2)) ^ ((val + c3) & (val + c4))) >> 64) as u32
+}
+
+// The same for a single threshold: 1 once val reaches t.
+#[inline]
+const fn carry_u32(val: u64, t: u64) -> u32 {
+    ((val + (1 << 32) - t) >> 32) as u32
+}
+
+#[inline]
+const fn carry_u64(val: u128, t: u128) -> u32 {
+    ((val + (1 << 64) - t) >> 64) as u32
+}
+
+/// less_than_5's constant additions over the whole u32 range: two
+/// groups of four thresholds and a last carry out for 10^9. 0 gives 0.
+#[inline]
+pub const fn ilog10_swar(val: u32) -> u32 {
+    let v = val as u64;
+    count4_u32(v, [10, 100, 1000, 10_000])
+        + count4_u32(v, [100_000, 1_000_000, 10_000_000, 100_000_000])
+        + carry_u32(v, 1_000_000_000)
+}
+
+/// As `ilog10_swar`, for u64: four groups, then 10^17..=10^19 one
+/// carry at a time.
+#[inline]
+pub const fn ilog10_u64_swar(val: u64) -> u32 {
+    let v = val as u128;
+    count4_u64(v, [10, 100, 1000, 10_000])
+        + count4_u64(v, [100_000, 1_000_000, 10_000_000, 100_000_000])
+        + count4_u64(
+            v,
+            [1_000_000_000, 10_000_000_000, 100_000_000_000, 1_000_000_000_000],
+        )
+        + count4_u64(
+            v,
+            [
+                10_000_000_000_000,
+                100_000_000_000_000,
+                1_000_000_000_000_000,
+                10_000_000_000_000_000,
+            ],
+        )
+        + carry_u64(v, 100_000_000_000_000_000)
+        + carry_u64(v, 1_000_000_000_000_000_000)
+        + carry_u64(v, 10_000_000_000_000_000_000)
+}
+
+#[inline]
+pub const fn ilog10_u32(mut val: u32) -> u32 {
+    let mut log = 0;
+    if val >= 100_000 {
+        val /= 100_000;
+        log += 5;
+    }
+    log + less_than_5(val)
+}
+
+// dga version with speedup from @sahnehaeubchen
+
+/// An array of `10^(k + 1) - 1` at index `k`, the largest value with
+/// `k + 1` digits, for each such value that fits the integer type
+/// `T`. A constant expression, so it can build the table for whatever
+/// type a formatter works in:
+///
+/// ```
+/// const T: [u16; 4] = ilog::thresholds!(u16);
+/// assert_eq!(T, [9, 99, 999, 9999]);
+/// assert_eq!(ilog::thresholds!(i64).len(), 18);
+/// ```
+#[macro_export]
+macro_rules! thresholds {
+    ($t:ty) => {{
+        const N: usize = {
+            let max = <$t>::MAX as u128;
+            let mut n = 0;
+            let mut p: u128 = 10;
+            while p - 1 <= max {
+                n += 1;
+                match p.checked_mul(10) {
+                    Some(next) => p = next,
+                    None => break,
+                }
+            }
+            n
+        };
+        let mut table = [0 as $t; N];
+        let mut p: u128 = 10;
+        let mut k = 0;
+        while k < N {
+            table[k] = (p - 1) as $t;
+            p = p.saturating_mul(10);
+            k += 1;
+        }
+        table
+    }};
+}
+
+/// `thresholds!(u32)`: what the u32 guesses here are corrected
+/// against, `10^(guess + 1) - 1` being the last value with `guess + 1`
+/// digits.
+pub const TEN_THRESHOLDS: [u32; 9] = thresholds!(u32);
+
+/// `thresholds!(u64)`, as `TEN_THRESHOLDS` for the u64 guesses.
+pub const U64_THRESHOLDS: [u64; 19] = thresholds!(u64);
+
+/// `thresholds!(u128)`.
+pub const U128_THRESHOLDS: [u128; 38] = thresholds!(u128);
+
+// The following functions mostly combine two parts:
+// (1) A guess for ilog10 based on ilog2 or leading zeros;
+// (2) A correction based on a lookup table listing powers
+// of ten. The major differences are in the guess function,
+// as most optimizations to the lookup table are common.
+// Guess functions:
+// dave shift/popcount - 2 instructions but popcount is slow on many arch
+// warren x*9 >> 5 version - 2 instructions on x64 (lea + shr), all fast.
+// The dave shift one can use the results of lzcnt directly, whereas
+// the warren one needs to be 31 - lzcnt (one more xor). Mostly unimportant
+// difference as the popcnt cost dominates everywhere but AMD.
+// `parts` exposes both halves, for mixing in a guess of one's own.
+
+// dave's popcount version that only works really well on AMD EPYC. :)
+#[inline]
+const fn ilogpopc(val_lz: u32) -> u32 {    
+    // const LZ_GUESSMASK: u32 = 0b01001001000100100100010010010000;
+    // Here's a cute optimization: Leave out the upper '1'. Our
+    // guess will be too low but we still compare vs 1B. So we can
+    // omit the u32::MAX at the end of the comparison table, saving 4 bytes.
+    const LZ_GUESSMASK: u32 = 0b00001001000100100100010010010000;
+    let guess = (LZ_GUESSMASK << val_lz).count_ones();
+    if guess > LZ_GUESSMASK.count_ones() {
+        // SAFETY: shifting never increases the count of ones
+        unsafe { crate::unchecked::unreachable() }
+    }
+    guess
+}
+
+/// # Safety
+///
+/// `val` must be nonzero.
+pub const unsafe fn ilog10(val: u32) -> u32 {
+    if val == 0 {
+        // SAFETY: This is ensured by our caller
+        unsafe {
+            crate::unchecked::unreachable();
+        }
+    }
+    let guess = ilogpopc(val.leading_zeros());
+    let ttg = statics::TEN_THRESHOLDS[guess as usize];
+    guess + (val > ttg) as u32
+}
+
+// hacker's delight version borrowing optimizations
+// from the rust forum discussion.
+#[inline]
+pub const fn ilog10_mul(x: u32) -> u32 {
+    let guess = ilog2_u32(x).wrapping_mul(9) >> 5;
+    debug_assert!(guess < 9);
+    if guess >= 9 {
+        unsafe { crate::unchecked::unreachable() }
+    }
+    let ttg = statics::TEN_THRESHOLDS[guess as usize];
+    guess + (x > ttg) as u32
+}
+
+// `log10_table_table`'s tables, read through `statics`.
+const TABLE_GUESSES: [u8; 33] = [
+    0, 0, 0, 0, 1, 1, 1, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 5, 5, 5, 6, 6, 6, 6, 7, 7, 7, 8, 8, 8, 9, 9,
+    9,
+];
+const TABLE_THRESHOLDS: [u32; 10] = [
+    9,
+    99,
+    999,
+    9999,
+    99999,
+    999999,
+    9999999,
+    99999999,
+    999_999_999,
+    u32::MAX,
+];
+
+#[inline]
+pub const fn log10_table_table(x: u32) -> u32 {
+    let log2 = ilog2_u32(x);
+    let guess = statics::TABLE_GUESSES[log2 as usize] as u32;
+    // As a static, the guesses' values are unknown to LLVM; they are at
+    // most 9, a valid index into the thresholds.
+    debug_assert!(guess <= 9);
+    if guess > 9 {
+        unsafe { crate::unchecked::unreachable() }
+    }
+    guess + (x > statics::TABLE_THRESHOLDS[guess as usize]) as u32
+}
+
+// hacker's delight borrowing optimization idea from scottmcm@rustforum
+// to ensure the table access is unchecked. Seems to save a bounds check
+// standalone but that may get optimized away when used with ilog10_checked.
+#[inline]
+pub fn ilog10_mul_alt(x: u32) -> u32 {
+    let guess = (ilog2_u32(x) * 9) >> 5;
+    let ttg = unsafe { crate::unchecked::get(&statics::TEN_THRESHOLDS, guess as usize) };
+    guess + (x > ttg) as u32
+}
+
+// Table-free corrections, for hot loops where the threshold table
+// competing for L1 costs more than a few multiplies: the threshold
+// 10^(guess+1) - 1 is computed from the guess instead of loaded.
+
+// 10^n for n < 20, the powers a u64 holds (`histogram::Spacing::bound`
+// keeps to them too), one conditional multiply per bit of n, which
+// LLVM turns into selects rather than branches.
+#[inline]
+const fn pow10(n: u32) -> u64 {
+    debug_assert!(n < 20);
+    let mut p = if n & 1 != 0 { 10 } else { 1 };
+    p *= if n & 2 != 0 { 100 } else { 1 };
+    p *= if n & 4 != 0 { 10_000 } else { 1 };
+    p *= if n & 8 != 0 { 100_000_000 } else { 1 };
+    p *= if n & 16 != 0 { 10_000_000_000_000_000 } else { 1 };
+    p
+}
+
+// 10^n by repeated multiplication: shorter code than `pow10`, but a
+// loop of up to n multiplies.
+#[inline]
+const fn pow10_loop(n: u32) -> u64 {
+    let mut p = 1;
+    let mut i = 0;
+    while i < n {
+        p *= 10;
+        i += 1;
+    }
+    p
+}
+
+// 10^n for n < 20 from a 4-entry table and powers of 10^4: the load
+// of 10^(n % 4) from eight bytes, which stay in L1 where a full table
+// might not, and a conditional multiply per bit of n / 4.
+const POW4_LOW: [u16; 4] = [1, 10, 100, 1000];
+
+#[inline]
+const fn pow10_pow4(n: u32) -> u64 {
+    let mut p = statics::POW4_LOW[(n & 3) as usize] as u64;
+    p *= if n & 4 != 0 { 10_000 } else { 1 };
+    p *= if n & 8 != 0 { 100_000_000 } else { 1 };
+    p *= if n & 16 != 0 { 10_000_000_000_000_000 } else { 1 };
+    p
+}
+
+/// As `ilog10_mul`, with the threshold computed by `pow10`.
+#[inline]
+pub const fn ilog10_mul_pow(x: u32) -> u32 {
+    let guess = ilog2_u32(x).wrapping_mul(9) >> 5;
+    // guess <= 8, so this is at most 10^9 - 1, which fits a u32.
+    let ttg = (pow10(guess) * 10 - 1) as u32;
+    guess + (x > ttg) as u32
+}
+
+/// As `ilog10_mul`, with the threshold computed by `pow10_loop`.
+#[inline]
+pub const fn ilog10_mul_loop(x: u32) -> u32 {
+    let guess = ilog2_u32(x).wrapping_mul(9) >> 5;
+    let ttg = (pow10_loop(guess + 1) - 1) as u32;
+    guess + (x > ttg) as u32
+}
+
+/// As `ilog10_mul`, with the threshold computed by `pow10_pow4`.
+#[inline]
+pub const fn ilog10_mul_pow4(x: u32) -> u32 {
+    let guess = ilog2_u32(x).wrapping_mul(9) >> 5;
+    // guess + 1 <= 9, so the threshold fits a u32.
+    let ttg = (pow10_pow4(guess + 1) - 1) as u32;
+    guess + (x > ttg) as u32
+}
+
+/// As `ilog10_u64_mul`, with the threshold computed by `pow10`.
+#[inline]
+pub const fn ilog10_u64_mul_pow(x: u64) -> u32 {
+    let guess: u32 = ilog2_u64(x).wrapping_mul(19) >> 6;
+    // guess <= 18, and 10^19 still fits a u64.
+    let ttg = pow10(guess) * 10 - 1;
+    guess + (x > ttg) as u32
+}
+
+/// As `ilog10_u64_mul`, with the threshold computed by `pow10_loop`.
+#[inline]
+pub const fn ilog10_u64_mul_loop(x: u64) -> u32 {
+    let guess: u32 = ilog2_u64(x).wrapping_mul(19) >> 6;
+    let ttg = pow10_loop(guess + 1) - 1;
+    guess + (x > ttg) as u32
+}
+
+/// As `ilog10_u64_mul`, with the threshold computed by `pow10_pow4`.
+#[inline]
+pub const fn ilog10_u64_mul_pow4(x: u64) -> u32 {
+    let guess: u32 = ilog2_u64(x).wrapping_mul(19) >> 6;
+    let ttg = pow10_pow4(guess + 1) - 1;
+    guess + (x > ttg) as u32
+}
+
+// Thresholds in half the cache footprint, for when the table is what
+// misses (see `Context::Pressure`). For u32, `TEN_THRESHOLDS` two to a
+// u64, so that a guess and its neighbour come in one 8-byte load; for
+// u64, only every other power of ten, 10^1, 10^3 and so on to 10^19,
+// the others ten times the one below: 80 bytes in place of 152, two
+// cache lines in place of three. (No u16 encoding is exact: 10^k - 1
+// is 5^k << k plus k low one bits, and 5^9 alone takes 21 bits.)
+const PACKED_THRESHOLDS: [u64; 5] = {
+    let mut packed = [0; 5];
+    let mut i = 0;
+    while i < 9 {
+        packed[i / 2] |= (TEN_THRESHOLDS[i] as u64) << (i % 2 * 32);
+        i += 1;
+    }
+    packed
+};
+
+const ODD_POWERS_OF_TEN: [u64; 10] = {
+    let mut powers = [0; 10];
+    let mut i = 0;
+    while i < 10 {
+        powers[i] = U64_THRESHOLDS[2 * i] + 1;
+        i += 1;
+    }
+    powers
+};
+
+/// As `ilog10_mul`, with the threshold from `PACKED_THRESHOLDS`.
+#[inline]
+pub const fn ilog10_mul_packed(x: u32) -> u32 {
+    let guess = ilog2_u32(x).wrapping_mul(9) >> 5;
+    if guess >= 9 {
+        // SAFETY: as in ilog10_mul.
+        unsafe { crate::unchecked::unreachable() }
+    }
+    let pair = statics::PACKED_THRESHOLDS[(guess / 2) as usize];
+    let ttg = (pair >> (guess % 2 * 32)) as u32;
+    guess + (x > ttg) as u32
+}
+
+/// As `ilog10_u64_mul`, with 10^(guess+1) from `ODD_POWERS_OF_TEN`,
+/// times ten for an odd guess.
+#[inline]
+pub const fn ilog10_u64_mul_packed(x: u64) -> u32 {
+    let guess: u32 = ilog2_u64(x).wrapping_mul(19) >> 6;
+    if guess >= 19 {
+        // SAFETY: as for u64_threshold.
+        unsafe { crate::unchecked::unreachable() }
+    }
+    // guess <= 18, so an odd guess is at most 17 and 10^18 fits.
+    let power = statics::ODD_POWERS_OF_TEN[(guess / 2) as usize] * (1 + 9 * (guess % 2) as u64);
+    guess + (x >= power) as u32
+}
+
+#[inline]
+pub const fn ilog10_u64_mul(x: u64) -> u32 {
+    // Use slightly more accurate approximation of log2(10) for u64;
+    // this takes two lea instructions on x64 instead of just 1 but not bad.
+    let guess: u32 = ilog2_u64(x).wrapping_mul(19) >> 6;
+    let ttg = u64_threshold(guess);
+    guess + (x > ttg) as u32
+}
+
+#[cfg(not(feature = "small-tables"))]
+#[inline]
+const fn u64_threshold(guess: u32) -> u64 {
+    // As in ilog10_mul: slice::get_unchecked isn't const, so promise
+    // the bound instead to drop the check.
+    if guess >= 19 {
+        unsafe { crate::unchecked::unreachable() }
+    }
+    statics::U64_THRESHOLDS[guess as usize]
+}
+
+// small-tables: 10^(guess+1) - 1 by repeated multiplication, trading
+// up to 19 multiplies for the 152 bytes of U64_THRESHOLDS.
+#[cfg(feature = "small-tables")]
+#[inline]
+const fn u64_threshold(guess: u32) -> u64 {
+    pow10_loop(guess + 1) - 1
+}
+
+// 10^0 to 10^19, for a guess that may be one over.
+const U64_POWERS: [u64; 20] = {
+    let mut powers = [1; 20];
+    let mut i = 1;
+    while i < 20 {
+        powers[i] = powers[i - 1] * 10;
+        i += 1;
+    }
+    powers
+};
+
+/// As `ilog10_u64_mul`, with the guess `(ilog2(x) * 5 + 4) >> 4`: on
+/// aarch64 a shift-and-add and an add where `* 19 >> 6` is a multiply,
+/// on x86 a lea and an add against two leas. 5/16 is over log10(2) where
+/// 19/64 is under, so this guess is ilog10(x) or one more, up to 19,
+/// and the correction takes one off below 10^guess, from a 20-entry
+/// table of the powers (160 bytes against `U64_THRESHOLDS`' 152).
+/// No multiplier of 3, 5 or 9 guesses low closely enough.
+#[inline]
+pub const fn ilog10_u64_mul20(x: u64) -> u32 {
+    let guess = (ilog2_u64(x) * 5 + 4) >> 4;
+    guess - (x < u64_power(guess)) as u32
+}
+
+#[inline]
+const fn u64_power(guess: u32) -> u64 {
+    // As in u64_threshold.
+    if guess >= 20 {
+        // SAFETY: the guess is at most (63 * 5 + 4) >> 4 = 19.
+        unsafe { crate::unchecked::unreachable() }
+    }
+    statics::U64_POWERS[guess as usize]
+}
+
+// The algorithms above by strategy, each `ilog10_<width>_<name>` after
+// its name in `registry`, so that `u64/mul_pow` is
+// `mul_shift::ilog10_u64_mul_pow`. The definitions stay here, where
+// they share the tables and the ilog2s; the older names stay too.
+
+/// The popcount guess: `ilog10` as `ilog10_u32_popcount`.
+pub mod popcount {
+    pub use crate::ilog10 as ilog10_u32_popcount;
+}
+
+/// The multiply-and-shift guesses, corrected against a table or
+/// against a power of ten worked out from the guess.
+pub mod mul_shift {
+    pub use crate::ilog10_mul as ilog10_u32_mul;
+    pub use crate::ilog10_mul_alt as ilog10_u32_mul_alt;
+    pub use crate::ilog10_mul_loop as ilog10_u32_mul_loop;
+    pub use crate::ilog10_mul_packed as ilog10_u32_mul_packed;
+    pub use crate::ilog10_mul_pow as ilog10_u32_mul_pow;
+    pub use crate::ilog10_mul_pow4 as ilog10_u32_mul_pow4;
+    pub use crate::{
+        ilog10_u64_mul, ilog10_u64_mul20, ilog10_u64_mul_loop, ilog10_u64_mul_packed,
+        ilog10_u64_mul_pow, ilog10_u64_mul_pow4,
+    };
+}
+
+/// The guess looked up by ilog2: `log10_table_table` as
+/// `ilog10_u32_table`.
+pub mod table {
+    pub use crate::log10_table_table as ilog10_u32_table;
+}
+
+/// No guess: every threshold's carry added up at once.
+pub mod swar {
+    pub use crate::ilog10_swar as ilog10_u32_swar;
+    pub use crate::ilog10_u64_swar;
+}
+
+/// Several values at a time: `batch`'s slice functions and the
+/// portable multi-lane kernels they build on.
+pub mod simd {
+    pub use crate::batch::{ilog10_u32_slice, ilog10_u64_slice};
+    pub use crate::{digits_u32x4, digits_u64x2};
+}
+
+/// Every algorithm with a plain function, under its uniform name:
+/// those of `popcount`, `mul_shift`, `table`, `swar` and `simd`, and
+/// those of the modules that predate them. One `use` for comparing
+/// them:
+///
+/// ```
+/// use ilog::prelude::*;
+///
+/// let x = 4_000_000_000u32;
+/// assert_eq!(ilog10_u32_mul(x), ilog10_u32_swar(x));
+/// assert_eq!(ilog10_u32_table(x), ilog10_u32_ladder(x));
+/// assert_eq!(ilog10_u64_mul20(u64::MAX), ilog10_u64_split(u64::MAX));
+/// ```
+pub mod prelude {
+    pub use crate::arm32::ilog10_u64_split;
+    pub use crate::constant_time::ilog10_u32 as ilog10_u32_constant_time;
+    pub use crate::constant_time::ilog10_u64 as ilog10_u64_constant_time;
+    pub use crate::float::ilog10_f32 as ilog10_u32_f32;
+    pub use crate::float::ilog10_f64 as ilog10_u32_f64;
+    pub use crate::float::ilog10_u64_f64;
+    pub use crate::ilog10_u32 as ilog10_u32_stdlib;
+    pub use crate::mcu::ilog10_ladder as ilog10_u32_ladder;
+    pub use crate::mcu::ilog10_u64_ladder;
+    pub use crate::soft_clz::ilog10_bytes as ilog10_u32_bytes;
+    pub use crate::soft_clz::ilog10_debruijn as ilog10_u32_debruijn;
+    pub use crate::soft_clz::{ilog10_u64_bytes, ilog10_u64_debruijn};
+    pub use crate::{mul_shift::*, popcount::*, simd::*, swar::*, table::*};
+}
+
+// The ilog10s of the entry points that don't name an algorithm
+// (`fast_ilog10` and the `ffi` wrappers), by target: on AVR and MSP430
+// the comparison ladder, as they have no clz or multiplier (see
+// `mcu`); for u64 on 32-bit ARM, the version that keeps small values
+// in one register (see `arm32`); elsewhere the multiply guess.
+//
+// For u32, the `prefer-popcount`, `prefer-table` and `prefer-mul`
+// features override that with `ilog10`, `log10_table_table` or
+// `ilog10_mul`, for builds that know which wins on the CPU they will
+// run on. Features add up across the dependency graph, so more than
+// one may be on; the first in that order wins. Without any of them,
+// the `dispatch` feature picks at run time on x86-64 (see `dispatch`).
+// Both callers have ruled out 0.
+#[cfg(any(feature = "ffi", feature = "num-traits"))]
+mod target {
+    #[cfg(feature = "prefer-popcount")]
+    #[inline]
+    pub(crate) const fn ilog10_u32(x: u32) -> u32 {
+        // SAFETY: x is nonzero, see above.
+        unsafe { crate::ilog10(x) }
+    }
+
+    #[cfg(all(feature = "prefer-table", not(feature = "prefer-popcount")))]
+    #[inline]
+    pub(crate) const fn ilog10_u32(x: u32) -> u32 {
+        crate::log10_table_table(x)
+    }
+
+    #[cfg(all(
+        feature = "prefer-mul",
+        not(any(feature = "prefer-popcount", feature = "prefer-table"))
+    ))]
+    #[inline]
+    pub(crate) const fn ilog10_u32(x: u32) -> u32 {
+        crate::ilog10_mul(x)
+    }
+
+    #[cfg(all(
+        not(any(
+            feature = "prefer-popcount",
+            feature = "prefer-table",
+            feature = "prefer-mul"
+        )),
+        any(target_arch = "avr", target_arch = "msp430")
+    ))]
+    #[inline]
+    pub(crate) const fn ilog10_u32(x: u32) -> u32 {
+        crate::mcu::ilog10_ladder(x)
+    }
+
+    #[cfg(all(
+        not(any(
+            feature = "prefer-popcount",
+            feature = "prefer-table",
+            feature = "prefer-mul"
+        )),
+        feature = "dispatch",
+        target_arch = "x86_64"
+    ))]
+    #[inline]
+    pub(crate) fn ilog10_u32(x: u32) -> u32 {
+        // SAFETY: x is nonzero, see above.
+        crate::dispatch::ilog10_u32(unsafe { core::num::NonZeroU32::new_unchecked(x) })
+    }
+
+    #[cfg(not(any(
+        feature = "prefer-popcount",
+        feature = "prefer-table",
+        feature = "prefer-mul",
+        all(feature = "dispatch", target_arch = "x86_64"),
+        target_arch = "avr",
+        target_arch = "msp430"
+    )))]
+    #[inline]
+    pub(crate) const fn ilog10_u32(x: u32) -> u32 {
+        crate::ilog10_mul(x)
+    }
+
+    #[cfg(any(target_arch = "avr", target_arch = "msp430"))]
+    #[inline]
+    pub(crate) const fn ilog10_u64(x: u64) -> u32 {
+        crate::mcu::ilog10_u64_ladder(x)
+    }
+
+    #[cfg(target_arch = "arm")]
+    #[inline]
+    pub(crate) const fn ilog10_u64(x: u64) -> u32 {
+        crate::arm32::ilog10_u64_split(x)
+    }
+
+    #[cfg(not(any(target_arch = "arm", target_arch = "avr", target_arch = "msp430")))]
+    #[inline]
+    pub(crate) const fn ilog10_u64(x: u64) -> u32 {
+        crate::ilog10_u64_mul(x)
+    }
+}
+
+/// Number of decimal digits in `x`, counting 0 as one digit.
+#[inline]
+pub const fn digits_u32(x: u32) -> u32 {
+    // x | 1 never crosses a power of ten (they are all even except 1),
+    // and it turns 0 into 1 without a branch.
+    ilog10_mul(x | 1) + 1
+}
+
+/// Number of decimal digits in `x`, counting 0 as one digit.
+#[inline]
+pub const fn digits_u64(x: u64) -> u32 {
+    ilog10_u64_mul(x | 1) + 1
+}
+
+/// Number of decimal digits in `x`, counting 0 as one digit: as
+/// `digits_u64` up to `u64::MAX`, and above it the guess and threshold
+/// of `ilog10_product`.
+#[inline]
+pub const fn digits_u128(x: u128) -> u32 {
+    if x <= u64::MAX as u128 {
+        return digits_u64(x as u64);
+    }
+    let guess = ((127 - x.leading_zeros()) * 1233) >> 12;
+    let guess = if guess > 37 { 37 } else { guess };
+    guess + (x > statics::U128_THRESHOLDS[guess as usize]) as u32 + 1
+}
+
+/// `(ilog2(x), ilog10(x))`, from one count of the leading zeros, for
+/// code that needs both (scientific notation, or sizing a buffer in
+/// bits and digits). Panics if `x` is 0.
+#[inline]
+pub const fn ilog2_and_ilog10_u32(x: u32) -> (u32, u32) {
+    let log2 = ilog2_u32(x);
+    let guess = log2.wrapping_mul(9) >> 5;
+    if guess >= 9 {
+        // SAFETY: as in ilog10_mul.
+        unsafe { crate::unchecked::unreachable() }
+    }
+    (log2, guess + (x > statics::TEN_THRESHOLDS[guess as usize]) as u32)
+}
+
+/// As `ilog2_and_ilog10_u32`, for u64.
+#[inline]
+pub const fn ilog2_and_ilog10_u64(x: u64) -> (u32, u32) {
+    let log2 = ilog2_u64(x);
+    let guess = log2.wrapping_mul(19) >> 6;
+    (log2, guess + (x > u64_threshold(guess)) as u32)
+}
+
+/// `(ilog10(x), corrected)`: `ilog10_mul`, and whether its correction
+/// added one to the guess from the ilog2, for seeing how often a set
+/// of inputs takes it (`ilog corrections` counts the same thing for
+/// the built-in distributions) or comparing a guess of one's own,
+/// made with `parts::correct_with_flag`, against this one. Panics if
+/// `x` is 0.
+///
+/// ```
+/// assert_eq!(ilog::ilog10_with_flag(999), (2, false));
+/// assert_eq!(ilog::ilog10_with_flag(1000), (3, true));
+/// assert_eq!(ilog::ilog10_with_flag(1024), (3, true));
+/// ```
+#[inline]
+pub const fn ilog10_with_flag(x: u32) -> (u32, bool) {
+    parts::correct_with_flag(ilog2_u32(x).wrapping_mul(9) >> 5, x)
+}
+
+/// As `ilog10_with_flag`, for u64: `ilog10_u64_mul` and whether its
+/// correction fired.
+#[inline]
+pub const fn ilog10_u64_with_flag(x: u64) -> (u32, bool) {
+    parts::correct_u64_with_flag(ilog2_u64(x).wrapping_mul(19) >> 6, x)
+}
+
+// ilog2(x) * 1233 >> 12 is floor(ilog2(x) * log10(2)), the ilog10 of
+// the power of two at or below x, for every u128 ilog2; the assertion
+// checks it against the powers of ten.
+const _: () = {
+    let mut log2 = 0;
+    let mut power = 1u128;
+    let mut log10 = 0;
+    while log2 < 128 {
+        if let Some(next) = power.checked_mul(10) {
+            if next <= 1 << log2 {
+                power = next;
+                log10 += 1;
+            }
+        }
+        assert!((log2 * 1233) >> 12 == log10);
+        log2 += 1;
+    }
+};
+
+/// floor(log10(a * b)), with the product taken in 128 bits so that it
+/// can't overflow, for code that multiplies scores or probabilities
+/// and only needs the magnitude. Panics if `a` or `b` is 0.
+///
+/// ```
+/// assert_eq!(ilog::ilog10_product(1_000_000_000_000, 10_000_000_000), 22);
+/// assert_eq!(ilog::ilog10_product(u64::MAX, u64::MAX), 38);
+/// ```
+#[inline]
+pub const fn ilog10_product(a: u64, b: u64) -> u32 {
+    let p = a as u128 * b as u128;
+    if p == 0 {
+        panic!("argument of integer logarithm must be positive");
+    }
+    // A guess of 38 is only made for p >= 2^127 > 10^38, which the
+    // test against 10^38 - 1 takes to 38 as well, so the table needs
+    // no 39th entry.
+    let guess = ((127 - p.leading_zeros()) * 1233) >> 12;
+    let guess = if guess > 37 { 37 } else { guess };
+    guess + (p > statics::U128_THRESHOLDS[guess as usize]) as u32
+}
+
+// Entry `guess`: 10^guess and 10^(guess + 1), the two candidates for
+// the leading digit's power of ten, so that one load serves both the
+// righting of the guess and the power.
+const POW10_PAIRS_U32: [[u32; 2]; 9] = {
+    let mut pairs = [[0; 2]; 9];
+    let mut p = 1;
+    let mut i = 0;
+    while i < 9 {
+        pairs[i] = [p, p * 10];
+        p *= 10;
+        i += 1;
+    }
+    pairs
+};
+
+#[cfg(not(feature = "small-tables"))]
+const POW10_PAIRS_U64: [[u64; 2]; 19] = {
+    let mut pairs = [[0; 2]; 19];
+    let mut p = 1;
+    let mut i = 0;
+    while i < 19 {
+        pairs[i] = [p, p * 10];
+        p *= 10;
+        i += 1;
+    }
+    pairs
+};
+
+#[cfg(not(feature = "small-tables"))]
+#[inline]
+const fn u64_pow10_pair(guess: u32) -> [u64; 2] {
+    if guess >= 19 {
+        // SAFETY: as for u64_threshold.
+        unsafe { crate::unchecked::unreachable() }
+    }
+    POW10_PAIRS_U64[guess as usize]
+}
+
+#[cfg(feature = "small-tables")]
+#[inline]
+const fn u64_pow10_pair(guess: u32) -> [u64; 2] {
+    let p = pow10(guess);
+    [p, p * 10]
+}
+
+/// `(digits_u32(x), 10^(digits_u32(x) - 1))`: the number of digits and
+/// the power of ten of the leading one, which a formatter divides by
+/// to peel it off, from a single table load. 0 counts as one digit,
+/// with 1.
+#[inline]
+pub const fn digits_and_pow10_u32(x: u32) -> (u32, u32) {
+    let v = x | 1;
+    let guess = ilog2_u32(v).wrapping_mul(9) >> 5;
+    if guess >= 9 {
+        // SAFETY: as in ilog10_mul.
+        unsafe { crate::unchecked::unreachable() }
+    }
+    let pair = POW10_PAIRS_U32[guess as usize];
+    // high is 10 * low: a multiply by 1 or 10 rather than a choice
+    // between the two keeps LLVM from branching around a second load.
+    let up = (v >= pair[1]) as u32;
+    (guess + 1 + up, pair[0] * (1 + 9 * up))
+}
+
+/// As `digits_and_pow10_u32`, for u64.
+#[inline]
+pub const fn digits_and_pow10_u64(x: u64) -> (u32, u64) {
+    let v = x | 1;
+    let guess = ilog2_u64(v).wrapping_mul(19) >> 6;
+    let pair = u64_pow10_pair(guess);
+    // high is 10 * low: a multiply by 1 or 10 rather than a choice
+    // between the two keeps LLVM from branching around a second load.
+    let up = (v >= pair[1]) as u64;
+    (guess + 1 + up as u32, pair[0] * (1 + 9 * up))
+}
+
+// Entry e: m and k with floor(x / 10^e) = (x >> e) * m >> k for every
+// u32 x. 10^e is 2^e * 5^e, so after the shift only 5^e is left to
+// divide by, and taking m = floor(2^k / 5^e) + 1 with 2^(k - 32) just
+// under 5^e keeps the error of the product below 1 / 5^e while m
+// still fits in 32 bits. Entry 0 is m = 2^32, for the division by 1.
+const RECIPROCALS_U32: [(u64, u32); 11] = {
+    let mut table = [(1 << 32, 32); 11];
+    let mut five = 5u64;
+    let mut e = 1;
+    while e < 11 {
+        let k = 32 + 63 - five.leading_zeros();
+        table[e] = ((1 << k) / five + 1, k);
+        assert!(table[e].0 < 1 << 32);
+        five *= 5;
+        e += 1;
+    }
+    table
+};
+
+// floor(x / 10^e) for e <= 10, without a division.
+#[inline]
+const fn div_pow10_u32(x: u32, e: u32) -> u32 {
+    if e > 10 {
+        // SAFETY: the callers pass at most digits_u32(x) <= 10.
+        unsafe { crate::unchecked::unreachable() }
+    }
+    let (m, k) = RECIPROCALS_U32[e as usize];
+    (((x >> e) as u64 * m) >> k) as u32
+}
+
+// As RECIPROCALS_U32, for u64 and with a 128-bit product, of which
+// the shift is past the low 64 bits. Entry 0 is unused, as 2^64
+// doesn't fit.
+#[cfg(not(feature = "small-tables"))]
+const RECIPROCALS_U64: [(u64, u32); 21] = {
+    let mut table = [(0, 0); 21];
+    let mut five = 5u128;
+    let mut e = 1;
+    while e < 21 {
+        let s = 127 - five.leading_zeros();
+        let m = (1 << (64 + s)) / five + 1;
+        assert!(m < 1 << 64);
+        table[e] = (m as u64, s);
+        five *= 5;
+        e += 1;
+    }
+    table
+};
+
+// floor(x / 10^e) for e <= 20, without a division.
+#[cfg(not(feature = "small-tables"))]
+#[inline]
+const fn div_pow10_u64(x: u64, e: u32) -> u64 {
+    if e > 20 {
+        // SAFETY: the callers pass at most digits_u64(x) <= 20.
+        unsafe { crate::unchecked::unreachable() }
+    }
+    let (m, s) = RECIPROCALS_U64[e as usize];
+    let q = (((x >> e) as u128 * m as u128) >> 64) as u64 >> s;
+    if e == 0 {
+        x
+    } else {
+        q
+    }
+}
+
+#[cfg(feature = "small-tables")]
+#[inline]
+const fn div_pow10_u64(x: u64, e: u32) -> u64 {
+    if e >= 20 {
+        0
+    } else {
+        x / pow10(e)
+    }
+}
+
+/// The first decimal digit of `x`, as for Benford's law; 0 gives 0.
+///
+/// ```
+/// assert_eq!(ilog::leading_digit_u32(7), 7);
+/// assert_eq!(ilog::leading_digit_u32(31_415), 3);
+/// ```
+#[inline]
+pub const fn leading_digit_u32(x: u32) -> u32 {
+    leading_n_digits_u32(x, 1)
+}
+
+/// The first decimal digit of `x`; 0 gives 0.
+#[inline]
+pub const fn leading_digit_u64(x: u64) -> u32 {
+    leading_n_digits_u64(x, 1) as u32
+}
+
+/// The first `n` decimal digits of `x`, as a number: `x` itself if it
+/// has no more than `n`, and 0 for `n` = 0. The digits are cut off,
+/// not rounded. The division by a power of ten is a multiply by a
+/// table reciprocal.
+///
+/// ```
+/// assert_eq!(ilog::leading_n_digits_u32(31_415, 3), 314);
+/// assert_eq!(ilog::leading_n_digits_u32(42, 3), 42);
+/// ```
+#[inline]
+pub const fn leading_n_digits_u32(x: u32, n: u32) -> u32 {
+    div_pow10_u32(x, digits_u32(x).saturating_sub(n))
+}
+
+/// As `leading_n_digits_u32`, for u64. Without `small-tables` the
+/// reciprocal takes a 64 by 64-bit multiply with a 128-bit product;
+/// with it, a division.
+#[inline]
+pub const fn leading_n_digits_u64(x: u64, n: u32) -> u64 {
+    div_pow10_u64(x, digits_u64(x).saturating_sub(n))
+}
+
+/// How `round_sig_figs` settles a value exactly halfway between two.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rounding {
+    /// Away from zero: 25 to one figure is 30.
+    HalfUp,
+    /// To the even neighbour: 25 to one figure is 20, 35 is 40.
+    HalfEven,
+}
+
+/// `x` rounded to `figs` significant decimal figures, with ties broken
+/// by `rounding`; `x` itself if it has no more digits than that, and 0
+/// for `figs` = 0.
+///
+/// Panics if the result doesn't fit a u64, which only happens when
+/// rounding up a value above 10^19.
+///
+/// ```
+/// use ilog::{round_sig_figs, Rounding};
+///
+/// assert_eq!(round_sig_figs(1_234_567, 3, Rounding::HalfUp), 1_230_000);
+/// assert_eq!(round_sig_figs(1_250, 2, Rounding::HalfUp), 1_300);
+/// assert_eq!(round_sig_figs(1_250, 2, Rounding::HalfEven), 1_200);
+/// ```
+#[inline]
+#[track_caller]
+pub const fn round_sig_figs(x: u64, figs: u32, rounding: Rounding) -> u64 {
+    match checked_round_sig_figs(x, figs, rounding) {
+        Some(rounded) => rounded,
+        None => panic!("rounding overflowed u64"),
+    }
+}
+
+/// As `round_sig_figs`, or `None` if the result doesn't fit a u64.
+#[inline]
+pub const fn checked_round_sig_figs(x: u64, figs: u32, rounding: Rounding) -> Option<u64> {
+    if figs == 0 {
+        return Some(0);
+    }
+    let digits = digits_u64(x);
+    if figs >= digits {
+        return Some(x);
+    }
+    // 1 <= cut <= 19, so the power fits and is even.
+    let cut = digits - figs;
+    let p = pow10(cut);
+    let q = div_pow10_u64(x, cut);
+    let r = x - q * p;
+    let half = p / 2;
+    let up = r > half
+        || (r == half && (matches!(rounding, Rounding::HalfUp) || q & 1 != 0));
+    (q + up as u64).checked_mul(p)
+}
+
+/// Which way `log10_with_rounding` takes a log10 that falls between two
+/// integers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Log10Rounding {
+    /// Down, the ilog10: the exponent of `x` in scientific notation.
+    Floor,
+    /// Up: the power of ten at or above `x`, as for sizing a buffer or
+    /// the top tick of a log-scale axis.
+    Ceil,
+    /// To the nearer integer on a log scale, with the geometric
+    /// midpoint 10^(k + 1/2) as the dividing line: the order of
+    /// magnitude `x` is closest to. A tie would go to the even power,
+    /// but 10^(2k + 1) is never a square, so no integer lands on one.
+    Nearest,
+}
+
+/// log10(x) rounded to an integer by `mode`. Panics if `x` is 0.
+///
+/// ```
+/// use ilog::{log10_with_rounding, Log10Rounding};
+///
+/// assert_eq!(log10_with_rounding(999, Log10Rounding::Floor), 2);
+/// assert_eq!(log10_with_rounding(999, Log10Rounding::Ceil), 3);
+/// assert_eq!(log10_with_rounding(1000, Log10Rounding::Ceil), 3);
+/// // 10^2.5 is 316.2...
+/// assert_eq!(log10_with_rounding(316, Log10Rounding::Nearest), 2);
+/// assert_eq!(log10_with_rounding(317, Log10Rounding::Nearest), 3);
+/// ```
+#[inline]
+#[track_caller]
+pub const fn log10_with_rounding(x: u64, mode: Log10Rounding) -> u32 {
+    if x == 0 {
+        panic!("log10 of 0");
+    }
+    let (digits, p) = digits_and_pow10_u64(x);
+    let floor = digits - 1;
+    let up = match mode {
+        Log10Rounding::Floor => false,
+        Log10Rounding::Ceil => x > p,
+        Log10Rounding::Nearest => nearest_is_up(x, floor, p),
+    };
+    floor + up as u32
+}
+
+// Whether log10(x) > floor + 1/2, for p = 10^floor <= x, which is
+// exactly when x^2 > 10^(2 floor + 1). At floor = 19 that power is past
+// a u128, and x^2 below it.
+#[inline]
+const fn nearest_is_up(x: u64, floor: u32, p: u64) -> bool {
+    let p = p as u128;
+    floor < 19 && x as u128 * x as u128 > p * p * 10
+}
+
+/// The power of ten nearest `x` on a log scale, as for the ticks of a
+/// log axis or an order-of-magnitude bucket: 10^k for the k of
+/// `log10_with_rounding(x, Log10Rounding::Nearest)`. The dividing line
+/// between 10^k and 10^(k + 1) is 10^(k + 1/2), and no integer is on
+/// it, so there are no ties: 316 gives 100 and 317 gives 1000. 0 gives
+/// 1, and every u64 has a nearest power that fits.
+///
+/// ```
+/// assert_eq!(ilog::nearest_power_of_ten(316), 100);
+/// assert_eq!(ilog::nearest_power_of_ten(317), 1000);
+/// assert_eq!(ilog::nearest_power_of_ten(u64::MAX), 10_000_000_000_000_000_000);
+/// ```
+#[inline]
+pub const fn nearest_power_of_ten(x: u64) -> u64 {
+    if x == 0 {
+        return 1;
+    }
+    let (digits, p) = digits_and_pow10_u64(x);
+    p * (1 + 9 * nearest_is_up(x, digits - 1, p) as u64)
+}
+
+/// floor(log10(1 / x)), which is -ceil(log10(x)): the exponent of 1/x
+/// in scientific notation, as for a per-event rate or a probability of
+/// one in `x`. `-(ilog10(x) + 1)`, the obvious way there, is one too low
+/// at every power of ten, where the reciprocal is exact. Panics if `x`
+/// is 0.
+///
+/// ```
+/// assert_eq!(ilog::neg_ilog10_of_reciprocal(1), 0);
+/// // 1/999 is 1.001e-3, 1/1000 is 1e-3 and 1/1001 is 9.99e-4.
+/// assert_eq!(ilog::neg_ilog10_of_reciprocal(999), -3);
+/// assert_eq!(ilog::neg_ilog10_of_reciprocal(1000), -3);
+/// assert_eq!(ilog::neg_ilog10_of_reciprocal(1001), -4);
+/// ```
+#[inline]
+#[track_caller]
+pub const fn neg_ilog10_of_reciprocal(x: u64) -> i32 {
+    if x == 0 {
+        panic!("reciprocal of 0");
+    }
+    -(log10_with_rounding(x, Log10Rounding::Ceil) as i32)
+}
+
+/// floor(log10(numerator / denominator)), exactly, for taking a count
+/// per unit (bytes per second, errors per million requests) to log
+/// space without going through a float or losing the fraction to an
+/// integer division. Negative when the ratio is below 1; from -20 to 19.
+/// Panics if either is 0.
+///
+/// ```
+/// // 3 errors in 4,000 requests is 7.5e-4.
+/// assert_eq!(ilog::ilog10_ratio(3, 4_000), -4);
+/// assert_eq!(ilog::ilog10_ratio(4, 4_000), -3);
+/// assert_eq!(ilog::ilog10_ratio(1_500_000, 3), 5);
+/// assert_eq!(ilog::ilog10_ratio(1, 1), 0);
+/// ```
+#[inline]
+#[track_caller]
+pub const fn ilog10_ratio(numerator: u64, denominator: u64) -> i32 {
+    if numerator == 0 || denominator == 0 {
+        panic!("log10 of a ratio with 0 in it");
+    }
+    let (n_digits, n_pow) = digits_and_pow10_u64(numerator);
+    let (d_digits, d_pow) = digits_and_pow10_u64(denominator);
+    // The ratio is within a factor of ten either side of 10^k =
+    // n_pow / d_pow, and at or above it exactly when cross-multiplying
+    // says so. Each product is below 2^64 * 10^19, in a u128.
+    let k = n_digits as i32 - d_digits as i32;
+    let at_least = numerator as u128 * d_pow as u128 >= denominator as u128 * n_pow as u128;
+    if at_least {
+        k
+    } else {
+        k - 1
+    }
+}
+
+// ilog2(x) * 205 >> 11 is floor(ilog2(x) * log1000(2)), the ilog1000
+// of the power of two at or below x; the assertion checks it against
+// the powers of 1000 for every u64 ilog2. x can be up to one power of
+// 1000 past that, never two, as log1000(2) < 1.
+#[inline]
+const fn guess_ilog1000(log2: u32) -> u32 {
+    log2.wrapping_mul(205) >> 11
+}
+
+const _: () = {
+    let mut log2 = 0;
+    let mut power = 1u128;
+    let mut log1000 = 0;
+    while log2 < 64 {
+        if power * 1000 <= 1 << log2 {
+            power *= 1000;
+            log1000 += 1;
+        }
+        assert!(guess_ilog1000(log2) == log1000);
+        log2 += 1;
+    }
+};
+
+// Entry g: 1000^(g + 1) - 1, or the maximum where that doesn't fit.
+const THOUSAND_THRESHOLDS_U32: [u32; 4] = [999, 999_999, 999_999_999, u32::MAX];
+const THOUSAND_THRESHOLDS_U64: [u64; 7] = {
+    let mut table = [u64::MAX; 7];
+    let mut power = 1000;
+    let mut g = 0;
+    while g < 6 {
+        table[g] = power - 1;
+        power = power.wrapping_mul(1000);
+        g += 1;
+    }
+    table
+};
+
+/// floor(log1000(x)), the power of 1000 of an SI prefix (k, M, G), in
+/// a single guess and correction. Panics if `x` is 0.
+#[inline]
+pub const fn ilog1000_u32(x: u32) -> u32 {
+    let guess = guess_ilog1000(ilog2_u32(x));
+    if guess >= 4 {
+        // SAFETY: 31 * 205 >> 11 is 3.
+        unsafe { crate::unchecked::unreachable() }
+    }
+    guess + (x > THOUSAND_THRESHOLDS_U32[guess as usize]) as u32
+}
+
+/// floor(log1000(x)). Panics if `x` is 0.
+#[inline]
+pub const fn ilog1000_u64(x: u64) -> u32 {
+    let guess = guess_ilog1000(ilog2_u64(x));
+    if guess >= 7 {
+        // SAFETY: 63 * 205 >> 11 is 6.
+        unsafe { crate::unchecked::unreachable() }
+    }
+    guess + (x > THOUSAND_THRESHOLDS_U64[guess as usize]) as u32
+}
+
+/// `(x / 1000^k, k)` for `k = ilog1000_u32(x)`: the value and SI prefix
+/// index to print `x` with, as in 12_345 to 12 k. 0 gives `(0, 0)`. The
+/// division is a multiply by a table reciprocal.
+///
+/// ```
+/// const PREFIXES: [&str; 4] = ["", "k", "M", "G"];
+///
+/// let (value, k) = ilog::si_scale_u32(12_345_678);
+/// assert_eq!(format!("{value} {}", PREFIXES[k as usize]), "12 M");
+/// ```
+#[inline]
+pub const fn si_scale_u32(x: u32) -> (u32, u32) {
+    // Powers of 1000 are even, except 1, so x | 1 has the same ilog1000.
+    let k = ilog1000_u32(x | 1);
+    (div_pow10_u32(x, 3 * k), k)
+}
+
+/// As `si_scale_u32`, for u64, up to `k` = 6 (E).
+#[inline]
+pub const fn si_scale_u64(x: u64) -> (u64, u32) {
+    let k = ilog1000_u64(x | 1);
+    (div_pow10_u64(x, 3 * k), k)
+}
+
+/// `x` as `m` x 10^`e` with the decimal point after the first digit of
+/// `m`, i.e. d.ddd x 10^e, for scientific notation: `e` is
+/// `ilog10(x)` and `m` is `x` without its trailing zeros. 0 gives
+/// `(0, 0)`.
+///
+/// ```
+/// let (m, e) = ilog::to_decimal_sci(12_300);
+/// assert_eq!((m, e), (123, 4));
+///
+/// let digits = m.to_string();
+/// let (lead, rest) = digits.split_at(1);
+/// assert_eq!(format!("{lead}.{rest}e{e}"), "1.23e4");
+/// ```
+#[inline]
+pub const fn to_decimal_sci(x: u64) -> (u64, u32) {
+    let exponent = digits_u64(x) - 1;
+    // At most 19 trailing zeros, so each of 16, 8, 4, 2 and 1 comes
+    // off at most once, largest first. The divisions are by constants,
+    // so multiplies, and the remainder is implied by the quotient.
+    let mut m = x;
+    let mut i = 0;
+    while i < 5 {
+        let p = [10_000_000_000_000_000, 100_000_000, 10_000, 100, 10][i];
+        let q = m / p;
+        if q * p == m {
+            m = q;
+        }
+        i += 1;
+    }
+    (m, exponent)
+}
+
+// Entry r, for the radices that aren't powers of two: m with
+// ilog2(x) * m >> 16 equal to ilog_r(2^ilog2(x)), from which ilog_r(x)
+// is at most one more. m is the least value of the interval that each
+// ilog2 rules out the rest of, and the assertion that the interval
+// isn't empty checks it for all of them.
+const RADIX_GUESSES: [u32; 37] = {
+    let mut table = [0; 37];
+    let mut r = 3;
+    while r <= 36 {
+        if !(r as u32).is_power_of_two() {
+            let (mut lo, mut hi) = (0, u64::MAX);
+            let (mut log2, mut log_r, mut power) = (1, 0, r as u128);
+            while log2 < 64 {
+                while power <= 1u128 << log2 {
+                    power *= r as u128;
+                    log_r += 1;
+                }
+                // The m with log_r <= log2 * m / 2^16 < log_r + 1.
+                let (a, b) = (log_r << 16, (log_r + 1) << 16);
+                let low = a / log2 + (a % log2 != 0) as u64;
+                let high = b / log2 + (b % log2 != 0) as u64;
+                if low > lo {
+                    lo = low;
+                }
+                if high < hi {
+                    hi = high;
+                }
+                log2 += 1;
+            }
+            assert!(lo < hi);
+            table[r] = lo as u32;
+        }
+        r += 1;
+    }
+    table
+};
+
+/// The length of `values` written out in decimal one after the other
+/// with `separator` bytes between each two (1 for a comma, 2 for ", "),
+/// for sizing a CSV or JSON writer's buffer in one allocation. The
+/// digit counts are taken two at a time with `digits_u64x2`, which
+/// LLVM vectorizes on x86-64 to about three times the speed of one at
+/// a time.
+///
+/// ```
+/// let values = [7, 42, 1_000, 0];
+/// let text = "7, 42, 1000, 0";
+/// assert_eq!(ilog::total_decimal_len(&values, 2), text.len());
+/// ```
+#[inline]
+pub fn total_decimal_len(values: &[u64], separator: usize) -> usize {
+    let mut pairs = values.chunks_exact(2);
+    let mut total = 0;
+    for pair in &mut pairs {
+        let [a, b] = digits_u64x2([pair[0], pair[1]]);
+        total += (a + b) as usize;
+    }
+    if let [x] = pairs.remainder() {
+        total += digits_u64(*x) as usize;
+    }
+    total + separator * values.len().saturating_sub(1)
+}
+
+/// `total_decimal_len` a value at a time, for integers that are
+/// streamed rather than held in a slice.
+///
+/// ```
+/// use ilog::DecimalLenAccumulator;
+///
+/// let mut len = DecimalLenAccumulator::new(1);
+/// for x in [7, 42, 1_000] {
+///     len.push(x);
+/// }
+/// assert_eq!(len.finish(), "7,42,1000".len());
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct DecimalLenAccumulator {
+    separator: usize,
+    digits: usize,
+    count: usize,
+}
+
+impl DecimalLenAccumulator {
+    /// An empty tally, with `separator` bytes between each two values.
+    pub const fn new(separator: usize) -> Self {
+        DecimalLenAccumulator {
+            separator,
+            digits: 0,
+            count: 0,
+        }
+    }
+
+    /// Counts `x`.
+    #[inline]
+    pub fn push(&mut self, x: u64) {
+        self.digits += digits_u64(x) as usize;
+        self.count += 1;
+    }
+
+    /// The length of everything pushed, with the separators.
+    pub const fn finish(self) -> usize {
+        self.digits + self.separator * self.count.saturating_sub(1)
+    }
+}
+
+impl Extend<u64> for DecimalLenAccumulator {
+    fn extend<I: IntoIterator<Item = u64>>(&mut self, values: I) {
+        for x in values {
+            self.push(x);
+        }
+    }
+}
+
+/// The length of a message that starts with its own length in
+/// decimal, followed by `payload_len` more bytes (separators count as
+/// payload): the `t` with `t = digits_u64(t) + payload_len`. Adding the
+/// prefix can carry the total past a power of ten, which lengthens
+/// the prefix by one, so there are two candidates and one digit count
+/// picks between them.
+///
+/// Panics if the total doesn't fit a u64.
+///
+/// ```
+/// // "11" followed by 9 bytes is 11 bytes long, where "10" would have
+/// // been wrong.
+/// assert_eq!(ilog::encoded_len_with_prefix(9), 11);
+/// assert_eq!(ilog::encoded_len_with_prefix(8), 9);
+/// ```
+#[inline]
+#[track_caller]
+pub const fn encoded_len_with_prefix(payload_len: u64) -> u64 {
+    match checked_encoded_len_with_prefix(payload_len) {
+        Some(len) => len,
+        None => panic!("encoded length overflowed u64"),
+    }
+}
+
+/// As `encoded_len_with_prefix`, or `None` if the total doesn't fit a
+/// u64.
+#[inline]
+pub const fn checked_encoded_len_with_prefix(payload_len: u64) -> Option<u64> {
+    let digits = digits_u64(payload_len) as u64;
+    let short = match payload_len.checked_add(digits) {
+        Some(len) => len,
+        None => return None,
+    };
+    // A prefix of `digits` digits fits unless it pushes the total to
+    // 10^digits, and then one more digit always does.
+    if digits_u64(short) as u64 == digits {
+        Some(short)
+    } else {
+        short.checked_add(1)
+    }
+}
+
+/// The length of `payload_len` bytes as a netstring,
+/// `<payload_len>:<payload>,`.
+///
+/// Panics if the total doesn't fit a u64.
+///
+/// ```
+/// assert_eq!(ilog::netstring_len(12), "12:hello world!,".len() as u64);
+/// ```
+#[inline]
+#[track_caller]
+pub const fn netstring_len(payload_len: u64) -> u64 {
+    match checked_netstring_len(payload_len) {
+        Some(len) => len,
+        None => panic!("netstring length overflowed u64"),
+    }
+}
+
+/// As `netstring_len`, or `None` if the total doesn't fit a u64.
+#[inline]
+pub const fn checked_netstring_len(payload_len: u64) -> Option<u64> {
+    payload_len.checked_add(digits_u64(payload_len) as u64 + 2)
+}
+
+/// Number of digits of `x` in `radix`, counting 0 as one digit: the
+/// length of its `to_string_radix`-style representation, to size a
+/// buffer with. A power of two radix takes a shift and a division by
+/// its bit count, 10 is `digits_u64`, and the others a multiply guess
+/// and a comparison against a power of the radix.
+///
+/// Panics if `radix` is not in 2..=36.
+///
+/// ```
+/// assert_eq!(ilog::digits_in_radix(255, 16), 2);
+/// assert_eq!(ilog::digits_in_radix(255, 2), 8);
+/// assert_eq!(ilog::digits_in_radix(u64::MAX, 36), 13);
+/// assert_eq!(ilog::digits_in_radix(0, 7), 1);
+/// ```
+#[inline]
+#[track_caller]
+pub const fn digits_in_radix(x: u64, radix: u32) -> u32 {
+    if radix < 2 || radix > 36 {
+        panic!("radix must be in 2..=36");
+    }
+    if radix == 10 {
+        return digits_u64(x);
+    }
+    let log2 = ilog2_u64(x | 1);
+    if radix.is_power_of_two() {
+        return log2 / radix.trailing_zeros() + 1;
+    }
+    let guess = (log2 * RADIX_GUESSES[radix as usize]) >> 16;
+    let above = match (radix as u64).checked_pow(guess + 1) {
+        Some(power) => x >= power,
+        None => false,
+    };
+    guess + 1 + above as u32
+}
+
+/// `digits_u32` of four values at once, for callers with a few
+/// integers in hand rather than a slice. Each step is written out for
+/// all four before the next, so that the four independent chains come
+/// out interleaved instead of one after the other; on x86-64 LLVM goes
+/// on to do the ilog2s, multiplies and compares as 4-lane vector
+/// operations, with only the table loads left scalar.
+#[inline]
+pub const fn digits_u32x4(x: [u32; 4]) -> [u32; 4] {
+    let v = [x[0] | 1, x[1] | 1, x[2] | 1, x[3] | 1];
+    let g = [
+        ilog2_u32(v[0]).wrapping_mul(9) >> 5,
+        ilog2_u32(v[1]).wrapping_mul(9) >> 5,
+        ilog2_u32(v[2]).wrapping_mul(9) >> 5,
+        ilog2_u32(v[3]).wrapping_mul(9) >> 5,
+    ];
+    if g[0] >= 9 || g[1] >= 9 || g[2] >= 9 || g[3] >= 9 {
+        // SAFETY: as in ilog10_mul, 31 * 9 >> 5 is 8.
+        unsafe { crate::unchecked::unreachable() }
+    }
+    [
+        g[0] + (v[0] > statics::TEN_THRESHOLDS[g[0] as usize]) as u32 + 1,
+        g[1] + (v[1] > statics::TEN_THRESHOLDS[g[1] as usize]) as u32 + 1,
+        g[2] + (v[2] > statics::TEN_THRESHOLDS[g[2] as usize]) as u32 + 1,
+        g[3] + (v[3] > statics::TEN_THRESHOLDS[g[3] as usize]) as u32 + 1,
+    ]
+}
+
+/// `digits_u64` of two values at once, written as `digits_u32x4`.
+#[inline]
+pub const fn digits_u64x2(x: [u64; 2]) -> [u32; 2] {
+    let v = [x[0] | 1, x[1] | 1];
+    let g = [
+        ilog2_u64(v[0]).wrapping_mul(19) >> 6,
+        ilog2_u64(v[1]).wrapping_mul(19) >> 6,
+    ];
+    [
+        g[0] + (v[0] > u64_threshold(g[0])) as u32 + 1,
+        g[1] + (v[1] > u64_threshold(g[1])) as u32 + 1,
+    ]
+}
+
+/// Number of decimal digits in an integer expression of any width, as
+/// a `usize`, with one more for the `-` of a negative value: the length
+/// of its `to_string()`. It expands to the const fn `digits_u128`, so
+/// it can size arrays:
+///
+/// ```
+/// let mut buf = [0u8; ilog::digits!(u64::MAX)];
+/// assert_eq!(ilog::write_u64(u64::MAX, &mut buf), buf.len());
+///
+/// const _: () = assert!(ilog::digits!(0u8) == 1);
+/// assert_eq!(ilog::digits!(u128::MAX), u128::MAX.to_string().len());
+/// assert_eq!(ilog::digits!(-1i32), 2);
+/// assert_eq!(ilog::digits!(i64::MIN), i64::MIN.to_string().len());
+/// assert_eq!(ilog::digits!(i128::MIN), i128::MIN.to_string().len());
+/// ```
+#[macro_export]
+macro_rules! digits {
+    ($x:expr) => {{
+        let x = $x;
+        // All ones is below zero only in a signed type, which widens to
+        // i128 without loss as an unsigned one does to u128.
+        let zero = x ^ x;
+        if !zero < zero {
+            let x = x as i128;
+            $crate::digits_u128(x.unsigned_abs()) as usize + (x < 0) as usize
+        } else {
+            $crate::digits_u128(x as u128) as usize
+        }
+    }};
+}
+
+/// The length of an integer type's `MAX` in decimal, its longest value
+/// without a sign: `i8` gives 3, for 127 (and "-128" takes one more).
+pub trait MaxDecimalLen {
+    /// The number of digits of `Self::MAX`.
+    const MAX_DECIMAL_LEN: usize;
+}
+
+macro_rules! max_decimal_len {
+    ($($t:ty)*) => {
+        $(
+            impl MaxDecimalLen for $t {
+                // One more than the thresholds up to MAX.
+                const MAX_DECIMAL_LEN: usize = thresholds!($t).len() + 1;
+            }
+        )*
+    };
+}
+
+max_decimal_len!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+
+/// `T::MAX_DECIMAL_LEN`, the number of digits of `T::MAX`, as a const fn
+/// to size stack buffers with. It needs trait bounds on a const fn,
+/// stable from Rust 1.61; before that, `<u64 as MaxDecimalLen>::MAX_DECIMAL_LEN`
+/// does the same.
+///
+/// ```
+/// let mut buf = [0u8; ilog::max_decimal_len::<u64>()];
+/// assert_eq!(ilog::write_u64(u64::MAX, &mut buf), buf.len());
+/// assert_eq!(ilog::max_decimal_len::<i8>(), 3);
+/// ```
+#[cfg(ilog_const_fn_bounds)]
+pub const fn max_decimal_len<T: MaxDecimalLen>() -> usize {
+    T::MAX_DECIMAL_LEN
+}
+
+// "00" to "99", for writing two digits at a time.
+const DIGIT_PAIRS: [u8; 200] = {
+    let mut pairs = [0; 200];
+    let mut i = 0;
+    while i < 100 {
+        pairs[2 * i] = b'0' + (i / 10) as u8;
+        pairs[2 * i + 1] = b'0' + (i % 10) as u8;
+        i += 1;
+    }
+    pairs
+};
+
+/// Writes the decimal digits of `x` to the start of `buf` and returns
+/// how many were written. Counting the digits first means they can be
+/// written back to front straight into place, with no reversal, two at
+/// a time from a table of pairs.
+///
+/// Panics if `buf` is shorter than `digits_u64(x)`.
+pub fn write_u64(mut x: u64, buf: &mut [u8]) -> usize {
+    let len = digits_u64(x) as usize;
+    let mut end = len;
+    for pair in buf[..len].rchunks_exact_mut(2) {
+        let i = (x % 100) as usize * 2;
+        pair.copy_from_slice(&DIGIT_PAIRS[i..i + 2]);
+        x /= 100;
+        end -= 2;
+    }
+    if end == 1 {
+        buf[0] = b'0' + x as u8;
+    }
+    len
+}
+
+/// `x` for `{}`, written with `write_u64` into a stack buffer and
+/// handed to the formatter in one `write_str`, with no allocation. A
+/// width or `+` goes through `Formatter::pad_integral` as for `u64`
+/// itself, so the text is always the same as `x`'s own; `ilog display`
+/// times the two.
+///
+/// ```
+/// use ilog::DisplayU64;
+///
+/// assert_eq!(DisplayU64(1_024).to_string(), "1024");
+/// assert_eq!(format!("{:>6}|{:+}", DisplayU64(42), DisplayU64(7)), "    42|+7");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DisplayU64(pub u64);
+
+impl core::fmt::Display for DisplayU64 {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut buf = [0; 20];
+        let len = write_u64(self.0, &mut buf);
+        write_integer(f, true, &buf[..len])
+    }
+}
+
+/// As `DisplayU64`, for i64, the sign in the same buffer as the digits.
+///
+/// ```
+/// use ilog::DisplayI64;
+///
+/// assert_eq!(DisplayI64(i64::MIN).to_string(), i64::MIN.to_string());
+/// assert_eq!(format!("{:05}", DisplayI64(-42)), "-0042");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct DisplayI64(pub i64);
+
+impl core::fmt::Display for DisplayI64 {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut buf = [b'-'; 21];
+        let len = write_u64(self.0.unsigned_abs(), &mut buf[1..]);
+        let negative = self.0 < 0;
+        if negative && f.width().is_none() {
+            return f.write_str(ascii(&buf[..len + 1]));
+        }
+        write_integer(f, !negative, &buf[1..len + 1])
+    }
+}
+
+// `digits` in one write_str unless there is padding or a sign to add,
+// which pad_integral does as the standard library's integers have it.
+#[inline]
+fn write_integer(
+    f: &mut core::fmt::Formatter<'_>,
+    nonnegative: bool,
+    digits: &[u8],
+) -> core::fmt::Result {
+    if nonnegative && f.width().is_none() && !f.sign_plus() {
+        f.write_str(ascii(digits))
+    } else {
+        f.pad_integral(nonnegative, "", ascii(digits))
+    }
+}
+
+#[inline]
+fn ascii(text: &[u8]) -> &str {
+    // SAFETY: only ASCII digits and '-' are ever passed.
+    unsafe { core::str::from_utf8_unchecked(text) }
+}
+
+/// The decimal digits of a u64 (0 to 9, not ASCII) from the most
+/// significant, for encoders that stream their output and so can't
+/// write back to front as `write_u64` does. The digit count is known
+/// from the start, making it an `ExactSizeIterator`, and each digit is
+/// cut off the top by a multiply by a table reciprocal (a division with
+/// `small-tables`). 0 has the one digit 0.
+///
+/// ```
+/// use ilog::DecimalDigits;
+///
+/// let digits = DecimalDigits::new(1_024);
+/// assert_eq!(digits.len(), 4);
+/// assert!(digits.eq([1, 0, 2, 4]));
+/// let text: String = DecimalDigits::new(907).map(|d| char::from(b'0' + d)).collect();
+/// assert_eq!(text, "907");
+/// ```
+#[derive(Clone, Debug)]
+pub struct DecimalDigits {
+    rest: u64,
+    // 10^(len - 1), the place of the next digit.
+    power: u64,
+    len: u32,
+}
+
+impl DecimalDigits {
+    /// The digits of `x`.
+    pub const fn new(x: u64) -> Self {
+        let (len, power) = digits_and_pow10_u64(x);
+        DecimalDigits {
+            rest: x,
+            power,
+            len,
+        }
+    }
+}
+
+impl Iterator for DecimalDigits {
+    type Item = u8;
+
+    #[inline]
+    fn next(&mut self) -> Option<u8> {
+        if self.len == 0 {
+            return None;
+        }
+        self.len -= 1;
+        let digit = div_pow10_u64(self.rest, self.len);
+        self.rest -= digit * self.power;
+        self.power /= 10;
+        Some(digit as u8)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len as usize, Some(self.len as usize))
+    }
+}
+
+impl ExactSizeIterator for DecimalDigits {}
+
+impl core::iter::FusedIterator for DecimalDigits {}
@@ -0,0 +1,145 @@
+//! `ilog10_mul` with its final step, `guess + (x > ttg) as u32`, pinned
+//! to each way it can be lowered. Left alone, LLVM picks a compare and
+//! `adc` (x86-64) or `cinc` (aarch64), which is what `mul` benchmarks;
+//! these write the step out in `asm!` so that the alternatives can be
+//! timed next to it. The `digits` and `sorted` distributions hold the
+//! same values shuffled and in order, which is where the branch wins or
+//! loses.
+//!
+//! - `ilog10_branch`: a conditional jump over the increment.
+//! - `ilog10_cmov`: `guess + 1` computed up front and selected with
+//!   `cmova` / `csel`.
+//! - `ilog10_sub_shift`: `ttg - x` in 64 bits, whose top bit is the
+//!   borrow, shifted down and added.
+//!
+//! The guess and table load stay in Rust, so only the step differs.
+
+use core::arch::asm;
+
+#[inline]
+fn guess_and_threshold(x: u32) -> (u32, u32) {
+    let guess = crate::ilog2_u32(x).wrapping_mul(9) >> 5;
+    if guess >= 9 {
+        // SAFETY: ilog2 is at most 31, and 31 * 9 >> 5 is 8.
+        unsafe { crate::unchecked::unreachable() }
+    }
+    (guess, crate::statics::TEN_THRESHOLDS[guess as usize])
+}
+
+#[cfg(target_arch = "x86_64")]
+#[inline]
+pub fn ilog10_branch(x: u32) -> u32 {
+    let (mut r, ttg) = guess_and_threshold(x);
+    // SAFETY: register arithmetic only.
+    unsafe {
+        asm!(
+            "cmp {x:e}, {t:e}",
+            "jbe 2f",
+            "inc {r:e}",
+            "2:",
+            x = in(reg) x,
+            t = in(reg) ttg,
+            r = inout(reg) r,
+            options(pure, nomem, nostack),
+        );
+    }
+    r
+}
+
+#[cfg(target_arch = "x86_64")]
+#[inline]
+pub fn ilog10_cmov(x: u32) -> u32 {
+    let (mut r, ttg) = guess_and_threshold(x);
+    // SAFETY: register arithmetic only.
+    unsafe {
+        asm!(
+            "lea {n:e}, [{r:r} + 1]",
+            "cmp {x:e}, {t:e}",
+            "cmova {r:e}, {n:e}",
+            x = in(reg) x,
+            t = in(reg) ttg,
+            n = out(reg) _,
+            r = inout(reg) r,
+            options(pure, nomem, nostack),
+        );
+    }
+    r
+}
+
+#[cfg(target_arch = "x86_64")]
+#[inline]
+pub fn ilog10_sub_shift(x: u32) -> u32 {
+    let (mut r, ttg) = guess_and_threshold(x);
+    // SAFETY: register arithmetic only.
+    unsafe {
+        asm!(
+            // Both are zero-extended by the 32-bit moves into them.
+            "sub {t}, {x}",
+            "shr {t}, 63",
+            "add {r:e}, {t:e}",
+            x = in(reg) x as u64,
+            t = inout(reg) ttg as u64 => _,
+            r = inout(reg) r,
+            options(pure, nomem, nostack),
+        );
+    }
+    r
+}
+
+#[cfg(target_arch = "aarch64")]
+#[inline]
+pub fn ilog10_branch(x: u32) -> u32 {
+    let (mut r, ttg) = guess_and_threshold(x);
+    // SAFETY: register arithmetic only.
+    unsafe {
+        asm!(
+            "cmp {x:w}, {t:w}",
+            "b.ls 2f",
+            "add {r:w}, {r:w}, #1",
+            "2:",
+            x = in(reg) x,
+            t = in(reg) ttg,
+            r = inout(reg) r,
+            options(pure, nomem, nostack),
+        );
+    }
+    r
+}
+
+#[cfg(target_arch = "aarch64")]
+#[inline]
+pub fn ilog10_cmov(x: u32) -> u32 {
+    let (mut r, ttg) = guess_and_threshold(x);
+    // SAFETY: register arithmetic only.
+    unsafe {
+        asm!(
+            "add {n:w}, {r:w}, #1",
+            "cmp {x:w}, {t:w}",
+            "csel {r:w}, {n:w}, {r:w}, hi",
+            x = in(reg) x,
+            t = in(reg) ttg,
+            n = out(reg) _,
+            r = inout(reg) r,
+            options(pure, nomem, nostack),
+        );
+    }
+    r
+}
+
+#[cfg(target_arch = "aarch64")]
+#[inline]
+pub fn ilog10_sub_shift(x: u32) -> u32 {
+    let (mut r, ttg) = guess_and_threshold(x);
+    // SAFETY: register arithmetic only.
+    unsafe {
+        asm!(
+            "sub {t:x}, {t:x}, {x:x}",
+            "add {r:x}, {r:x}, {t:x}, lsr #63",
+            x = in(reg) x as u64,
+            t = inout(reg) ttg as u64 => _,
+            r = inout(reg) r,
+            options(pure, nomem, nostack),
+        );
+    }
+    r
+}
@@ -1,280 +1,2468 @@
-use clap::Parser;
+use std::num::{NonZeroU32, NonZeroUsize};
+use std::panic::{self, AssertUnwindSafe, UnwindSafe};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::Mutex;
+
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
 use rand::prelude::*;
+use rayon::iter::Either;
 use rayon::prelude::*;
 
+mod against;
+mod check;
+mod codegen;
+mod corrections;
+mod csource;
+mod csv;
+mod disasm;
+mod display;
+mod energy;
+mod explain;
+mod filter;
+mod guesses;
+mod history;
+mod html;
+mod hybrid;
+mod isolate;
+mod latency;
+mod matrix;
+mod mca;
+mod multiversion;
+mod plot;
+mod plugin;
+mod report;
+mod shader;
+mod smt;
+mod suite;
+mod table;
+mod tail;
+mod timing;
+mod verify;
+mod widths;
+
+use check::{check, checks_report, each, sweep};
+use filter::Filter;
+use ilog::bench::{Distribution, Histogram, Permutation};
+use ilog::histogram::DecadeHistogram;
+use ilog::{core_compat, dispatch, float, humanize, mcu, parts, soft_clz};
+use ilog::registry::{self, Algorithm, Width};
+use ilog::results::CheckResult;
+use suite::Suite;
+use report::{Format, Output, Report};
+
 /// Test and benchmark program for alternative ilog10 implementations.
 #[derive(Parser, Debug)]
+#[command(
+    version,
+    after_help = "Exit status: 0 on success, 1 if verification failed, 2 on usage errors, \
+                  3 on a benchmark regression, 4 on internal errors."
+)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Report format [default: markdown, or the suite's format]
+    #[arg(short, long, value_enum, global = true)]
+    format: Option<Format>,
+
+    /// Write the report to a file instead of stdout
+    #[arg(short, long, global = true)]
+    output: Option<PathBuf>,
+
+    /// Append a timestamped section to the output file instead of replacing it
+    #[arg(short, long, requires = "output", global = true)]
+    append: bool,
+
+    /// Only run algorithms or test cases matching this substring or glob (repeatable)
+    #[arg(long, global = true)]
+    filter: Vec<String>,
+
+    /// Treat --filter patterns as regular expressions
+    #[arg(long, requires = "filter", global = true)]
+    regex: bool,
+
+    /// Register SYMBOL, an `extern "C" fn(u64) -> u32` from the shared
+    /// library at PATH, as an algorithm at both widths (repeatable)
+    #[arg(long, value_name = "PATH:SYMBOL", global = true)]
+    plugin: Vec<String>,
+}
+
+// Parsed once per run, so Bench's size costs nothing worth boxing it for.
+#[allow(clippy::large_enum_variant)]
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Benchmark the u32 implementations (the default)
+    Bench {
+        /// Run a named suite from the suite file
+        #[arg(short, long)]
+        suite: Option<String>,
+
+        /// File defining the benchmark suites
+        #[arg(long, default_value = "bench.toml")]
+        suite_file: PathBuf,
+
+        /// Digit counts to draw inputs from in place of the suite's
+        /// distributions, as weighted ranges like 1-3:60,4-6:30,7+:10
+        #[arg(long)]
+        histogram: Option<Histogram>,
+
+        /// Also draw the results as grouped bar charts into this SVG file
+        #[arg(long, value_name = "PATH")]
+        plot: Option<PathBuf>,
+
+        /// Also write a self-contained HTML report of the run, with the
+        /// environment, charts and tables, to this file
+        #[arg(long, value_name = "PATH")]
+        report: Option<PathBuf>,
+
+        #[command(flatten)]
+        sampling: suite::Sampling,
+
+        /// Exit with status 3 if any result is more than PERCENT slower
+        /// than in BASELINE, a report saved with `-f json -o`
+        #[arg(long, value_name = "BASELINE:PERCENT", value_parser = suite::parse_threshold)]
+        fail_if_slower_than: Option<suite::Threshold>,
+
+        /// Also build REF, a git revision, and report this build's times against its
+        #[arg(
+            long,
+            value_name = "REF",
+            conflicts_with_all = ["plot", "report", "fail_if_slower_than", "db", "per_core_type"]
+        )]
+        against: Option<String>,
+
+        /// Also append the run and its environment to this SQLite database; see `history`
+        #[arg(long, value_name = "PATH")]
+        db: Option<PathBuf>,
+
+        /// On a hybrid CPU, run on a P-core and on an E-core and report them side by side (Linux)
+        #[arg(long, conflicts_with_all = ["plot", "report", "fail_if_slower_than", "db"])]
+        per_core_type: bool,
+
+        /// Run each algorithm's benchmark in a fresh process of its own, so
+        /// that none inherits another's code layout, predictor state or heap
+        #[arg(long, conflicts_with_all = ["against", "per_core_type"])]
+        isolate: bool,
+
+        /// Time each algorithm at every width from u8 to u128 and report
+        /// them side by side (select with --filter), over the suite's
+        /// uniform, digits and sorted distributions, or digits
+        #[arg(
+            long,
+            conflicts_with_all = [
+                "histogram", "plot", "report", "fail_if_slower_than", "against", "db",
+                "per_core_type", "isolate", "profile"
+            ]
+        )]
+        compare_widths: bool,
+
+        /// A preset of widths, distributions, contexts, sampling and
+        /// output format; options given alongside it take precedence
+        #[arg(long, value_enum)]
+        profile: Option<suite::Profile>,
+
+        /// The one algorithm an --isolate child process runs
+        #[arg(long, hide = true, value_name = "NAME")]
+        isolated: Option<String>,
+    },
+    /// Build `ilog` for each target CPU and feature set, run the same benchmark with each, and report them side by side
+    BenchMatrix {
+        /// Comma-separated values for `-C target-cpu`, `default` for none
+        #[arg(long, value_delimiter = ',', default_value = "default,native")]
+        cpus: Vec<String>,
+
+        /// Comma-separated features to build with on top of the defaults
+        /// (repeatable, one build per use; an empty one for the defaults alone)
+        #[arg(long = "features", default_value = "")]
+        feature_sets: Vec<String>,
+
+        /// Arguments for each build's `bench`, e.g. `-- --suite random`
+        #[arg(last = true)]
+        bench_args: Vec<String>,
+    },
+    /// Show how the results saved with `bench --db` changed from run to run (select with --filter)
+    History {
+        /// The database `bench --db` wrote
+        #[arg(long, value_name = "PATH", default_value = "results.sqlite")]
+        db: PathBuf,
+
+        /// Only the runs on this machine, by host name
+        #[arg(long)]
+        machine: Option<String>,
+    },
     /// Run an exhaustive u32 test
-    #[arg(short, long)]
-    test: bool,
+    Test {
+        /// Test the u64 implementation instead
+        #[arg(long)]
+        u64: bool,
+
+        /// Check structural properties of the release codegen instead
+        #[arg(long, conflicts_with = "u64")]
+        codegen: bool,
+
+        /// Target to check the --codegen of, as for `asm --target`
+        #[arg(long, default_value = disasm::HOST, requires = "codegen")]
+        target: String,
+
+        /// Visit the exhaustive ranges in a pseudo-random order, and with
+        /// --u64 check a permuted --slice instead of random values
+        #[arg(long, conflicts_with = "codegen")]
+        permuted: bool,
+
+        /// Seed of the --permuted order
+        #[arg(long, default_value_t = 0, requires = "permuted")]
+        seed: u64,
+
+        /// START:BITS, the u64s START..START + 2^BITS for --u64 --permuted
+        #[arg(
+            long,
+            value_parser = parse_slice,
+            default_value = "4294967296:32",
+            requires_all = ["u64", "permuted"]
+        )]
+        slice: (u64, u32),
+
+        /// Worker threads to check with [default: one per logical CPU]
+        #[arg(long)]
+        threads: Option<NonZeroUsize>,
+    },
+    /// Check every algorithm on the powers of ten and two and a random sample, in well under a second
+    Verify {
+        /// Seed of the random sample
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+    },
+    /// Print annotated disassembly of library functions
+    Asm {
+        #[command(flatten)]
+        codegen: Codegen,
+    },
+    /// Report instruction counts, code size and branches per function
+    Size {
+        #[command(flatten)]
+        codegen: Codegen,
+    },
+    /// Compare instruction counts and branches per function across targets
+    Cross {
+        /// Function or registry name, as for `asm` (repeatable; default all)
+        #[arg(long)]
+        algo: Vec<String>,
+
+        /// Comma-separated targets, each as for `asm --target`
+        #[arg(long, value_delimiter = ',', default_value = CROSS_TARGETS)]
+        targets: Vec<String>,
+    },
+    /// Estimate cycles, uops and port pressure with llvm-mca
+    Mca {
+        #[command(flatten)]
+        codegen: Codegen,
+
+        /// CPU model for llvm-mca, e.g. skylake or neoverse-n1 [default: llvm-mca's generic model]
+        #[arg(long)]
+        cpu: Option<String>,
+    },
+    /// Predict each function's fast-path latency per microarchitecture from per-instruction tables
+    Latency {
+        #[command(flatten)]
+        codegen: Codegen,
+
+        /// Comma-separated microarchitectures [default: haswell, skylake, icelake, alderlake, zen2, zen3 and zen4]
+        #[arg(long, value_delimiter = ',')]
+        uarch: Vec<String>,
+    },
+    /// Count how often each guess needs the +1 correction (select with --filter)
+    Corrections {
+        /// Comma-separated input distributions
+        #[arg(
+            long,
+            value_delimiter = ',',
+            value_parser = parse_distribution,
+            default_value = "uniform,digits,sorted"
+        )]
+        distributions: Vec<Distribution>,
+    },
+    /// Time the leading-zero count, guess and correction of each guess strategy apart (select with --filter)
+    Guesses {
+        /// Comma-separated input distributions, each one of the random ones
+        #[arg(
+            long,
+            value_delimiter = ',',
+            value_parser = parse_distribution,
+            default_value = "uniform,digits"
+        )]
+        distributions: Vec<Distribution>,
+    },
+    /// Time each algorithm alone and against a competitor on its SMT sibling (Linux)
+    Smt {
+        /// Time the u64 implementations instead
+        #[arg(long)]
+        u64: bool,
+
+        /// Input distribution, one of the random ones
+        #[arg(long, value_parser = parse_distribution, default_value = "digits")]
+        distribution: Distribution,
+
+        /// Registry name of the algorithm looping on the sibling, or
+        /// `same` for each algorithm against itself
+        #[arg(long, default_value = smt::SAME)]
+        competitor: String,
 
-    #[arg(short, long)]
-    testu64: bool,
+        /// A,B: the CPU to time on and its sibling [default: the first pair in sysfs]
+        #[arg(long, value_parser = smt::parse_cpus)]
+        cpus: Option<(usize, usize)>,
+    },
+    /// Walk through each guess-and-correct algorithm for one input, step by step
+    Explain {
+        /// The input, a positive integer
+        value: u64,
+
+        /// Explain the u64 implementations instead
+        #[arg(long)]
+        u64: bool,
+    },
+    /// Time each algorithm over many short batches and report the slow ones: p99, p999 and max (select with --filter)
+    Tail {
+        /// Time the u64 implementations instead
+        #[arg(long)]
+        u64: bool,
+
+        /// Input distribution, one of the random ones
+        #[arg(long, value_parser = parse_distribution, default_value = "digits")]
+        distribution: Distribution,
+
+        /// Values per timed batch
+        #[arg(long, default_value_t = 256)]
+        batch: usize,
+
+        /// Batches timed per algorithm
+        #[arg(long, default_value_t = 100_000)]
+        batches: usize,
+    },
+    /// Time the lzcnt and baseline builds of mul inlined, called, through a pointer and dispatched at run time
+    Multiversion {
+        /// Time the u64 builds instead
+        #[arg(long)]
+        u64: bool,
+
+        /// Input distribution, one of the random ones
+        #[arg(long, value_parser = parse_distribution, default_value = "uniform")]
+        distribution: Distribution,
+
+        /// Rounds timed; the best of each counts
+        #[arg(long, default_value_t = 5)]
+        samples: usize,
+    },
+    /// Time writing random u64s as CSV rows with `write!`, sized a value at a time, and sized a row ahead with the batch kernels
+    Csv {
+        /// Input distribution, one of the random ones
+        #[arg(long, value_parser = parse_distribution, default_value = "uniform")]
+        distribution: Distribution,
+
+        /// Values per row
+        #[arg(long, default_value_t = 16)]
+        fields: usize,
+
+        /// Rounds timed; the best of each counts
+        #[arg(long, default_value_t = 5)]
+        samples: usize,
+    },
+    /// Time DisplayU64 and DisplayI64 against the standard library's Display for u64 and i64
+    Display {
+        /// Input distribution, one of the random ones
+        #[arg(long, value_parser = parse_distribution, default_value = "uniform")]
+        distribution: Distribution,
+
+        /// Rounds timed; the best of each counts
+        #[arg(long, default_value_t = 5)]
+        samples: usize,
+    },
+    /// Look for a difference in the constant_time algorithms' running time between a fixed input and random ones, with the ladders as a control
+    Timing {
+        /// Input distribution for the random class, one of the random ones
+        #[arg(long, value_parser = parse_distribution, default_value = "digits")]
+        distribution: Distribution,
+
+        /// Values per timed batch
+        #[arg(long, default_value_t = 64)]
+        batch: usize,
+
+        /// Batches timed per algorithm
+        #[arg(long, default_value_t = 1_000_000)]
+        batches: usize,
+
+        /// |t| past which a difference is a leak
+        #[arg(long, default_value_t = 10.0)]
+        threshold: f64,
+    },
+    /// Print an ilog10 for GPU shaders, which have none, as WGSL or GLSL source
+    Shader {
+        /// Source language
+        #[arg(long, value_enum, default_value = "wgsl")]
+        language: shader::Language,
+
+        /// Guess the function is built from
+        #[arg(long, value_enum, default_value = "mul")]
+        algo: shader::Algo,
+
+        /// Name of the function; its constants are named after it
+        #[arg(long, default_value = "ilog10")]
+        name: String,
+    },
+    /// Print standalone source for the guess-and-correct algorithms, generated from the crate's constants
+    Codegen {
+        /// Source language
+        #[arg(value_enum)]
+        language: csource::Language,
+
+        /// Comma-separated algorithms [default: all]
+        #[arg(long, value_enum, value_delimiter = ',')]
+        algo: Vec<csource::Algo>,
+    },
+    /// Print the guess and threshold tables for an ilog of any width and base, for porting
+    Table {
+        /// Bits of the integer type: 8, 16, 32, 64 or 128
+        #[arg(long, default_value_t = 32)]
+        width: u32,
+
+        /// Base of the logarithm
+        #[arg(long, default_value_t = 10)]
+        base: u32,
+
+        /// Source language
+        #[arg(long, value_enum, default_value = "rust")]
+        language: table::Language,
+    },
+    /// Print a shell completion script
+    Completions {
+        #[arg(value_enum)]
+        shell: Shell,
+    },
+    /// Print a man page in roff format
+    Man,
 }
 
-fn main() {
+fn parse_slice(slice: &str) -> Result<(u64, u32), String> {
+    let (start, bits) = slice.split_once(':').ok_or("expected START:BITS")?;
+    let start = match start.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => start.parse(),
+    }
+    .map_err(|e| format!("START: {e}"))?;
+    let bits: u32 = bits.parse().map_err(|e| format!("BITS: {e}"))?;
+    if !(1..64).contains(&bits) || start.checked_add((1 << bits) - 1).is_none() {
+        return Err(format!("{start} + 2^{bits} is past u64::MAX"));
+    }
+    Ok((start, bits))
+}
+
+fn parse_distribution(name: &str) -> Result<Distribution, String> {
+    Distribution::ALL
+        .into_iter()
+        .find(|d| d.name() == name)
+        .ok_or_else(|| {
+            let known: Vec<&str> = Distribution::ALL.iter().map(|d| d.name()).collect();
+            format!("expected one of {}", known.join(", "))
+        })
+}
+
+const CROSS_TARGETS: &str = "x86_64-unknown-linux-gnu,x86_64-unknown-linux-gnu:x86-64-v3,\
+                             aarch64-unknown-linux-gnu,riscv64gc-unknown-linux-gnu,\
+                             wasm32-unknown-unknown";
+
+/// Which functions to build, and for which target.
+#[derive(clap::Args, Debug)]
+struct Codegen {
+    /// Function or registry name, e.g. ilog10_mul or u32/mul (repeatable; default all)
+    #[arg(long)]
+    algo: Vec<String>,
+
+    /// Target triple to build for (x86, aarch64, riscv64 or thumb; needs its rustup target)
+    #[arg(long, default_value = disasm::HOST)]
+    target: String,
+}
+
+/// How a run can fail. Each kind has its own exit status so that
+/// scripts can tell a wrong answer from a slow one from a broken run.
+enum Failure {
+    /// An implementation disagreed with the reference
+    Verification(String),
+    /// Bad arguments or suite definitions, like clap's own usage errors
+    Usage(String),
+    /// A benchmark was slower than allowed
+    Regression(String),
+    /// I/O errors and panics
+    Internal(String),
+}
+
+impl Failure {
+    fn exit_code(&self) -> ExitCode {
+        ExitCode::from(match self {
+            Failure::Verification(_) => 1,
+            Failure::Usage(_) => 2,
+            Failure::Regression(_) => 3,
+            Failure::Internal(_) => 4,
+        })
+    }
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Failure::Verification(msg) => write!(f, "verification failed: {msg}"),
+            Failure::Usage(msg) => write!(f, "error: {msg}"),
+            Failure::Regression(msg) => write!(f, "benchmark regression: {msg}"),
+            Failure::Internal(msg) => write!(f, "internal error: {msg}"),
+        }
+    }
+}
+
+fn main() -> ExitCode {
     let args = Args::parse();
-    if args.testu64 {
-        test_ilog64();
-        return;
+    // The last panic's message and location, kept for the internal
+    // error below in place of the default hook's report. The checks of
+    // panicking forms swap in their own quiet hook while they run.
+    static PANIC: Mutex<Option<String>> = Mutex::new(None);
+    panic::set_hook(Box::new(|info| {
+        let msg = (info.payload().downcast_ref::<&str>().copied())
+            .or_else(|| info.payload().downcast_ref::<String>().map(|s| s.as_str()))
+            .unwrap_or("a non-string payload");
+        let at = info.location().map_or(String::new(), |l| format!(" at {l}"));
+        *PANIC.lock().unwrap_or_else(|e| e.into_inner()) = Some(format!("panicked{at}: {msg}"));
+    }));
+    // rayon re-raises worker panics on the calling thread, so this
+    // also catches a panic inside a parallel test.
+    let failure = match panic::catch_unwind(AssertUnwindSafe(|| run(args))) {
+        Ok(Ok(())) => return ExitCode::SUCCESS,
+        Ok(Err(failure)) => failure,
+        Err(_) => {
+            let msg = PANIC.lock().unwrap_or_else(|e| e.into_inner()).take();
+            Failure::Internal(msg.unwrap_or_else(|| "panicked".into()))
+        }
+    };
+    eprintln!("{failure}");
+    failure.exit_code()
+}
+
+fn run(args: Args) -> Result<(), Failure> {
+    plugin::load(&args.plugin).map_err(Failure::Usage)?;
+    let mut output = Output {
+        format: args.format.unwrap_or(Format::Markdown),
+        path: args.output,
+        append: args.append,
+    };
+    let filter = Filter::new(&args.filter, args.regex).map_err(Failure::Usage)?;
+    let mut failure = None;
+    let command = args.command.unwrap_or(Command::Bench {
+        suite: None,
+        suite_file: "bench.toml".into(),
+        histogram: None,
+        plot: None,
+        report: None,
+        sampling: suite::Sampling::default(),
+        fail_if_slower_than: None,
+        against: None,
+        db: None,
+        per_core_type: false,
+        isolate: false,
+        compare_widths: false,
+        profile: None,
+        isolated: None,
+    });
+    let report = match command {
+        Command::Bench {
+            suite,
+            suite_file,
+            histogram,
+            plot,
+            report: html_path,
+            mut sampling,
+            fail_if_slower_than,
+            against,
+            db,
+            per_core_type,
+            mut isolate,
+            compare_widths,
+            profile,
+            isolated,
+        } => {
+            if let Some(reference) = against {
+                let report = against::report(&reference).map_err(Failure::Usage)?;
+                return output
+                    .emit(&report)
+                    .map_err(|e| Failure::Internal(format!("writing report: {e}")));
+            }
+            let mut suite = match suite {
+                Some(name) => suite::load_suite(&suite_file, &name).map_err(Failure::Usage)?,
+                None if !filter.is_empty() => Suite::all(),
+                None => Suite::default(),
+            };
+            if let Some(profile) = profile {
+                profile.apply(&mut suite, &mut sampling);
+                // Not again in the processes it starts.
+                isolate |= profile.isolates() && isolated.is_none();
+            }
+            if let Some(histogram) = histogram {
+                suite.distributions = vec![Distribution::Histogram];
+                suite.histogram = Some(histogram);
+            }
+            if let (None, Some(format)) = (args.format, suite.format) {
+                output.format = format;
+            }
+            let filter = match &isolated {
+                Some(name) => isolate::only(name),
+                None => filter,
+            };
+            if compare_widths {
+                let mut distributions: Vec<Distribution> = suite
+                    .distributions
+                    .iter()
+                    .copied()
+                    .filter(|d| widths::DISTRIBUTIONS.contains(d))
+                    .collect();
+                if distributions.is_empty() {
+                    distributions.push(Distribution::Digits);
+                }
+                let samples = sampling.samples.unwrap_or(suite.repetitions.max(3));
+                let report =
+                    widths::report(&distributions, samples, &filter).map_err(Failure::Usage)?;
+                return output
+                    .emit(&report)
+                    .map_err(|e| Failure::Internal(format!("writing report: {e}")));
+            }
+            if per_core_type {
+                let benchmark = || suite::benchmark(&suite, &filter, &sampling);
+                return output
+                    .emit(&hybrid::report(benchmark).map_err(Failure::Usage)?)
+                    .map_err(|e| Failure::Internal(format!("writing report: {e}")));
+            }
+            let baseline = match fail_if_slower_than {
+                Some(threshold) => {
+                    let baseline = suite::load_baseline(&threshold).map_err(Failure::Usage)?;
+                    Some((threshold, baseline))
+                }
+                None => None,
+            };
+            let report = if isolate {
+                isolate::benchmark(&suite, &filter, sampling.max_time)
+            } else {
+                suite::benchmark(&suite, &filter, &sampling)
+            }
+            .map_err(Failure::Usage)?;
+            if let Some(path) = plot {
+                std::fs::write(&path, plot::svg(&report.records()))
+                    .map_err(|e| Failure::Internal(format!("{}: {e}", path.display())))?;
+            }
+            if let Some(path) = db {
+                history::record(&path, &report.records()).map_err(Failure::Internal)?;
+            }
+            if let Some(path) = html_path {
+                std::fs::write(&path, html::page(&report))
+                    .map_err(|e| Failure::Internal(format!("{}: {e}", path.display())))?;
+            }
+            if let Some((threshold, baseline)) = &baseline {
+                let regressions = suite::regressions(&report, baseline, threshold.percent);
+                if !regressions.is_empty() {
+                    failure = Some(Failure::Regression(format!(
+                        "{} of {} results over {}%: {}",
+                        regressions.len(),
+                        report.rows.len(),
+                        threshold.percent,
+                        regressions.join("; ")
+                    )));
+                }
+            }
+            report
+        }
+        Command::BenchMatrix {
+            cpus,
+            feature_sets,
+            mut bench_args,
+        } => {
+            // The filters and plugins apply to every build's benchmark.
+            for pattern in &args.filter {
+                bench_args.extend(["--filter".to_string(), pattern.clone()]);
+            }
+            if args.regex {
+                bench_args.push("--regex".into());
+            }
+            for plugin in &args.plugin {
+                bench_args.extend(["--plugin".to_string(), plugin.clone()]);
+            }
+            matrix::report(&cpus, &feature_sets, &bench_args).map_err(Failure::Usage)?
+        }
+        Command::History { db, machine } => {
+            history::report(&db, machine.as_deref(), &filter).map_err(Failure::Usage)?
+        }
+        Command::Test {
+            u64,
+            codegen,
+            target,
+            permuted,
+            seed,
+            slice: (start, bits),
+            threads,
+        } => {
+            if let Some(threads) = threads {
+                rayon::ThreadPoolBuilder::new()
+                    .num_threads(threads.get())
+                    .build_global()
+                    .map_err(|e| Failure::Internal(format!("starting {threads} threads: {e}")))?;
+            }
+            let permuted = permuted.then_some(Permuted { seed, start, bits });
+            let started = std::time::Instant::now();
+            let checks = if codegen {
+                let functions = disassembled(&[], &target)?;
+                codegen::check_codegen(&functions, &target, &filter)
+            } else if u64 {
+                test_ilog64(&filter, permuted.as_ref())
+            } else {
+                test_ilog(&filter, permuted.as_ref())
+            };
+            // How the work spread over the threads, to stderr beside the
+            // progress lines so that it stays out of the report.
+            let workers = check::workers(started.elapsed().as_secs_f64());
+            eprint!("{}", check::workers_report(&workers).render(Format::Markdown, None, true));
+            failure = verification_failure(&checks);
+            checks_report(&checks)
+        }
+        Command::Verify { seed } => {
+            let checks = verify::verify(&filter, seed);
+            failure = verification_failure(&checks);
+            checks_report(&checks)
+        }
+        Command::Asm { codegen } => {
+            for (f, instructions) in disassembled(&codegen.algo, &codegen.target)? {
+                println!("{}", disasm::annotate(f.name, &codegen.target, &instructions));
+            }
+            return Ok(());
+        }
+        Command::Size { codegen } => {
+            let mut report = Report::new(
+                "size",
+                &[
+                    "function",
+                    "algorithm",
+                    "target",
+                    "instructions",
+                    "fast_path",
+                    "bytes",
+                    "data_bytes",
+                    "conditional_branches",
+                    "calls",
+                ],
+            );
+            for (f, instructions) in disassembled(&codegen.algo, &codegen.target)? {
+                report.push(&disasm::code_size(f, &codegen.target, &instructions));
+            }
+            report
+        }
+        Command::Cross { algo, targets } => cross_report(&algo, &targets)?,
+        Command::Mca { codegen, cpu } => {
+            let functions = disassembled(&codegen.algo, &codegen.target)?;
+            mca::report(&functions, &codegen.target, cpu.as_deref()).map_err(Failure::Usage)?
+        }
+        Command::Latency { codegen, uarch } => {
+            let functions = disassembled(&codegen.algo, &codegen.target)?;
+            latency::report(&functions, &codegen.target, &uarch).map_err(Failure::Usage)?
+        }
+        Command::Corrections { distributions } => {
+            corrections::report(&distributions, &filter).map_err(Failure::Usage)?
+        }
+        Command::Guesses { distributions } => {
+            guesses::report(&distributions, &filter).map_err(Failure::Usage)?
+        }
+        Command::Smt {
+            u64,
+            distribution,
+            competitor,
+            cpus,
+        } => {
+            let width = if u64 { Width::U64 } else { Width::U32 };
+            smt::report(width, distribution, &competitor, cpus, &filter).map_err(Failure::Usage)?
+        }
+        Command::Explain { value, u64 } => {
+            let width = if u64 { Width::U64 } else { Width::U32 };
+            explain::report(width, value).map_err(Failure::Usage)?
+        }
+        Command::Tail {
+            u64,
+            distribution,
+            batch,
+            batches,
+        } => {
+            let width = if u64 { Width::U64 } else { Width::U32 };
+            tail::report(width, distribution, batch, batches, &filter).map_err(Failure::Usage)?
+        }
+        Command::Csv {
+            distribution,
+            fields,
+            samples,
+        } => csv::report(distribution, fields, samples).map_err(Failure::Usage)?,
+        Command::Display {
+            distribution,
+            samples,
+        } => display::report(distribution, samples).map_err(Failure::Usage)?,
+        Command::Timing {
+            distribution,
+            batch,
+            batches,
+            threshold,
+        } => {
+            let (report, leaks) = timing::report(distribution, batch, batches, threshold, &filter)
+                .map_err(Failure::Usage)?;
+            if !leaks.is_empty() {
+                failure = Some(Failure::Verification(format!(
+                    "timing depends on the input: {}",
+                    leaks.join(", ")
+                )));
+            }
+            report
+        }
+        Command::Multiversion {
+            u64,
+            distribution,
+            samples,
+        } => {
+            let width = if u64 { Width::U64 } else { Width::U32 };
+            multiversion::report(width, distribution, samples).map_err(Failure::Usage)?
+        }
+        Command::Shader {
+            language,
+            algo,
+            name,
+        } => {
+            print!("{}", shader::emit(language, algo, &name).map_err(Failure::Usage)?);
+            return Ok(());
+        }
+        Command::Codegen { language, algo } => {
+            let algos = if algo.is_empty() { csource::Algo::ALL.to_vec() } else { algo };
+            print!("{}", csource::emit(language, &algos));
+            return Ok(());
+        }
+        Command::Table {
+            width,
+            base,
+            language,
+        } => {
+            print!("{}", table::emit(language, width, base).map_err(Failure::Usage)?);
+            return Ok(());
+        }
+        Command::Completions { shell } => {
+            clap_complete::generate(shell, &mut Args::command(), "ilog", &mut std::io::stdout());
+            return Ok(());
+        }
+        Command::Man => {
+            return clap_mangen::Man::new(Args::command())
+                .render(&mut std::io::stdout())
+                .map_err(|e| Failure::Internal(format!("writing man page: {e}")));
+        }
+    };
+    output
+        .emit(&report)
+        .map_err(|e| Failure::Internal(format!("writing report: {e}")))?;
+    failure.map_or(Ok(()), Err)
+}
+
+// The machine code of the functions `codegen` asks for.
+fn disassembled(
+    names: &[String],
+    target: &str,
+) -> Result<Vec<(&'static disasm::Function, Vec<disasm::Instruction>)>, Failure> {
+    if let Some(name) = names.iter().find(|n| !disasm::FUNCTIONS.iter().any(|f| f.is(n))) {
+        let known: Vec<&str> = disasm::FUNCTIONS.iter().map(|f| f.name).collect();
+        return Err(Failure::Usage(format!(
+            "unknown function {name:?}; expected one of {}",
+            known.join(", ")
+        )));
+    }
+    let functions: Vec<&disasm::Function> = disasm::FUNCTIONS
+        .iter()
+        .filter(|f| names.is_empty() || names.iter().any(|n| f.is(n)))
+        .collect();
+    let rlib = disasm::build_probe(target).map_err(Failure::Usage)?;
+    let symbols = disasm::extract(&rlib, &functions).map_err(Failure::Internal)?;
+    let mut out = Vec::new();
+    for (f, symbol) in functions.into_iter().zip(symbols) {
+        let Some(symbol) = symbol else {
+            // Only an explicitly requested function is an error.
+            if !names.is_empty() {
+                return Err(Failure::Usage(format!("{} is not built for {target}", f.name)));
+            }
+            continue;
+        };
+        out.push((f, disasm::disassemble(target, &symbol).map_err(Failure::Usage)?));
     }
-    if args.test {
-        test_ilog();
+    Ok(out)
+}
+
+// One row per function, one column per target, each cell giving
+// the instruction count (and that of the fast path, if shorter) and
+// the number of conditional branches. Functions not built for a
+// target (the asm ones) have an empty cell.
+fn cross_report(names: &[String], targets: &[String]) -> Result<Report, Failure> {
+    let mut columns = vec!["function"];
+    columns.extend(targets.iter().map(String::as_str));
+    let mut report = Report::new("cross-target codegen", &columns);
+    let mut rows: Vec<(&str, serde_json::Map<String, serde_json::Value>)> = Vec::new();
+    for target in targets {
+        for (f, instructions) in disassembled(names, target)? {
+            let size = disasm::code_size(f, target, &instructions);
+            let mut cell = format!("{} ins", size.instructions);
+            if size.fast_path < size.instructions {
+                cell += &format!(" ({} fast)", size.fast_path);
+            }
+            cell += &match size.conditional_branches {
+                0 => ", branch-free".to_string(),
+                1 => ", 1 branch".to_string(),
+                n => format!(", {n} branches"),
+            };
+            let row = match rows.iter_mut().find(|(name, _)| *name == f.name) {
+                Some((_, row)) => row,
+                None => {
+                    rows.push((f.name, serde_json::Map::new()));
+                    &mut rows.last_mut().unwrap().1
+                }
+            };
+            row.insert(target.clone(), cell.into());
+        }
+    }
+    // Ordered as in disasm::FUNCTIONS, whichever target saw them first.
+    rows.sort_by_key(|(name, _)| disasm::FUNCTIONS.iter().position(|f| f.name == *name));
+    for (name, cells) in rows {
+        let mut row = serde_json::Map::new();
+        row.insert("function".into(), name.into());
+        for target in targets {
+            let cell = cells.get(target).cloned().unwrap_or_default();
+            row.insert(target.clone(), cell);
+        }
+        report.push(&row);
+    }
+    Ok(report)
+}
+
+fn verification_failure(checks: &[CheckResult]) -> Option<Failure> {
+    let failed: Vec<&CheckResult> = checks.iter().filter(|c| !c.passed()).collect();
+    let first = failed.first()?;
+    let mut msg = format!("{} of {} checks failed", failed.len(), checks.len());
+    if let Some(ce) = &first.counterexample {
+        msg += &format!(
+            "; first: {} {}({}) = {}, expected {}",
+            first.test, first.algorithm, ce.input, ce.actual, ce.expected
+        );
+    }
+    Some(Failure::Verification(msg))
+}
+
+fn wanted(filter: &Filter, case: &str, algorithm: &Algorithm) -> bool {
+    filter.matches(&[case, algorithm.name, &algorithm.qualified_name()])
+}
+
+/// `test --permuted`: the seed of the order, and the u64 slice
+/// `start..start + 2^bits` to check in place of random values.
+struct Permuted {
+    seed: u64,
+    start: u64,
+    bits: u32,
+}
+
+// 1..=u32::MAX, in order or permuted, with its description.
+fn u32_range(permuted: Option<&Permuted>) -> (String, impl ParallelIterator<Item = u64>) {
+    match u32_order(permuted) {
+        (range, None) => (range, Either::Left((1..=u32::MAX).into_par_iter().map(|x| x as u64))),
+        (range, Some(order)) => (
+            range,
+            Either::Right(
+                (0..order.size())
+                    .into_par_iter()
+                    .map(move |i| order.at(i))
+                    .filter(|&x| x != 0),
+            ),
+        ),
+    }
+}
+
+// The order of a `sweep` of 1..=u32::MAX, with its description.
+fn u32_order(permuted: Option<&Permuted>) -> (String, Option<Permutation>) {
+    match permuted {
+        None => ("1..=u32::MAX".to_string(), None),
+        Some(p) => (
+            format!("1..=u32::MAX, permuted with seed {}", p.seed),
+            Some(Permutation::new(32, p.seed)),
+        ),
+    }
+}
+
+fn test_ilog(filter: &Filter, permuted: Option<&Permuted>) -> Vec<CheckResult> {
+    let mut checks = Vec::new();
+    for a in registry::of_width(Width::U32) {
+        if !wanted(filter, "exhaustive u32", a) {
+            continue;
+        }
+        let (range, order) = u32_order(permuted);
+        // h/t @steffahn for suggesting using rayon to parallelize. goes brrr.
+        checks.push(sweep(
+            "exhaustive u32",
+            &a.qualified_name(),
+            &range,
+            order.as_ref(),
+            a.eval_all,
+        ));
+    }
+    if filter.matches(&[FUSED]) {
+        checks.push(check(
+            FUSED,
+            "ilog2_and_ilog10_u32",
+            "2^k - 1, 2^k, 10^k - 1, 10^k",
+            power_boundaries(u32::MAX as u64).into_par_iter(),
+            |x| pack_logs(ilog::ilog2_and_ilog10_u32(x as u32)),
+            |x| pack_logs((x.ilog2(), x.ilog10())),
+        ));
+    }
+    if filter.matches(&[CORRECTION_FLAG]) {
+        checks.push(check(
+            CORRECTION_FLAG,
+            "ilog10_with_flag",
+            "2^k - 1, 2^k, 10^k - 1, 10^k",
+            power_boundaries(u32::MAX as u64).into_par_iter(),
+            |x| pack_flag(ilog::ilog10_with_flag(x as u32)),
+            |x| pack_flag(reference_with_flag(x, 9, 5)),
+        ));
+    }
+    if filter.matches(&[FUSED]) {
+        checks.push(check(
+            FUSED,
+            "digits_and_pow10_u32",
+            "0, 2^k - 1, 2^k, 10^k - 1, 10^k",
+            std::iter::once(0)
+                .chain(power_boundaries(u32::MAX as u64))
+                .collect::<Vec<u64>>()
+                .into_par_iter(),
+            |x| digits_if_pow10(ilog::digits_and_pow10_u32(x as u32)),
+            reference_digits,
+        ));
+    }
+    if filter.matches(&[LEADING]) {
+        let (range, inputs) = u32_range(permuted);
+        checks.push(check(
+            LEADING,
+            "leading_digit_u32",
+            &range,
+            inputs,
+            |x| ilog::leading_digit_u32(x as u32),
+            |x| reference_leading(x, 1) as u32,
+        ));
+        let values = leading_boundaries(u32::MAX as u64);
+        for n in 0..=11 {
+            checks.push(check(
+                LEADING,
+                &format!("leading_n_digits_u32(x, {n})"),
+                LEADING_RANGE,
+                values.par_iter().copied(),
+                |x| ilog::leading_n_digits_u32(x as u32, n),
+                |x| reference_leading(x, n) as u32,
+            ));
+        }
+    }
+    if filter.matches(&[SI]) {
+        let (range, inputs) = u32_range(permuted);
+        checks.push(check(
+            SI,
+            "ilog1000_u32",
+            &range,
+            inputs,
+            |x| ilog::ilog1000_u32(x as u32),
+            |x| x.ilog10() / 3,
+        ));
+        checks.push(check(
+            SI,
+            "si_scale_u32",
+            LEADING_RANGE,
+            leading_boundaries(u32::MAX as u64).into_par_iter(),
+            |x| pack_si(ilog::si_scale_u32(x as u32)),
+            |x| pack_si(reference_si(x)),
+        ));
+    }
+    // The vector kernels a block at a time; the boundaries then go
+    // through every lane, as rotations, and a lane mix-up shows in the
+    // packed result.
+    if filter.matches(&[BATCH]) {
+        let (range, order) = u32_order(permuted);
+        checks.push(sweep(
+            BATCH,
+            "digits_u32x4",
+            &range,
+            order.as_ref(),
+            |values, out| {
+                for (x, o) in values.chunks(4).zip(out.chunks_mut(4)) {
+                    let lanes = core::array::from_fn(|i| x.get(i).copied().unwrap_or(1));
+                    let digits = ilog::digits_u32x4(lanes);
+                    for (o, d) in o.iter_mut().zip(digits) {
+                        *o = d - 1;
+                    }
+                }
+            },
+        ));
+        let lanes = |x: u64| [0, 8, 16, 24].map(|r| (x as u32).rotate_left(r));
+        checks.push(check(
+            BATCH,
+            "digits_u32x4",
+            "0, 2^k - 1, 2^k, 10^k - 1, 10^k and rotations",
+            std::iter::once(0)
+                .chain(power_boundaries(u32::MAX as u64))
+                .collect::<Vec<u64>>()
+                .into_par_iter(),
+            |x| pack_digits(&ilog::digits_u32x4(lanes(x))),
+            |x| pack_digits(&lanes(x).map(|l| reference_digits(l as u64))),
+        ));
+    }
+    for (kernel, f) in ilog::batch::u32_kernels() {
+        if !filter.matches(&[BATCH]) {
+            continue;
+        }
+        let (range, order) = u32_order(permuted);
+        let name = format!("ilog10_u32_slice ({kernel})");
+        checks.push(sweep(BATCH, &name, &range, order.as_ref(), f));
+        // Nineteen values per call: a full 16-lane vector and a tail.
+        let values = |x: u64| core::array::from_fn::<u32, 19, _>(|i| (x as u32).rotate_left(i as u32));
+        checks.push(check(
+            BATCH,
+            &name,
+            "0, 2^k - 1, 2^k, 10^k - 1, 10^k and rotations",
+            std::iter::once(0)
+                .chain(power_boundaries(u32::MAX as u64))
+                .collect::<Vec<u64>>()
+                .into_par_iter(),
+            move |x| {
+                let mut out = [0; 19];
+                f(&values(x), &mut out);
+                (out == values(x).map(|v| (v | 1).ilog10())) as u32
+            },
+            |_| 1,
+        ));
+    }
+    // The version the dispatcher didn't pick, too, if this CPU can
+    // run it.
+    for choice in dispatch::Choice::ALL {
+        if !choice.supported() || !filter.matches(&[DISPATCH, choice.name()]) {
+            continue;
+        }
+        let (range, order) = u32_order(permuted);
+        let picked = if choice == dispatch::choice() { "picked" } else { "not picked" };
+        checks.push(sweep(
+            DISPATCH,
+            &format!("{} ({picked} here)", choice.name()),
+            &range,
+            order.as_ref(),
+            each(|x| choice.ilog10(NonZeroU32::new(x).unwrap())),
+        ));
+    }
+    // Both builds of mul, where the lzcnt one's guess is written apart.
+    for build in dispatch::Build::ALL {
+        let name = format!("mul {}", build.name());
+        if !build.supported() || !filter.matches(&[DISPATCH, &name]) {
+            continue;
+        }
+        let (range, order) = u32_order(permuted);
+        let picked = if build == dispatch::build() { "picked" } else { "not picked" };
+        checks.push(sweep(
+            DISPATCH,
+            &format!("{name} ({picked} here)"),
+            &range,
+            order.as_ref(),
+            each(|x| build.ilog10_u32(NonZeroU32::new(x).unwrap())),
+        ));
+    }
+    for algo in shader::Algo::ALL {
+        if !filter.matches(&[SHADER, algo.name()]) {
+            continue;
+        }
+        let (range, order) = u32_order(permuted);
+        checks.push(sweep(
+            SHADER,
+            algo.name(),
+            &range,
+            order.as_ref(),
+            each(|x| shader::eval(algo, x)),
+        ));
+    }
+    // The committed C is what `codegen c` prints now; with `c-reference`
+    // the c_gen_ algorithms among the registry's check the C itself.
+    if filter.matches(&[GENERATED_C]) {
+        checks.push(check(
+            GENERATED_C,
+            "csrc/generated.c",
+            "against `ilog codegen c`",
+            rayon::iter::once(0),
+            |_| (csource::emit(csource::Language::C, &csource::Algo::ALL) == csource::GENERATED) as u32,
+            |_| 1,
+        ));
+    }
+    if filter.matches(&[COMPAT]) {
+        checks.extend(test_core_compat());
+    }
+    if filter.matches(&[PARTS]) {
+        checks.extend(test_parts());
+    }
+    if filter.matches(&[GENERIC]) {
+        checks.extend(test_generic());
+    }
+    if filter.matches(&[PRELUDE]) {
+        checks.extend(test_prelude());
+    }
+    checks
+}
+
+const DISPATCH: &str = "dispatch";
+
+const SHADER: &str = "shader";
+
+const GENERATED_C: &str = "generated c";
+
+// The nonzero 2^k - 1, 2^k, 10^k - 1 and 10^k up to `max`.
+fn power_boundaries(max: u64) -> Vec<u64> {
+    let mut values: Vec<u64> = (0..64)
+        // 2^k, and 2^(k + 1) - 1 written so as not to overflow
+        .flat_map(|k| [1u64 << k, (1u64 << k) | ((1u64 << k) - 1)])
+        .chain((0..20).flat_map(|k| [10u64.pow(k), 10u64.pow(k) - 1]))
+        .filter(|&x| x != 0 && x <= max)
+        .collect();
+    values.sort_unstable();
+    values.dedup();
+    values
+}
+
+// Both logs change only at a power of two or of ten, so the
+// boundaries cover every case.
+const FUSED: &str = "fused";
+
+fn pack_logs((log2, log10): (u32, u32)) -> u32 {
+    log2 << 8 | log10
+}
+
+// Whether the correction fired is whether ilog10 is past the guess
+// ilog2 * m >> s.
+const CORRECTION_FLAG: &str = "correction flag";
+
+fn reference_with_flag(x: u64, m: u32, s: u32) -> (u32, bool) {
+    (x.ilog10(), x.ilog10() > (x.ilog2() * m) >> s)
+}
+
+fn pack_flag((log, flag): (u32, bool)) -> u32 {
+    log << 1 | flag as u32
+}
+
+// The digit count, if the power of ten that came with it matches.
+fn digits_if_pow10((digits, pow10): (u32, impl Into<u64>)) -> u32 {
+    if pow10.into() == 10u64.pow(digits - 1) {
+        digits
     } else {
-        benchmark_ilog();
+        u32::MAX
     }
 }
 
-fn test_ilog() {
-    // h/t @steffahn for suggesting using rayon to parallelize. goes brrr.
-    let start = std::time::Instant::now();
-    (1..=u32::MAX)
-        .into_par_iter()
-        .for_each(|x| assert_eq!(ilog10(x), x.ilog10()));
-    let elapsed = start.elapsed();
-    println!(
-        "passed exhaustive u32 test in {:.2} seconds",
-        elapsed.as_secs_f64()
-    );
+// A prefix of the digits only changes at a multiple of a power of
+// ten, and the reciprocals are least exact at the top of the range;
+// the boundaries and the maximum cover both.
+const LEADING: &str = "leading";
+
+const LEADING_RANGE: &str = "0, m * 10^k - 1, m * 10^k for m < 1000, 2^k - 1, 2^k, max";
+
+fn leading_boundaries(max: u64) -> Vec<u64> {
+    let mut values: Vec<u64> = (0..20)
+        .flat_map(|k| (1..1000).filter_map(move |m| 10u64.pow(k).checked_mul(m)))
+        .flat_map(|x| [x - 1, x])
+        .chain(power_boundaries(max))
+        .chain([0, max])
+        .filter(|&x| x <= max)
+        .collect();
+    values.sort_unstable();
+    values.dedup();
+    values
 }
 
-// the warren mapping follows a slightly unintuitive invariant:
-// The warren map value must be correctable to the real log10 value
-// with the addition of at most 1.
-fn test_warren_64bit() {
+fn reference_leading(x: u64, n: u32) -> u64 {
+    let cut = reference_digits(x).saturating_sub(n);
+    10u64.checked_pow(cut).map_or(0, |p| x / p)
+}
+
+// Both change only at a power of ten; si_scale's values, at multiples
+// of one as well.
+const SI: &str = "si";
+
+fn reference_si(x: u64) -> (u64, u32) {
+    let k = x.checked_ilog10().unwrap_or(0) / 3;
+    (x / 1000u64.pow(k), k)
+}
+
+// The scaled value is below 1000, so 10 bits hold it.
+fn pack_si((value, k): (impl Into<u64>, u32)) -> u32 {
+    (value.into() as u32) << 4 | k
+}
+
+// Read back, the text has to be the value to three significant
+// figures, with the largest prefix that leaves a whole number in
+// front, and agree with the parts it was made from.
+const HUMANIZE: &str = "humanize";
+
+fn humanized_ok(x: u64, base: humanize::Base) -> bool {
+    let h = humanize::bytes(x, base);
+    let text = h.to_string();
+    let Some((number, unit)) = text.split_once(' ') else {
+        return false;
+    };
+    let k = h.power();
+    if unit != format!("{}B", base.prefix(k)) {
+        return false;
+    }
+    let scale = match base {
+        humanize::Base::Decimal => 1000f64,
+        humanize::Base::Binary => 1024f64,
+    };
+    let Ok(shown) = number.parse::<f64>() else {
+        return false;
+    };
+    let figures = number.replace('.', "").trim_matches('0').len();
+    let value = shown * scale.powi(k as i32);
+    if k == 0 {
+        return value == x as f64 && h.hundredths() == x * 100;
+    }
+    (h.hundredths() as f64 - shown * 100.0).abs() < 1e-6
+        && figures <= 3
+        && (1.0..scale).contains(&shown)
+        && (value - x as f64).abs() <= 0.0051 * x as f64
+}
+
+const PRODUCT: &str = "product";
+
+const RATIO: &str = "ratio";
+
+// Plain, and each way a format spec sends the text through
+// pad_integral instead.
+const DISPLAY: &str = "display";
+const DISPLAY_SPECS: &str = "with {}, {:+}, {:>24}, {:024}";
+
+fn displays_alike(ours: impl std::fmt::Display, std: impl std::fmt::Display) -> bool {
+    format!("{ours}") == format!("{std}")
+        && format!("{ours:+}") == format!("{std:+}")
+        && format!("{ours:>24}") == format!("{std:>24}")
+        && format!("{ours:024}") == format!("{std:024}")
+}
+
+// The greatest k with 10^k <= n / d, counting down from the most a
+// ratio of u64s can reach.
+fn reference_ilog10_ratio(n: u64, d: u64) -> i32 {
+    let (n, d) = (n as u128, d as u128);
+    (-20..=19)
+        .rev()
+        .find(|&k: &i32| match k {
+            0.. => d * 10u128.pow(k as u32) <= n,
+            _ => d <= n * 10u128.pow(k.unsigned_abs()),
+        })
+        .unwrap()
+}
+
+// The total is at most 21 more than the payload, so the difference
+// fits, with u32::MAX for an overflow. A carry into a longer prefix is
+// at a payload just below a power of ten.
+const PREFIX: &str = "prefix";
+
+fn pack_len(len: Option<u64>, payload_len: u64) -> u32 {
+    len.map_or(u32::MAX, |len| (len - payload_len) as u32)
+}
+
+// The first total past the payload that counts its own digits.
+fn reference_prefixed_len(payload_len: u64) -> Option<u64> {
+    (1..=21)
+        .filter_map(|digits| payload_len.checked_add(digits))
+        .find(|&len| reference_digits(len) as u64 + payload_len == len)
+}
+
+// Both odd and even lengths, for the single value left over after the
+// pairs.
+const TOTAL: &str = "total";
+
+// The count changes only at a power of the radix; every radix is
+// checked on the boundaries of all of them.
+const RADIX: &str = "radix";
+
+fn radix_boundaries() -> Vec<u64> {
+    let mut values: Vec<u64> = (2..=36u64)
+        .flat_map(|r| (0..64).map_while(move |k| r.checked_pow(k)))
+        .flat_map(|p| [p - 1, p])
+        .chain(power_boundaries(u64::MAX))
+        .chain([u64::MAX])
+        .collect();
+    values.sort_unstable();
+    values.dedup();
+    values
+}
+
+fn reference_digits_in_radix(mut x: u64, radix: u32) -> u32 {
+    let mut digits = 1;
+    while x >= radix as u64 {
+        x /= radix as u64;
+        digits += 1;
+    }
+    digits
+}
+
+// The multiples of powers of ten among the leading boundaries have
+// every number of trailing zeros.
+const DIGIT_ITER: &str = "decimal digits";
+
+const MAX_LEN: &str = "max decimal len";
+
+const DIGITS_U128: &str = "u128 digits";
+
+const HISTOGRAM: &str = "decade histogram";
+
+const BUCKET_BOUNDS: &str = "bucket bounds";
+
+const SCI: &str = "sci";
+
+const TABLES: &str = "generated tables";
+
+// For a `bits`-bit type and each base from 2 to 36: the powers of the
+// base and of two, one either side of each, and the maximum.
+fn table_boundaries(bits: u32) -> Vec<(u32, u128)> {
+    let max = u128::MAX >> (128 - bits);
+    let mut cases = Vec::new();
+    for base in 2..=36u32 {
+        let mut powers: Vec<u128> = (0..bits).map(|k| 1 << k).collect();
+        powers.extend(std::iter::successors(Some(1u128), |p| p.checked_mul(base as u128)));
+        let mut values: Vec<u128> = powers
+            .into_iter()
+            .filter(|&p| p <= max)
+            .flat_map(|p| [p - 1, p, p.saturating_add(1)])
+            .chain([max])
+            .filter(|&x| x != 0 && x <= max)
+            .collect();
+        values.sort_unstable();
+        values.dedup();
+        cases.extend(values.into_iter().map(|x| (base, x)));
+    }
+    cases
+}
+
+// The ilog of `x` by the tables, with `guess` from its ilog2.
+fn table_ilog(base: u32, bits: u32, x: u128, guess: impl Fn(u32) -> u32) -> u32 {
+    let g = guess(127 - x.leading_zeros());
+    g + (x > ilog::tables::threshold(base, bits, g)) as u32
+}
+
+fn reference_sci(x: u64) -> (u64, u32) {
+    let mut m = x;
+    while m != 0 && m.is_multiple_of(10) {
+        m /= 10;
+    }
+    (m, x.checked_ilog10().unwrap_or(0))
+}
+
+// Ties are m * 10^k with m ending in 5, among the leading boundaries.
+const LOG10_ROUNDING: &str = "log10 rounding";
+
+// The power boundaries and the integers around each geometric midpoint
+// sqrt(10^(2k + 1)).
+fn log10_rounding_boundaries() -> Vec<u64> {
+    let midpoints = (0..19).map(|k| 10u128.pow(2 * k + 1).isqrt() as u64);
+    let mut values: Vec<u64> = midpoints
+        .flat_map(|m| m - 2..=m + 2)
+        .chain(power_boundaries(u64::MAX))
+        .filter(|&x| x != 0)
+        .collect();
+    values.sort_unstable();
+    values.dedup();
+    values
+}
+
+fn pack_rounded_logs(floor: u32, ceil: u32, nearest: u32) -> u32 {
+    floor << 16 | ceil << 8 | nearest
+}
+
+// Ceil is the least k with 10^k >= x; nearest rounds up when x is
+// further above 10^floor than below 10^(floor + 1) as a ratio, that
+// is when x * x > 10^floor * 10^(floor + 1).
+fn reference_log10_rounding(x: u64) -> u32 {
+    let floor = x.ilog10();
+    let ceil = (0..=20).find(|&k| 10u128.pow(k) >= x as u128).unwrap();
+    let square = x as u128 * x as u128;
+    let up = 10u128.checked_pow(2 * floor + 1).is_some_and(|t| square > t);
+    pack_rounded_logs(floor, ceil, floor + up as u32)
+}
+
+const ROUND: &str = "round";
+
+fn reference_round(x: u64, figs: u32, rounding: ilog::Rounding) -> Option<u64> {
+    if figs == 0 {
+        return Some(0);
+    }
+    let Some(cut) = reference_digits(x).checked_sub(figs) else {
+        return Some(x);
+    };
+    let (x, p) = (x as u128, 10u128.pow(cut));
+    let (q, r) = (x / p, x % p);
+    let up = 2 * r > p
+        || (2 * r == p && (rounding == ilog::Rounding::HalfUp || q % 2 == 1));
+    u64::try_from((q + up as u128) * p).ok()
+}
+
+const BATCH: &str = "batch";
+
+// Digit counts of up to four lanes, four bits each.
+fn pack_digits(digits: &[u32]) -> u32 {
+    digits.iter().rev().fold(0, |packed, &d| packed << 4 | d)
+}
+
+fn reference_digits(x: u64) -> u32 {
+    x.checked_ilog10().map_or(1, |l| l + 1)
+}
+
+const PARTS: &str = "parts";
+
+// Each guess composed with the correction. A guess only changes with
+// the leading zero count, so between powers of two the composition is
+// a single step at a power of ten; both sides of every power of two
+// and of ten cover every case.
+fn test_parts() -> Vec<CheckResult> {
+    let u32_guesses = [
+        ("guess_ilog10_u32", parts::guess_ilog10_u32 as fn(u32) -> u32),
+        ("guess_ilog10_u32_popcount", parts::guess_ilog10_u32_popcount),
+        ("guess_ilog10_u32_table", parts::guess_ilog10_u32_table),
+        ("mcu::guess_ilog10_u32", mcu::guess_ilog10_u32),
+    ];
+    let mut checks: Vec<CheckResult> = u32_guesses
+        .into_iter()
+        .map(|(name, guess)| {
+            check(
+                PARTS,
+                &format!("{name} + correct"),
+                "2^k - 1, 2^k, 10^k - 1, 10^k",
+                power_boundaries(u32::MAX as u64).into_par_iter(),
+                |x| parts::correct(guess((x as u32).leading_zeros()), x as u32),
+                |x| x.ilog10(),
+            )
+        })
+        .collect();
+    checks.push(check(
+        PARTS,
+        "guess_ilog10_u64 + correct_u64",
+        "2^k - 1, 2^k, 10^k - 1, 10^k",
+        power_boundaries(u64::MAX).into_par_iter(),
+        |x| parts::correct_u64(parts::guess_ilog10_u64(x.leading_zeros()), x),
+        |x| x.ilog10(),
+    ));
+    checks
+}
+
+const GENERIC: &str = "generic";
+
+// fast_ilog10 at each primitive width against the type's own ilog10,
+// including the panics for 0 and negative values: the 8- and 16-bit
+// types exhaustively, the wider ones within 2 of each threshold of
+// `boundary::near_thresholds_u128` and at the extremes.
+fn test_generic() -> Vec<CheckResult> {
+    // The values near the thresholds up to `max`, then `max`.
+    fn boundaries(max: u128) -> Vec<u128> {
+        let mut values: Vec<u128> =
+            ilog::boundary::near_thresholds_u128(2).filter(|&x| x <= max).collect();
+        values.push(max);
+        values
+    }
+    macro_rules! exhaustive {
+        ($t:ty) => {
+            check(
+                GENERIC,
+                concat!("fast_ilog10::<", stringify!($t), ">"),
+                concat!(stringify!($t), "::MIN..=", stringify!($t), "::MAX"),
+                (0..1u64 << <$t>::BITS).into_par_iter(),
+                |x| outcome(|| ilog::fast_ilog10(x as $t)),
+                |x| outcome(|| (x as $t).ilog10()),
+            )
+        };
+    }
+    macro_rules! boundary {
+        ($t:ty) => {{
+            let mut values: Vec<$t> = boundaries(<$t>::MAX as u128)
+                .into_iter()
+                .map(|x| x as $t)
+                .collect();
+            let signed = <$t>::MIN != 0;
+            if signed {
+                let negated: Vec<$t> = values.iter().map(|&x| x.wrapping_neg()).collect();
+                values.extend(negated);
+                values.push(<$t>::MIN);
+            }
+            let values = &values;
+            check(
+                GENERIC,
+                concat!("fast_ilog10::<", stringify!($t), ">"),
+                if signed {
+                    "±(10^k, 2^k, 2^j 10^k, j ≤ 3, ± 2), MIN, MAX"
+                } else {
+                    "10^k, 2^k, 2^j 10^k, j ≤ 3, ± 2, MAX"
+                },
+                (0..values.len() as u64).into_par_iter(),
+                |i| outcome(|| ilog::fast_ilog10(values[i as usize])),
+                |i| outcome(|| values[i as usize].ilog10()),
+            )
+        }};
+    }
+    // As in test_core_compat, keep the expected panics quiet.
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let checks = vec![
+        exhaustive!(u8),
+        exhaustive!(i8),
+        exhaustive!(u16),
+        exhaustive!(i16),
+        boundary!(u32),
+        boundary!(i32),
+        boundary!(u64),
+        boundary!(i64),
+        boundary!(u128),
+        boundary!(i128),
+        boundary!(usize),
+        boundary!(isize),
+    ];
+    panic::set_hook(hook);
+    checks
+}
+
+const PRELUDE: &str = "prelude";
+
+// Each of the prelude's names against the registry entry it is named
+// for, `ilog10_u64_mul_pow` against `u64/mul_pow`, so that the two
+// can't drift apart; a name with no entry fails.
+fn test_prelude() -> Vec<CheckResult> {
+    use ilog::prelude::*;
+    type Named = (&'static str, fn(u64) -> u32);
+    const FUNCTIONS: &[Named] = &[
+        // SAFETY: the boundaries are nonzero.
+        ("ilog10_u32_popcount", |x| unsafe { ilog10_u32_popcount(x as u32) }),
+        ("ilog10_u32_mul", |x| ilog10_u32_mul(x as u32)),
+        ("ilog10_u32_mul_alt", |x| ilog10_u32_mul_alt(x as u32)),
+        ("ilog10_u32_mul_loop", |x| ilog10_u32_mul_loop(x as u32)),
+        ("ilog10_u32_mul_packed", |x| ilog10_u32_mul_packed(x as u32)),
+        ("ilog10_u32_mul_pow", |x| ilog10_u32_mul_pow(x as u32)),
+        ("ilog10_u32_mul_pow4", |x| ilog10_u32_mul_pow4(x as u32)),
+        ("ilog10_u32_table", |x| ilog10_u32_table(x as u32)),
+        ("ilog10_u32_swar", |x| ilog10_u32_swar(x as u32)),
+        ("ilog10_u32_stdlib", |x| ilog10_u32_stdlib(x as u32)),
+        ("ilog10_u32_debruijn", |x| ilog10_u32_debruijn(x as u32)),
+        ("ilog10_u32_bytes", |x| ilog10_u32_bytes(x as u32)),
+        ("ilog10_u32_constant_time", |x| ilog10_u32_constant_time(x as u32)),
+        ("ilog10_u32_f32", |x| ilog10_u32_f32(x as u32)),
+        ("ilog10_u32_f64", |x| ilog10_u32_f64(x as u32)),
+        ("ilog10_u32_ladder", |x| ilog10_u32_ladder(x as u32)),
+        ("ilog10_u32_slice", |x| {
+            let mut out = [0];
+            ilog10_u32_slice(&[x as u32], &mut out);
+            out[0]
+        }),
+        ("ilog10_u64_mul", ilog10_u64_mul),
+        ("ilog10_u64_mul20", ilog10_u64_mul20),
+        ("ilog10_u64_mul_loop", ilog10_u64_mul_loop),
+        ("ilog10_u64_mul_packed", ilog10_u64_mul_packed),
+        ("ilog10_u64_mul_pow", ilog10_u64_mul_pow),
+        ("ilog10_u64_mul_pow4", ilog10_u64_mul_pow4),
+        ("ilog10_u64_swar", ilog10_u64_swar),
+        ("ilog10_u64_debruijn", ilog10_u64_debruijn),
+        ("ilog10_u64_bytes", ilog10_u64_bytes),
+        ("ilog10_u64_constant_time", ilog10_u64_constant_time),
+        ("ilog10_u64_f64", ilog10_u64_f64),
+        ("ilog10_u64_split", ilog10_u64_split),
+        ("ilog10_u64_ladder", ilog10_u64_ladder),
+        ("ilog10_u64_slice", |x| {
+            let mut out = [0];
+            ilog10_u64_slice(&[x], &mut out);
+            out[0]
+        }),
+    ];
+    FUNCTIONS
+        .iter()
+        .map(|&(name, f)| {
+            let (width, max) = if name.starts_with("ilog10_u32_") {
+                (Width::U32, u32::MAX as u64)
+            } else {
+                (Width::U64, u64::MAX)
+            };
+            let short = &name["ilog10_u32_".len()..];
+            let entry = registry::of_width(width).find(|a| a.name == short);
+            let range = match entry {
+                Some(a) => format!("against {}", a.qualified_name()),
+                None => "no registry entry".to_string(),
+            };
+            check(
+                PRELUDE,
+                name,
+                &range,
+                power_boundaries(max).into_par_iter(),
+                f,
+                |x| entry.map_or(u32::MAX, |a| (a.eval)(x)),
+            )
+        })
+        .collect()
+}
+
+const COMPAT: &str = "core compat";
+
+// core_compat against the standard library, including the handling
+// of 0 that the registry entries leave out.
+fn test_core_compat() -> Vec<CheckResult> {
+    let mut boundaries: Vec<u64> = (0..20)
+        .flat_map(|k| [10u64.pow(k) - 1, 10u64.pow(k)])
+        .collect();
+    boundaries.push(u64::MAX);
+    let mut checks = vec![
+        check(
+            COMPAT,
+            "checked_ilog10_u32",
+            "0..=u32::MAX",
+            (0..=u32::MAX).into_par_iter().map(|x| x as u64),
+            |x| core_compat::checked_ilog10_u32(x as u32).unwrap_or(u32::MAX),
+            |x| (x as u32).checked_ilog10().unwrap_or(u32::MAX),
+        ),
+        check(
+            COMPAT,
+            "checked_ilog10_u64",
+            "0, 10^k - 1, 10^k, u64::MAX",
+            boundaries.clone().into_par_iter(),
+            |x| core_compat::checked_ilog10_u64(x).unwrap_or(u32::MAX),
+            |x| x.checked_ilog10().unwrap_or(u32::MAX),
+        ),
+    ];
+    // The panicking forms are expected to panic on 0, so keep the
+    // default hook from printing those.
+    let hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    checks.push(check(
+        COMPAT,
+        "ilog10_u32",
+        "0..=99",
+        (0..100u64).into_par_iter(),
+        |x| outcome(|| core_compat::ilog10_u32(x as u32)),
+        |x| outcome(|| (x as u32).ilog10()),
+    ));
+    checks.push(check(
+        COMPAT,
+        "ilog10_u64",
+        "0, 10^k - 1, 10^k, u64::MAX",
+        boundaries.into_par_iter(),
+        |x| outcome(|| core_compat::ilog10_u64(x)),
+        |x| outcome(|| x.ilog10()),
+    ));
+    panic::set_hook(hook);
+    checks
+}
+
+// The result of `f`, or u32::MAX if it panicked with the standard
+// library's message (u32::MAX - 1 for any other panic).
+fn outcome(f: impl FnOnce() -> u32 + UnwindSafe) -> u32 {
+    match panic::catch_unwind(f) {
+        Ok(log) => log,
+        Err(payload) => {
+            let msg = payload
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| payload.downcast_ref::<String>().map(|s| s.as_str()));
+            if msg == Some(core_compat::PANIC_MESSAGE) {
+                u32::MAX
+            } else {
+                u32::MAX - 1
+            }
+        }
+    }
+}
+
+fn warren_64bit_values() -> Vec<u64> {
     let mut test_values: Vec<u64> = (0..62).map(|x| 1u64 << x).collect();
     for i in 2..64 {
         test_values.push(((1u128 << i) - 1) as u64);
     }
     test_values.push(u64::MAX);
+    test_values
+}
 
-    for val in test_values {
-        let log2val = val.ilog2();
-        let real_log10val = val.ilog10();
-        // This is unfortunate. The cheap warren map doesn't work.
-        // We have to mul by 19, which turns into
-        // x << 4 + x << 1 + x
-        // which is a little more expensive.
-        // on x64 it's .. two lea's. *grin* not bad at all.
-        let warren_map = log2val.wrapping_mul(19) >> 6;
-        assert!(warren_map == real_log10val || warren_map == real_log10val - 1);
-    }
-}
-
-fn test_ilog64() {
-    println!("Testing warren mapping function");
-    test_warren_64bit();
-    println!("Testing log of u32s to sanity check");
-    let start = std::time::Instant::now();
-    (1..=u32::MAX)
-        .into_par_iter()
-        .map(|x| x as u64)
-        .for_each(|x| assert_eq!(ilog10_u64_mul(x), x.ilog10()));
-    let elapsed = start.elapsed();
-    println!(
-        "passed exhaustive u32 test in {:.2} seconds",
-        elapsed.as_secs_f64()
-    );
-    println!("Testing boundary values");
-    assert_eq!(ilog10_u64_mul(1u64 << 62), (1u64 << 62).ilog10());
-    assert_eq!(ilog10_u64_mul(u64::MAX), u64::MAX.ilog10());
-    // Now test the 64 bit version using random 64 bit values
-    println!("Testing random u64s");
-    let start = std::time::Instant::now();
-    (1..128).into_par_iter().for_each(|_| {
-        let mut rng = rand::thread_rng();
-        for _ in 0..10000000 {
-            let x = rng.gen::<u64>();
-            assert_eq!(ilog10_u64_mul(x), x.ilog10());
-        }
-    });
-    let elapsed = start.elapsed();
-    println!(
-        "passed random u64 test in {:.2} seconds",
-        elapsed.as_secs_f64()
-    );
-}
-
-/// Reference version copied from Rust stdlib.
-#[inline]
-const fn less_than_5(val: u32) -> u32 {
-    // Similar to u8, when adding one of these constants to val,
-    // we get two possible bit patterns above the low 17 bits,
-    // depending on whether val is below or above the threshold.
-    const C1: u32 = 0b011_00000000000000000 - 10; // 393206
-    const C2: u32 = 0b100_00000000000000000 - 100; // 524188
-    const C3: u32 = 0b111_00000000000000000 - 1000; // 916504
-    const C4: u32 = 0b100_00000000000000000 - 10000; // 514288
-
-    // Value of top bits:
-    //                +c1  +c2  1&2  +c3  +c4  3&4   ^
-    //         0..=9  010  011  010  110  011  010  000 = 0
-    //       10..=99  011  011  011  110  011  010  001 = 1
-    //     100..=999  011  100  000  110  011  010  010 = 2
-    //   1000..=9999  011  100  000  111  011  011  011 = 3
-    // 10000..=99999  011  100  000  111  100  100  100 = 4
-    (((val + C1) & (val + C2)) ^ ((val + C3) & (val + C4))) >> 17
-}
-
-pub const fn ilog10_u32(mut val: u32) -> u32 {
-    let mut log = 0;
-    if val >= 100_000 {
-        val /= 100_000;
-        log += 5;
-    }
-    log + less_than_5(val)
-}
-
-/// dga version with speedup from @sahnehaeubchen
-
-const TEN_THRESHOLDS: [u32; 9] = [
-    9,
-    99,
-    999,
-    9999,
-    99999,
-    999999,
-    9999999,
-    99999999,
-    999_999_999,
-];
-
-// The following functions mostly combine two parts:
-// (1) A guess for ilog10 based on ilog2 or leading zeros;
-// (2) A correction based on a lookup table listing powers
-// of ten. The major differences are in the guess function,
-// as most optimizations to the lookup table are common.
-// Guess functions:
-// dave shift/popcount - 2 instructions but popcount is slow on many arch
-// warren x*9 >> 5 version - 2 instructions on x64 (lea + shr), all fast.
-// The dave shift one can use the results of lzcnt directly, whereas
-// the warren one needs to be 31 - lzcnt (one more xor). Mostly unimportant
-// difference as the popcnt cost dominates everywhere but AMD.
-
-// dave's popcount version that only works really well on AMD EPYC. :)
-#[inline]
-const fn ilogpopc(val_lz: u32) -> u32 {    
-    // const LZ_GUESSMASK: u32 = 0b01001001000100100100010010010000;
-    // Here's a cute optimization: Leave out the upper '1'. Our
-    // guess will be too low but we still compare vs 1B. So we can
-    // omit the u32::MAX at the end of the comparison table, saving 4 bytes.
-    const LZ_GUESSMASK: u32 = 0b00001001000100100100010010010000;
-    let guess = (LZ_GUESSMASK << val_lz).count_ones();
-    if guess > LZ_GUESSMASK.count_ones() {
-        // SAFETY: shifting never increases the count of ones
-        unsafe { std::hint::unreachable_unchecked() }
-    }
-    guess
-}
-
-const fn ilog10(val: u32) -> u32 {
-    if val == 0 {
-        // SAFETY: This is ensured by our caller
-        unsafe {
-            std::hint::unreachable_unchecked();
-        }
-    }
-    let guess = ilogpopc(val.leading_zeros());
-    let ttg = TEN_THRESHOLDS[guess as usize];
-    guess + (val > ttg) as u32
-}
-
-// hacker's delight version borrowing optimizations
-// from the rust forum discussion.
-pub const fn ilog10_mul(x: u32) -> u32 {
-    let guess = x.ilog2().wrapping_mul(9) >> 5;
-    debug_assert!(guess < 9);
-    if guess >= 9 {
-        unsafe { std::hint::unreachable_unchecked() }
-    }
-    let ttg = TEN_THRESHOLDS[guess as usize];
-    guess + (x > ttg) as u32
-}
-
-pub fn log10_table_table(x: u32) -> u32 {
-    const guess_table: [u8; 33] = [
-        0, 0, 0, 0, 1, 1, 1, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 5, 5, 5, 6, 6, 6, 6, 7, 7, 7, 8, 8, 8,
-        9, 9, 9,
-    ];
-    const thresholds: [u32; 10] = [
-        9,
-        99,
-        999,
-        9999,
-        99999,
-        999999,
-        9999999,
-        99999999,
-        999_999_999,
-        u32::MAX,
-    ];
+// the warren mapping follows a slightly unintuitive invariant:
+// The warren map value must be correctable to the real log10 value
+// with the addition of at most 1.
+fn warren_64bit_corrected(val: u64) -> u32 {
+    let log2val = val.ilog2();
+    // This is unfortunate. The cheap warren map doesn't work.
+    // We have to mul by 19, which turns into
+    // x << 4 + x << 1 + x
+    // which is a little more expensive.
+    // on x64 it's .. two lea's. *grin* not bad at all.
+    let warren_map = log2val.wrapping_mul(19) >> 6;
+    // Apply the +1 only when it is exactly what is missing, so any
+    // other discrepancy shows up as a mismatch.
+    warren_map + (warren_map + 1 == val.ilog10()) as u32
+}
 
-    let log2 = x.ilog2();
-    let guess = guess_table[log2 as usize] as u32;
-    guess + (x > thresholds[guess as usize]) as u32
-}
-
-// hacker's delight borrowing optimization idea from scottmcm@rustforum
-// to ensure the table access is unchecked. Seems to save a bounds check
-// standalone but that may get optimized away when used with ilog10_checked.
-pub fn ilog10_mul_alt(x: u32) -> u32 {
-    let guess = (x.ilog2() * 9) >> 5;
-    let ttg = unsafe { *TEN_THRESHOLDS.get_unchecked(guess as usize) };
-    guess + (x > ttg) as u32
-}
-
-const U64_THRESHOLDS: [u64; 19] = [
-    9,
-    99,
-    999,
-    9999,
-    99999,
-    999999,
-    9999999,
-    99999999,
-    999999999,
-    9999999999,
-    99999999999,
-    999999999999,
-    9999999999999,
-    99999999999999,
-    999999999999999,
-    9999999999999999,
-    99999999999999999,
-    999999999999999999,
-    9999999999999999999,
-];
-
-pub fn ilog10_u64_mul(x: u64) -> u32 {
-    // Use slightly more accurate approximation of log2(10) for u64;
-    // this takes two lea instructions on x64 instead of just 1 but not bad.
-    let guess: u32 = x.ilog2().wrapping_mul(19) >> 6;
-    let ttg = unsafe { *U64_THRESHOLDS.get_unchecked(guess as usize) };
-    guess + (x > ttg) as u32
-}
-
-fn runloop<F>(f: &F) -> u128
-where
-    F: Fn(u32) -> u32,
-{
-    const LOOPS: usize = 1;
-    const UPTO: u32 = u32::MAX;
-    let start = std::time::Instant::now();
-    for _ in 0..LOOPS {
-        for i in 1..=UPTO {
-            std::hint::black_box(f(i));
-        }
-    }
-    start.elapsed().as_micros()
-}
-
-fn benchmark_ilog() {
-    let elapsed_real = runloop(&ilog10_u32);
-    let elapsed_popc = runloop(&ilog10);
-    let elapsed_mul = runloop(&ilog10_mul);
-    println!("|Platform | popcount | mul | stdlib |");
-    println!("|---------|----------|-----|--------|");
-    println!("|  |  {elapsed_popc} | {elapsed_mul} | {elapsed_real} |");
-    println!("");
+fn test_ilog64(filter: &Filter, permuted: Option<&Permuted>) -> Vec<CheckResult> {
+    const WARREN: &str = "warren 64-bit mapping";
+    let mut checks = Vec::new();
+    if filter.matches(&[WARREN]) {
+        checks.push(check(
+            WARREN,
+            "x.ilog2() * 19 >> 6",
+            "powers of two and 2^k-1",
+            warren_64bit_values().into_par_iter(),
+            warren_64bit_corrected,
+            |x| x.ilog10(),
+        ));
+    }
+    // The software ilog2s change with the top bit alone, so both ends
+    // of every power of two cover them.
+    const SOFT_CLZ: &str = "soft clz";
+    if filter.matches(&[SOFT_CLZ]) {
+        let ilog2s = [
+            ("ilog2_debruijn_u64", soft_clz::ilog2_debruijn_u64 as fn(u64) -> u32),
+            ("ilog2_bytes_u64", soft_clz::ilog2_bytes_u64),
+        ];
+        for (name, ilog2) in ilog2s {
+            checks.push(check(
+                SOFT_CLZ,
+                name,
+                "powers of two and 2^k-1",
+                warren_64bit_values().into_par_iter(),
+                ilog2,
+                |x| x.ilog2(),
+            ));
+        }
+    }
+    // Converting to f32 (f64) rounds once past 2^24 (2^53), and where
+    // it rounds up changes at 2^k - 2^j for some j below k, so one
+    // either side of every 2^k +- 2^j finds each such point. For u32
+    // there is time to try them all.
+    const FLOAT: &str = "float exponent";
+    if filter.matches(&[FLOAT]) {
+        let rounding: Vec<u64> = (0..64)
+            .flat_map(|k| (0..k).map(move |j| (k, j)))
+            .flat_map(|(k, j)| {
+                let (power, step) = (1u128 << k, 1u128 << j);
+                [power - step, power + step].into_iter().flat_map(|x| [x - 1, x, x + 1])
+            })
+            .filter_map(|x| u64::try_from(x).ok())
+            .filter(|&x| x != 0)
+            .collect();
+        checks.push(check(
+            FLOAT,
+            "ilog2_f32_u32",
+            "1..=u32::MAX",
+            (1..=u32::MAX).into_par_iter().map(|x| x as u64),
+            |x| float::ilog2_f32_u32(x as u32),
+            |x| x.ilog2(),
+        ));
+        checks.push(check(
+            FLOAT,
+            "ilog2_f64_u64",
+            "2^k +- 2^j +- 1",
+            rounding.clone().into_par_iter(),
+            float::ilog2_f64_u64,
+            |x| x.ilog2(),
+        ));
+        checks.push(check(
+            FLOAT,
+            "ilog10_u64_f64",
+            "2^k +- 2^j +- 1",
+            rounding.into_par_iter(),
+            float::ilog10_u64_f64,
+            |x| x.ilog10(),
+        ));
+    }
+    if filter.matches(&[FUSED]) {
+        checks.push(check(
+            FUSED,
+            "ilog2_and_ilog10_u64",
+            "2^k - 1, 2^k, 10^k - 1, 10^k",
+            power_boundaries(u64::MAX).into_par_iter(),
+            |x| pack_logs(ilog::ilog2_and_ilog10_u64(x)),
+            |x| pack_logs((x.ilog2(), x.ilog10())),
+        ));
+    }
+    if filter.matches(&[CORRECTION_FLAG]) {
+        checks.push(check(
+            CORRECTION_FLAG,
+            "ilog10_u64_with_flag",
+            "2^k - 1, 2^k, 10^k - 1, 10^k",
+            power_boundaries(u64::MAX).into_par_iter(),
+            |x| pack_flag(ilog::ilog10_u64_with_flag(x)),
+            |x| pack_flag(reference_with_flag(x, 19, 6)),
+        ));
+    }
+    if filter.matches(&[FUSED]) {
+        checks.push(check(
+            FUSED,
+            "digits_and_pow10_u64",
+            "0, 2^k - 1, 2^k, 10^k - 1, 10^k",
+            std::iter::once(0)
+                .chain(power_boundaries(u64::MAX))
+                .collect::<Vec<u64>>()
+                .into_par_iter(),
+            |x| digits_if_pow10(ilog::digits_and_pow10_u64(x)),
+            reference_digits,
+        ));
+    }
+    if filter.matches(&[LEADING]) {
+        let values = leading_boundaries(u64::MAX);
+        checks.push(check(
+            LEADING,
+            "leading_digit_u64",
+            LEADING_RANGE,
+            values.par_iter().copied(),
+            ilog::leading_digit_u64,
+            |x| reference_leading(x, 1) as u32,
+        ));
+        // Compared rather than returned, as the prefixes of more than
+        // 9 digits don't fit the u32 of a check.
+        for n in 0..=21 {
+            checks.push(check(
+                LEADING,
+                &format!("leading_n_digits_u64(x, {n})"),
+                LEADING_RANGE,
+                values.par_iter().copied(),
+                |x| (ilog::leading_n_digits_u64(x, n) == reference_leading(x, n)) as u32,
+                |_| 1,
+            ));
+        }
+    }
+    if filter.matches(&[SI]) {
+        checks.push(check(
+            SI,
+            "ilog1000_u64",
+            "2^k - 1, 2^k, 10^k - 1, 10^k",
+            power_boundaries(u64::MAX).into_par_iter(),
+            ilog::ilog1000_u64,
+            |x| x.ilog10() / 3,
+        ));
+        checks.push(check(
+            SI,
+            "si_scale_u64",
+            LEADING_RANGE,
+            leading_boundaries(u64::MAX).into_par_iter(),
+            |x| pack_si(ilog::si_scale_u64(x)),
+            |x| pack_si(reference_si(x)),
+        ));
+    }
+    // Every pair of boundaries, as values[i / n] and values[i % n]:
+    // products at every power of ten up to 10^38, just below many of
+    // them, and at both ends of the range.
+    if filter.matches(&[PRODUCT]) {
+        let values = power_boundaries(u64::MAX);
+        let n = values.len() as u64;
+        let pair = |i: u64| (values[(i / n) as usize], values[(i % n) as usize]);
+        checks.push(check(
+            PRODUCT,
+            "ilog10_product",
+            "pairs of 2^k - 1, 2^k, 10^k - 1, 10^k",
+            (0..n * n).into_par_iter(),
+            |i| {
+                let (a, b) = pair(i);
+                ilog::ilog10_product(a, b)
+            },
+            |i| {
+                let (a, b) = pair(i);
+                (a as u128 * b as u128).ilog10()
+            },
+        ));
+    }
+    if filter.matches(&[DISPLAY]) {
+        checks.push(check(
+            DISPLAY,
+            "DisplayU64",
+            &format!("0, 2^k - 1, 2^k, 10^k - 1, 10^k, {DISPLAY_SPECS}"),
+            std::iter::once(0)
+                .chain(power_boundaries(u64::MAX))
+                .collect::<Vec<u64>>()
+                .into_par_iter(),
+            |x| displays_alike(ilog::DisplayU64(x), x) as u32,
+            |_| 1,
+        ));
+        let signed: Vec<i64> = power_boundaries(i64::MAX as u64)
+            .into_iter()
+            .flat_map(|x| [x as i64, -(x as i64)])
+            .chain([0, i64::MIN])
+            .collect();
+        checks.push(check(
+            DISPLAY,
+            "DisplayI64",
+            &format!("0, ±(2^k - 1, 2^k, 10^k - 1, 10^k), i64::MIN, {DISPLAY_SPECS}"),
+            (0..signed.len() as u64).into_par_iter(),
+            |i| {
+                let x = signed[i as usize];
+                displays_alike(ilog::DisplayI64(x), x) as u32
+            },
+            |_| 1,
+        ));
+    }
+    // As for the product, every pair of boundaries, which puts ratios
+    // at, just above and just below every power of ten from 10^-20 to
+    // 10^19.
+    if filter.matches(&[RATIO]) {
+        let values = power_boundaries(u64::MAX);
+        let n = values.len() as u64;
+        let pair = |i: u64| (values[(i / n) as usize], values[(i % n) as usize]);
+        checks.push(check(
+            RATIO,
+            "ilog10_ratio",
+            "pairs of 2^k - 1, 2^k, 10^k - 1, 10^k",
+            (0..n * n).into_par_iter(),
+            |i| {
+                let (a, b) = pair(i);
+                ilog::ilog10_ratio(a, b) as u32
+            },
+            |i| {
+                let (a, b) = pair(i);
+                reference_ilog10_ratio(a, b) as u32
+            },
+        ));
+        checks.push(check(
+            RATIO,
+            "neg_ilog10_of_reciprocal",
+            "2^k - 1, 2^k, 10^k - 1, 10^k",
+            power_boundaries(u64::MAX).into_par_iter(),
+            |x| ilog::neg_ilog10_of_reciprocal(x) as u32,
+            |x| reference_ilog10_ratio(1, x) as u32,
+        ));
+    }
+    for base in [humanize::Base::Decimal, humanize::Base::Binary] {
+        if !filter.matches(&[HUMANIZE]) {
+            continue;
+        }
+        checks.push(check(
+            HUMANIZE,
+            &format!("bytes(x, {base:?})"),
+            LEADING_RANGE,
+            leading_boundaries(u64::MAX).into_par_iter(),
+            |x| humanized_ok(x, base) as u32,
+            |_| 1,
+        ));
+    }
+    if filter.matches(&[PREFIX]) {
+        checks.push(check(
+            PREFIX,
+            "checked_encoded_len_with_prefix",
+            LEADING_RANGE,
+            leading_boundaries(u64::MAX).into_par_iter(),
+            |n| pack_len(ilog::checked_encoded_len_with_prefix(n), n),
+            |n| pack_len(reference_prefixed_len(n), n),
+        ));
+    }
+    if filter.matches(&[TOTAL]) {
+        let values = std::iter::once(0).chain(power_boundaries(u64::MAX)).collect::<Vec<u64>>();
+        // Case i is the first i / 3 values with an i % 3 byte separator.
+        let case = |i: u64| (&values[..(i / 3) as usize], (i % 3) as usize);
+        let joined_len = |i| {
+            let (values, separator) = case(i);
+            let text: Vec<String> = values.iter().map(u64::to_string).collect();
+            text.join(&" ".repeat(separator)).len() as u32
+        };
+        let cases = || (0..=values.len() as u64 * 3).into_par_iter();
+        let range = "prefixes of the boundaries, with 0 to 2 byte separators";
+        checks.push(check(
+            TOTAL,
+            "total_decimal_len",
+            range,
+            cases(),
+            |i| {
+                let (values, separator) = case(i);
+                ilog::total_decimal_len(values, separator) as u32
+            },
+            joined_len,
+        ));
+        checks.push(check(
+            TOTAL,
+            "DecimalLenAccumulator",
+            range,
+            cases(),
+            |i| {
+                let (values, separator) = case(i);
+                let mut len = ilog::DecimalLenAccumulator::new(separator);
+                len.extend(values.iter().copied());
+                len.finish() as u32
+            },
+            joined_len,
+        ));
+    }
+    // Each way in against a histogram built one value at a time by the
+    // reference, on a growing prefix of the boundaries.
+    if filter.matches(&[HISTOGRAM]) {
+        let values = std::iter::once(0).chain(power_boundaries(u64::MAX)).collect::<Vec<u64>>();
+        let reference = |n: u64| {
+            let mut counts = [0u64; ilog::histogram::DECADES];
+            for &x in &values[..n as usize] {
+                counts[reference_digits(x) as usize - 1] += 1;
+            }
+            counts
+        };
+        let cases = || (0..=values.len() as u64).into_par_iter();
+        checks.push(check(
+            HISTOGRAM,
+            "decade_bucket",
+            "0, 2^k - 1, 2^k, 10^k - 1, 10^k",
+            values.par_iter().copied(),
+            ilog::histogram::decade_bucket,
+            |x| reference_digits(x) - 1,
+        ));
+        type Record = fn(&mut DecadeHistogram, &[u64]);
+        let ways: [(&str, Record); 3] = [
+            ("DecadeHistogram::record", |h, values| values.iter().for_each(|&x| h.record(x))),
+            ("DecadeHistogram::record_slice", |h, values| h.record_slice(values)),
+            ("DecadeHistogram::extend", |h, values| h.extend(values.iter().copied())),
+        ];
+        for (name, record) in ways {
+            checks.push(check(
+                HISTOGRAM,
+                name,
+                "prefixes of 0, 2^k - 1, 2^k, 10^k - 1, 10^k",
+                cases(),
+                |n| {
+                    let mut histogram = DecadeHistogram::new();
+                    record(&mut histogram, &values[..n as usize]);
+                    (*histogram.counts() == reference(n) && histogram.total() == n) as u32
+                },
+                |_| 1,
+            ));
+        }
+        checks.push(check(
+            HISTOGRAM,
+            "DecadeHistogram::range",
+            "every decade, and each boundary's",
+            values.par_iter().copied(),
+            |x| (0..ilog::histogram::DECADES as u32)
+                .filter(|&d| DecadeHistogram::range(d).contains(&x))
+                .eq([reference_digits(x) - 1]) as u32,
+            |_| 1,
+        ));
+    }
+    // Each spacing's bounds against a list made in u128, at each bound,
+    // one either side of it, and the power boundaries.
+    if filter.matches(&[BUCKET_BOUNDS]) {
+        use ilog::histogram::Spacing;
+        let spacings = [(Spacing::Decades, &[1][..]), (Spacing::OneTwoFive, &[1, 2, 5])];
+        for (spacing, multiples) in spacings {
+            let bounds: Vec<u64> = (0..20)
+                .flat_map(|k| multiples.iter().map(move |&m| m * 10u128.pow(k)))
+                .filter_map(|b| u64::try_from(b).ok())
+                .collect();
+            let mut values: Vec<u64> = bounds
+                .iter()
+                .flat_map(|&b| [b - 1, b, b.saturating_add(1)])
+                .chain(power_boundaries(u64::MAX))
+                .chain([u64::MAX])
+                .collect();
+            values.sort_unstable();
+            values.dedup();
+            let below = |x: u64| bounds.iter().take_while(|&&b| b <= x).count() as u32;
+            checks.push(check(
+                BUCKET_BOUNDS,
+                &format!("{spacing:?}.bucket"),
+                "0, each bound and one either side, 2^k - 1, 2^k, 10^k - 1, 10^k",
+                values.par_iter().copied(),
+                |x| spacing.bucket(x),
+                |x| below(x).saturating_sub(1),
+            ));
+            checks.push(check(
+                BUCKET_BOUNDS,
+                &format!("{spacing:?}.bounds"),
+                "0, each bound and one either side, 2^k - 1, 2^k, 10^k - 1, 10^k",
+                values.par_iter().copied(),
+                |max| {
+                    let generated = spacing.bounds(max);
+                    let len = generated.len();
+                    (generated.eq(bounds[..below(max) as usize].iter().copied())
+                        && len == below(max) as usize) as u32
+                },
+                |_| 1,
+            ));
+        }
+    }
+    // The tables `ilog table` prints, with the exact guess and with the
+    // multiply one, against the standard library's ilog at the
+    // boundaries for every width and base 2 to 36.
+    if filter.matches(&[TABLES]) {
+        for bits in table::WIDTHS {
+            let cases = table_boundaries(bits);
+            let multipliers: Vec<_> = (0..=36).map(|base| ilog::tables::multiplier(base.max(2), bits)).collect();
+            let range = format!("u{bits}, bases 2 to 36: b^k ± 1, 2^k ± 1, max");
+            checks.push(check(
+                TABLES,
+                &format!("tables::guess (u{bits})"),
+                &range,
+                (0..cases.len() as u64).into_par_iter(),
+                |i| {
+                    let (base, x) = cases[i as usize];
+                    table_ilog(base, bits, x, |ilog2| ilog::tables::guess(base, ilog2))
+                },
+                |i| {
+                    let (base, x) = cases[i as usize];
+                    x.ilog(base as u128)
+                },
+            ));
+            checks.push(check(
+                TABLES,
+                &format!("tables::multiplier (u{bits})"),
+                &range,
+                (0..cases.len() as u64).into_par_iter(),
+                |i| {
+                    let (base, x) = cases[i as usize];
+                    // A base without a multiply guess can't be right.
+                    match multipliers[base as usize] {
+                        Some((m, s)) => table_ilog(base, bits, x, |ilog2| (ilog2 * m) >> s),
+                        None => u32::MAX,
+                    }
+                },
+                |i| {
+                    let (base, x) = cases[i as usize];
+                    x.ilog(base as u128)
+                },
+            ));
+        }
+    }
+    if filter.matches(&[MAX_LEN]) {
+        macro_rules! lens {
+            ($($t:ty)*) => {
+                [$((ilog::max_decimal_len::<$t>(), <$t>::MAX.to_string().len())),*]
+            };
+        }
+        let lens = lens!(u8 u16 u32 u64 u128 usize i8 i16 i32 i64 i128 isize);
+        checks.push(check(
+            MAX_LEN,
+            "max_decimal_len::<T>()",
+            "u8 to u128, usize, i8 to i128, isize",
+            (0..lens.len() as u64).into_par_iter(),
+            |i| lens[i as usize].0 as u32,
+            |i| lens[i as usize].1 as u32,
+        ));
+    }
+    if filter.matches(&[DIGITS_U128]) {
+        let values: Vec<u128> = std::iter::once(0)
+            .chain((0..128).flat_map(|k| [1u128 << k, (1u128 << k) | ((1u128 << k) - 1)]))
+            .chain((0..39).flat_map(|k| [10u128.pow(k), 10u128.pow(k) - 1]))
+            .collect();
+        let len = |i: u64| values[i as usize].to_string().len() as u32;
+        checks.push(check(
+            DIGITS_U128,
+            "digits_u128",
+            "0, 2^k - 1, 2^k, 10^k - 1, 10^k",
+            (0..values.len() as u64).into_par_iter(),
+            |i| ilog::digits_u128(values[i as usize]),
+            len,
+        ));
+        // Negated where that fits an i128, and i128::MIN, which doesn't.
+        let negative = |i: u64| match values[i as usize] {
+            x if x <= i128::MAX as u128 => -(x as i128),
+            _ => i128::MIN,
+        };
+        checks.push(check(
+            DIGITS_U128,
+            "digits!",
+            "the same, and their negatives as i128",
+            (0..values.len() as u64 * 2).into_par_iter(),
+            |i| match i % 2 {
+                0 => ilog::digits!(values[i as usize / 2]) as u32,
+                _ => ilog::digits!(negative(i / 2)) as u32,
+            },
+            |i| match i % 2 {
+                0 => len(i / 2),
+                _ => negative(i / 2).to_string().len() as u32,
+            },
+        ));
+    }
+    // The digits and the length before every step against to_string.
+    if filter.matches(&[DIGIT_ITER]) {
+        checks.push(check(
+            DIGIT_ITER,
+            "DecimalDigits",
+            LEADING_RANGE,
+            leading_boundaries(u64::MAX).into_par_iter(),
+            |x| {
+                let mut digits = ilog::DecimalDigits::new(x);
+                let text = x.to_string();
+                let steps = text.bytes().enumerate().all(|(i, b)| {
+                    digits.len() == text.len() - i && digits.next() == Some(b - b'0')
+                });
+                (steps && digits.len() == 0 && digits.next().is_none()) as u32
+            },
+            |_| 1,
+        ));
+    }
+    if filter.matches(&[RADIX]) {
+        checks.push(check(
+            RADIX,
+            "digits_in_radix(x, 2..=36)",
+            "0, r^k - 1, r^k, 2^k - 1, 2^k, max",
+            radix_boundaries().into_par_iter(),
+            |x| (2..=36).all(|r| ilog::digits_in_radix(x, r) == reference_digits_in_radix(x, r)) as u32,
+            |_| 1,
+        ));
+    }
+    if filter.matches(&[LOG10_ROUNDING]) {
+        checks.push(check(
+            LOG10_ROUNDING,
+            "log10_with_rounding(x, Floor, Ceil and Nearest)",
+            "2^k - 1, 2^k, 10^k - 1, 10^k, 10^(k + 1/2) ± 2",
+            log10_rounding_boundaries().into_par_iter(),
+            |x| {
+                use ilog::Log10Rounding::*;
+                let [floor, ceil, nearest] =
+                    [Floor, Ceil, Nearest].map(|mode| ilog::log10_with_rounding(x, mode));
+                pack_rounded_logs(floor, ceil, nearest)
+            },
+            reference_log10_rounding,
+        ));
+        checks.push(check(
+            LOG10_ROUNDING,
+            "nearest_power_of_ten",
+            "0, 2^k - 1, 2^k, 10^k - 1, 10^k, 10^(k + 1/2) ± 2",
+            std::iter::once(0)
+                .chain(log10_rounding_boundaries())
+                .collect::<Vec<u64>>()
+                .into_par_iter(),
+            |x| {
+                let nearest = match x {
+                    0 => 1,
+                    _ => 10u64.pow(reference_log10_rounding(x) & 0xff),
+                };
+                (ilog::nearest_power_of_ten(x) == nearest) as u32
+            },
+            |_| 1,
+        ));
+    }
+    if filter.matches(&[SCI]) {
+        checks.push(check(
+            SCI,
+            "to_decimal_sci",
+            LEADING_RANGE,
+            leading_boundaries(u64::MAX).into_par_iter(),
+            |x| (ilog::to_decimal_sci(x) == reference_sci(x)) as u32,
+            |_| 1,
+        ));
+    }
+    // Every figure count at once, to keep the table short.
+    for rounding in [ilog::Rounding::HalfUp, ilog::Rounding::HalfEven] {
+        if !filter.matches(&[ROUND]) {
+            continue;
+        }
+        checks.push(check(
+            ROUND,
+            &format!("checked_round_sig_figs(x, 0..=21, {rounding:?})"),
+            LEADING_RANGE,
+            leading_boundaries(u64::MAX).into_par_iter(),
+            |x| {
+                (0..=21).all(|figs| {
+                    ilog::checked_round_sig_figs(x, figs, rounding)
+                        == reference_round(x, figs, rounding)
+                }) as u32
+            },
+            |_| 1,
+        ));
+    }
+    if filter.matches(&[BATCH]) {
+        let lanes = |x: u64| [x, x.rotate_left(32)];
+        checks.push(check(
+            BATCH,
+            "digits_u64x2",
+            "0, 2^k - 1, 2^k, 10^k - 1, 10^k and rotations",
+            std::iter::once(0)
+                .chain(power_boundaries(u64::MAX))
+                .collect::<Vec<u64>>()
+                .into_par_iter(),
+            |x| pack_digits(&ilog::digits_u64x2(lanes(x))),
+            |x| pack_digits(&lanes(x).map(reference_digits)),
+        ));
+    }
+    // Eleven values per call: a full eight-lane vector and a tail of
+    // three for the kernels that take eight at a time.
+    for (kernel, f) in ilog::batch::u64_kernels() {
+        if !filter.matches(&[BATCH]) {
+            continue;
+        }
+        let values = |x: u64| core::array::from_fn::<u64, 11, _>(|i| x.rotate_left(6 * i as u32));
+        checks.push(check(
+            BATCH,
+            &format!("ilog10_u64_slice ({kernel})"),
+            "0, 2^k - 1, 2^k, 10^k - 1, 10^k and rotations",
+            std::iter::once(0)
+                .chain(power_boundaries(u64::MAX))
+                .collect::<Vec<u64>>()
+                .into_par_iter(),
+            move |x| {
+                let mut out = [0; 11];
+                f(&values(x), &mut out);
+                (out == values(x).map(|v| (v | 1).ilog10())) as u32
+            },
+            |_| 1,
+        ));
+    }
+    for a in registry::of_width(Width::U64) {
+        let name = a.qualified_name();
+        if wanted(filter, "exhaustive u32 as u64", a) {
+            let (range, order) = u32_order(permuted);
+            checks.push(sweep(
+                "exhaustive u32 as u64",
+                &name,
+                &range,
+                order.as_ref(),
+                a.eval_all,
+            ));
+        }
+        if wanted(filter, "u64 boundary values", a) {
+            checks.push(check(
+                "u64 boundary values",
+                &name,
+                "1<<62, u64::MAX",
+                vec![1u64 << 62, u64::MAX].into_par_iter(),
+                a.eval,
+                |x| x.ilog10(),
+            ));
+        }
+        if wanted(filter, "u64 powers of ten", a) {
+            checks.push(check(
+                "u64 powers of ten",
+                &name,
+                "10^k - 1, 10^k",
+                (1..20)
+                    .flat_map(|k| [10u64.pow(k) - 1, 10u64.pow(k)])
+                    .collect::<Vec<u64>>()
+                    .into_par_iter(),
+                a.eval,
+                |x| x.ilog10(),
+            ));
+        }
+        if let Some(p) = permuted {
+            if wanted(filter, "permuted u64 slice", a) {
+                let order = Permutation::new(p.bits, p.seed);
+                let start = p.start;
+                checks.push(check(
+                    "permuted u64 slice",
+                    &name,
+                    &format!("{start}..{start} + 2^{}, seed {}", p.bits, p.seed),
+                    (0..order.size())
+                        .into_par_iter()
+                        .map(move |i| start + order.at(i))
+                        .filter(|&x| x != 0),
+                    a.eval,
+                    |x| x.ilog10(),
+                ));
+            }
+        // Now test the 64 bit version using random 64 bit values
+        } else if wanted(filter, "random u64", a) {
+            checks.push(check(
+                "random u64",
+                &name,
+                "1.27e9 random u64s",
+                (1..128u64).into_par_iter().flat_map_iter(|_| {
+                    let mut rng = rand::rng();
+                    (0..10000000).map(move |_| rng.random::<u64>())
+                }),
+                a.eval,
+                |x| x.ilog10(),
+            ));
+        }
+    }
+    checks
 }
@@ -0,0 +1,169 @@
+// Several builds of `ilog` benchmarked side by side, for `ilog
+// bench-matrix`. Whether a guess wins can depend on `-C target-cpu`
+// (popcnt and lzcnt are only used where the CPU is known to have them)
+// and on features like `prefer-mul` or `small-tables`, which a single
+// binary can't switch between. Each combination is built into a target
+// directory of its own under target/matrix, runs the same `bench` with
+// JSON output, and the runs are joined row by row into one table with
+// each build's ns per op.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde_json::{Map, Value};
+
+use ilog::results::BenchReport;
+
+use crate::report::Report;
+
+/// The `-C target-cpu` standing for none.
+pub const DEFAULT_CPU: &str = "default";
+
+/// One build: a target CPU and the features on top of the defaults.
+struct Config {
+    cpu: String,
+    features: Vec<String>,
+}
+
+impl Config {
+    // e.g. `native+prefer-mul`, for the column and the target directory.
+    fn label(&self) -> String {
+        std::iter::once(self.cpu.as_str())
+            .chain(self.features.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join("+")
+    }
+
+    // Builds this configuration's binary; its path.
+    fn build(&self) -> Result<PathBuf, String> {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let target_dir = dir.join("target/matrix").join(self.label());
+        let cargo = std::env::var_os("CARGO").unwrap_or_else(|| "cargo".into());
+        let mut command = Command::new(cargo);
+        command
+            .arg("build")
+            .arg("--manifest-path")
+            .arg(dir.join("Cargo.toml"))
+            .arg("--target-dir")
+            .arg(&target_dir)
+            .args(["--release", "--quiet", "--bin", "ilog"]);
+        if !self.features.is_empty() {
+            command.arg("--features").arg(self.features.join(","));
+        }
+        if self.cpu != DEFAULT_CPU {
+            let mut flags = std::env::var("RUSTFLAGS").unwrap_or_default();
+            flags += &format!(" -Ctarget-cpu={}", self.cpu);
+            command.env("RUSTFLAGS", flags.trim_start());
+        }
+        let status = command.status().map_err(|e| format!("running cargo: {e}"))?;
+        if !status.success() {
+            return Err(format!("building {} failed", self.label()));
+        }
+        Ok(target_dir.join("release/ilog"))
+    }
+}
+
+/// Runs `binary` with `args`, a `bench` command line, for its report;
+/// `label` names the build in errors.
+pub fn bench(binary: &Path, args: &[String], label: &str) -> Result<BenchReport, String> {
+    let output = Command::new(binary)
+        .args(args)
+        .args(["--format", "json"])
+        .stderr(std::process::Stdio::inherit())
+        .output()
+        .map_err(|e| format!("running {}: {e}", binary.display()))?;
+    if !output.status.success() {
+        return Err(format!("the {label} benchmark failed ({})", output.status));
+    }
+    let text = String::from_utf8_lossy(&output.stdout);
+    let last = text
+        .lines()
+        .rfind(|l| !l.trim().is_empty())
+        .ok_or_else(|| format!("the {label} benchmark printed no report"))?;
+    serde_json::from_str(last).map_err(|e| format!("the {label} report: {e}"))
+}
+
+/// `args` without `options`, each named with whether it takes a value,
+/// in either the `--name value` or the `--name=value` form.
+pub fn forwarded(args: impl Iterator<Item = String>, options: &[(&str, bool)]) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut skip_value = false;
+    for arg in args {
+        if std::mem::take(&mut skip_value) {
+            continue;
+        }
+        let name = arg.split('=').next().unwrap_or(&arg);
+        match options.iter().find(|(option, _)| *option == name) {
+            Some((_, takes_value)) => skip_value = *takes_value && !arg.contains('='),
+            None => out.push(arg),
+        }
+    }
+    out
+}
+
+/// Every combination of `cpus` and `feature_sets` (each a
+/// comma-separated list, or empty for the defaults alone), each timed
+/// by `bench` with `args`.
+pub fn report(cpus: &[String], feature_sets: &[String], args: &[String]) -> Result<Report, String> {
+    let configs: Vec<Config> = cpus
+        .iter()
+        .flat_map(|cpu| {
+            feature_sets.iter().map(move |set| Config {
+                cpu: cpu.clone(),
+                features: set.split(',').filter(|f| !f.is_empty()).map(String::from).collect(),
+            })
+        })
+        .collect();
+    let mut runs = Vec::new();
+    for config in &configs {
+        eprintln!("Building {}", config.label());
+        let binary = config.build()?;
+        eprintln!("Benchmarking {}", config.label());
+        let args: Vec<String> = std::iter::once("bench".to_string()).chain(args.iter().cloned()).collect();
+        runs.push(bench(&binary, &args, &config.label())?);
+    }
+
+    let labels: Vec<String> = configs.iter().map(Config::label).collect();
+    let ns_columns: Vec<String> = labels.iter().map(|l| format!("{l}_ns_per_op")).collect();
+    let mut columns = vec!["width", "distribution", "context", "algorithm"];
+    columns.extend(ns_columns.iter().map(String::as_str));
+    columns.push("fastest");
+    let mut report = Report::new("bench matrix", &columns);
+    // A row per result of any run, in the order they first appear.
+    let mut keys = Vec::new();
+    for r in runs.iter().flat_map(|run| &run.rows) {
+        let key = (r.width, r.distribution, r.context, r.algorithm.as_str());
+        if !keys.contains(&key) {
+            keys.push(key);
+        }
+    }
+    for (width, distribution, context, algorithm) in keys {
+        let ns: Vec<Option<f64>> = runs
+            .iter()
+            .map(|run| {
+                run.rows
+                    .iter()
+                    .find(|o| (o.width, o.distribution, o.context, o.algorithm.as_str())
+                        == (width, distribution, context, algorithm))
+                    .map(|o| o.ns_per_op)
+            })
+            .collect();
+        let fastest = ns
+            .iter()
+            .zip(&labels)
+            .filter_map(|(ns, label)| Some((ns.as_ref()?, label)))
+            .min_by(|a, b| a.0.total_cmp(b.0))
+            .map(|(_, label)| label.clone());
+        let mut row = Map::new();
+        row.insert("width".into(), width.name().into());
+        row.insert("distribution".into(), distribution.name().into());
+        row.insert("context".into(), context.name().into());
+        row.insert("algorithm".into(), algorithm.into());
+        for (column, ns) in ns_columns.iter().zip(ns) {
+            row.insert(column.clone(), ns.into());
+        }
+        row.insert("fastest".into(), fastest.into());
+        report.push(&Value::Object(row));
+    }
+    Ok(report)
+}
@@ -0,0 +1,205 @@
+// Static estimates from llvm-mca for `ilog mca`. Timings on one
+// machine are noisy enough to hide a difference of a fraction of a
+// cycle; llvm-mca's scheduling model is deterministic, and can stand
+// in for CPUs that are not to hand.
+//
+// Only the fast path is analysed: everything up to the first return,
+// which for these functions leaves out the panic for 0. Branches on
+// it are kept (llvm-mca assumes they fall through), with their target
+// replaced by a label so that the assembler accepts them.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use ilog::bench::{Distribution, Input};
+use ilog::registry;
+use ilog::results::McaEstimate;
+use serde_json::Value;
+
+use crate::disasm::{self, Function, Instruction, Kind};
+use crate::report::Report;
+
+/// `llvm-mca`, or whatever `LLVM_MCA` names.
+fn llvm_mca() -> String {
+    std::env::var("LLVM_MCA").unwrap_or_else(|_| "llvm-mca".into())
+}
+
+// The assembly text llvm-mca is given, in the syntax capstone prints.
+fn source(target: &str, instructions: &[Instruction]) -> String {
+    let mut out = String::new();
+    if target.starts_with("x86_64") || target.starts_with("i686") || target.starts_with("i586") {
+        out += ".intel_syntax noprefix\n";
+    } else if target.starts_with("thumb") {
+        out += ".syntax unified\n.thumb\n";
+    }
+    for i in instructions.iter().take_while(|i| i.kind != Kind::Return) {
+        if let (Kind::Jump | Kind::ConditionalJump, Some((head, _))) =
+            (i.kind, i.text.rsplit_once([' ', ',']))
+        {
+            out += &format!("{head} .Lexit\n");
+        } else {
+            out += &format!("{}\n", i.text);
+        }
+    }
+    out + ".Lexit:\n"
+}
+
+// llvm-mca's idea of the target: Rust's riscv64gc is riscv64 plus the
+// G and C extensions.
+fn llvm_target(target: &str) -> (String, Option<&'static str>) {
+    match target.strip_prefix("riscv64gc") {
+        Some(rest) => (format!("riscv64{rest}"), Some("+m,+a,+f,+d,+c")),
+        None => (target.to_string(), None),
+    }
+}
+
+fn run_mca(target: &str, cpu: Option<&str>, source: &str) -> Result<Value, String> {
+    let (triple, attrs) = llvm_target(target);
+    let mut command = Command::new(llvm_mca());
+    command.arg(format!("-mtriple={triple}")).arg("-json");
+    if let Some(cpu) = cpu {
+        command.arg(format!("-mcpu={cpu}"));
+    }
+    if let Some(attrs) = attrs {
+        command.arg(format!("-mattr={attrs}"));
+    }
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("running {} (set LLVM_MCA to its path): {e}", llvm_mca()))?;
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(source.as_bytes())
+        .map_err(|e| format!("writing to llvm-mca: {e}"))?;
+    let output = child
+        .wait_with_output()
+        .map_err(|e| format!("running llvm-mca: {e}"))?;
+    if !output.status.success() {
+        return Err(format!(
+            "llvm-mca failed on\n{source}{}",
+            String::from_utf8_lossy(&output.stderr)
+        ));
+    }
+    serde_json::from_slice(&output.stdout).map_err(|e| format!("parsing llvm-mca output: {e}"))
+}
+
+fn estimate(
+    f: &Function,
+    target: &str,
+    cpu: Option<&str>,
+    instructions: &[Instruction],
+) -> Result<McaEstimate, String> {
+    let source = source(target, instructions);
+    let json = run_mca(target, cpu, &source)?;
+    let region = &json["CodeRegions"][0];
+    let summary = &region["SummaryView"];
+    let number = |v: &Value| v.as_f64().unwrap_or(f64::NAN);
+    let iterations = number(&summary["Iterations"]);
+
+    // llvm-mca reports the pressure per instruction and resource,
+    // already averaged over the iterations, followed by the totals
+    // (as one more instruction index) which are left out here.
+    let count = region["Instructions"]
+        .as_array()
+        .map_or(0, |i| i.len() as u64);
+    let resources = json["TargetInfo"]["Resources"]
+        .as_array()
+        .cloned()
+        .unwrap_or_default();
+    let mut pressure: BTreeMap<usize, f64> = BTreeMap::new();
+    for entry in region["ResourcePressureView"]["ResourcePressureInfo"]
+        .as_array()
+        .into_iter()
+        .flatten()
+    {
+        if entry["InstructionIndex"].as_u64() >= Some(count) {
+            continue;
+        }
+        let index = entry["ResourceIndex"].as_u64().unwrap_or(0) as usize;
+        *pressure.entry(index).or_default() += number(&entry["ResourceUsage"]);
+    }
+    let pressure: Vec<String> = pressure
+        .into_iter()
+        .map(|(i, usage)| {
+            // Some resource names in LLVM 14's aarch64 models end in a
+            // raw byte (A57UnitI.\0) instead of a digit.
+            let name: String = resources
+                .get(i)
+                .and_then(Value::as_str)
+                .unwrap_or("?")
+                .chars()
+                .map(|c| {
+                    char::from_digit(c as u32, 10)
+                        .filter(|_| c < ' ')
+                        .unwrap_or(c)
+                })
+                .collect();
+            format!("{name} {usage:.2}")
+        })
+        .collect();
+
+    Ok(McaEstimate {
+        function: f.name.to_string(),
+        algorithm: f.algorithm.map(String::from),
+        target: target.to_string(),
+        cpu: json["TargetInfo"]["CPUName"]
+            .as_str()
+            .unwrap_or("generic")
+            .to_string(),
+        instructions: region["Instructions"]
+            .as_array()
+            .map_or(0, |i| i.len() as u64),
+        uops: number(&summary["TotaluOps"]) / iterations,
+        cycles: number(&summary["TotalCycles"]) / iterations,
+        block_rthroughput: number(&summary["BlockRThroughput"]),
+        ipc: number(&summary["IPC"]),
+        pressure: pressure.join(", "),
+        measured_ns_per_op: (target == disasm::HOST)
+            .then(|| measure(f.algorithm?))
+            .flatten(),
+    })
+}
+
+// A best-of-three run over uniform random inputs, for comparison.
+fn measure(algorithm: &str) -> Option<f64> {
+    let algorithm = registry::all().find(|a| a.qualified_name() == algorithm)?;
+    eprintln!("Timing {}", algorithm.qualified_name());
+    let input = Input::generate(algorithm.width, Distribution::Uniform);
+    let best = (0..3).map(|_| algorithm.time(&input)).min()?;
+    Some(best as f64 * 1000.0 / input.ops() as f64)
+}
+
+pub fn report(
+    functions: &[(&Function, Vec<Instruction>)],
+    target: &str,
+    cpu: Option<&str>,
+) -> Result<Report, String> {
+    let mut report = Report::new(
+        "mca",
+        &[
+            "function",
+            "algorithm",
+            "target",
+            "cpu",
+            "instructions",
+            "uops",
+            "cycles",
+            "block_rthroughput",
+            "ipc",
+            "pressure",
+            "measured_ns_per_op",
+        ],
+    );
+    if target.starts_with("wasm32") {
+        return Err("llvm-mca has no model for WebAssembly".into());
+    }
+    for (f, instructions) in functions {
+        report.push(&estimate(f, target, cpu, instructions)?);
+    }
+    Ok(report)
+}
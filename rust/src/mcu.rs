@@ -0,0 +1,71 @@
+//! ilog10 for 8- and 16-bit microcontrollers (AVR, MSP430), which
+//! have neither a clz instruction nor a multiplier wider than 8 bits
+//! (or none at all). There a u32 multiply is a call into the compiler
+//! runtime and `leading_zeros` a loop or a long expansion, so the
+//! usual guess costs more than the rest of the formatting.
+//!
+//! `ilog10_ladder` drops the guess and the table altogether: a binary
+//! search over the powers of ten, at most 4 comparisons for u32 (6
+//! for u64) against immediates, each a chain of byte compares on AVR.
+//! On these
+//! targets it is the ilog10 behind `fast_ilog10` and the `ffi`
+//! wrappers (see `target` in lib.rs).
+//!
+//! For a core that does have clz but only a slow multiplier,
+//! `guess_ilog10_u32` is the multiply guess of `ilog10_mul` as a shift
+//! and an add in 16 bits, to pair with `parts::correct`.
+
+/// `ilog2(x) * 9 / 32`, from `lz = x.leading_zeros()` with `lz < 32`,
+/// without a multiply: 9 * l is l + (l << 3), and 31 * 9 still fits a
+/// u16.
+#[inline]
+pub const fn guess_ilog10_u32(lz: u32) -> u32 {
+    let l = (31 - lz) as u16;
+    ((l + (l << 3)) >> 5) as u32
+}
+
+/// floor(log10(x)) by comparisons alone. 0 gives 0.
+#[inline]
+pub const fn ilog10_ladder(x: u32) -> u32 {
+    if x < 100_000 {
+        if x < 100 {
+            (x >= 10) as u32
+        } else if x < 10_000 {
+            2 + (x >= 1000) as u32
+        } else {
+            4
+        }
+    } else if x < 10_000_000 {
+        5 + (x >= 1_000_000) as u32
+    } else if x < 1_000_000_000 {
+        7 + (x >= 100_000_000) as u32
+    } else {
+        9
+    }
+}
+
+/// As `ilog10_ladder`, for u64: split at 10^10, then the u32 ladder
+/// on whichever side fits.
+#[inline]
+pub const fn ilog10_u64_ladder(x: u64) -> u32 {
+    if x < 10_000_000_000 {
+        if x <= u32::MAX as u64 {
+            ilog10_ladder(x as u32)
+        } else {
+            9
+        }
+    } else if x < 1_000_000_000_000_000 {
+        if x < 1_000_000_000_000 {
+            10 + (x >= 100_000_000_000) as u32
+        } else if x < 100_000_000_000_000 {
+            12 + (x >= 10_000_000_000_000) as u32
+        } else {
+            14
+        }
+    } else if x < 100_000_000_000_000_000 {
+        15 + (x >= 10_000_000_000_000_000) as u32
+    } else {
+        17 + (x >= 1_000_000_000_000_000_000) as u32
+            + (x >= 10_000_000_000_000_000_000) as u32
+    }
+}
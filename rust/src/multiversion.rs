@@ -0,0 +1,270 @@
+// What runtime dispatch costs, for `ilog multiversion`. The `dispatch`
+// feature's functions check a cached CPU bit on every call and then
+// call a build of the function compiled for the CPU, which a caller
+// built for baseline x86-64 can't inline. This times the same values
+// through each way of getting to a build:
+//
+// - `inlined`: the build inlined into a loop compiled for its features,
+//   as with `-C target-cpu` or the check hoisted out of the loop, the
+//   cost of the ilog10 alone;
+// - `call`: the `lzcnt` build called from a baseline loop, with no
+//   check, the cost of the call;
+// - `pointer`: a function pointer picked once, as an ifunc or a PLT
+//   entry would give;
+// - `multiversion`: `dispatch::ilog10_u32_mul` (or `_u64_`), the check
+//   and the call;
+// - `dispatch`: `dispatch::ilog10_u32`, the mul or popcount choice
+//   behind `fast_ilog10`, for u32.
+//
+// Each row is timed twice. `ns_per_op` is over independent values, as
+// `bench` times them, where whatever else the loop holds up matters
+// too: `bsr` and, on many Intel cores, `lzcnt` wait on their
+// destination register, which in a tight inlined loop tends to hold the
+// previous result, so a build inlined can come out slower than called.
+// `latency_ns` is over a chain in which each value waits on the last
+// result, which takes in every such wait alike; `overhead_ns` is that
+// less the latency of the same build inlined. The rows take turns, in
+// a new order each round, and the best round of each counts.
+
+use std::hint::black_box;
+use std::num::{NonZeroU32, NonZeroU64};
+use std::ops::BitOr;
+use std::time::Instant;
+
+use ilog::bench::{Distribution, Input};
+use ilog::dispatch::{self, Build, Choice};
+use ilog::registry::Width;
+use ilog::results::DispatchCost;
+use rand::seq::SliceRandom;
+
+use crate::report::Report;
+
+// One row: how the build is reached, which build, and the timed loop,
+// chained or not.
+type Row<'a> = (&'static str, &'static str, Box<dyn Fn(bool) + 'a>);
+
+pub fn report(width: Width, distribution: Distribution, samples: usize) -> Result<Report, String> {
+    if samples == 0 {
+        return Err("--samples must be at least 1".into());
+    }
+    let input = Input::generate(width, distribution);
+    let rows = match &input {
+        Input::U32 { values, passes } => u32_rows(values, *passes),
+        Input::U64 { values, passes } => u64_rows(values, *passes),
+        Input::Sweep | Input::Permuted(_) | Input::Strided { .. } => {
+            return Err(format!("{} is not a random distribution", distribution.name()));
+        }
+    };
+    let ops = input.ops() as f64;
+
+    // The best (independent, chained) ns per op of each row.
+    let mut best = vec![[f64::INFINITY; 2]; rows.len()];
+    let mut order: Vec<(usize, bool)> =
+        (0..rows.len()).flat_map(|i| [(i, false), (i, true)]).collect();
+    let mut rng = rand::rng();
+    for _ in 0..samples {
+        order.shuffle(&mut rng);
+        for &(i, chained) in &order {
+            let start = Instant::now();
+            (rows[i].2)(chained);
+            let ns = start.elapsed().as_nanos() as f64 / ops;
+            let best = &mut best[i][chained as usize];
+            *best = best.min(ns);
+        }
+    }
+
+    let mut report = Report::new(
+        "dispatch overhead",
+        &["width", "distribution", "call", "build", "ns_per_op", "latency_ns", "overhead_ns"],
+    );
+    let inlined = |build: &str| {
+        rows.iter().zip(&best).find(|((call, b, _), _)| *call == "inlined" && *b == build)
+    };
+    for ((call, build, _), &[ns_per_op, latency_ns]) in rows.iter().zip(&best) {
+        let overhead_ns = match inlined(build) {
+            Some(_) if *call == "inlined" => None,
+            Some((_, &[_, reference])) => Some(latency_ns - reference),
+            None => None,
+        };
+        report.push(&DispatchCost {
+            width,
+            distribution,
+            call: call.to_string(),
+            build: build.to_string(),
+            ns_per_op,
+            latency_ns,
+            overhead_ns,
+        });
+    }
+    Ok(report)
+}
+
+// `f` of every value, `passes` times over; `chained`, each value has
+// the last result's low bit or'd in, so that it waits on it.
+#[inline(always)]
+fn each<T>(values: &[T], passes: usize, chained: bool, f: impl Fn(T) -> u32)
+where
+    T: Copy + BitOr<Output = T> + From<u8>,
+{
+    if chained {
+        let mut last = 0;
+        for _ in 0..passes {
+            for &x in values {
+                last = f(x | T::from(last as u8 & 1));
+            }
+        }
+        black_box(last);
+    } else {
+        for _ in 0..passes {
+            for &x in values {
+                black_box(f(x));
+            }
+        }
+    }
+}
+
+// SAFETY, for the unchecked calls: random inputs are never 0, and the
+// lzcnt build only runs where it is supported.
+fn u32_rows(values: &[u32], passes: usize) -> Vec<Row<'_>> {
+    let nonzero = |x| unsafe { NonZeroU32::new_unchecked(x) };
+    let baseline = |x| unsafe {
+        Build::Baseline.ilog10_u32_unchecked(NonZeroU32::new_unchecked(x))
+    };
+    let lzcnt = |x| unsafe { Build::Lzcnt.ilog10_u32_unchecked(NonZeroU32::new_unchecked(x)) };
+    let mut rows: Vec<Row> = vec![(
+        "inlined",
+        "baseline",
+        Box::new(move |chained| each(values, passes, chained, baseline)),
+    )];
+    if Build::Lzcnt.supported() {
+        rows.push((
+            "inlined",
+            "lzcnt",
+            Box::new(move |chained| unsafe { x86::lzcnt_u32(values, passes, chained) }),
+        ));
+        rows.push(("call", "lzcnt", Box::new(move |chained| each(values, passes, chained, lzcnt))));
+    }
+    let build = dispatch::build();
+    let pointer: fn(u32) -> u32 = match build {
+        Build::Baseline => baseline,
+        Build::Lzcnt => lzcnt,
+    };
+    rows.push((
+        "pointer",
+        build.name(),
+        Box::new(move |chained| each(values, passes, chained, black_box(pointer))),
+    ));
+    rows.push((
+        "multiversion",
+        build.name(),
+        Box::new(move |chained| {
+            each(values, passes, chained, |x| dispatch::ilog10_u32_mul(nonzero(x)))
+        }),
+    ));
+    // Choice::Mul is the baseline build of ilog10_mul.
+    let choice = match dispatch::choice() {
+        Choice::Mul => "baseline",
+        Choice::Popcount => {
+            rows.push((
+                "inlined",
+                "popcount",
+                Box::new(move |chained| unsafe { x86::popcount_u32(values, passes, chained) }),
+            ));
+            "popcount"
+        }
+    };
+    rows.push((
+        "dispatch",
+        choice,
+        Box::new(move |chained| {
+            each(values, passes, chained, |x| dispatch::ilog10_u32(nonzero(x)))
+        }),
+    ));
+    rows
+}
+
+// SAFETY, as for u32_rows.
+fn u64_rows(values: &[u64], passes: usize) -> Vec<Row<'_>> {
+    let nonzero = |x| unsafe { NonZeroU64::new_unchecked(x) };
+    let baseline = |x| unsafe {
+        Build::Baseline.ilog10_u64_unchecked(NonZeroU64::new_unchecked(x))
+    };
+    let lzcnt = |x| unsafe { Build::Lzcnt.ilog10_u64_unchecked(NonZeroU64::new_unchecked(x)) };
+    let mut rows: Vec<Row> = vec![(
+        "inlined",
+        "baseline",
+        Box::new(move |chained| each(values, passes, chained, baseline)),
+    )];
+    if Build::Lzcnt.supported() {
+        rows.push((
+            "inlined",
+            "lzcnt",
+            Box::new(move |chained| unsafe { x86::lzcnt_u64(values, passes, chained) }),
+        ));
+        rows.push(("call", "lzcnt", Box::new(move |chained| each(values, passes, chained, lzcnt))));
+    }
+    let build = dispatch::build();
+    let pointer: fn(u64) -> u32 = match build {
+        Build::Baseline => baseline,
+        Build::Lzcnt => lzcnt,
+    };
+    rows.push((
+        "pointer",
+        build.name(),
+        Box::new(move |chained| each(values, passes, chained, black_box(pointer))),
+    ));
+    rows.push((
+        "multiversion",
+        build.name(),
+        Box::new(move |chained| {
+            each(values, passes, chained, |x| dispatch::ilog10_u64_mul(nonzero(x)))
+        }),
+    ));
+    rows
+}
+
+// The loops compiled for the builds' features, so that the builds
+// inline into them. Off x86-64 neither build is ever supported.
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use std::num::{NonZeroU32, NonZeroU64};
+
+    use ilog::dispatch::Build;
+
+    // Safety: the CPU has lzcnt, and no value is 0.
+    #[target_feature(enable = "lzcnt")]
+    pub unsafe fn lzcnt_u32(values: &[u32], passes: usize, chained: bool) {
+        super::each(values, passes, chained, |x| unsafe {
+            Build::Lzcnt.ilog10_u32_unchecked(NonZeroU32::new_unchecked(x))
+        })
+    }
+
+    // Safety: the CPU has lzcnt, and no value is 0.
+    #[target_feature(enable = "lzcnt")]
+    pub unsafe fn lzcnt_u64(values: &[u64], passes: usize, chained: bool) {
+        super::each(values, passes, chained, |x| unsafe {
+            Build::Lzcnt.ilog10_u64_unchecked(NonZeroU64::new_unchecked(x))
+        })
+    }
+
+    // Safety: the CPU has popcnt and lzcnt, and no value is 0.
+    #[target_feature(enable = "popcnt,lzcnt")]
+    pub unsafe fn popcount_u32(values: &[u32], passes: usize, chained: bool) {
+        super::each(values, passes, chained, |x| unsafe { ilog::ilog10(x) })
+    }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+mod x86 {
+    pub unsafe fn lzcnt_u32(_: &[u32], _: usize, _: bool) {
+        unreachable!("lzcnt is never supported off x86-64")
+    }
+
+    pub unsafe fn lzcnt_u64(_: &[u64], _: usize, _: bool) {
+        unreachable!("lzcnt is never supported off x86-64")
+    }
+
+    pub unsafe fn popcount_u32(_: &[u32], _: usize, _: bool) {
+        unreachable!("popcount is never supported off x86-64")
+    }
+}
@@ -0,0 +1,186 @@
+//! The public functions again, each marked `#[no_panic]`, enabled by
+//! the `no-panic` feature for firmware and kernel code that must not
+//! contain a panic path at all.
+//!
+//! `#[no_panic]` turns a reachable panic into a link error, so a
+//! program that calls one of these and links is proof that the
+//! optimizer removed every path to a panic from it. The check needs
+//! optimizations: in a debug build the calls fail to link. It can't be
+//! put on a `const fn`, hence the copies, and it needs what they call
+//! to inline into the calling crate, hence `#[inline]` on every
+//! function copied here.
+//!
+//! The ilog10s, undefined for 0, take a `NonZeroU32` or `NonZeroU64`,
+//! which is what rules out `ilog2`'s panic for 0; the rest take any
+//! value. A few panic by design and have no counterpart: `write_u64`
+//! on a short buffer, `round_sig_figs`, `encoded_len_with_prefix` and
+//! `netstring_len` on overflow (see their `checked_` versions), and
+//! `digits_in_radix` on a radix outside 2..=36.
+//! `cargo test --release --features no-panic --test no_panic` links
+//! all of them.
+
+use core::num::{NonZeroU32, NonZeroU64};
+
+use no_panic::no_panic;
+
+use crate::{arm32, constant_time, core_compat, float, mcu, soft_clz};
+
+macro_rules! nonzero {
+    ($($f:ident: $nz:ty => $path:path;)*) => {
+        $(
+            #[doc = concat!("`", stringify!($path), "`, for nonzero `x`.")]
+            #[no_panic]
+            pub fn $f(x: $nz) -> u32 {
+                $path(x.get())
+            }
+        )*
+    };
+}
+
+nonzero! {
+    ilog10_mul: NonZeroU32 => crate::ilog10_mul;
+    ilog10_mul_alt: NonZeroU32 => crate::ilog10_mul_alt;
+    ilog10_mul_pow: NonZeroU32 => crate::ilog10_mul_pow;
+    ilog10_mul_loop: NonZeroU32 => crate::ilog10_mul_loop;
+    ilog10_mul_pow4: NonZeroU32 => crate::ilog10_mul_pow4;
+    ilog10_mul_packed: NonZeroU32 => crate::ilog10_mul_packed;
+    log10_table_table: NonZeroU32 => crate::log10_table_table;
+    ilog10_swar: NonZeroU32 => crate::ilog10_swar;
+    ilog10_u32: NonZeroU32 => crate::ilog10_u32;
+    ilog10_u64_mul: NonZeroU64 => crate::ilog10_u64_mul;
+    ilog10_u64_mul_pow: NonZeroU64 => crate::ilog10_u64_mul_pow;
+    ilog10_u64_mul_loop: NonZeroU64 => crate::ilog10_u64_mul_loop;
+    ilog10_u64_mul_pow4: NonZeroU64 => crate::ilog10_u64_mul_pow4;
+    ilog10_u64_mul_packed: NonZeroU64 => crate::ilog10_u64_mul_packed;
+    ilog10_u64_mul20: NonZeroU64 => crate::ilog10_u64_mul20;
+    ilog10_u64_swar: NonZeroU64 => crate::ilog10_u64_swar;
+    ilog10_u64_split: NonZeroU64 => arm32::ilog10_u64_split;
+    ilog1000_u32: NonZeroU32 => crate::ilog1000_u32;
+    ilog1000_u64: NonZeroU64 => crate::ilog1000_u64;
+}
+
+/// `ilog2_and_ilog10_u32`, for nonzero `x`.
+#[no_panic]
+pub fn ilog2_and_ilog10_u32(x: NonZeroU32) -> (u32, u32) {
+    crate::ilog2_and_ilog10_u32(x.get())
+}
+
+/// `ilog2_and_ilog10_u64`, for nonzero `x`.
+#[no_panic]
+pub fn ilog2_and_ilog10_u64(x: NonZeroU64) -> (u32, u32) {
+    crate::ilog2_and_ilog10_u64(x.get())
+}
+
+/// `ilog10_with_flag`, for nonzero `x`.
+#[no_panic]
+pub fn ilog10_with_flag(x: NonZeroU32) -> (u32, bool) {
+    crate::ilog10_with_flag(x.get())
+}
+
+/// `ilog10_u64_with_flag`, for nonzero `x`.
+#[no_panic]
+pub fn ilog10_u64_with_flag(x: NonZeroU64) -> (u32, bool) {
+    crate::ilog10_u64_with_flag(x.get())
+}
+
+/// `log10_with_rounding`, for nonzero `x`.
+#[no_panic]
+pub fn log10_with_rounding(x: NonZeroU64, mode: crate::Log10Rounding) -> u32 {
+    crate::log10_with_rounding(x.get(), mode)
+}
+
+/// `crate::nearest_power_of_ten`.
+#[no_panic]
+pub fn nearest_power_of_ten(x: u64) -> u64 {
+    crate::nearest_power_of_ten(x)
+}
+
+/// `neg_ilog10_of_reciprocal`, for nonzero `x`.
+#[no_panic]
+pub fn neg_ilog10_of_reciprocal(x: NonZeroU64) -> i32 {
+    crate::neg_ilog10_of_reciprocal(x.get())
+}
+
+/// `ilog10_ratio`, for nonzero `numerator` and `denominator`.
+#[no_panic]
+pub fn ilog10_ratio(numerator: NonZeroU64, denominator: NonZeroU64) -> i32 {
+    crate::ilog10_ratio(numerator.get(), denominator.get())
+}
+
+/// `crate::histogram::decade_bucket`.
+#[no_panic]
+pub fn decade_bucket(x: u64) -> u32 {
+    crate::histogram::decade_bucket(x)
+}
+
+/// `ilog10_product`, for nonzero `a` and `b`.
+#[no_panic]
+pub fn ilog10_product(a: NonZeroU64, b: NonZeroU64) -> u32 {
+    crate::ilog10_product(a.get(), b.get())
+}
+
+macro_rules! total {
+    ($($f:ident: $t:ty => $r:ty = $path:path;)*) => {
+        $(
+            #[doc = concat!("`", stringify!($path), "`.")]
+            #[no_panic]
+            pub fn $f(x: $t) -> $r {
+                $path(x)
+            }
+        )*
+    };
+}
+
+total! {
+    digits_u32: u32 => u32 = crate::digits_u32;
+    digits_u64: u64 => u32 = crate::digits_u64;
+    digits_u128: u128 => u32 = crate::digits_u128;
+    digits_u32x4: [u32; 4] => [u32; 4] = crate::digits_u32x4;
+    digits_u64x2: [u64; 2] => [u32; 2] = crate::digits_u64x2;
+    digits_and_pow10_u32: u32 => (u32, u32) = crate::digits_and_pow10_u32;
+    digits_and_pow10_u64: u64 => (u32, u64) = crate::digits_and_pow10_u64;
+    leading_digit_u32: u32 => u32 = crate::leading_digit_u32;
+    leading_digit_u64: u64 => u32 = crate::leading_digit_u64;
+    si_scale_u32: u32 => (u32, u32) = crate::si_scale_u32;
+    si_scale_u64: u64 => (u64, u32) = crate::si_scale_u64;
+    to_decimal_sci: u64 => (u64, u32) = crate::to_decimal_sci;
+    checked_encoded_len_with_prefix: u64 => Option<u64> = crate::checked_encoded_len_with_prefix;
+    checked_netstring_len: u64 => Option<u64> = crate::checked_netstring_len;
+    checked_ilog10_u32: u32 => Option<u32> = core_compat::checked_ilog10_u32;
+    checked_ilog10_u64: u64 => Option<u32> = core_compat::checked_ilog10_u64;
+    ilog10_ladder: u32 => u32 = mcu::ilog10_ladder;
+    ilog10_u64_ladder: u64 => u32 = mcu::ilog10_u64_ladder;
+    ilog10_debruijn: u32 => u32 = soft_clz::ilog10_debruijn;
+    ilog10_u64_debruijn: u64 => u32 = soft_clz::ilog10_u64_debruijn;
+    ilog10_bytes: u32 => u32 = soft_clz::ilog10_bytes;
+    ilog10_u64_bytes: u64 => u32 = soft_clz::ilog10_u64_bytes;
+    ilog10_f32: u32 => u32 = float::ilog10_f32;
+    ilog10_f64: u32 => u32 = float::ilog10_f64;
+    ilog10_u64_f64: u64 => u32 = float::ilog10_u64_f64;
+    ilog10_constant_time: u32 => u32 = constant_time::ilog10_u32;
+    ilog10_u64_constant_time: u64 => u32 = constant_time::ilog10_u64;
+}
+
+/// `crate::leading_n_digits_u32`.
+#[no_panic]
+pub fn leading_n_digits_u32(x: u32, n: u32) -> u32 {
+    crate::leading_n_digits_u32(x, n)
+}
+
+/// `crate::leading_n_digits_u64`.
+#[no_panic]
+pub fn leading_n_digits_u64(x: u64, n: u32) -> u64 {
+    crate::leading_n_digits_u64(x, n)
+}
+
+/// `crate::total_decimal_len`.
+#[no_panic]
+pub fn total_decimal_len(values: &[u64], separator: usize) -> usize {
+    crate::total_decimal_len(values, separator)
+}
+
+/// `crate::checked_round_sig_figs`.
+#[no_panic]
+pub fn checked_round_sig_figs(x: u64, figs: u32, rounding: crate::Rounding) -> Option<u64> {
+    crate::checked_round_sig_figs(x, figs, rounding)
+}
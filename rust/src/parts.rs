@@ -0,0 +1,119 @@
+//! The two halves most of the implementations here are built from,
+//! for trying a new guess against the shared correction and tables.
+//!
+//! A guess takes `lz = x.leading_zeros()` for a nonzero `x` and
+//! returns either `ilog10(x)` or one less; all of the guesses here are
+//! at most 8 (18 for u64), the last index of the threshold table.
+//! (`guess_ilog10_u64_over` is the exception: it is one more, if
+//! anything, and goes with `correct_u64_down`.) `correct` then adds one
+//! if `x` is past the guess's power of ten:
+//!
+//! ```
+//! use ilog::parts::{correct, guess_ilog10_u32_popcount};
+//!
+//! let x = 12345u32;
+//! assert_eq!(correct(guess_ilog10_u32_popcount(x.leading_zeros()), x), 4);
+//! ```
+//!
+//! The guesses expect `lz < 32` (`< 64` for u64), and the corrections
+//! panic on an out-of-range guess rather than reading past the table;
+//! with a guess that is provably in range, that check compiles away.
+
+/// The multiply guess of `ilog10_mul`: `ilog2(x) * 9 / 32`, as
+/// 9/32 is just under log10(2).
+#[inline]
+pub const fn guess_ilog10_u32(lz: u32) -> u32 {
+    (31 - lz).wrapping_mul(9) >> 5
+}
+
+/// The popcount guess of `ilog10`: count the powers of ten passed
+/// by `lz` bits of a mask. Two instructions, but popcount is only
+/// fast on some CPUs.
+#[inline]
+pub const fn guess_ilog10_u32_popcount(lz: u32) -> u32 {
+    crate::ilogpopc(lz)
+}
+
+/// A table lookup, one byte per value of `lz`. It is `log10_table_table`'s
+/// guess, except that it stops at 8 (the correction takes it to 9)
+/// so that it fits the same threshold table as the others.
+#[inline]
+pub const fn guess_ilog10_u32_table(lz: u32) -> u32 {
+    // Indexed by ilog2(x), i.e. 31 - lz.
+    const GUESS_TABLE: [u8; 32] = [
+        0, 0, 0, 0, 1, 1, 1, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 5, 5, 5, 6, 6, 6, 6, 7, 7, 7, 8, 8, 8,
+        8, 8,
+    ];
+    GUESS_TABLE[(31 - lz) as usize] as u32
+}
+
+/// The multiply guess of `ilog10_u64_mul`: `ilog2(x) * 19 / 64`.
+#[inline]
+pub const fn guess_ilog10_u64(lz: u32) -> u32 {
+    (63 - lz).wrapping_mul(19) >> 6
+}
+
+/// The guess of `ilog10_u64_mul20`: `(ilog2(x) * 5 + 4) >> 4`, which
+/// unlike the others is either `ilog10(x)` or one *more*, up to 19;
+/// pair it with `correct_u64_down`.
+#[inline]
+pub const fn guess_ilog10_u64_over(lz: u32) -> u32 {
+    ((63 - lz) * 5 + 4) >> 4
+}
+
+/// `guess + 1` if `x` has more than `guess + 1` digits, else `guess`.
+///
+/// Panics if `guess > 8`.
+#[inline]
+pub const fn correct(guess: u32, x: u32) -> u32 {
+    guess + (x > crate::statics::TEN_THRESHOLDS[guess as usize]) as u32
+}
+
+/// `correct`, with whether it added one: for counting how often a
+/// guess of one's own is short, or logging where.
+///
+/// ```
+/// use ilog::parts::{correct_with_flag, guess_ilog10_u32};
+///
+/// // 1000 and 999 have the same ilog2, 9, and so the same guess, 2.
+/// assert_eq!(correct_with_flag(guess_ilog10_u32(1000u32.leading_zeros()), 1000), (3, true));
+/// assert_eq!(correct_with_flag(guess_ilog10_u32(999u32.leading_zeros()), 999), (2, false));
+/// ```
+///
+/// Panics if `guess > 8`.
+#[inline]
+pub const fn correct_with_flag(guess: u32, x: u32) -> (u32, bool) {
+    let short = x > crate::statics::TEN_THRESHOLDS[guess as usize];
+    (guess + short as u32, short)
+}
+
+/// As `correct`, for u64. Panics if `guess > 18`.
+#[inline]
+pub const fn correct_u64(guess: u32, x: u64) -> u32 {
+    assert!(guess < 19, "guess out of range");
+    guess + (x > crate::u64_threshold(guess)) as u32
+}
+
+/// `guess - 1` if `x` is below 10^guess, else `guess`: the correction
+/// for a guess that is one over rather than one under, such as
+/// `guess_ilog10_u64_over`. Panics if `guess > 19`.
+///
+/// ```
+/// use ilog::parts::{correct_u64_down, guess_ilog10_u64_over};
+///
+/// // ilog2(999) = 9, and (9 * 5 + 4) >> 4 = 3.
+/// assert_eq!(correct_u64_down(guess_ilog10_u64_over(999u64.leading_zeros()), 999), 2);
+/// ```
+#[inline]
+pub const fn correct_u64_down(guess: u32, x: u64) -> u32 {
+    assert!(guess < 20, "guess out of range");
+    guess - (x < crate::u64_power(guess)) as u32
+}
+
+/// As `correct_with_flag`, for u64. Panics if `guess > 18`.
+#[inline]
+pub const fn correct_u64_with_flag(guess: u32, x: u64) -> (u32, bool) {
+    assert!(guess < 19, "guess out of range");
+    let short = x > crate::u64_threshold(guess);
+    (guess + short as u32, short)
+}
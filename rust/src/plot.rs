@@ -0,0 +1,175 @@
+// Grouped bar charts of `ilog bench` results as SVG, for `bench
+// --plot`. One chart per width and context; within it a group of bars
+// per input distribution, one bar per algorithm, their height the best
+// ns per op. The SVG is written out by hand: a few rectangles and
+// labels need no plotting library, and the file opens in any browser.
+
+use std::fmt::Write;
+
+use ilog::results::BenchResult;
+
+const WIDTH: f64 = 760.0;
+const CHART_HEIGHT: f64 = 280.0;
+// Room for the axis labels on the left and the title and distribution
+// names above and below each chart.
+const LEFT: f64 = 64.0;
+const RIGHT: f64 = 16.0;
+const TOP: f64 = 36.0;
+const BOTTOM: f64 = 40.0;
+const LEGEND_ROW: f64 = 20.0;
+
+// One colour per algorithm, in order of first appearance.
+const COLOURS: [&str; 10] = [
+    "#4e79a7", "#f28e2b", "#e15759", "#76b7b2", "#59a14f", "#edc948", "#b07aa1", "#ff9da7",
+    "#9c755f", "#bab0ac",
+];
+
+/// The SVG document for `results`.
+pub fn svg(results: &[BenchResult]) -> String {
+    let mut algorithms: Vec<&str> = Vec::new();
+    let mut charts: Vec<(String, Vec<&BenchResult>)> = Vec::new();
+    for r in results {
+        if !algorithms.contains(&r.algorithm.as_str()) {
+            algorithms.push(&r.algorithm);
+        }
+        let title = format!("{} ({})", r.width.name(), r.context.name());
+        match charts.iter_mut().find(|(t, _)| *t == title) {
+            Some((_, rows)) => rows.push(r),
+            None => charts.push((title, vec![r])),
+        }
+    }
+    let legend_rows = algorithms.len().div_ceil(4) as f64;
+    let height = charts.len() as f64 * CHART_HEIGHT + legend_rows * LEGEND_ROW + 16.0;
+
+    let mut out = String::new();
+    let w = &mut out;
+    writeln!(
+        w,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{WIDTH}" height="{height}" viewBox="0 0 {WIDTH} {height}" font-family="sans-serif" font-size="12">"#
+    )
+    .unwrap();
+    writeln!(w, r#"<rect width="100%" height="100%" fill="white"/>"#).unwrap();
+    for (i, name) in algorithms.iter().enumerate() {
+        let (x, y) = (LEFT + (i % 4) as f64 * 170.0, 8.0 + (i / 4) as f64 * LEGEND_ROW);
+        let colour = COLOURS[i % COLOURS.len()];
+        writeln!(w, r#"<rect x="{x}" y="{y}" width="12" height="12" fill="{colour}"/>"#).unwrap();
+        writeln!(w, r#"<text x="{}" y="{}">{}</text>"#, x + 18.0, y + 10.0, escape(name)).unwrap();
+    }
+    let mut top = legend_rows * LEGEND_ROW + 16.0;
+    for (title, rows) in &charts {
+        chart(w, top, title, rows, &algorithms);
+        top += CHART_HEIGHT;
+    }
+    writeln!(w, "</svg>").unwrap();
+    out
+}
+
+fn chart(w: &mut String, top: f64, title: &str, rows: &[&BenchResult], algorithms: &[&str]) {
+    let mut distributions = Vec::new();
+    for r in rows {
+        if !distributions.contains(&r.distribution) {
+            distributions.push(r.distribution);
+        }
+    }
+    let (x0, y0) = (LEFT, top + TOP);
+    let (plot_width, plot_height) = (WIDTH - LEFT - RIGHT, CHART_HEIGHT - TOP - BOTTOM);
+    let max = rows.iter().map(|r| r.ns_per_op).fold(0.0, f64::max);
+    let step = tick_step(max);
+    let scale = plot_height / (step * (max / step).ceil()).max(f64::MIN_POSITIVE);
+
+    writeln!(
+        w,
+        r#"<text x="{}" y="{}" text-anchor="middle" font-weight="bold">{}</text>"#,
+        x0 + plot_width / 2.0,
+        top + 20.0,
+        escape(title)
+    )
+    .unwrap();
+    // Grid lines and labels for the y axis.
+    let mut tick = 0.0;
+    while tick * scale <= plot_height + 0.5 {
+        let y = y0 + plot_height - tick * scale;
+        writeln!(
+            w,
+            r##"<line x1="{x0}" y1="{y:.1}" x2="{}" y2="{y:.1}" stroke="#ddd"/>"##,
+            x0 + plot_width
+        )
+        .unwrap();
+        writeln!(
+            w,
+            r#"<text x="{}" y="{:.1}" text-anchor="end">{}</text>"#,
+            x0 - 6.0,
+            y + 4.0,
+            trim(tick)
+        )
+        .unwrap();
+        tick += step;
+    }
+    writeln!(
+        w,
+        r#"<text transform="translate(14 {:.1}) rotate(-90)" text-anchor="middle">ns per op</text>"#,
+        y0 + plot_height / 2.0
+    )
+    .unwrap();
+
+    let group = plot_width / distributions.len().max(1) as f64;
+    let bar = group * 0.8 / algorithms.len() as f64;
+    for (g, &distribution) in distributions.iter().enumerate() {
+        let left = x0 + g as f64 * group + group * 0.1;
+        for r in rows.iter().filter(|r| r.distribution == distribution) {
+            let i = algorithms.iter().position(|&a| a == r.algorithm).unwrap();
+            let h = r.ns_per_op * scale;
+            writeln!(
+                w,
+                r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{h:.1}" fill="{}"><title>{}: {:.2} ns</title></rect>"#,
+                left + i as f64 * bar,
+                y0 + plot_height - h,
+                bar * 0.9,
+                COLOURS[i % COLOURS.len()],
+                escape(&r.algorithm),
+                r.ns_per_op
+            )
+            .unwrap();
+        }
+        writeln!(
+            w,
+            r#"<text x="{:.1}" y="{:.1}" text-anchor="middle">{}</text>"#,
+            x0 + (g as f64 + 0.5) * group,
+            y0 + plot_height + 18.0,
+            distribution.name()
+        )
+        .unwrap();
+    }
+    writeln!(
+        w,
+        r#"<line x1="{x0}" y1="{y}" x2="{}" y2="{y}" stroke="black"/>"#,
+        x0 + plot_width,
+        y = y0 + plot_height
+    )
+    .unwrap();
+}
+
+// A 1, 2 or 5 times a power of ten giving four to ten ticks up to `max`.
+fn tick_step(max: f64) -> f64 {
+    if max <= 0.0 {
+        return 1.0;
+    }
+    let power = 10f64.powf((max / 4.0).log10().floor());
+    [1.0, 2.0, 5.0, 10.0]
+        .into_iter()
+        .map(|m| m * power)
+        .find(|&step| max / step <= 10.0)
+        .unwrap()
+}
+
+// A tick label without the float noise, as "0.2" rather than
+// "0.20000000000000004".
+fn trim(x: f64) -> String {
+    let s = format!("{x:.3}");
+    s.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+/// `s` as SVG or HTML text.
+pub fn escape(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
@@ -0,0 +1,175 @@
+// Implementations loaded from shared libraries with `--plugin
+// PATH:SYMBOL`, for trying out a variant without patching this crate.
+//
+// The ABI is one C function per candidate:
+//
+//     uint32_t SYMBOL(uint64_t x);
+//
+// returning floor(log10(x)). It is never called with 0, and u32 inputs
+// are passed zero-extended. Each symbol is registered under its own
+// name at both widths (`u32/SYMBOL` and `u64/SYMBOL`), so `--filter
+// SYMBOL` picks it out in `test`, `test --u64` and `bench`; see
+// csrc/example_plugin.c.
+//
+// The registry wants plain fn pointers, so every plugin gets a slot
+// here and the entries point at trampolines monomorphized per slot.
+// Unlike the built-in loops these pay for an indirect call per value,
+// which is worth remembering when comparing times.
+
+use std::sync::OnceLock;
+
+use ilog::bench::{
+    eval_u64, format_u32, format_u64, pressure_u32, pressure_u64, run_u32, run_u64, serialize_u32,
+    serialize_u64, Input,
+};
+use ilog::registry::{self, Algorithm, Width};
+
+type Candidate = extern "C" fn(u64) -> u32;
+
+const SLOTS: usize = 8;
+
+static FUNCTIONS: [OnceLock<Candidate>; SLOTS] = [const { OnceLock::new() }; SLOTS];
+
+fn function<const N: usize>() -> Candidate {
+    *FUNCTIONS[N]
+        .get()
+        .expect("plugin slot is filled before registering")
+}
+
+fn eval<const N: usize>(x: u64) -> u32 {
+    function::<N>()(x)
+}
+
+fn eval_all<const N: usize>(values: &[u32], out: &mut [u32]) {
+    let f = function::<N>();
+    eval_u64(values, out, |x| f(x))
+}
+
+fn run32<const N: usize>(input: &Input) {
+    let f = function::<N>();
+    run_u32(input, |x| f(x as u64))
+}
+
+fn run64<const N: usize>(input: &Input) {
+    let f = function::<N>();
+    run_u64(input, |x| f(x))
+}
+
+fn format32<const N: usize>(input: &Input) {
+    let f = function::<N>();
+    format_u32(input, |x| f(x as u64))
+}
+
+fn format64<const N: usize>(input: &Input) {
+    let f = function::<N>();
+    format_u64(input, |x| f(x))
+}
+
+fn serialize32<const N: usize>(input: &Input) {
+    let f = function::<N>();
+    serialize_u32(input, |x| f(x as u64))
+}
+
+fn serialize64<const N: usize>(input: &Input) {
+    let f = function::<N>();
+    serialize_u64(input, |x| f(x))
+}
+
+fn pressure32<const N: usize>(input: &Input) {
+    let f = function::<N>();
+    pressure_u32(input, |x| f(x as u64))
+}
+
+fn pressure64<const N: usize>(input: &Input) {
+    let f = function::<N>();
+    pressure_u64(input, |x| f(x))
+}
+
+// The u32 and u64 entries for slot N.
+fn entries<const N: usize>(name: &'static str) -> [Algorithm; 2] {
+    [
+        Algorithm {
+            name,
+            width: Width::U32,
+            eval: eval::<N>,
+            eval_all: eval_all::<N>,
+            run: run32::<N>,
+            format: format32::<N>,
+            serialize: serialize32::<N>,
+            pressure: pressure32::<N>,
+        },
+        Algorithm {
+            name,
+            width: Width::U64,
+            eval: eval::<N>,
+            eval_all: eval_all::<N>,
+            run: run64::<N>,
+            format: format64::<N>,
+            serialize: serialize64::<N>,
+            pressure: pressure64::<N>,
+        },
+    ]
+}
+
+const ENTRIES: [fn(&'static str) -> [Algorithm; 2]; SLOTS] = [
+    entries::<0>,
+    entries::<1>,
+    entries::<2>,
+    entries::<3>,
+    entries::<4>,
+    entries::<5>,
+    entries::<6>,
+    entries::<7>,
+];
+
+/// Loads each `PATH:SYMBOL` and registers the symbols. The libraries
+/// stay loaded until the process exits.
+pub fn load(specs: &[String]) -> Result<(), String> {
+    if specs.is_empty() {
+        return Ok(());
+    }
+    if specs.len() > SLOTS {
+        return Err(format!("at most {SLOTS} --plugin functions are supported"));
+    }
+    let mut algorithms = Vec::new();
+    for (slot, spec) in specs.iter().enumerate() {
+        let (path, symbol) = spec
+            .rsplit_once(':')
+            .filter(|(path, symbol)| !path.is_empty() && !symbol.is_empty())
+            .ok_or_else(|| format!("--plugin {spec:?}: expected PATH:SYMBOL"))?;
+        if registry::all().any(|a| a.name == symbol)
+            || algorithms.iter().any(|a: &Algorithm| a.name == symbol)
+        {
+            return Err(format!("--plugin {spec:?}: {symbol} is already registered"));
+        }
+        // SAFETY: loading runs the library's initializers, and the
+        // symbol is trusted to have the documented signature; both are
+        // the point of asking for a plugin.
+        let f = unsafe {
+            let library = libloading::Library::new(path).map_err(|e| failed(spec, e))?;
+            let f: Candidate = *library
+                .get::<Candidate>(symbol.as_bytes())
+                .map_err(|e| failed(spec, e))?;
+            // The fn pointer is only valid while the library is loaded.
+            std::mem::forget(library);
+            f
+        };
+        FUNCTIONS[slot]
+            .set(f)
+            .map_err(|_| "plugins loaded twice".to_string())?;
+        algorithms.extend(ENTRIES[slot](String::leak(symbol.to_string())));
+    }
+    if !registry::register(algorithms) {
+        return Err("plugins loaded twice".into());
+    }
+    Ok(())
+}
+
+// libloading's own message is only "dlopen failed"; the reason is in
+// its source.
+fn failed(spec: &str, e: libloading::Error) -> String {
+    match std::error::Error::source(&e) {
+        Some(reason) => format!("--plugin {spec:?}: {e}: {reason}"),
+        None => format!("--plugin {spec:?}: {e}"),
+    }
+}
@@ -0,0 +1,89 @@
+//! `ilog2`, `ilog10` and their `checked_` forms as trait methods on
+//! `u32` and `u64`, for toolchains from before they were stable
+//! (1.67), enabled by the `polyfill` feature. `ilog10` is this crate's
+//! `ilog10_mul` / `ilog10_u64_mul`, with the standard library's
+//! handling of 0, and `ilog2` is computed from `leading_zeros`.
+//!
+//! Code written against the standard methods needs only the import:
+//!
+//! ```
+//! #[allow(unused_imports)] // the inherent methods win from 1.67 on
+//! use ilog::polyfill::IntLog;
+//!
+//! assert_eq!(12345u32.ilog10(), 4);
+//! assert_eq!(u64::MAX.ilog2(), 63);
+//! assert_eq!(0u64.checked_ilog10(), None);
+//! ```
+//!
+//! On a newer toolchain the inherent methods take precedence, so the
+//! same code calls the standard library's versions there.
+
+use crate::core_compat;
+
+/// The integer logarithm methods of `u32` and `u64`.
+pub trait IntLog: Sized {
+    /// floor(log2(self)); panics if `self` is 0.
+    fn ilog2(self) -> u32;
+    /// floor(log10(self)); panics if `self` is 0.
+    fn ilog10(self) -> u32;
+    /// floor(log2(self)), or `None` for 0.
+    fn checked_ilog2(self) -> Option<u32>;
+    /// floor(log10(self)), or `None` for 0.
+    fn checked_ilog10(self) -> Option<u32>;
+}
+
+impl IntLog for u32 {
+    #[inline]
+    #[track_caller]
+    fn ilog2(self) -> u32 {
+        crate::ilog2_u32(self)
+    }
+
+    #[inline]
+    #[track_caller]
+    fn ilog10(self) -> u32 {
+        core_compat::ilog10_u32(self)
+    }
+
+    #[inline]
+    fn checked_ilog2(self) -> Option<u32> {
+        if self == 0 {
+            None
+        } else {
+            Some(31 - self.leading_zeros())
+        }
+    }
+
+    #[inline]
+    fn checked_ilog10(self) -> Option<u32> {
+        core_compat::checked_ilog10_u32(self)
+    }
+}
+
+impl IntLog for u64 {
+    #[inline]
+    #[track_caller]
+    fn ilog2(self) -> u32 {
+        crate::ilog2_u64(self)
+    }
+
+    #[inline]
+    #[track_caller]
+    fn ilog10(self) -> u32 {
+        core_compat::ilog10_u64(self)
+    }
+
+    #[inline]
+    fn checked_ilog2(self) -> Option<u32> {
+        if self == 0 {
+            None
+        } else {
+            Some(63 - self.leading_zeros())
+        }
+    }
+
+    #[inline]
+    fn checked_ilog10(self) -> Option<u32> {
+        core_compat::checked_ilog10_u64(self)
+    }
+}
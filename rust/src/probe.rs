@@ -0,0 +1,185 @@
+//! `#[no_mangle]` exports of the algorithms, compiled in only with
+//! `--cfg ilog_probe` for `ilog asm` to find in the object code. Each is
+//! the function inlined into a wrapper, as it would be at a call site;
+//! none of them are meant to be called.
+
+macro_rules! probe {
+    ($export:ident, $t:ty, $f:path) => {
+        #[no_mangle]
+        pub fn $export(x: $t) -> u32 {
+            #[allow(unused_unsafe)]
+            // SAFETY: never called; the unsafe ones are only here to
+            // be disassembled.
+            unsafe {
+                $f(x)
+            }
+        }
+    };
+}
+
+probe!(ilog_probe_ilog10, u32, crate::ilog10);
+probe!(ilog_probe_ilog10_mul, u32, crate::ilog10_mul);
+probe!(ilog_probe_ilog10_mul_alt, u32, crate::ilog10_mul_alt);
+probe!(ilog_probe_log10_table_table, u32, crate::log10_table_table);
+probe!(ilog_probe_ilog10_u32, u32, crate::ilog10_u32);
+probe!(ilog_probe_ilog10_u64_mul, u64, crate::ilog10_u64_mul);
+probe!(ilog_probe_ilog10_mul_pow, u32, crate::ilog10_mul_pow);
+probe!(ilog_probe_ilog10_mul_loop, u32, crate::ilog10_mul_loop);
+probe!(ilog_probe_ilog10_mul_pow4, u32, crate::ilog10_mul_pow4);
+probe!(ilog_probe_ilog10_mul_packed, u32, crate::ilog10_mul_packed);
+probe!(ilog_probe_ilog10_u64_mul_pow, u64, crate::ilog10_u64_mul_pow);
+probe!(ilog_probe_ilog10_u64_mul_loop, u64, crate::ilog10_u64_mul_loop);
+probe!(ilog_probe_ilog10_u64_mul_pow4, u64, crate::ilog10_u64_mul_pow4);
+probe!(ilog_probe_ilog10_u64_mul_packed, u64, crate::ilog10_u64_mul_packed);
+probe!(ilog_probe_ilog10_u64_mul20, u64, crate::ilog10_u64_mul20);
+probe!(ilog_probe_ilog10_swar, u32, crate::ilog10_swar);
+probe!(ilog_probe_ilog10_u64_swar, u64, crate::ilog10_u64_swar);
+probe!(ilog_probe_soft_clz_ilog10_debruijn, u32, crate::soft_clz::ilog10_debruijn);
+probe!(ilog_probe_soft_clz_ilog10_u64_debruijn, u64, crate::soft_clz::ilog10_u64_debruijn);
+probe!(ilog_probe_soft_clz_ilog10_bytes, u32, crate::soft_clz::ilog10_bytes);
+probe!(ilog_probe_soft_clz_ilog10_u64_bytes, u64, crate::soft_clz::ilog10_u64_bytes);
+probe!(ilog_probe_constant_time_ilog10_u32, u32, crate::constant_time::ilog10_u32);
+probe!(ilog_probe_constant_time_ilog10_u64, u64, crate::constant_time::ilog10_u64);
+probe!(ilog_probe_float_ilog10_f32, u32, crate::float::ilog10_f32);
+probe!(ilog_probe_float_ilog10_f64, u32, crate::float::ilog10_f64);
+probe!(ilog_probe_float_ilog10_u64_f64, u64, crate::float::ilog10_u64_f64);
+probe!(ilog_probe_arm32_ilog10_u64_split, u64, crate::arm32::ilog10_u64_split);
+probe!(ilog_probe_mcu_ilog10_ladder, u32, crate::mcu::ilog10_ladder);
+probe!(ilog_probe_mcu_ilog10_u64_ladder, u64, crate::mcu::ilog10_u64_ladder);
+probe!(ilog_probe_digits_u32, u32, crate::digits_u32);
+probe!(ilog_probe_digits_u64, u64, crate::digits_u64);
+probe!(ilog_probe_leading_digit_u32, u32, crate::leading_digit_u32);
+probe!(ilog_probe_leading_digit_u64, u64, crate::leading_digit_u64);
+probe!(ilog_probe_ilog1000_u32, u32, crate::ilog1000_u32);
+probe!(ilog_probe_ilog1000_u64, u64, crate::ilog1000_u64);
+
+// The functions that return more than one value.
+#[no_mangle]
+pub fn ilog_probe_ilog2_and_ilog10_u32(x: u32) -> (u32, u32) {
+    crate::ilog2_and_ilog10_u32(x)
+}
+
+#[no_mangle]
+pub fn ilog_probe_ilog2_and_ilog10_u64(x: u64) -> (u32, u32) {
+    crate::ilog2_and_ilog10_u64(x)
+}
+
+#[no_mangle]
+pub fn ilog_probe_ilog10_with_flag(x: u32) -> (u32, bool) {
+    crate::ilog10_with_flag(x)
+}
+
+#[no_mangle]
+pub fn ilog_probe_ilog10_u64_with_flag(x: u64) -> (u32, bool) {
+    crate::ilog10_u64_with_flag(x)
+}
+
+#[no_mangle]
+pub fn ilog_probe_digits_and_pow10_u32(x: u32) -> (u32, u32) {
+    crate::digits_and_pow10_u32(x)
+}
+
+#[no_mangle]
+pub fn ilog_probe_digits_and_pow10_u64(x: u64) -> (u32, u64) {
+    crate::digits_and_pow10_u64(x)
+}
+
+#[no_mangle]
+pub fn ilog_probe_si_scale_u32(x: u32) -> (u32, u32) {
+    crate::si_scale_u32(x)
+}
+
+#[no_mangle]
+pub fn ilog_probe_si_scale_u64(x: u64) -> (u64, u32) {
+    crate::si_scale_u64(x)
+}
+
+#[no_mangle]
+pub fn ilog_probe_to_decimal_sci(x: u64) -> (u64, u32) {
+    crate::to_decimal_sci(x)
+}
+
+#[no_mangle]
+pub fn ilog_probe_checked_encoded_len_with_prefix(payload_len: u64) -> Option<u64> {
+    crate::checked_encoded_len_with_prefix(payload_len)
+}
+
+#[no_mangle]
+pub fn ilog_probe_digits_u32x4(x: [u32; 4]) -> [u32; 4] {
+    crate::digits_u32x4(x)
+}
+
+#[no_mangle]
+pub fn ilog_probe_digits_u64x2(x: [u64; 2]) -> [u32; 2] {
+    crate::digits_u64x2(x)
+}
+
+// And those that take more than one.
+#[no_mangle]
+pub fn ilog_probe_ilog10_product(a: u64, b: u64) -> u32 {
+    crate::ilog10_product(a, b)
+}
+
+#[no_mangle]
+pub fn ilog_probe_leading_n_digits_u32(x: u32, n: u32) -> u32 {
+    crate::leading_n_digits_u32(x, n)
+}
+
+#[no_mangle]
+pub fn ilog_probe_leading_n_digits_u64(x: u64, n: u32) -> u64 {
+    crate::leading_n_digits_u64(x, n)
+}
+
+#[no_mangle]
+pub fn ilog_probe_round_sig_figs(x: u64, figs: u32, rounding: crate::Rounding) -> u64 {
+    crate::round_sig_figs(x, figs, rounding)
+}
+
+#[no_mangle]
+pub fn ilog_probe_log10_with_rounding(x: u64, mode: crate::Log10Rounding) -> u32 {
+    crate::log10_with_rounding(x, mode)
+}
+
+#[no_mangle]
+pub fn ilog_probe_nearest_power_of_ten(x: u64) -> u64 {
+    crate::nearest_power_of_ten(x)
+}
+
+#[no_mangle]
+pub fn ilog_probe_neg_ilog10_of_reciprocal(x: u64) -> i32 {
+    crate::neg_ilog10_of_reciprocal(x)
+}
+
+#[no_mangle]
+pub fn ilog_probe_ilog10_ratio(numerator: u64, denominator: u64) -> i32 {
+    crate::ilog10_ratio(numerator, denominator)
+}
+
+#[no_mangle]
+pub fn ilog_probe_decade_bucket(x: u64) -> u32 {
+    crate::histogram::decade_bucket(x)
+}
+
+#[no_mangle]
+pub fn ilog_probe_digits_in_radix(x: u64, radix: u32) -> u32 {
+    crate::digits_in_radix(x, radix)
+}
+
+#[no_mangle]
+pub fn ilog_probe_total_decimal_len(values: &[u64], separator: usize) -> usize {
+    crate::total_decimal_len(values, separator)
+}
+
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+probe!(ilog_probe_asm_ilog10_u32, u32, crate::asm::ilog10_u32);
+#[cfg(all(
+    any(target_arch = "x86_64", target_arch = "aarch64"),
+    not(feature = "small-tables")
+))]
+probe!(ilog_probe_asm_ilog10_u64, u64, crate::asm::ilog10_u64);
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+probe!(ilog_probe_lowering_branch, u32, crate::lowering::ilog10_branch);
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+probe!(ilog_probe_lowering_cmov, u32, crate::lowering::ilog10_cmov);
+#[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+probe!(ilog_probe_lowering_sub_shift, u32, crate::lowering::ilog10_sub_shift);
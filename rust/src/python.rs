@@ -0,0 +1,134 @@
+//! Python bindings, enabled by the `python` feature.
+//!
+//! `maturin develop --release` builds and installs the `ilog` module
+//! into the current virtualenv (pyproject.toml selects the feature).
+//! The array functions take one-dimensional numpy `uint64` arrays, so
+//! a dataframe column can be checked or timed with
+//! `ilog.bench(df["id"].to_numpy(dtype="uint64"))`.
+
+use std::collections::HashMap;
+
+use numpy::{IntoPyArray, PyArray1, PyReadonlyArray1};
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+use crate::bench::Input;
+use crate::registry::{self, Width};
+
+/// floor(log10(x)); raises ValueError for 0.
+#[pyfunction]
+fn ilog10_u64(x: u64) -> PyResult<u32> {
+    if x == 0 {
+        return Err(PyValueError::new_err("ilog10 of 0 is undefined"));
+    }
+    Ok(crate::ilog10_u64_mul(x))
+}
+
+/// Number of decimal digits in x, counting 0 as one digit.
+#[pyfunction]
+fn digits_u64(x: u64) -> u32 {
+    crate::digits_u64(x)
+}
+
+/// Digit counts of every value in a uint64 array.
+#[pyfunction]
+fn digits<'py>(py: Python<'py>, values: PyReadonlyArray1<'py, u64>) -> Bound<'py, PyArray1<u32>> {
+    let values = values.as_array();
+    values
+        .iter()
+        .map(|&x| crate::digits_u64(x))
+        .collect::<Vec<u32>>()
+        .into_pyarray(py)
+}
+
+fn parse_width(width: &str) -> PyResult<Width> {
+    match width {
+        "u32" => Ok(Width::U32),
+        "u64" => Ok(Width::U64),
+        _ => Err(PyValueError::new_err(format!("unknown width {width:?}"))),
+    }
+}
+
+// Short arrays are replayed so that each timing covers at least this
+// many calls; the timer only has microsecond resolution.
+const MIN_OPS: usize = 1 << 26;
+
+// ilog10 is undefined for 0, and most of the algorithms panic on it
+// (those that need a nonzero argument return 0 instead), so zeros are
+// rejected up front.
+fn check_values(values: &[u64], width: Width) -> PyResult<()> {
+    if values.contains(&0) {
+        return Err(PyValueError::new_err("values must be nonzero"));
+    }
+    if width == Width::U32 && values.iter().any(|&x| x > u32::MAX as u64) {
+        return Err(PyValueError::new_err("value does not fit in a u32"));
+    }
+    Ok(())
+}
+
+fn to_input(values: &[u64], width: Width) -> PyResult<Input> {
+    check_values(values, width)?;
+    let passes = MIN_OPS.div_ceil(values.len().max(1));
+    Ok(match width {
+        Width::U32 => Input::U32 {
+            values: values.iter().map(|&x| x as u32).collect(),
+            passes,
+        },
+        Width::U64 => Input::U64 {
+            values: values.to_vec(),
+            passes,
+        },
+    })
+}
+
+/// Number of mismatches against the standard library for every
+/// registered implementation of the given width.
+#[pyfunction]
+#[pyo3(signature = (values, width = "u64"))]
+fn verify(values: PyReadonlyArray1<'_, u64>, width: &str) -> PyResult<HashMap<String, u64>> {
+    let width = parse_width(width)?;
+    let values = values.as_array().to_vec();
+    check_values(&values, width)?;
+    Ok(registry::of_width(width)
+        .map(|a| {
+            let bad = values.iter().filter(|&&x| (a.eval)(x) != x.ilog10()).count();
+            (a.qualified_name(), bad as u64)
+        })
+        .collect())
+}
+
+/// Best-of-`repetitions` nanoseconds per call for every registered
+/// implementation of the given width, over the given values.
+#[pyfunction(name = "bench")]
+#[pyo3(signature = (values, width = "u64", repetitions = 3))]
+fn benchmark(
+    py: Python<'_>,
+    values: PyReadonlyArray1<'_, u64>,
+    width: &str,
+    repetitions: usize,
+) -> PyResult<HashMap<String, f64>> {
+    let width = parse_width(width)?;
+    let input = to_input(&values.as_array().to_vec(), width)?;
+    Ok(py.detach(|| {
+        registry::of_width(width)
+            .map(|a| {
+                let best = (0..repetitions.max(1))
+                    .map(|_| a.time(&input))
+                    .min()
+                    .unwrap();
+                let ns = best as f64 * 1000.0 / input.ops() as f64;
+                (a.qualified_name(), ns)
+            })
+            .collect()
+    }))
+}
+
+#[pymodule]
+fn ilog(m: &Bound<'_, PyModule>) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(ilog10_u64, m)?)?;
+    m.add_function(wrap_pyfunction!(digits_u64, m)?)?;
+    m.add_function(wrap_pyfunction!(digits, m)?)?;
+    m.add_function(wrap_pyfunction!(verify, m)?)?;
+    m.add_function(wrap_pyfunction!(benchmark, m)?)?;
+    Ok(())
+}
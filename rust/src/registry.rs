@@ -0,0 +1,272 @@
+//! The set of implementations known to the benchmark and test modes,
+//! looked up by name.
+//!
+//! Each entry carries its own monomorphized benchmark loop rather than
+//! a plain function pointer: calling through a pointer in the timed
+//! loop would measure the indirect call instead of the ilog10.
+
+use serde::{Deserialize, Serialize};
+
+use crate::bench::{
+    eval_u32, eval_u64, format_u32, format_u64, prepare, pressure_u32, pressure_u64, run_u32,
+    run_u32_slices, run_u64, run_u64_slices, serialize_u32, serialize_u64, Context, Input,
+};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Width {
+    U32,
+    U64,
+}
+
+impl Width {
+    pub const ALL: [Width; 2] = [Width::U32, Width::U64];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Width::U32 => "u32",
+            Width::U64 => "u64",
+        }
+    }
+}
+
+/// One implementation, by name and width. Any input is safe to pass,
+/// 0 included: the entries that need a nonzero argument are given one,
+/// and return 0 for 0, while the rest give 0 or panic as they would if
+/// called directly.
+///
+/// ```
+/// use ilog::bench::Input;
+/// use ilog::registry::{Width, ALGORITHMS};
+///
+/// let wrapped = ALGORITHMS.iter().filter(|algorithm| {
+///     matches!(algorithm.name, "popcount" | "asm" | "dispatch" | "mul_multiversion")
+///         || algorithm.name.starts_with("c_")
+/// });
+/// for algorithm in wrapped {
+///     assert_eq!((algorithm.eval)(0), 0, "{}", algorithm.qualified_name());
+///     let mut out = [1];
+///     (algorithm.eval_all)(&[0], &mut out);
+///     assert_eq!(out, [0], "{}", algorithm.qualified_name());
+///     let input = match algorithm.width {
+///         Width::U32 => Input::U32 { values: vec![0, 7], passes: 1 },
+///         Width::U64 => Input::U64 { values: vec![0, 7], passes: 1 },
+///     };
+///     (algorithm.run)(&input);
+/// }
+/// ```
+pub struct Algorithm {
+    pub name: &'static str,
+    pub width: Width,
+    /// The implementation, widened to take a u64 for checking.
+    pub eval: fn(u64) -> u32,
+    /// The implementation over a slice of u32s, for the exhaustive
+    /// check; a loop of its own, like `run`, where calling `eval` would
+    /// be an indirect call per value.
+    pub eval_all: fn(&[u32], &mut [u32]),
+    /// Runs the implementation over every value of `input`.
+    pub run: fn(&Input),
+    /// Formats every value of `input` as decimal with the help of the
+    /// implementation; see `Context::Format`.
+    pub format: fn(&Input),
+    /// Writes every value of `input` into one text buffer with the
+    /// help of the implementation; see `Context::Serialize`.
+    pub serialize: fn(&Input),
+    /// Runs the implementation over `input` under cache pressure; see
+    /// `Context::Pressure`.
+    pub pressure: fn(&Input),
+}
+
+macro_rules! u32_algorithm {
+    ($name:expr, $f:expr) => {
+        Algorithm {
+            name: $name,
+            width: Width::U32,
+            eval: |x| $f(x as u32),
+            eval_all: |values, out| eval_u32(values, out, $f),
+            run: |input| run_u32(input, $f),
+            format: |input| format_u32(input, $f),
+            serialize: |input| serialize_u32(input, $f),
+            pressure: |input| pressure_u32(input, $f),
+        }
+    };
+}
+
+macro_rules! u64_algorithm {
+    ($name:expr, $f:expr) => {
+        Algorithm {
+            name: $name,
+            width: Width::U64,
+            eval: $f,
+            eval_all: |values, out| eval_u64(values, out, $f),
+            run: |input| run_u64(input, $f),
+            format: |input| format_u64(input, $f),
+            serialize: |input| serialize_u64(input, $f),
+            pressure: |input| pressure_u64(input, $f),
+        }
+    };
+}
+
+impl Algorithm {
+    /// The name qualified by width, e.g. `u64/mul`.
+    pub fn qualified_name(&self) -> String {
+        format!("{}/{}", self.width.name(), self.name)
+    }
+
+    /// Microseconds taken to run over `input`.
+    pub fn time(&self, input: &Input) -> u128 {
+        self.time_in(input, Context::Standalone)
+    }
+
+    /// Microseconds taken to run over `input` in the given context.
+    pub fn time_in(&self, input: &Input, context: Context) -> u128 {
+        let run = match context {
+            Context::Standalone => self.run,
+            Context::Format => self.format,
+            Context::Serialize => self.serialize,
+            Context::Pressure => self.pressure,
+        };
+        prepare(context);
+        let start = std::time::Instant::now();
+        run(input);
+        start.elapsed().as_micros()
+    }
+}
+
+pub const ALGORITHMS: &[Algorithm] = &[
+    // The registry is public, so the entries that need a nonzero
+    // argument are given `x | 1`: it is never 0, and has the same
+    // ilog10 as `x` whenever `x` is nonzero, since no power of ten but
+    // 1 is odd. So they return 0 for 0.
+    //
+    // SAFETY: `x | 1` is nonzero.
+    u32_algorithm!("popcount", |x| unsafe { crate::popcount::ilog10_u32_popcount(x | 1) }),
+    u32_algorithm!("mul", crate::mul_shift::ilog10_u32_mul),
+    u32_algorithm!("mul_alt", crate::mul_shift::ilog10_u32_mul_alt),
+    u32_algorithm!("table", crate::table::ilog10_u32_table),
+    u32_algorithm!("stdlib", crate::ilog10_u32),
+    u32_algorithm!("mul_pow", crate::mul_shift::ilog10_u32_mul_pow),
+    u32_algorithm!("mul_loop", crate::mul_shift::ilog10_u32_mul_loop),
+    u32_algorithm!("mul_pow4", crate::mul_shift::ilog10_u32_mul_pow4),
+    u32_algorithm!("mul_packed", crate::mul_shift::ilog10_u32_mul_packed),
+    u32_algorithm!("swar", crate::swar::ilog10_u32_swar),
+    u32_algorithm!("debruijn", crate::soft_clz::ilog10_debruijn),
+    u32_algorithm!("bytes", crate::soft_clz::ilog10_bytes),
+    u32_algorithm!("constant_time", crate::constant_time::ilog10_u32),
+    u32_algorithm!("f32", crate::float::ilog10_f32),
+    u32_algorithm!("f64", crate::float::ilog10_f64),
+    u32_algorithm!("ladder", crate::mcu::ilog10_ladder),
+    // Timed a slice at a time; the other contexts take one value at a
+    // time, so they get a one-element slice.
+    Algorithm {
+        eval_all: crate::simd::ilog10_u32_slice,
+        run: |input| run_u32_slices(input, crate::simd::ilog10_u32_slice),
+        ..u32_algorithm!("slice", |x| {
+            let mut out = [0];
+            crate::simd::ilog10_u32_slice(&[x], &mut out);
+            out[0]
+        })
+    },
+    u64_algorithm!("mul", crate::mul_shift::ilog10_u64_mul),
+    u64_algorithm!("stdlib", u64::ilog10),
+    u64_algorithm!("mul_pow", crate::mul_shift::ilog10_u64_mul_pow),
+    u64_algorithm!("mul_loop", crate::mul_shift::ilog10_u64_mul_loop),
+    u64_algorithm!("mul_pow4", crate::mul_shift::ilog10_u64_mul_pow4),
+    u64_algorithm!("mul_packed", crate::mul_shift::ilog10_u64_mul_packed),
+    u64_algorithm!("mul20", crate::mul_shift::ilog10_u64_mul20),
+    u64_algorithm!("swar", crate::swar::ilog10_u64_swar),
+    u64_algorithm!("debruijn", crate::soft_clz::ilog10_u64_debruijn),
+    u64_algorithm!("bytes", crate::soft_clz::ilog10_u64_bytes),
+    u64_algorithm!("constant_time", crate::constant_time::ilog10_u64),
+    u64_algorithm!("f64", crate::float::ilog10_u64_f64),
+    u64_algorithm!("split", crate::arm32::ilog10_u64_split),
+    u64_algorithm!("ladder", crate::mcu::ilog10_u64_ladder),
+    // As u32/slice.
+    Algorithm {
+        eval_all: |values, out| {
+            let mut wide = [0u64; 256];
+            for (x, o) in values.chunks(256).zip(out.chunks_mut(256)) {
+                for (w, &x) in wide.iter_mut().zip(x) {
+                    *w = x as u64;
+                }
+                crate::simd::ilog10_u64_slice(&wide[..x.len()], o);
+            }
+        },
+        run: |input| run_u64_slices(input, crate::simd::ilog10_u64_slice),
+        ..u64_algorithm!("slice", |x| {
+            let mut out = [0];
+            crate::simd::ilog10_u64_slice(&[x], &mut out);
+            out[0]
+        })
+    },
+    // SAFETY: as for popcount.
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    u32_algorithm!("asm", |x| unsafe { crate::asm::ilog10_u32(x | 1) }),
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    u32_algorithm!("branch", crate::lowering::ilog10_branch),
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    u32_algorithm!("cmov", crate::lowering::ilog10_cmov),
+    #[cfg(any(target_arch = "x86_64", target_arch = "aarch64"))]
+    u32_algorithm!("sub_shift", crate::lowering::ilog10_sub_shift),
+    // SAFETY: as for popcount.
+    #[cfg(all(
+        any(target_arch = "x86_64", target_arch = "aarch64"),
+        not(feature = "small-tables")
+    ))]
+    u64_algorithm!("asm", |x| unsafe { crate::asm::ilog10_u64(x | 1) }),
+    #[cfg(feature = "dispatch")]
+    u32_algorithm!("dispatch", |x| {
+        core::num::NonZeroU32::new(x).map_or(0, crate::dispatch::ilog10_u32)
+    }),
+    #[cfg(feature = "dispatch")]
+    u32_algorithm!("mul_multiversion", |x| {
+        core::num::NonZeroU32::new(x).map_or(0, crate::dispatch::ilog10_u32_mul)
+    }),
+    #[cfg(feature = "dispatch")]
+    u64_algorithm!("mul_multiversion", |x| {
+        core::num::NonZeroU64::new(x).map_or(0, crate::dispatch::ilog10_u64_mul)
+    }),
+    #[cfg(feature = "nightly")]
+    u32_algorithm!("stdlib_large", crate::hints::ilog10_u32_large),
+    #[cfg(feature = "nightly")]
+    u32_algorithm!("stdlib_small", crate::hints::ilog10_u32_small),
+    #[cfg(feature = "nightly")]
+    u32_algorithm!("mul_exact", crate::hints::ilog10_mul_exact),
+    #[cfg(feature = "nightly")]
+    u32_algorithm!("mul_corrected", crate::hints::ilog10_mul_corrected),
+    #[cfg(feature = "c-reference")]
+    crate::c_reference::HD,
+    #[cfg(feature = "c-reference")]
+    crate::c_reference::CLZ_TABLE,
+    #[cfg(feature = "c-reference")]
+    crate::c_reference::LADDER,
+    #[cfg(feature = "c-reference")]
+    crate::c_reference::U64_MUL,
+    #[cfg(feature = "c-reference")]
+    crate::c_reference::GEN_MUL,
+    #[cfg(feature = "c-reference")]
+    crate::c_reference::GEN_POPCOUNT,
+    #[cfg(feature = "c-reference")]
+    crate::c_reference::GEN_TABLE,
+    #[cfg(feature = "c-reference")]
+    crate::c_reference::GEN_U64_MUL,
+];
+
+static REGISTERED: std::sync::OnceLock<Vec<Algorithm>> = std::sync::OnceLock::new();
+
+/// Adds implementations from outside this crate (the `ilog` binary's
+/// `--plugin`s) after the built-in ones. Only the first call counts,
+/// and it has to come before anything looks the names up; returns
+/// false if there already were registered entries.
+pub fn register(algorithms: Vec<Algorithm>) -> bool {
+    REGISTERED.set(algorithms).is_ok()
+}
+
+/// The built-in implementations followed by any registered ones.
+pub fn all() -> impl Iterator<Item = &'static Algorithm> {
+    ALGORITHMS.iter().chain(REGISTERED.get().into_iter().flatten())
+}
+
+pub fn of_width(width: Width) -> impl Iterator<Item = &'static Algorithm> {
+    all().filter(move |a| a.width == width)
+}
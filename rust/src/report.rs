@@ -0,0 +1,177 @@
+// Rendering of benchmark and test results. Everything the binary
+// reports goes through a `Report` so that the same numbers can be
+// printed as a markdown table for pasting into the forum thread,
+// or written as JSON / CSV for scripts that collect results across
+// machines.
+
+use std::fs::OpenOptions;
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+use clap::ValueEnum;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+#[derive(ValueEnum, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Markdown,
+    Json,
+    Csv,
+}
+
+/// A titled table: one header row and any number of data rows.
+pub struct Report {
+    pub title: String,
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<Value>>,
+}
+
+impl Report {
+    pub fn new(title: &str, columns: &[&str]) -> Self {
+        Report {
+            title: title.to_string(),
+            columns: columns.iter().map(|c| c.to_string()).collect(),
+            rows: Vec::new(),
+        }
+    }
+
+    /// Adds a row from one of the `ilog::results` records, whose
+    /// field names must be the report's columns.
+    pub fn push<T: Serialize>(&mut self, record: &T) {
+        let Ok(Value::Object(mut fields)) = serde_json::to_value(record) else {
+            panic!("report rows must serialize as objects");
+        };
+        debug_assert_eq!(fields.len(), self.columns.len());
+        let row = self
+            .columns
+            .iter()
+            .map(|c| fields.remove(c).unwrap_or(Value::Null))
+            .collect();
+        self.rows.push(row);
+    }
+
+    /// The rows as the records they were pushed from.
+    pub fn records<T: DeserializeOwned>(&self) -> Vec<T> {
+        self.rows
+            .iter()
+            .map(|row| {
+                let fields: Map<String, Value> =
+                    self.columns.iter().cloned().zip(row.iter().cloned()).collect();
+                serde_json::from_value(Value::Object(fields)).expect("rows of another record type")
+            })
+            .collect()
+    }
+
+    // `timestamp` is only set when writing to a file, where each run
+    // becomes its own section. `header` is false when appending CSV
+    // rows to a file that already has its header line.
+    pub fn render(&self, format: Format, timestamp: Option<&str>, header: bool) -> String {
+        match format {
+            Format::Markdown => self.render_markdown(timestamp),
+            Format::Json => self.render_json(timestamp),
+            Format::Csv => self.render_csv(timestamp, header),
+        }
+    }
+
+    fn render_markdown(&self, timestamp: Option<&str>) -> String {
+        let mut out = String::new();
+        if let Some(ts) = timestamp {
+            out += &format!("## {} ({ts})\n\n", self.title);
+        }
+        out += &format!("| {} |\n", self.columns.join(" | "));
+        out += &format!("|{}\n", "---|".repeat(self.columns.len()));
+        for row in &self.rows {
+            let cells: Vec<String> = row.iter().map(plain).collect();
+            out += &format!("| {} |\n", cells.join(" | "));
+        }
+        out += "\n";
+        out
+    }
+
+    // One compact object per report, so that appending runs to a file
+    // yields valid JSON Lines.
+    fn render_json(&self, timestamp: Option<&str>) -> String {
+        let rows: Vec<Value> = self
+            .rows
+            .iter()
+            .map(|row| {
+                let obj: Map<String, Value> =
+                    self.columns.iter().cloned().zip(row.iter().cloned()).collect();
+                Value::Object(obj)
+            })
+            .collect();
+        let mut obj = Map::new();
+        obj.insert("title".into(), self.title.clone().into());
+        if let Some(ts) = timestamp {
+            obj.insert("timestamp".into(), ts.into());
+        }
+        obj.insert("rows".into(), Value::Array(rows));
+        format!("{}\n", Value::Object(obj))
+    }
+
+    fn render_csv(&self, timestamp: Option<&str>, header: bool) -> String {
+        let mut out = String::new();
+        if header {
+            let mut cols: Vec<String> = self.columns.iter().map(|c| csv_field(c)).collect();
+            if timestamp.is_some() {
+                cols.insert(0, "timestamp".into());
+            }
+            out += &cols.join(",");
+            out += "\n";
+        }
+        for row in &self.rows {
+            let mut cells: Vec<String> = row.iter().map(|v| csv_field(&plain(v))).collect();
+            if let Some(ts) = timestamp {
+                cells.insert(0, csv_field(ts));
+            }
+            out += &cells.join(",");
+            out += "\n";
+        }
+        out
+    }
+}
+
+fn plain(v: &Value) -> String {
+    match v {
+        Value::String(s) => s.clone(),
+        Value::Null => String::new(),
+        v => v.to_string(),
+    }
+}
+
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+/// Where reports go: stdout by default, or a file which is either
+/// truncated or appended to.
+pub struct Output {
+    pub format: Format,
+    pub path: Option<PathBuf>,
+    pub append: bool,
+}
+
+impl Output {
+    pub fn emit(&self, report: &Report) -> io::Result<()> {
+        let Some(path) = &self.path else {
+            print!("{}", report.render(self.format, None, true));
+            return Ok(());
+        };
+        let mut file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(self.append)
+            .truncate(!self.append)
+            .open(path)?;
+        let header = file.metadata()?.len() == 0;
+        let timestamp = humantime::format_rfc3339_seconds(SystemTime::now()).to_string();
+        file.write_all(report.render(self.format, Some(&timestamp), header).as_bytes())
+    }
+}
@@ -0,0 +1,315 @@
+//! The records behind `ilog bench` and `ilog test` output.
+//!
+//! The binary builds its tables from these types, so the JSON it writes
+//! (one `Report` per line) deserializes straight back into them, and
+//! the CSV and markdown columns are their field names in order.
+
+use serde::{Deserialize, Serialize};
+
+use crate::bench::{Context, Distribution};
+use crate::registry::Width;
+
+/// One algorithm timed over one input distribution.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct BenchResult {
+    pub width: Width,
+    pub distribution: Distribution,
+    pub context: Context,
+    pub algorithm: String,
+    pub ops: u64,
+    pub best_us: u64,
+    pub median_us: u64,
+    /// Where the algorithm ran in each round, shuffled afresh for each;
+    /// `2,0,1` for third, then first, then second
+    #[serde(default)]
+    pub positions: String,
+    /// Net of `overhead_ns`
+    pub ns_per_op: f64,
+    /// The harness's own cost per op, taken off `ns_per_op`: the two
+    /// `Instant::now()` calls spread over the run and, standalone and
+    /// under pressure, the loop around the call as timed with nothing
+    /// in it
+    #[serde(default)]
+    pub overhead_ns: Option<f64>,
+    /// Over the repetitions, when there are two or more
+    pub stddev_ns: Option<f64>,
+    /// The standard error of the mean over the mean
+    pub rse: Option<f64>,
+    /// Whether `rse` came within `bench --target-rse`, if one was given
+    pub target_met: Option<bool>,
+    /// Package energy from RAPL, where the counters can be read
+    pub joules_per_billion_ops: Option<f64>,
+}
+
+/// The size and shape of one function's machine code.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CodeSize {
+    pub function: String,
+    pub algorithm: Option<String>,
+    pub target: String,
+    pub instructions: u64,
+    /// Instructions up to the first return, leaving out cold paths
+    pub fast_path: u64,
+    pub bytes: u64,
+    /// Tables and other constants loaded on the fast path, which is
+    /// what the function adds to the data cache footprint of a loop.
+    /// Not known for wasm32.
+    pub data_bytes: Option<u64>,
+    pub conditional_branches: u64,
+    pub calls: u64,
+}
+
+/// llvm-mca's static estimate for one function's fast path, with the
+/// measured time when it was built for the machine running `ilog`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct McaEstimate {
+    pub function: String,
+    pub algorithm: Option<String>,
+    pub target: String,
+    pub cpu: String,
+    pub instructions: u64,
+    pub uops: f64,
+    pub cycles: f64,
+    pub block_rthroughput: f64,
+    pub ipc: f64,
+    /// Resource pressure per iteration, e.g. `SKLPort0 0.5, SKLPort1 1`
+    pub pressure: String,
+    pub measured_ns_per_op: Option<f64>,
+}
+
+/// `ilog latency`'s critical path through one function's fast path on
+/// one microarchitecture, from published per-instruction latencies.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LatencyEstimate {
+    pub function: String,
+    pub algorithm: Option<String>,
+    pub target: String,
+    /// e.g. `skylake` or `zen3`
+    pub uarch: String,
+    pub instructions: u64,
+    /// Cycles from the argument to the result
+    pub latency_cycles: u32,
+    /// The instructions on that path, e.g. `bsr > lea > shr > cmp+load > adc`
+    pub critical_path: String,
+    /// Mnemonics the table has no figure for, counted as one cycle
+    pub unmodelled: String,
+}
+
+/// One algorithm timed alone on a CPU and then with a competing
+/// workload on its SMT sibling.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SmtResult {
+    pub width: Width,
+    pub distribution: Distribution,
+    pub algorithm: String,
+    /// The algorithm running on the sibling
+    pub competitor: String,
+    /// The two CPUs, e.g. `0,4`
+    pub cpus: String,
+    pub alone_ns_per_op: f64,
+    pub contended_ns_per_op: f64,
+    /// contended / alone
+    pub slowdown: f64,
+}
+
+/// The spread of one algorithm's times over many short batches of
+/// values, each time in ns per op of its batch.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TailLatency {
+    pub width: Width,
+    pub distribution: Distribution,
+    pub algorithm: String,
+    /// Values per batch
+    pub batch: usize,
+    pub batches: usize,
+    pub mean_ns: f64,
+    pub p50_ns: f64,
+    pub p99_ns: f64,
+    pub p999_ns: f64,
+    pub max_ns: f64,
+}
+
+/// One algorithm's running time on a fixed input against random ones,
+/// compared with Welch's t-test for `ilog timing`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct TimingLeak {
+    pub width: Width,
+    pub distribution: Distribution,
+    pub algorithm: String,
+    /// `constant time`, or `control` for one expected to leak
+    pub role: String,
+    /// Batches timed of each class, once the slowest are cropped
+    pub fixed_batches: usize,
+    pub random_batches: usize,
+    /// Mean ns per call
+    pub fixed_ns: f64,
+    pub random_ns: f64,
+    /// Welch's t of the two classes' batch times
+    pub t: f64,
+    /// Whether |t| is over `--threshold`
+    pub leaks: bool,
+}
+
+/// One algorithm timed at each integer width for `bench
+/// --compare-widths`, in ns per value; `None` where it has no version
+/// for the width.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WidthComparison {
+    pub distribution: Distribution,
+    pub algorithm: String,
+    pub u8_ns: Option<f64>,
+    pub u16_ns: Option<f64>,
+    pub u32_ns: Option<f64>,
+    pub u64_ns: Option<f64>,
+    pub u128_ns: Option<f64>,
+}
+
+/// One way of calling a build of `ilog10_mul` or `ilog10_u64_mul`,
+/// timed for `ilog multiversion`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DispatchCost {
+    pub width: Width,
+    pub distribution: Distribution,
+    /// `inlined`, `call`, `pointer`, `multiversion` or `dispatch`
+    pub call: String,
+    /// The build that ran: `baseline`, `lzcnt` or `popcount`
+    pub build: String,
+    /// Over independent values
+    pub ns_per_op: f64,
+    /// Over a chain of values, each waiting on the last result
+    pub latency_ns: f64,
+    /// latency_ns less that of the same build inlined, for the calls
+    /// that aren't
+    pub overhead_ns: Option<f64>,
+}
+
+/// One way of writing rows of u64s as CSV, timed end to end for
+/// `ilog csv`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CsvThroughput {
+    pub distribution: Distribution,
+    /// Values per row
+    pub fields: usize,
+    /// `write`, `per_value` or `batch`
+    pub writer: String,
+    pub ns_per_value: f64,
+    /// Including the separators and newlines
+    pub bytes_per_value: f64,
+    pub mb_per_second: f64,
+    /// How many times faster than `write`
+    pub speedup: f64,
+}
+
+/// One integer type formatted with one `Display`, timed for
+/// `ilog display`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct DisplayThroughput {
+    /// `u64` or `i64`
+    pub integer: String,
+    pub distribution: Distribution,
+    /// `std`, the type's own, or `ilog`, `DisplayU64` or `DisplayI64`
+    pub formatter: String,
+    pub ns_per_value: f64,
+    /// How many times faster than `std`
+    pub speedup: f64,
+}
+
+/// One step of `ilog explain`'s walk through an algorithm for one
+/// input, or, in a `result` row, what a registered algorithm returns.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ExplainStep {
+    /// e.g. `u32/popcount`
+    pub algorithm: String,
+    /// `leading_zeros`, `guess`, `threshold`, `correct` or `result`
+    pub step: String,
+    pub value: u64,
+    /// The arithmetic behind `value`
+    pub detail: String,
+}
+
+/// How often a guess strategy came up with one value over an input
+/// distribution, and how often the correction then added one. In the
+/// row totalling the strategy, `value` is absent and `flips` counts
+/// consecutive inputs that disagreed on the correction.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CorrectionCount {
+    /// e.g. `u32/mul`, the guess shared by mul, mul_alt, asm and others
+    pub guess: String,
+    pub distribution: Distribution,
+    pub value: Option<u32>,
+    pub values: u64,
+    /// The fraction of all values that got this guess
+    pub share: f64,
+    pub corrected: u64,
+    pub correction_rate: f64,
+    pub flips: Option<u64>,
+}
+
+/// One step of a guess-and-correct ilog10 timed apart from the rest:
+/// the leading-zero count, the guess from precomputed leading zeros,
+/// the correction from precomputed guesses, and the whole function
+/// for scale.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GuessTiming {
+    /// e.g. `u32/popcount`
+    pub guess: String,
+    pub distribution: Distribution,
+    /// `leading_zeros`, `guess`, `correct` or `whole`
+    pub step: String,
+    pub ns_per_op: f64,
+    /// ns_per_op over that of the whole function
+    pub of_whole: f64,
+}
+
+/// The smallest input on which a check failed.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Counterexample {
+    pub input: u64,
+    pub expected: u32,
+    pub actual: u32,
+}
+
+/// What one rayon worker got through over an `ilog test` run.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct WorkerStats {
+    /// The worker's index in the thread pool
+    pub worker: usize,
+    /// Inputs checked
+    pub values: u64,
+    /// Time spent checking them, out of the run's `seconds`
+    pub busy_seconds: f64,
+    pub values_per_second: f64,
+    /// busy_seconds over the run's wall-clock time
+    pub busy_share: f64,
+}
+
+/// One algorithm checked against the reference over one range.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CheckResult {
+    pub test: String,
+    pub algorithm: String,
+    pub range: String,
+    pub checked: u64,
+    pub failures: u64,
+    pub counterexample: Option<Counterexample>,
+    pub seconds: f64,
+}
+
+impl CheckResult {
+    pub fn passed(&self) -> bool {
+        self.failures == 0
+    }
+}
+
+/// One run of a command, as written to a JSON output file. The
+/// timestamp (RFC 3339) is only present when writing to a file.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Report<T> {
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timestamp: Option<String>,
+    pub rows: Vec<T>,
+}
+
+pub type BenchReport = Report<BenchResult>;
+pub type VerifyReport = Report<CheckResult>;
@@ -0,0 +1,147 @@
+// ilog10 as WGSL or GLSL source, for `ilog shader`. Neither language
+// has an ilog10, but both have the ilog2 of a nonzero u32
+// (`firstLeadingBit`, `findMSB`), so the crate's guess and threshold
+// table carry over as they are, the table as a const array. u32 only:
+// WGSL has no 64-bit integers, and GLSL has them only behind an
+// extension.
+//
+// The emitted function works on `x | 1`, which has the same ilog10 as
+// any nonzero x and keeps the all-ones ilog2 of 0 from indexing past
+// the table, so 0 gives 0. `eval` is the same computation in Rust;
+// `ilog test` checks it against `ilog10` on every u32.
+
+use std::fmt::Write;
+
+use ilog::parts;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum Language {
+    Wgsl,
+    /// GLSL 4.00 or ES 3.10 and later, for findMSB
+    Glsl,
+}
+
+/// The guesses a shader can be built from, each followed by the usual
+/// correction.
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum Algo {
+    /// ilog2(x) * 9 >> 5, as ilog10_mul
+    Mul,
+    /// A 32-entry guess table indexed by ilog2(x), as parts::guess_ilog10_u32_table
+    Table,
+}
+
+impl Algo {
+    pub const ALL: [Algo; 2] = [Algo::Mul, Algo::Table];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Algo::Mul => "mul",
+            Algo::Table => "table",
+        }
+    }
+}
+
+// The multiply guess, written out for the shader; the assertion below
+// keeps it equal to parts::guess_ilog10_u32.
+const MUL: u32 = 9;
+const SHIFT: u32 = 5;
+
+const _: () = {
+    let mut lz = 0;
+    while lz < 32 {
+        assert!(((31 - lz) * MUL) >> SHIFT == parts::guess_ilog10_u32(lz));
+        lz += 1;
+    }
+};
+
+// parts::guess_ilog10_u32_table, indexed by ilog2(x).
+const GUESSES: [u32; 32] = {
+    let mut table = [0; 32];
+    let mut log2 = 0;
+    while log2 < 32 {
+        table[log2] = parts::guess_ilog10_u32_table(31 - log2 as u32);
+        log2 += 1;
+    }
+    table
+};
+
+/// floor(log10(x)) as the shader from `emit` computes it; 0 gives 0.
+pub fn eval(algo: Algo, x: u32) -> u32 {
+    let v = x | 1;
+    let log2 = 31 - v.leading_zeros();
+    let guess = match algo {
+        Algo::Mul => (log2 * MUL) >> SHIFT,
+        Algo::Table => GUESSES[log2 as usize],
+    };
+    guess + (v > ilog::TEN_THRESHOLDS[guess as usize]) as u32
+}
+
+/// The shader source of a function `name(x: u32) -> u32` and the
+/// constants it needs, which are named after it.
+pub fn emit(language: Language, algo: Algo, name: &str) -> Result<String, String> {
+    let mut chars = name.chars();
+    if !chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        || !chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        return Err(format!("{name:?} is not a shader identifier"));
+    }
+    let prefix = name.to_ascii_uppercase();
+    let thresholds = format!("{prefix}_THRESHOLDS");
+    let guesses = format!("{prefix}_GUESSES");
+
+    let mut out = String::new();
+    let w = &mut out;
+    writeln!(w, "// floor(log10(x)) for a u32 x, and 0 for 0.").unwrap();
+    writeln!(w, "// Generated by `ilog shader --algo {}` from the ilog crate.", algo.name()).unwrap();
+    let ilog2 = match language {
+        Language::Wgsl => {
+            writeln!(w, "{}", wgsl_array(&thresholds, &ilog::TEN_THRESHOLDS)).unwrap();
+            if let Algo::Table = algo {
+                writeln!(w, "{}", wgsl_array(&guesses, &GUESSES)).unwrap();
+            }
+            writeln!(w, "\nfn {name}(x: u32) -> u32 {{").unwrap();
+            writeln!(w, "    let v = x | 1u;").unwrap();
+            "firstLeadingBit(v)"
+        }
+        Language::Glsl => {
+            writeln!(w, "{}", glsl_array(&thresholds, &ilog::TEN_THRESHOLDS)).unwrap();
+            if let Algo::Table = algo {
+                writeln!(w, "{}", glsl_array(&guesses, &GUESSES)).unwrap();
+            }
+            writeln!(w, "\nuint {name}(uint x) {{").unwrap();
+            writeln!(w, "    uint v = x | 1u;").unwrap();
+            "uint(findMSB(v))"
+        }
+    };
+    let guess = match algo {
+        Algo::Mul => format!("({ilog2} * {MUL}u) >> {SHIFT}u"),
+        Algo::Table => format!("{guesses}[{ilog2}]"),
+    };
+    match language {
+        Language::Wgsl => {
+            writeln!(w, "    let guess = {guess};").unwrap();
+            writeln!(w, "    return guess + select(0u, 1u, v > {thresholds}[guess]);").unwrap();
+        }
+        Language::Glsl => {
+            writeln!(w, "    uint guess = {guess};").unwrap();
+            writeln!(w, "    return guess + uint(v > {thresholds}[guess]);").unwrap();
+        }
+    }
+    writeln!(w, "}}").unwrap();
+    Ok(out)
+}
+
+fn wgsl_array(name: &str, values: &[u32]) -> String {
+    format!("const {name} = array<u32, {}>({});", values.len(), literals(values))
+}
+
+fn glsl_array(name: &str, values: &[u32]) -> String {
+    let n = values.len();
+    format!("const uint {name}[{n}] = uint[{n}]({});", literals(values))
+}
+
+fn literals(values: &[u32]) -> String {
+    let literals: Vec<String> = values.iter().map(|v| format!("{v}u")).collect();
+    literals.join(", ")
+}
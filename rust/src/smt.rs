@@ -0,0 +1,212 @@
+// SMT contention for `ilog smt`. Two hyperthreads of one core share
+// its execution ports, so a version that leans on one port (`popcnt`,
+// or the multiplier for the multiply guesses) can be as fast as any
+// other on an idle core and fall behind once the sibling wants that
+// port too. Each algorithm is timed pinned to one CPU with its sibling
+// idle, and again with a competing algorithm looping on the sibling.
+//
+// Linux only: the siblings come from sysfs, and the pinning is
+// sched_setaffinity.
+
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Barrier;
+
+use ilog::bench::{Distribution, Input};
+use ilog::registry::{self, Algorithm, Width};
+use ilog::results::SmtResult;
+
+use crate::filter::Filter;
+use crate::report::Report;
+
+/// The competitor that is the candidate itself.
+pub const SAME: &str = "same";
+
+// Best of this many runs, alone and contended.
+const REPETITIONS: usize = 3;
+
+pub fn report(
+    width: Width,
+    distribution: Distribution,
+    competitor: &str,
+    cpus: Option<(usize, usize)>,
+    filter: &Filter,
+) -> Result<Report, String> {
+    let (cpu, sibling) = match cpus {
+        Some(cpus) => cpus,
+        None => siblings()?,
+    };
+    if cpu == sibling {
+        return Err(format!("--cpus {cpu},{sibling} names one CPU twice"));
+    }
+    let competitor = match competitor {
+        SAME => None,
+        name => Some(registry::of_width(width).find(|a| a.name == name).ok_or_else(|| {
+            let known: Vec<&str> = registry::of_width(width).map(|a| a.name).collect();
+            format!("unknown competitor {name:?}; expected {SAME} or one of {}", known.join(", "))
+        })?),
+    };
+    let input = Input::generate(width, distribution);
+    // The competitor loops over a single pass of the values, so that it
+    // sees the stop flag soon after the candidate finishes.
+    let one_pass = match &input {
+        Input::U32 { values, .. } => Input::U32 { values: values.clone(), passes: 1 },
+        Input::U64 { values, .. } => Input::U64 { values: values.clone(), passes: 1 },
+        Input::Sweep | Input::Permuted(_) | Input::Strided { .. } => {
+            return Err(format!("{} is not a random distribution", distribution.name()));
+        }
+    };
+
+    let mut report = Report::new(
+        "smt contention",
+        &[
+            "width",
+            "distribution",
+            "algorithm",
+            "competitor",
+            "cpus",
+            "alone_ns_per_op",
+            "contended_ns_per_op",
+            "slowdown",
+        ],
+    );
+    // Unpinned again once the report is done.
+    let _pinned = pin(cpu)?;
+    for algorithm in registry::of_width(width).filter(|a| filter.matches(&[a.name, &a.qualified_name()])) {
+        let rival = competitor.unwrap_or(algorithm);
+        let ns = |us: u128| us as f64 * 1000.0 / input.ops() as f64;
+        let alone = (0..REPETITIONS).map(|_| algorithm.time(&input)).min().unwrap();
+        let contended = (0..REPETITIONS)
+            .map(|_| contended(algorithm, rival, &input, &one_pass, sibling))
+            .collect::<Result<Vec<_>, _>>()?;
+        let (alone, contended) = (ns(alone), ns(*contended.iter().min().unwrap()));
+        report.push(&SmtResult {
+            width,
+            distribution,
+            algorithm: algorithm.name.to_string(),
+            competitor: rival.name.to_string(),
+            cpus: format!("{cpu},{sibling}"),
+            alone_ns_per_op: alone,
+            contended_ns_per_op: contended,
+            slowdown: contended / alone,
+        });
+    }
+    Ok(report)
+}
+
+// Microseconds for `algorithm` over `input` on this thread, while
+// `rival` loops over `one_pass` on a thread pinned to `sibling`.
+fn contended(
+    algorithm: &Algorithm,
+    rival: &Algorithm,
+    input: &Input,
+    one_pass: &Input,
+    sibling: usize,
+) -> Result<u128, String> {
+    let stop = AtomicBool::new(false);
+    let started = Barrier::new(2);
+    std::thread::scope(|s| {
+        let rival = s.spawn(|| {
+            let pinned = pin(sibling);
+            started.wait();
+            let _pinned = pinned?;
+            while !stop.load(Ordering::Relaxed) {
+                (rival.run)(one_pass);
+            }
+            Ok::<_, String>(())
+        });
+        started.wait();
+        let us = algorithm.time(input);
+        stop.store(true, Ordering::Relaxed);
+        rival.join().unwrap()?;
+        Ok(us)
+    })
+}
+
+// The first pair of hyperthreads of one core, from sysfs.
+fn siblings() -> Result<(usize, usize), String> {
+    let none = || "no SMT siblings found (is SMT off?); pass --cpus".to_string();
+    let cpus = std::fs::read_dir("/sys/devices/system/cpu").map_err(|_| none())?;
+    let mut lists: Vec<(usize, String)> = cpus
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let cpu = name.strip_prefix("cpu")?.parse().ok()?;
+            let list = std::fs::read_to_string(entry.path().join("topology/thread_siblings_list"));
+            Some((cpu, list.ok()?))
+        })
+        .collect();
+    lists.sort();
+    lists
+        .iter()
+        .find_map(|(_, list)| match parse_cpu_list(list.trim())?[..] {
+            [a, b, ..] => Some((a, b)),
+            _ => None,
+        })
+        .ok_or_else(none)
+}
+
+/// A sysfs CPU list, such as `0,4` or `0-1`.
+pub fn parse_cpu_list(list: &str) -> Option<Vec<usize>> {
+    let mut cpus = Vec::new();
+    for part in list.split(',') {
+        match part.split_once('-') {
+            Some((lo, hi)) => cpus.extend(lo.parse::<usize>().ok()?..=hi.parse().ok()?),
+            None => cpus.push(part.parse().ok()?),
+        }
+    }
+    Some(cpus)
+}
+
+/// Parses `--cpus A,B`.
+pub fn parse_cpus(cpus: &str) -> Result<(usize, usize), String> {
+    let (a, b) = cpus.split_once(',').ok_or("expected A,B")?;
+    let cpu = |s: &str| s.parse::<usize>().map_err(|e| format!("{s:?}: {e}"));
+    Ok((cpu(a)?, cpu(b)?))
+}
+
+/// The calling thread's CPUs from before `pin`, put back when this is
+/// dropped. Not `Send`: the affinity it restores is its own thread's.
+#[must_use = "the thread is unpinned again as soon as this is dropped"]
+pub struct Pinned {
+    #[cfg(target_os = "linux")]
+    saved: libc::cpu_set_t,
+    _thread: PhantomData<*const ()>,
+}
+
+/// Pins the calling thread to `cpu` until the returned guard drops.
+#[cfg(target_os = "linux")]
+pub fn pin(cpu: usize) -> Result<Pinned, String> {
+    if cpu >= libc::CPU_SETSIZE as usize {
+        return Err(format!("CPU {cpu} is past CPU_SETSIZE"));
+    }
+    // SAFETY: cpu_set_t is plain data, and zeroed is the empty set.
+    let (mut saved, mut set): (libc::cpu_set_t, libc::cpu_set_t) =
+        unsafe { (std::mem::zeroed(), std::mem::zeroed()) };
+    // SAFETY: both sets are as large as their sizes say.
+    let ok = unsafe {
+        libc::CPU_SET(cpu, &mut set);
+        libc::sched_getaffinity(0, std::mem::size_of_val(&saved), &mut saved) == 0
+            && libc::sched_setaffinity(0, std::mem::size_of_val(&set), &set) == 0
+    };
+    if ok {
+        Ok(Pinned { saved, _thread: PhantomData })
+    } else {
+        Err(format!("pinning to CPU {cpu}: {}", std::io::Error::last_os_error()))
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn pin(_: usize) -> Result<Pinned, String> {
+    Err("pinning threads to CPUs is only supported on Linux".into())
+}
+
+#[cfg(target_os = "linux")]
+impl Drop for Pinned {
+    fn drop(&mut self) {
+        // SAFETY: a set read by sched_getaffinity. If it can't be put
+        // back (a CPU went offline), the thread stays pinned, as it
+        // did before there was a guard.
+        unsafe { libc::sched_setaffinity(0, std::mem::size_of_val(&self.saved), &self.saved) };
+    }
+}
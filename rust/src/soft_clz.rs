@@ -0,0 +1,149 @@
+//! ilog2 without a count-leading-zeros instruction, for RISC-V cores
+//! without the Zbb extension (and anything else that lacks one).
+//!
+//! There, `leading_zeros` is expanded into smearing the top bit down
+//! followed by a software popcount, about 30 instructions for u32 and
+//! 40 for u64 before the guess even starts. The two here are shorter:
+//!
+//! - de Bruijn: smear, isolate the top bit, then one multiply by a de
+//!   Bruijn sequence puts a distinct pattern in the top 5 (6) bits to
+//!   look up in a 32 (64) byte table.
+//! - byte table: narrow to the top nonzero byte with two (three)
+//!   compare-and-shift steps, then look it up in a 256-byte table. No
+//!   multiply, but a bigger table.
+//!
+//! The ilog10 built on each is the multiply guess and the usual
+//! correction. On targets where `cfg(target_feature)` says there is
+//! no clz, the crate's own multiply-based functions use
+//! `ilog2_debruijn_*` instead of `ilog2` (see `ilog2_u32` in lib.rs);
+//! `ilog cross` and `ilog test --codegen --target` show the
+//! difference.
+//!
+//! Unlike `ilog2`, these don't panic on 0: the ilog2 is 0 and the
+//! ilog10 then comes out as 0 too.
+
+const DEBRUIJN_U32: u32 = 0x077C_B531;
+const DEBRUIJN_U64: u64 = 0x0218_A392_CD3D_5DBF;
+
+// Maps the top bits (from `shift` up) of 2^k * DEBRUIJN to k, failing
+// the build if DEBRUIJN is not a de Bruijn sequence.
+macro_rules! debruijn_table {
+    ($t:ty, $n:expr, $debruijn:expr, $shift:expr) => {{
+        let mut table = [0u8; $n];
+        let mut seen = [false; $n];
+        let mut k = 0;
+        while k < $n {
+            let i = ((1 as $t) << k).wrapping_mul($debruijn) >> $shift;
+            assert!(!seen[i as usize], "not a de Bruijn sequence");
+            seen[i as usize] = true;
+            table[i as usize] = k as u8;
+            k += 1;
+        }
+        table
+    }};
+}
+
+pub(crate) const DEBRUIJN_TABLE_U32: [u8; 32] = debruijn_table!(u32, 32, DEBRUIJN_U32, 27);
+pub(crate) const DEBRUIJN_TABLE_U64: [u8; 64] = debruijn_table!(u64, 64, DEBRUIJN_U64, 58);
+
+// ilog2 of each byte, and 0 for 0.
+pub(crate) const BYTE_TABLE: [u8; 256] = {
+    let mut table = [0u8; 256];
+    let mut i = 2;
+    while i < 256 {
+        table[i] = table[i / 2] + 1;
+        i += 1;
+    }
+    table
+};
+
+/// floor(log2(x)) by de Bruijn multiplication.
+#[inline]
+pub const fn ilog2_debruijn_u32(mut x: u32) -> u32 {
+    x |= x >> 1;
+    x |= x >> 2;
+    x |= x >> 4;
+    x |= x >> 8;
+    x |= x >> 16;
+    // Only the top bit is left.
+    let top = x - (x >> 1);
+    crate::statics::DEBRUIJN_TABLE_U32[(top.wrapping_mul(DEBRUIJN_U32) >> 27) as usize] as u32
+}
+
+/// floor(log2(x)) by de Bruijn multiplication.
+#[inline]
+pub const fn ilog2_debruijn_u64(mut x: u64) -> u32 {
+    x |= x >> 1;
+    x |= x >> 2;
+    x |= x >> 4;
+    x |= x >> 8;
+    x |= x >> 16;
+    x |= x >> 32;
+    let top = x - (x >> 1);
+    crate::statics::DEBRUIJN_TABLE_U64[(top.wrapping_mul(DEBRUIJN_U64) >> 58) as usize] as u32
+}
+
+/// floor(log2(x)) from the top nonzero byte.
+#[inline]
+pub const fn ilog2_bytes_u32(x: u32) -> u32 {
+    let a = (x >= 1 << 16) as u32 * 16;
+    let x = x >> a;
+    let b = (x >= 1 << 8) as u32 * 8;
+    let byte = x >> b;
+    if byte >= 256 {
+        // SAFETY: each step leaves x below 2^(a + b + 8); inlined into
+        // ilog2_bytes_u64, LLVM loses track of that.
+        unsafe { crate::unchecked::unreachable() }
+    }
+    a + b + crate::statics::BYTE_TABLE[byte as usize] as u32
+}
+
+/// floor(log2(x)) from the top nonzero byte.
+#[inline]
+pub const fn ilog2_bytes_u64(x: u64) -> u32 {
+    let a = (x >= 1 << 32) as u32 * 32;
+    a + ilog2_bytes_u32((x >> a) as u32)
+}
+
+// The multiply guess and correction of ilog10_mul and ilog10_u64_mul,
+// for an ilog2 the compiler can't bound.
+#[inline]
+const fn correct_u32(log2: u32, x: u32) -> u32 {
+    let guess = log2.wrapping_mul(9) >> 5;
+    if guess >= 9 {
+        // SAFETY: the ilog2s here are at most 31, and 31 * 9 >> 5 is 8.
+        unsafe { crate::unchecked::unreachable() }
+    }
+    guess + (x > crate::statics::TEN_THRESHOLDS[guess as usize]) as u32
+}
+
+#[inline]
+const fn correct_u64(log2: u32, x: u64) -> u32 {
+    // u64_threshold promises guess < 19, which holds for log2 <= 63.
+    let guess = log2.wrapping_mul(19) >> 6;
+    guess + (x > crate::u64_threshold(guess)) as u32
+}
+
+/// `ilog10_mul` with the de Bruijn ilog2.
+#[inline]
+pub const fn ilog10_debruijn(x: u32) -> u32 {
+    correct_u32(ilog2_debruijn_u32(x), x)
+}
+
+/// `ilog10_u64_mul` with the de Bruijn ilog2.
+#[inline]
+pub const fn ilog10_u64_debruijn(x: u64) -> u32 {
+    correct_u64(ilog2_debruijn_u64(x), x)
+}
+
+/// `ilog10_mul` with the byte-table ilog2.
+#[inline]
+pub const fn ilog10_bytes(x: u32) -> u32 {
+    correct_u32(ilog2_bytes_u32(x), x)
+}
+
+/// `ilog10_u64_mul` with the byte-table ilog2.
+#[inline]
+pub const fn ilog10_u64_bytes(x: u64) -> u32 {
+    correct_u64(ilog2_bytes_u64(x), x)
+}
@@ -0,0 +1,39 @@
+// The lookup tables as the algorithms index them at run time. A
+// `const` table is copied into every function that indexes it, so it
+// has as many addresses as users; a `static` has one, which is what
+// `Context::Pressure` flushes from the cache before each call. A const
+// fn can only read a static from Rust 1.83 (`ilog_const_statics`, set
+// by build.rs), so older toolchains, back to the polyfill's 1.60, get
+// the consts and their copies.
+
+macro_rules! tables {
+    ($($name:ident: $ty:ty = $value:expr;)*) => {
+        $(
+            #[cfg(ilog_const_statics)]
+            pub(crate) static $name: $ty = $value;
+            #[cfg(not(ilog_const_statics))]
+            pub(crate) const $name: $ty = $value;
+        )*
+
+        /// The address and size in bytes of each table.
+        #[cfg(all(feature = "std", ilog_const_statics))]
+        pub(crate) fn extents() -> impl Iterator<Item = (usize, usize)> {
+            [$((&$name as *const $ty as usize, core::mem::size_of::<$ty>())),*].into_iter()
+        }
+    };
+}
+
+tables! {
+    TEN_THRESHOLDS: [u32; 9] = crate::TEN_THRESHOLDS;
+    U64_THRESHOLDS: [u64; 19] = crate::U64_THRESHOLDS;
+    U128_THRESHOLDS: [u128; 38] = crate::U128_THRESHOLDS;
+    PACKED_THRESHOLDS: [u64; 5] = crate::PACKED_THRESHOLDS;
+    ODD_POWERS_OF_TEN: [u64; 10] = crate::ODD_POWERS_OF_TEN;
+    U64_POWERS: [u64; 20] = crate::U64_POWERS;
+    TABLE_GUESSES: [u8; 33] = crate::TABLE_GUESSES;
+    TABLE_THRESHOLDS: [u32; 10] = crate::TABLE_THRESHOLDS;
+    POW4_LOW: [u16; 4] = crate::POW4_LOW;
+    DEBRUIJN_TABLE_U32: [u8; 32] = crate::soft_clz::DEBRUIJN_TABLE_U32;
+    DEBRUIJN_TABLE_U64: [u8; 64] = crate::soft_clz::DEBRUIJN_TABLE_U64;
+    BYTE_TABLE: [u8; 256] = crate::soft_clz::BYTE_TABLE;
+}
@@ -0,0 +1,519 @@
+// Benchmark suites and the `bench` report.
+//
+// A suite names the algorithms, widths and input distributions to
+// time, so that the configuration behind a published table can be
+// checked into bench.toml and rerun by anyone with `bench --suite`.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use ilog::bench::{self, Context, Distribution, Histogram, Input};
+use ilog::registry::{self, Algorithm, Width};
+use ilog::results::{BenchReport, BenchResult};
+use rand::seq::SliceRandom;
+use serde::Deserialize;
+
+use crate::energy::Meter;
+use crate::filter::Filter;
+use crate::report::{Format, Report};
+
+/// `algorithms` holds registry names or glob patterns over them.
+/// `histogram`, as `Histogram` parses it, is what the `histogram`
+/// distribution draws from.
+#[derive(Deserialize, Debug)]
+#[serde(default, deny_unknown_fields)]
+pub struct Suite {
+    pub algorithms: Vec<String>,
+    pub widths: Vec<Width>,
+    pub distributions: Vec<Distribution>,
+    pub contexts: Vec<Context>,
+    pub repetitions: usize,
+    pub histogram: Option<Histogram>,
+    pub format: Option<Format>,
+}
+
+// The defaults reproduce the original popcount / mul / stdlib run.
+impl Default for Suite {
+    fn default() -> Self {
+        Suite {
+            algorithms: vec!["popcount".into(), "mul".into(), "stdlib".into()],
+            widths: vec![Width::U32],
+            distributions: vec![Distribution::Sweep],
+            contexts: vec![Context::Standalone],
+            repetitions: 1,
+            histogram: None,
+            format: None,
+        }
+    }
+}
+
+impl Suite {
+    /// Every algorithm at every width, for narrowing with `--filter`.
+    pub fn all() -> Self {
+        Suite {
+            algorithms: vec!["*".into()],
+            widths: Width::ALL.to_vec(),
+            ..Suite::default()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct SuiteFile {
+    suite: HashMap<String, Suite>,
+}
+
+pub fn load_suite(path: &Path, name: &str) -> Result<Suite, String> {
+    let text =
+        std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let mut file: SuiteFile =
+        toml::from_str(&text).map_err(|e| format!("{}: {e}", path.display()))?;
+    file.suite
+        .remove(name)
+        .ok_or_else(|| format!("{}: no suite named {name:?}", path.display()))
+}
+
+/// The algorithms of `width` that the suite names and `filter` lets
+/// through, in registry order.
+pub fn select(suite: &Suite, width: Width, filter: &Filter) -> Result<Vec<&'static Algorithm>, String> {
+    let patterns = suite
+        .algorithms
+        .iter()
+        .map(|p| glob::Pattern::new(p).map_err(|e| format!("{p:?}: {e}")))
+        .collect::<Result<Vec<_>, _>>()?;
+    // A name may exist for only some of the widths, so only a pattern
+    // matching nothing at all is an error.
+    if let Some(p) = patterns
+        .iter()
+        .find(|p| !registry::all().any(|a| p.matches(a.name)))
+    {
+        return Err(format!("unknown algorithm {:?}", p.as_str()));
+    }
+    Ok(registry::of_width(width)
+        .filter(|a| patterns.iter().any(|p| p.matches(a.name)))
+        .filter(|a| filter.matches(&[a.name, &a.qualified_name()]))
+        .collect())
+}
+
+// Ops to time each algorithm over before deciding how many a run under
+// --max-time can afford.
+const CALIBRATION_OPS: u64 = 1 << 18;
+
+// With --max-time, at least this many samples unless --samples says
+// otherwise, for stddev_ns.
+const BUDGETED_SAMPLES: usize = 3;
+
+// With --target-rse, the most samples of one algorithm before giving up.
+const MAX_SAMPLES: usize = 50;
+
+/// How long to time each algorithm for, and how many times.
+#[derive(clap::Args, Debug, Default)]
+pub struct Sampling {
+    /// Cut each algorithm's input down so that the whole run takes
+    /// about this long, e.g. 30s, with at least 3 samples
+    #[arg(long, value_parser = humantime::parse_duration)]
+    pub max_time: Option<Duration>,
+
+    /// Timed runs of each algorithm [default: the suite's repetitions]
+    #[arg(long)]
+    pub samples: Option<usize>,
+
+    /// With --max-time, never cut an input below this many ops
+    #[arg(long, value_name = "OPS", requires = "max_time")]
+    pub min_iters_per_sample: Option<u64>,
+
+    /// Keep sampling, up to 50 samples, until the relative standard
+    /// error of the mean is at most this, e.g. 0.01
+    #[arg(long, value_name = "RSE")]
+    pub target_rse: Option<f64>,
+}
+
+/// A preset for `bench --profile`: which widths, inputs and contexts
+/// to time the suite's algorithms over, how long for, and how to
+/// report it. Options given on the command line win over the profile.
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Profile {
+    /// u32 only, two random distributions, about 10 seconds in all
+    Quick,
+    /// Both widths and three random distributions in about a minute
+    Standard,
+    /// Both widths, four distributions and two contexts, each algorithm
+    /// in its own process and sampled to 0.5% RSE; JSON, for keeping
+    /// as a baseline or plotting
+    Publication,
+}
+
+impl Profile {
+    /// Sets the suite's widths, distributions, contexts and format, and
+    /// whatever of `sampling` is unset.
+    pub fn apply(self, suite: &mut Suite, sampling: &mut Sampling) {
+        use Distribution::*;
+        let (widths, distributions, contexts, max_time, samples, target_rse, format) = match self {
+            Profile::Quick => (
+                vec![Width::U32],
+                vec![Uniform, Digits],
+                vec![Context::Standalone],
+                10,
+                3,
+                None,
+                Format::Markdown,
+            ),
+            Profile::Standard => (
+                Width::ALL.to_vec(),
+                vec![Uniform, Digits, Mixed],
+                vec![Context::Standalone],
+                60,
+                5,
+                None,
+                Format::Markdown,
+            ),
+            Profile::Publication => (
+                Width::ALL.to_vec(),
+                vec![Uniform, Digits, Mixed, Sorted],
+                vec![Context::Standalone, Context::Format],
+                300,
+                10,
+                Some(0.005),
+                Format::Json,
+            ),
+        };
+        suite.widths = widths;
+        suite.distributions = distributions;
+        suite.contexts = contexts;
+        suite.format = Some(format);
+        sampling.max_time.get_or_insert(Duration::from_secs(max_time));
+        sampling.samples.get_or_insert(samples);
+        sampling.target_rse = sampling.target_rse.or(target_rse);
+    }
+
+    /// Whether the profile runs each algorithm in a process of its own.
+    pub fn isolates(self) -> bool {
+        self == Profile::Publication
+    }
+}
+
+/// The columns of a `benchmark` report, the `BenchResult` fields.
+pub const COLUMNS: [&str; 14] = [
+    "width",
+    "distribution",
+    "context",
+    "algorithm",
+    "ops",
+    "best_us",
+    "median_us",
+    "positions",
+    "ns_per_op",
+    "overhead_ns",
+    "stddev_ns",
+    "rse",
+    "target_met",
+    "joules_per_billion_ops",
+];
+
+/// Times the suite. With a `max_time`, each algorithm's input is cut
+/// down, after a short calibration run, to what lets the whole suite
+/// take about that long; a `target_rse` can take it past that.
+pub fn benchmark(suite: &Suite, filter: &Filter, sampling: &Sampling) -> Result<Report, String> {
+    let mut report = Report::new("benchmark", &COLUMNS);
+    let mut samples = sampling.samples.unwrap_or(suite.repetitions).max(1);
+    let mut run_us = None;
+    if let Some(max_time) = sampling.max_time {
+        if sampling.samples.is_none() {
+            samples = samples.max(BUDGETED_SAMPLES);
+        }
+        let mut runs = 0;
+        for &width in &suite.widths {
+            runs += select(suite, width, filter)?.len();
+        }
+        runs *= suite.distributions.len() * suite.contexts.len() * samples;
+        run_us = Some(max_time.as_micros() / runs.max(1) as u128);
+    }
+    let max_samples = match sampling.target_rse {
+        Some(_) => samples.max(MAX_SAMPLES),
+        None => samples,
+    };
+    let min_ops = sampling.min_iters_per_sample.unwrap_or(0);
+    let meter = Meter::open();
+    let timer_ns = timer_ns();
+    eprintln!("Calibration: Instant::now() takes {timer_ns:.1} ns");
+    for &width in &suite.widths {
+        let algorithms = select(suite, width, filter)?;
+        for &distribution in &suite.distributions {
+            let input = match (distribution, &suite.histogram) {
+                (Distribution::Histogram, Some(histogram)) => Input::from_histogram(width, histogram)?,
+                (Distribution::Histogram, None) => {
+                    return Err("the histogram distribution needs a histogram".into());
+                }
+                _ => Input::generate(width, distribution),
+            };
+            // Each algorithm's ns per op standalone and under pressure,
+            // for the note on what pressure costs it.
+            let mut standalone: HashMap<&str, f64> = HashMap::new();
+            let mut pressure: Vec<(&str, f64)> = Vec::new();
+            for &context in &suite.contexts {
+                // Cut to `PRESSURE_OPS` under pressure, which is slow
+                // enough per call that the whole input would take minutes.
+                let whole = match context {
+                    Context::Pressure => Cow::Owned(input.scaled(bench::PRESSURE_OPS.max(min_ops))),
+                    _ => Cow::Borrowed(&input),
+                };
+                let inputs: Vec<Cow<Input>> = algorithms
+                    .iter()
+                    .map(|algorithm| match run_us {
+                        Some(us) => Cow::Owned(calibrated(algorithm, &input, context, us, min_ops)),
+                        None => Cow::Borrowed(&*whole),
+                    })
+                    .collect();
+                // Inputs cut to the same length share one timing of the
+                // empty loop.
+                let mut empty: HashMap<u64, f64> = HashMap::new();
+                let overhead: Vec<f64> = (0..inputs.len())
+                    .map(|i| {
+                        let timer = 2.0 * timer_ns / inputs[i].ops() as f64;
+                        match context {
+                            Context::Standalone | Context::Pressure => {
+                                let ops = inputs[i].ops();
+                                timer
+                                    + *empty.entry(ops).or_insert_with(|| {
+                                        empty_loop_ns(width, context, &inputs[i])
+                                    })
+                            }
+                            _ => timer,
+                        }
+                    })
+                    .collect();
+                for (ops, ns) in &empty {
+                    eprintln!(
+                        "Calibration: the empty {} {} loop over {} ({ops} ops) takes {ns:.3} ns per op",
+                        context.name(),
+                        width.name(),
+                        distribution.name(),
+                    );
+                }
+                let ns = |i: usize, us: u128| {
+                    (us as f64 * 1000.0 / inputs[i].ops() as f64 - overhead[i]).max(0.0)
+                };
+                let mut times: Vec<Vec<u128>> = vec![Vec::new(); algorithms.len()];
+                let mut joules = vec![Vec::new(); algorithms.len()];
+                let mut positions = vec![Vec::new(); algorithms.len()];
+                let short = |i: usize, times: &[u128]| match sampling.target_rse {
+                    Some(target) => {
+                        let ns: Vec<f64> = times.iter().map(|&t| ns(i, t)).collect();
+                        rse(&ns).is_none_or(|rse| rse > target)
+                    }
+                    None => false,
+                };
+                for round in 0..max_samples {
+                    // A new order each round, so that drift over the run
+                    // (the CPU warming up, background load) doesn't
+                    // always favour the same algorithm.
+                    let mut order: Vec<usize> = (0..algorithms.len())
+                        .filter(|&i| round < samples || short(i, &times[i]))
+                        .collect();
+                    if order.is_empty() {
+                        break;
+                    }
+                    order.shuffle(&mut rand::rng());
+                    for (position, &i) in order.iter().enumerate() {
+                        let algorithm = algorithms[i];
+                        positions[i].push(position.to_string());
+                        let before = meter.as_ref().and_then(Meter::read);
+                        times[i].push(algorithm.time_in(&inputs[i], context));
+                        let after = meter.as_ref().and_then(Meter::read);
+                        if let (Some(meter), Some(before), Some(after)) = (&meter, before, after) {
+                            joules[i].push(meter.joules(&before, &after));
+                        }
+                    }
+                }
+                for (i, algorithm) in algorithms.iter().enumerate() {
+                    let ops = inputs[i].ops();
+                    let target_met = sampling.target_rse.map(|_| !short(i, &times[i]));
+                    if target_met == Some(false) {
+                        eprintln!(
+                            "{}/{} on {} ({}): target RSE not reached in {} samples",
+                            width.name(),
+                            algorithm.name,
+                            distribution.name(),
+                            context.name(),
+                            times[i].len()
+                        );
+                    }
+                    let ns_times: Vec<f64> = times[i].iter().map(|&t| ns(i, t)).collect();
+                    let times = &mut times[i];
+                    times.sort_unstable();
+                    let best = times[0];
+                    let median = times[times.len() / 2];
+                    // The least energy of any run, as best_us is the
+                    // least time.
+                    let least = joules[i].iter().copied().reduce(f64::min);
+                    match context {
+                        Context::Standalone => {
+                            standalone.insert(algorithm.name, ns(i, best));
+                        }
+                        Context::Pressure => pressure.push((algorithm.name, ns(i, best))),
+                        _ => {}
+                    }
+                    report.push(&BenchResult {
+                        width,
+                        distribution,
+                        context,
+                        algorithm: algorithm.name.to_string(),
+                        ops,
+                        best_us: best as u64,
+                        median_us: median as u64,
+                        positions: positions[i].join(","),
+                        ns_per_op: ns(i, best),
+                        overhead_ns: Some(overhead[i]),
+                        stddev_ns: stddev(&ns_times),
+                        rse: rse(&ns_times),
+                        target_met,
+                        joules_per_billion_ops: least.map(|j| j * 1e9 / ops as f64),
+                    });
+                }
+            }
+            // A table lookup should pay a load from memory under pressure
+            // and a table-free version next to nothing. Noted rather than
+            // checked: it is a timing, and as noisy as any.
+            let cold = if bench::flushes_tables() {
+                "with the tables flushed"
+            } else {
+                "with only the loads, the tables not flushed here"
+            };
+            for (name, ns) in pressure {
+                if let Some(alone) = standalone.get(name) {
+                    eprintln!(
+                        "Pressure: {}/{name} over {} takes {:+.1} ns per op from standalone, {cold}",
+                        width.name(),
+                        distribution.name(),
+                        ns - alone,
+                    );
+                }
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// What an `Instant::now()` call costs, in ns: the best of a few
+/// batches of them.
+pub fn timer_ns() -> f64 {
+    const CALLS: u32 = 10_000;
+    (0..5)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..CALLS {
+                std::hint::black_box(Instant::now());
+            }
+            start.elapsed().as_nanos() as f64 / CALLS as f64
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+// What the standalone or pressure loop costs per op with nothing in
+// it, as its best of three runs over `input`. The pressure loop needs
+// `f` to return a digit count's worth of bits; see `pressure_u32`.
+fn empty_loop_ns(width: Width, context: Context, input: &Input) -> f64 {
+    bench::prepare(context);
+    let best_us = (0..3)
+        .map(|_| {
+            let start = Instant::now();
+            match (width, context) {
+                (Width::U32, Context::Pressure) => bench::pressure_u32(input, |x| x & 63),
+                (Width::U64, Context::Pressure) => bench::pressure_u64(input, |x| x as u32 & 63),
+                (Width::U32, _) => bench::run_u32(input, |x| x),
+                (Width::U64, _) => bench::run_u64(input, |x| x as u32),
+            }
+            start.elapsed().as_micros()
+        })
+        .min()
+        .unwrap();
+    best_us as f64 * 1000.0 / input.ops() as f64
+}
+
+// `input` cut down to what `algorithm` gets through in about `us`
+// microseconds, going by a run over CALIBRATION_OPS of it, but to no
+// fewer than `min_ops`.
+fn calibrated(algorithm: &Algorithm, input: &Input, context: Context, us: u128, min_ops: u64) -> Input {
+    let sample = input.scaled(CALIBRATION_OPS);
+    let sample_us = algorithm.time_in(&sample, context).max(1);
+    let ops = us * sample.ops() as u128 / sample_us;
+    input.scaled((ops.min(u64::MAX as u128) as u64).max(min_ops))
+}
+
+// The standard error of the mean over the mean, or None for a single
+// value.
+fn rse(xs: &[f64]) -> Option<f64> {
+    let mean = xs.iter().sum::<f64>() / xs.len() as f64;
+    Some(stddev(xs)? / (xs.len() as f64).sqrt() / mean)
+}
+
+// The sample standard deviation, or None for a single value.
+fn stddev(xs: &[f64]) -> Option<f64> {
+    if xs.len() < 2 {
+        return None;
+    }
+    let n = xs.len() as f64;
+    let mean = xs.iter().sum::<f64>() / n;
+    let squares: f64 = xs.iter().map(|x| (x - mean) * (x - mean)).sum();
+    Some((squares / (n - 1.0)).sqrt())
+}
+
+/// `--fail-if-slower-than BASELINE:PERCENT`: a JSON `bench` report,
+/// as `-f json -o` writes it, and the slowdown to tolerate.
+#[derive(Clone, Debug)]
+pub struct Threshold {
+    pub baseline: PathBuf,
+    pub percent: f64,
+}
+
+pub fn parse_threshold(arg: &str) -> Result<Threshold, String> {
+    let (baseline, percent) = arg.rsplit_once(':').ok_or("expected BASELINE:PERCENT")?;
+    let percent: f64 = percent.parse().map_err(|e| format!("PERCENT: {e}"))?;
+    if percent.is_nan() || percent < 0.0 {
+        return Err("PERCENT must be at least 0".into());
+    }
+    Ok(Threshold {
+        baseline: baseline.into(),
+        percent,
+    })
+}
+
+/// The baseline of `threshold`: of a file of appended runs, the last.
+pub fn load_baseline(threshold: &Threshold) -> Result<BenchReport, String> {
+    let path = &threshold.baseline;
+    let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {e}", path.display()))?;
+    let last = text
+        .lines()
+        .rfind(|l| !l.trim().is_empty())
+        .ok_or_else(|| format!("{}: empty", path.display()))?;
+    serde_json::from_str(last).map_err(|e| format!("{}: {e}", path.display()))
+}
+
+/// A line for each result more than `percent` slower than the same
+/// algorithm, width, distribution and context in `baseline`. Results
+/// the baseline has no row for are left out.
+pub fn regressions(report: &Report, baseline: &BenchReport, percent: f64) -> Vec<String> {
+    let limit = 1.0 + percent / 100.0;
+    let mut regressions = Vec::new();
+    for r in report.records::<BenchResult>() {
+        let before = baseline.rows.iter().find(|b| {
+            (b.width, b.distribution, b.context, &b.algorithm)
+                == (r.width, r.distribution, r.context, &r.algorithm)
+        });
+        if let Some(before) = before.filter(|b| r.ns_per_op > b.ns_per_op * limit) {
+            regressions.push(format!(
+                "{}/{} on {} ({}): {:.3} ns per op, {:+.1}% from {:.3}",
+                r.width.name(),
+                r.algorithm,
+                r.distribution.name(),
+                r.context.name(),
+                r.ns_per_op,
+                (r.ns_per_op / before.ns_per_op - 1.0) * 100.0,
+                before.ns_per_op
+            ));
+        }
+    }
+    regressions
+}
@@ -0,0 +1,140 @@
+// The guess and threshold tables for any width and base, for `ilog
+// table`, as Rust, C or JSON source to paste into a port of the
+// technique. The numbers come from `ilog::tables`, whose const fns are
+// checked against the crate's own tables at compile time; here they are
+// only formatted, with the three lines that use them in a comment above.
+
+use std::fmt::Write;
+
+use ilog::tables;
+use serde::Serialize;
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum Language {
+    Rust,
+    /// C99 with stdint.h, and the GCC and Clang builtins for the leading zeros
+    C,
+    Json,
+}
+
+pub const WIDTHS: [u32; 5] = [8, 16, 32, 64, 128];
+
+#[derive(Serialize)]
+struct Tables {
+    width: u32,
+    base: u32,
+    /// The multiply guess `(ilog2 * multiplier) >> shift`, if there is one
+    multiplier: Option<u32>,
+    shift: Option<u32>,
+    /// Indexed by ilog2
+    guess: Vec<u32>,
+    /// Indexed by guess
+    thresholds: Vec<u128>,
+}
+
+pub fn emit(language: Language, width: u32, base: u32) -> Result<String, String> {
+    if !WIDTHS.contains(&width) {
+        return Err(format!("--width {width}: expected one of 8, 16, 32, 64 or 128"));
+    }
+    if base < 2 {
+        return Err(format!("--base {base}: expected 2 or more"));
+    }
+    let multiply = tables::multiplier(base, width);
+    let t = Tables {
+        width,
+        base,
+        multiplier: multiply.map(|(m, _)| m),
+        shift: multiply.map(|(_, s)| s),
+        guess: (0..width).map(|ilog2| tables::guess(base, ilog2)).collect(),
+        thresholds: (0..=tables::max_guess(base, width))
+            .map(|g| tables::threshold(base, width, g))
+            .collect(),
+    };
+    Ok(match language {
+        Language::Rust => rust(&t),
+        Language::C => c(&t),
+        Language::Json => serde_json::to_string_pretty(&t).unwrap() + "\n",
+    })
+}
+
+fn rust(t: &Tables) -> String {
+    let ty = format!("u{}", t.width);
+    let or_multiply = match (t.multiplier, t.shift) {
+        (Some(m), Some(s)) => format!(" // or (ilog2 * {m}) >> {s}"),
+        _ => String::new(),
+    };
+    let mut out = String::new();
+    let w = &mut out;
+    writeln!(w, "// ilog base {} of a nonzero {ty} x, from `{}`:", t.base, command(t)).unwrap();
+    writeln!(w, "//").unwrap();
+    writeln!(w, "//     let ilog2 = {} - x.leading_zeros();", t.width - 1).unwrap();
+    writeln!(w, "//     let guess = GUESS[ilog2 as usize] as u32;{or_multiply}").unwrap();
+    writeln!(w, "//     let ilog = guess + (x > THRESHOLDS[guess as usize]) as u32;").unwrap();
+    writeln!(w, "pub const GUESS: [u8; {}] = [", t.guess.len()).unwrap();
+    for row in rows(&t.guess) {
+        writeln!(w, "    {row},").unwrap();
+    }
+    writeln!(w, "];").unwrap();
+    writeln!(w, "pub const THRESHOLDS: [{ty}; {}] = [", t.thresholds.len()).unwrap();
+    for threshold in &t.thresholds {
+        writeln!(w, "    {threshold},").unwrap();
+    }
+    writeln!(w, "];").unwrap();
+    out
+}
+
+fn c(t: &Tables) -> String {
+    let (ty, ilog2) = match t.width {
+        8 | 16 | 32 => (format!("uint{}_t", t.width), "31 - __builtin_clz(x)".to_string()),
+        64 => ("uint64_t".to_string(), "63 - __builtin_clzll(x)".to_string()),
+        _ => (
+            "unsigned __int128".to_string(),
+            "x >> 64 ? 127 - __builtin_clzll(x >> 64) : 63 - __builtin_clzll(x)".to_string(),
+        ),
+    };
+    let or_multiply = match (t.multiplier, t.shift) {
+        (Some(m), Some(s)) => format!(" // or (ilog2 * {m}) >> {s}"),
+        _ => String::new(),
+    };
+    let mut out = String::new();
+    let w = &mut out;
+    writeln!(w, "#include <stdint.h>\n").unwrap();
+    writeln!(w, "/* ilog base {} of a nonzero {ty} x, from `{}`:", t.base, command(t)).unwrap();
+    writeln!(w, " *").unwrap();
+    writeln!(w, " *     unsigned ilog2 = {ilog2};").unwrap();
+    writeln!(w, " *     unsigned guess = GUESS[ilog2];{or_multiply}").unwrap();
+    writeln!(w, " *     unsigned ilog = guess + (x > THRESHOLDS[guess]);").unwrap();
+    writeln!(w, " */").unwrap();
+    writeln!(w, "static const uint8_t GUESS[{}] = {{", t.guess.len()).unwrap();
+    for row in rows(&t.guess) {
+        writeln!(w, "    {row},").unwrap();
+    }
+    writeln!(w, "}};").unwrap();
+    writeln!(w, "static const {ty} THRESHOLDS[{}] = {{", t.thresholds.len()).unwrap();
+    for &threshold in &t.thresholds {
+        writeln!(w, "    {},", c_literal(t.width, threshold)).unwrap();
+    }
+    writeln!(w, "}};").unwrap();
+    out
+}
+
+// C has no 128-bit literals, so those above 64 bits are built from
+// their halves.
+fn c_literal(width: u32, x: u128) -> String {
+    match width {
+        8 | 16 => x.to_string(),
+        32 => format!("{x}u"),
+        64 => format!("{x}ull"),
+        _ if x >> 64 == 0 => format!("(unsigned __int128){x}ull"),
+        _ => format!("((unsigned __int128){}ull << 64 | {}ull)", x >> 64, x as u64),
+    }
+}
+
+// The guesses 16 to a line.
+fn rows(guess: &[u32]) -> impl Iterator<Item = String> + '_ {
+    guess.chunks(16).map(|row| row.iter().map(u32::to_string).collect::<Vec<_>>().join(", "))
+}
+
+fn command(t: &Tables) -> String {
+    format!("ilog table --width {} --base {}", t.width, t.base)
+}
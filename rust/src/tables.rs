@@ -0,0 +1,113 @@
+//! The guess and threshold tables of a guess-and-correct ilog for any
+//! base and any width up to 128 bits, as const fns, for porting the
+//! technique to another language or base without working the tables
+//! out by hand. `ilog table` prints them as Rust, C or JSON.
+//!
+//! For nonzero `x` of `bits` bits with `ilog2 = bits - 1 - lz`:
+//!
+//! ```text
+//! guess = guess(base, ilog2)       // or (ilog2 * m) >> s
+//! ilog  = guess + (x > threshold(base, bits, guess)) as u32
+//! ```
+//!
+//! `guess` is the log of the power of two at or below `x`, which `x`
+//! can pass by at most one power of `base` as it is less than twice
+//! that. A multiply guess `(ilog2 * m) >> s` may be one less still where
+//! the exact one is at the top of its range, and the correction makes
+//! up for that too, so `multiplier` looks for the smallest shift that
+//! keeps the result within one below the largest ilog of the range.
+//!
+//! ```
+//! use ilog::tables::{guess, multiplier, threshold};
+//!
+//! assert_eq!(multiplier(10, 32), Some((9, 5)));
+//! let x = 12345u32;
+//! let g = guess(10, 31 - x.leading_zeros());
+//! assert_eq!(threshold(10, 32, g), 9999);
+//! assert_eq!(g + (x as u128 > threshold(10, 32, g)) as u32, 4);
+//! ```
+
+/// floor(log_base(x)), for `x` at least 1 and `base` at least 2.
+pub const fn ilog(base: u32, mut x: u128) -> u32 {
+    let mut n = 0;
+    while x >= base as u128 {
+        x /= base as u128;
+        n += 1;
+    }
+    n
+}
+
+/// The exact guess for `ilog2`: the log of `2^ilog2`.
+pub const fn guess(base: u32, ilog2: u32) -> u32 {
+    ilog(base, 1 << ilog2)
+}
+
+/// The guess for the widest value, `bits - 1`, and so the last index a
+/// threshold table needs.
+pub const fn max_guess(base: u32, bits: u32) -> u32 {
+    guess(base, bits - 1)
+}
+
+/// `base^(guess + 1) - 1`, the largest value with `guess + 1` digits,
+/// or the largest of `bits` bits where that doesn't fit.
+pub const fn threshold(base: u32, bits: u32, guess: u32) -> u128 {
+    let max = u128::MAX >> (128 - bits);
+    let mut power: u128 = 1;
+    let mut k = 0;
+    while k <= guess {
+        power = match power.checked_mul(base as u128) {
+            Some(p) if p - 1 <= max => p,
+            _ => return max,
+        };
+        k += 1;
+    }
+    power - 1
+}
+
+/// The multiply guess `(ilog2 * m) >> s` with the smallest shift `s`
+/// up to 16, and for it the smallest `m`, good for every `ilog2` of
+/// `bits` bits, as `Some((m, s))`; `None` if no such pair exists.
+pub const fn multiplier(base: u32, bits: u32) -> Option<(u32, u32)> {
+    let mut s = 0;
+    while s <= 16 {
+        let mut m = 0;
+        while m <= 1 << s {
+            if multiplies(base, bits, m, s) {
+                return Some((m, s));
+            }
+            m += 1;
+        }
+        s += 1;
+    }
+    None
+}
+
+// Whether `(ilog2 * m) >> s` is at most the exact guess and at most one
+// below the ilog of the top of the range, `2^(ilog2 + 1) - 1`, for
+// every `ilog2` of `bits` bits.
+const fn multiplies(base: u32, bits: u32, m: u32, s: u32) -> bool {
+    let mut ilog2 = 0;
+    while ilog2 < bits {
+        let g = (ilog2 * m) >> s;
+        let top = ilog(base, (1 << ilog2) + ((1 << ilog2) - 1));
+        if g > guess(base, ilog2) || g + 1 < top {
+            return false;
+        }
+        ilog2 += 1;
+    }
+    true
+}
+
+// The crate's own multiply guesses and tables are what these give.
+const _: () = {
+    assert!(matches!(multiplier(10, 32), Some((9, 5))));
+    assert!(matches!(multiplier(10, 64), Some((19, 6))));
+    let mut g = 0;
+    while g < 19 {
+        if g < 9 {
+            assert!(threshold(10, 32, g as u32) == crate::TEN_THRESHOLDS[g] as u128);
+        }
+        assert!(threshold(10, 64, g as u32) == crate::U64_THRESHOLDS[g] as u128);
+        g += 1;
+    }
+};
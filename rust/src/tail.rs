@@ -0,0 +1,111 @@
+// Tail latency for `ilog tail`. The benchmark's ns per op is a mean
+// over millions of calls, which hides the occasional slow one: a table
+// load that misses the cache, or a branchy variant mispredicting on a
+// run of unusual values. Here each algorithm is timed over many short
+// batches of values instead, and the report gives the median and the
+// 99th and 99.9th percentiles and the maximum of the batch times along
+// with their mean, as a soft-real-time caller would want to know them.
+//
+// Each batch is timed on its own with `Instant::now()`, whose cost is
+// measured and taken off as in `bench`. The algorithms take turns a
+// batch at a time, in a new order each round, so that a slow spell of
+// the machine's lands on all of them alike. The maximum takes in
+// whatever interrupted the process too, so it varies from run to run
+// far more than the percentiles do.
+
+use std::time::Instant;
+
+use ilog::bench::{Distribution, Input};
+use ilog::registry::{self, Width};
+use ilog::results::TailLatency;
+use rand::seq::SliceRandom;
+
+use crate::filter::Filter;
+use crate::report::Report;
+
+const TAIL: &str = "tail";
+
+pub fn report(
+    width: Width,
+    distribution: Distribution,
+    batch: usize,
+    batches: usize,
+    filter: &Filter,
+) -> Result<Report, String> {
+    if batch == 0 || batches == 0 {
+        return Err("--batch and --batches must be at least 1".into());
+    }
+    let chunks = match Input::generate(width, distribution) {
+        Input::U32 { values, .. } => values
+            .chunks_exact(batch)
+            .map(|c| Input::U32 { values: c.to_vec(), passes: 1 })
+            .collect::<Vec<_>>(),
+        Input::U64 { values, .. } => values
+            .chunks_exact(batch)
+            .map(|c| Input::U64 { values: c.to_vec(), passes: 1 })
+            .collect(),
+        Input::Sweep | Input::Permuted(_) | Input::Strided { .. } => {
+            return Err(format!("{} is not a random distribution", distribution.name()));
+        }
+    };
+    if chunks.is_empty() {
+        return Err(format!("--batch {batch} is more values than the distribution has"));
+    }
+    let algorithms: Vec<_> =
+        registry::of_width(width).filter(|a| crate::wanted(filter, TAIL, a)).collect();
+    let timer_ns = crate::suite::timer_ns();
+    eprintln!("Calibration: Instant::now() takes {timer_ns:.1} ns");
+
+    let mut times: Vec<Vec<f64>> = vec![Vec::with_capacity(batches); algorithms.len()];
+    let mut order: Vec<usize> = (0..algorithms.len()).collect();
+    let mut rng = rand::rng();
+    for round in 0..batches {
+        let input = &chunks[round % chunks.len()];
+        order.shuffle(&mut rng);
+        for &i in &order {
+            let run = algorithms[i].run;
+            let start = Instant::now();
+            run(input);
+            let ns = start.elapsed().as_nanos() as f64;
+            times[i].push(((ns - timer_ns) / batch as f64).max(0.0));
+        }
+    }
+
+    let mut report = Report::new(
+        "tail latency",
+        &[
+            "width",
+            "distribution",
+            "algorithm",
+            "batch",
+            "batches",
+            "mean_ns",
+            "p50_ns",
+            "p99_ns",
+            "p999_ns",
+            "max_ns",
+        ],
+    );
+    for (algorithm, mut times) in algorithms.iter().zip(times) {
+        times.sort_unstable_by(f64::total_cmp);
+        report.push(&TailLatency {
+            width,
+            distribution,
+            algorithm: algorithm.name.to_string(),
+            batch,
+            batches,
+            mean_ns: times.iter().sum::<f64>() / times.len() as f64,
+            p50_ns: percentile(&times, 0.5),
+            p99_ns: percentile(&times, 0.99),
+            p999_ns: percentile(&times, 0.999),
+            max_ns: times[times.len() - 1],
+        });
+    }
+    Ok(report)
+}
+
+// The nearest-rank `p` quantile of `sorted`.
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = (p * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
@@ -0,0 +1,179 @@
+// A dudect-style timing test for `ilog timing`: does an algorithm take
+// longer on some inputs than others? Each is timed over many short
+// batches of values, each batch either all the same fixed value, 1, or
+// random values from `--distribution`, the two classes shuffled
+// together so that whatever else the machine is doing lands on both
+// alike. Batches over the 90th percentile of all of them, mostly
+// interrupts, are dropped, and Welch's t-test compares the rest of the
+// two classes' times: past `--threshold` (dudect's own is 10), the
+// difference is too large to be chance.
+//
+// The `constant_time` algorithms should pass; the `ladder`s, which
+// branch on the input, are the control, and should fail with a large t
+// on any distribution that spreads the digit counts, showing that the
+// test can see a leak at all. Only a constant-time algorithm's leak
+// fails the run.
+//
+// Both classes' batches come from one pool, cycled through, so that
+// the fixed ones are no warmer in the cache than the random ones.
+
+use std::time::Instant;
+
+use ilog::bench::{Distribution, Input};
+use ilog::registry::{self, Algorithm, Width};
+use ilog::results::TimingLeak;
+use rand::prelude::*;
+
+use crate::filter::Filter;
+use crate::report::Report;
+
+const TIMING: &str = "timing";
+const CONSTANT_TIME: &str = "constant_time";
+const CONTROL: &str = "ladder";
+const FIXED: u64 = 1;
+// Batches in the pool.
+const POOL: usize = 1024;
+// The share of batches kept, fastest first.
+const KEEP: f64 = 0.9;
+
+pub fn report(
+    distribution: Distribution,
+    batch: usize,
+    batches: usize,
+    threshold: f64,
+    filter: &Filter,
+) -> Result<(Report, Vec<String>), String> {
+    if batch == 0 || batches < 4 {
+        return Err("--batch must be at least 1 and --batches at least 4".into());
+    }
+    let mut rng = rand::rng();
+    let mut report = Report::new(
+        "timing",
+        &[
+            "width",
+            "distribution",
+            "algorithm",
+            "role",
+            "fixed_batches",
+            "random_batches",
+            "fixed_ns",
+            "random_ns",
+            "t",
+            "leaks",
+        ],
+    );
+    let mut leaks = Vec::new();
+    for width in [Width::U32, Width::U64] {
+        let algorithms: Vec<&Algorithm> = registry::of_width(width)
+            .filter(|a| [CONSTANT_TIME, CONTROL].contains(&a.name))
+            .filter(|a| crate::wanted(filter, TIMING, a))
+            .collect();
+        if algorithms.is_empty() {
+            continue;
+        }
+        let pool = pool(width, distribution, batch, &mut rng)?;
+
+        // Each algorithm starts somewhere else in the pool, so that
+        // they don't all see the same class in a round.
+        let starts: Vec<usize> = algorithms.iter().map(|_| rng.random_range(0..POOL)).collect();
+        let mut times: Vec<Vec<(bool, f64)>> = vec![Vec::with_capacity(batches); algorithms.len()];
+        let mut order: Vec<usize> = (0..algorithms.len()).collect();
+        for round in 0..batches {
+            order.shuffle(&mut rng);
+            for &i in &order {
+                let (fixed, input) = &pool[(starts[i] + round) % POOL];
+                let run = algorithms[i].run;
+                let start = Instant::now();
+                run(input);
+                times[i].push((*fixed, start.elapsed().as_nanos() as f64));
+            }
+        }
+
+        for (algorithm, times) in algorithms.iter().zip(times) {
+            let mut sorted: Vec<f64> = times.iter().map(|&(_, ns)| ns).collect();
+            sorted.sort_unstable_by(f64::total_cmp);
+            let cutoff = sorted[((sorted.len() as f64 * KEEP) as usize).min(sorted.len() - 1)];
+            let class = |fixed: bool| -> Vec<f64> {
+                times
+                    .iter()
+                    .filter(|&&(f, ns)| f == fixed && ns <= cutoff)
+                    .map(|&(_, ns)| ns)
+                    .collect()
+            };
+            let (fixed, random) = (class(true), class(false));
+            let t = welch_t(&fixed, &random);
+            let leak = t.abs() > threshold;
+            let role = if algorithm.name == CONSTANT_TIME { "constant time" } else { "control" };
+            if leak && algorithm.name == CONSTANT_TIME {
+                leaks.push(format!("{} (t = {t:.1})", algorithm.qualified_name()));
+            }
+            report.push(&TimingLeak {
+                width,
+                distribution,
+                algorithm: algorithm.name.to_string(),
+                role: role.to_string(),
+                fixed_batches: fixed.len(),
+                random_batches: random.len(),
+                fixed_ns: mean(&fixed) / batch as f64,
+                random_ns: mean(&random) / batch as f64,
+                t,
+                leaks: leak,
+            });
+        }
+    }
+    Ok((report, leaks))
+}
+
+// `POOL` batches of `batch` values each, about half of them all
+// `FIXED`, each marked with whether it is.
+fn pool(
+    width: Width,
+    distribution: Distribution,
+    batch: usize,
+    rng: &mut impl Rng,
+) -> Result<Vec<(bool, Input)>, String> {
+    let not_random = || format!("{} is not a random distribution", distribution.name());
+    let values: Vec<u64> = match Input::generate(width, distribution) {
+        Input::U32 { values, .. } => values.into_iter().map(u64::from).collect(),
+        Input::U64 { values, .. } => values,
+        _ => return Err(not_random()),
+    };
+    if values.len() < batch * POOL {
+        return Err(format!("--batch {batch} is more values than the distribution has"));
+    }
+    Ok(values
+        .chunks_exact(batch)
+        .take(POOL)
+        .map(|chunk| {
+            let fixed = rng.random_bool(0.5);
+            let values = chunk.iter().map(|&x| if fixed { FIXED } else { x });
+            let input = match width {
+                Width::U32 => Input::U32 { values: values.map(|x| x as u32).collect(), passes: 1 },
+                Width::U64 => Input::U64 { values: values.collect(), passes: 1 },
+            };
+            (fixed, input)
+        })
+        .collect())
+}
+
+fn mean(xs: &[f64]) -> f64 {
+    xs.iter().sum::<f64>() / xs.len() as f64
+}
+
+// Welch's t statistic for the difference in means of two samples of
+// possibly unequal variance; 0 if either has fewer than two values.
+fn welch_t(a: &[f64], b: &[f64]) -> f64 {
+    if a.len() < 2 || b.len() < 2 {
+        return 0.0;
+    }
+    let variance = |xs: &[f64]| {
+        let m = mean(xs);
+        xs.iter().map(|x| (x - m) * (x - m)).sum::<f64>() / (xs.len() - 1) as f64
+    };
+    let se = (variance(a) / a.len() as f64 + variance(b) / b.len() as f64).sqrt();
+    if se == 0.0 {
+        0.0
+    } else {
+        (mean(a) - mean(b)) / se
+    }
+}
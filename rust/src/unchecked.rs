@@ -0,0 +1,43 @@
+//! The two unchecked hints the fast paths lean on, `unreachable_unchecked`
+//! and `get_unchecked`, routed through here so that the `validate-unsafe`
+//! feature can turn them into a panic and a checked index. Running the
+//! exhaustive tests that way,
+//!
+//! ```text
+//! cargo run --release --features validate-unsafe -- test
+//! ```
+//!
+//! checks every bound a `SAFETY` comment promises instead of trusting it:
+//! a wrong one panics at the first input that breaks it rather than
+//! quietly miscompiling. (It can't be combined with `no-panic`, whose
+//! wrappers must not panic.)
+
+/// `core::hint::unreachable_unchecked`, or under `validate-unsafe` a
+/// panic.
+///
+/// # Safety
+///
+/// As for `unreachable_unchecked`: it must never be reached.
+#[inline(always)]
+pub(crate) const unsafe fn unreachable() -> ! {
+    if cfg!(feature = "validate-unsafe") {
+        panic!("validate-unsafe: reached an unreachable_unchecked");
+    }
+    // SAFETY: passed on to the caller.
+    unsafe { core::hint::unreachable_unchecked() }
+}
+
+/// `slice[i]` without the bounds check, or under `validate-unsafe`
+/// with it.
+///
+/// # Safety
+///
+/// `i` must be below `slice.len()`.
+#[inline(always)]
+pub(crate) unsafe fn get<T: Copy>(slice: &[T], i: usize) -> T {
+    if cfg!(feature = "validate-unsafe") {
+        return slice[i];
+    }
+    // SAFETY: passed on to the caller.
+    unsafe { *slice.get_unchecked(i) }
+}
@@ -0,0 +1,63 @@
+// A quick check of every registered algorithm, for `ilog verify`: a
+// broken edit to a guess or a table almost always shows at a power of
+// ten or of two, so checking those (give or take two), the ends of the
+// range and a few thousand seeded random values takes well under a
+// second and catches most of what the exhaustive `ilog test` would,
+// before a benchmark session is spent timing a wrong answer.
+
+use ilog::registry::{self, Width};
+use ilog::results::CheckResult;
+use rand::prelude::*;
+use rand::rngs::StdRng;
+use rayon::prelude::*;
+
+use crate::check::check;
+use crate::filter::Filter;
+
+const VERIFY: &str = "verify";
+
+// Random values per width, on top of the boundaries.
+const SAMPLE: usize = 4096;
+
+pub fn verify(filter: &Filter, seed: u64) -> Vec<CheckResult> {
+    let mut checks = Vec::new();
+    for width in Width::ALL {
+        let max = match width {
+            Width::U32 => u32::MAX as u64,
+            Width::U64 => u64::MAX,
+        };
+        let values = values(max, seed);
+        let range = format!("10^k ± 2, 2^k ± 2, 1, max, {SAMPLE} random (seed {seed})");
+        for a in registry::of_width(width) {
+            if !crate::wanted(filter, VERIFY, a) {
+                continue;
+            }
+            checks.push(check(
+                VERIFY,
+                &a.qualified_name(),
+                &range,
+                values.par_iter().copied(),
+                a.eval,
+                |x| x.ilog10(),
+            ));
+        }
+    }
+    checks
+}
+
+// The nonzero values up to `max` within two of a power of ten or of two,
+// 1 and 2 and the top three, and a seeded sample.
+fn values(max: u64, seed: u64) -> Vec<u64> {
+    let powers = (0..20).map(|k| 10u64.pow(k)).chain((0..64).map(|k| 1u64 << k));
+    let mut values: Vec<u64> = powers
+        .flat_map(|p| (-2..=2).map(move |d: i64| p.checked_add_signed(d)))
+        .flatten()
+        .chain([1, 2, max - 2, max - 1, max])
+        .filter(|&x| x != 0 && x <= max)
+        .collect();
+    let mut rng = StdRng::seed_from_u64(seed);
+    values.extend((0..SAMPLE).map(|_| rng.random_range(1..=max)));
+    values.sort_unstable();
+    values.dedup();
+    values
+}
@@ -0,0 +1,122 @@
+//! JavaScript bindings, enabled by the `wasm` feature.
+//!
+//! To build a package in `pkg/` for Node (or `--target web`):
+//!
+//! ```text
+//! cargo rustc --release --lib --crate-type cdylib \
+//!     --target wasm32-unknown-unknown --features wasm
+//! wasm-bindgen --target nodejs --out-dir pkg \
+//!     target/wasm32-unknown-unknown/release/ilog.wasm
+//! ```
+//!
+//! `js/bench.mjs` then times the registered implementations under
+//! Node. JavaScript passes u64s as BigInts and the slices as
+//! `Uint32Array` / `BigUint64Array`.
+
+use wasm_bindgen::prelude::*;
+
+use crate::bench::Input;
+use crate::registry::{self, Width};
+
+/// floor(log10(x)); throws for 0.
+#[wasm_bindgen]
+pub fn ilog10_u32(x: u32) -> Result<u32, JsError> {
+    if x == 0 {
+        return Err(JsError::new("ilog10 of 0 is undefined"));
+    }
+    Ok(crate::ilog10_mul(x))
+}
+
+/// floor(log10(x)); throws for 0.
+#[wasm_bindgen]
+pub fn ilog10_u64(x: u64) -> Result<u32, JsError> {
+    if x == 0 {
+        return Err(JsError::new("ilog10 of 0 is undefined"));
+    }
+    Ok(crate::ilog10_u64_mul(x))
+}
+
+/// Number of decimal digits in x, counting 0 as one digit.
+#[wasm_bindgen]
+pub fn digits_u32(x: u32) -> u32 {
+    crate::digits_u32(x)
+}
+
+/// Number of decimal digits in x, counting 0 as one digit.
+#[wasm_bindgen]
+pub fn digits_u64(x: u64) -> u32 {
+    crate::digits_u64(x)
+}
+
+/// Digit counts of every value in a `Uint32Array`.
+#[wasm_bindgen]
+pub fn digits_u32_array(values: &[u32]) -> Vec<u32> {
+    values.iter().map(|&x| crate::digits_u32(x)).collect()
+}
+
+/// Digit counts of every value in a `BigUint64Array`.
+#[wasm_bindgen]
+pub fn digits_u64_array(values: &[u64]) -> Vec<u32> {
+    values.iter().map(|&x| crate::digits_u64(x)).collect()
+}
+
+/// x in decimal.
+#[wasm_bindgen]
+pub fn format_u64(x: u64) -> String {
+    let mut buf = [0u8; 20];
+    let len = crate::write_u64(x, &mut buf);
+    // Only ASCII digits were written.
+    String::from_utf8_lossy(&buf[..len]).into_owned()
+}
+
+/// Names of the registered implementations for "u32" or "u64".
+#[wasm_bindgen]
+pub fn algorithms(width: &str) -> Result<Vec<String>, JsError> {
+    Ok(registry::of_width(parse_width(width)?)
+        .map(|a| a.name.to_string())
+        .collect())
+}
+
+/// Runs the named u32 implementation `passes` times over `values`, for
+/// timing from JavaScript.
+#[wasm_bindgen]
+pub fn run_u32(name: &str, values: &[u32], passes: usize) -> Result<(), JsError> {
+    if values.contains(&0) {
+        return Err(JsError::new("values must be nonzero"));
+    }
+    let input = Input::U32 {
+        values: values.to_vec(),
+        passes,
+    };
+    run(Width::U32, name, &input)
+}
+
+/// Runs the named u64 implementation `passes` times over `values`, for
+/// timing from JavaScript.
+#[wasm_bindgen]
+pub fn run_u64(name: &str, values: &[u64], passes: usize) -> Result<(), JsError> {
+    if values.contains(&0) {
+        return Err(JsError::new("values must be nonzero"));
+    }
+    let input = Input::U64 {
+        values: values.to_vec(),
+        passes,
+    };
+    run(Width::U64, name, &input)
+}
+
+fn run(width: Width, name: &str, input: &Input) -> Result<(), JsError> {
+    let algorithm = registry::of_width(width)
+        .find(|a| a.name == name)
+        .ok_or_else(|| JsError::new(&format!("unknown algorithm {name:?}")))?;
+    (algorithm.run)(input);
+    Ok(())
+}
+
+fn parse_width(width: &str) -> Result<Width, JsError> {
+    match width {
+        "u32" => Ok(Width::U32),
+        "u64" => Ok(Width::U64),
+        _ => Err(JsError::new(&format!("unknown width {width:?}"))),
+    }
+}
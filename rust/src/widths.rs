@@ -0,0 +1,243 @@
+// `bench --compare-widths`: the same algorithms at every integer width
+// from u8 to u128, one row each, for a caller with a u16 or a u128 in
+// hand who would otherwise have to guess from the u32 and u64 runs.
+//
+// The crate's algorithms come at u32 and u64, and the other widths
+// reach them as `fast_ilog10` does: a u8 or u16 is widened to the u32
+// version, and a u128 above u64::MAX is divided by 10^20 for the u64
+// version. `stdlib` is each type's own ilog10 and `fast_ilog10` the
+// generic entry point itself. An algorithm with no version for a
+// width, such as `popcount` for u64, leaves that cell empty.
+//
+// Each width draws its own values from the distribution, up to its
+// MAX, so that uniform u8s mostly have three digits and uniform u128s
+// 39; `digits`, every digit count as often as any other, is the one
+// to compare widths by, and the default. Each algorithm takes turns
+// with the others at each width, in a new order each round, and the
+// best round of each counts.
+
+use std::hint::black_box;
+use std::time::Instant;
+
+use ilog::bench::Distribution;
+use ilog::fast_ilog10;
+use ilog::prelude::*;
+use ilog::results::WidthComparison;
+use rand::prelude::*;
+
+use crate::filter::Filter;
+use crate::report::Report;
+
+// Too many values for the branch predictor to learn, few enough that
+// the u128s stay in L2.
+const VALUES: usize = 1 << 16;
+const PASSES: usize = 64;
+const WIDTHS: usize = 5;
+
+// Each width's values, all nonzero.
+struct Values {
+    u8: Vec<u8>,
+    u16: Vec<u16>,
+    u32: Vec<u32>,
+    u64: Vec<u64>,
+    u128: Vec<u128>,
+}
+
+// One timed loop over one width's values.
+type Run = fn(&Values);
+
+// An algorithm's timed loop at each width from u8 to u128, where it has
+// a version for the width.
+struct Strategy {
+    name: &'static str,
+    runs: [Option<Run>; WIDTHS],
+}
+
+macro_rules! strategy {
+    // The same expression at every width.
+    ($name:expr, |$x:ident| $body:expr) => {
+        Strategy {
+            name: $name,
+            runs: [
+                Some(|v| run(&v.u8, |$x: u8| $body)),
+                Some(|v| run(&v.u16, |$x: u16| $body)),
+                Some(|v| run(&v.u32, |$x: u32| $body)),
+                Some(|v| run(&v.u64, |$x: u64| $body)),
+                Some(|v| run(&v.u128, |$x: u128| $body)),
+            ],
+        }
+    };
+    ($name:expr, u32: $f:expr, u64: $g:expr) => {
+        Strategy {
+            name: $name,
+            runs: [
+                Some(|v| run(&v.u8, |x| $f(x as u32))),
+                Some(|v| run(&v.u16, |x| $f(x as u32))),
+                Some(|v| run(&v.u32, $f)),
+                Some(|v| run(&v.u64, $g)),
+                Some(|v| run(&v.u128, |x| split(x, $g))),
+            ],
+        }
+    };
+    ($name:expr, u32: $f:expr) => {
+        Strategy {
+            name: $name,
+            runs: [
+                Some(|v| run(&v.u8, |x| $f(x as u32))),
+                Some(|v| run(&v.u16, |x| $f(x as u32))),
+                Some(|v| run(&v.u32, $f)),
+                None,
+                None,
+            ],
+        }
+    };
+    ($name:expr, u64: $g:expr) => {
+        Strategy {
+            name: $name,
+            runs: [
+                None,
+                None,
+                None,
+                Some(|v| run(&v.u64, $g)),
+                Some(|v| run(&v.u128, |x| split(x, $g))),
+            ],
+        }
+    };
+}
+
+// By their names in the registry.
+const STRATEGIES: &[Strategy] = &[
+    strategy!("stdlib", |x| x.ilog10()),
+    strategy!("fast_ilog10", |x| fast_ilog10(x)),
+    // SAFETY: the values are nonzero.
+    strategy!("popcount", u32: |x| unsafe { ilog10_u32_popcount(x) }),
+    strategy!("mul", u32: ilog10_u32_mul, u64: ilog10_u64_mul),
+    strategy!("mul_alt", u32: ilog10_u32_mul_alt),
+    strategy!("table", u32: ilog10_u32_table),
+    strategy!("mul_pow", u32: ilog10_u32_mul_pow, u64: ilog10_u64_mul_pow),
+    strategy!("mul_loop", u32: ilog10_u32_mul_loop, u64: ilog10_u64_mul_loop),
+    strategy!("mul_pow4", u32: ilog10_u32_mul_pow4, u64: ilog10_u64_mul_pow4),
+    strategy!("mul_packed", u32: ilog10_u32_mul_packed, u64: ilog10_u64_mul_packed),
+    strategy!("mul20", u64: ilog10_u64_mul20),
+    strategy!("swar", u32: ilog10_u32_swar, u64: ilog10_u64_swar),
+    strategy!("debruijn", u32: ilog10_u32_debruijn, u64: ilog10_u64_debruijn),
+    strategy!("bytes", u32: ilog10_u32_bytes, u64: ilog10_u64_bytes),
+    strategy!("constant_time", u32: ilog10_u32_constant_time, u64: ilog10_u64_constant_time),
+    strategy!("f32", u32: ilog10_u32_f32),
+    strategy!("f64", u32: ilog10_u32_f64, u64: ilog10_u64_f64),
+    strategy!("ladder", u32: ilog10_u32_ladder, u64: ilog10_u64_ladder),
+    strategy!("split", u64: ilog10_u64_split),
+];
+
+// Calls `f` on every value, `PASSES` times over.
+#[inline(always)]
+fn run<T: Copy>(values: &[T], f: impl Fn(T) -> u32) {
+    for _ in 0..PASSES {
+        for &x in values {
+            black_box(f(x));
+        }
+    }
+}
+
+// ilog10 of a u128 with a u64 ilog10, as in `fast_ilog10`.
+#[inline(always)]
+fn split(x: u128, f: impl Fn(u64) -> u32) -> u32 {
+    const E20: u128 = 100_000_000_000_000_000_000;
+    if x <= u64::MAX as u128 {
+        f(x as u64)
+    } else if x < E20 {
+        19
+    } else {
+        20 + f((x / E20) as u64)
+    }
+}
+
+/// The distributions that can be drawn at every width.
+pub const DISTRIBUTIONS: [Distribution; 3] =
+    [Distribution::Uniform, Distribution::Digits, Distribution::Sorted];
+
+pub fn report(
+    distributions: &[Distribution],
+    samples: usize,
+    filter: &Filter,
+) -> Result<Report, String> {
+    if samples == 0 {
+        return Err("--samples must be at least 1".into());
+    }
+    let strategies: Vec<&Strategy> =
+        STRATEGIES.iter().filter(|s| filter.matches(&[s.name])).collect();
+    if strategies.is_empty() {
+        return Err("--filter matches none of the algorithms".into());
+    }
+    let mut rng = rand::rng();
+    let mut report = Report::new(
+        "compare widths",
+        &["distribution", "algorithm", "u8_ns", "u16_ns", "u32_ns", "u64_ns", "u128_ns"],
+    );
+    for &distribution in distributions {
+        if !DISTRIBUTIONS.contains(&distribution) {
+            return Err(format!("{} can't be drawn at every width", distribution.name()));
+        }
+        let values = Values::generate(distribution, &mut rng);
+        // The best time of each strategy at each width, by width.
+        let mut best = [(); WIDTHS].map(|_| vec![f64::INFINITY; strategies.len()]);
+        for (width, best) in best.iter_mut().enumerate() {
+            let mut order: Vec<(usize, Run)> = strategies
+                .iter()
+                .enumerate()
+                .filter_map(|(i, s)| Some((i, s.runs[width]?)))
+                .collect();
+            for _ in 0..samples {
+                order.shuffle(&mut rng);
+                for &(i, run) in &order {
+                    let start = Instant::now();
+                    run(&values);
+                    best[i] = best[i].min(start.elapsed().as_nanos() as f64);
+                }
+            }
+        }
+        let ops = (VALUES * PASSES) as f64;
+        for (i, strategy) in strategies.iter().enumerate() {
+            let ns = |width: usize| strategy.runs[width].map(|_| best[width][i] / ops);
+            report.push(&WidthComparison {
+                distribution,
+                algorithm: strategy.name.to_string(),
+                u8_ns: ns(0),
+                u16_ns: ns(1),
+                u32_ns: ns(2),
+                u64_ns: ns(3),
+                u128_ns: ns(4),
+            });
+        }
+    }
+    Ok(report)
+}
+
+impl Values {
+    fn generate(distribution: Distribution, rng: &mut impl Rng) -> Values {
+        let mut draw = |max: u128| -> Vec<u128> {
+            let mut values: Vec<u128> = (0..VALUES)
+                .map(|_| match distribution {
+                    Distribution::Uniform => rng.random_range(1..=max),
+                    _ => {
+                        let digits = rng.random_range(1..=max.ilog10() + 1);
+                        let lo = 10u128.pow(digits - 1);
+                        let hi = 10u128.checked_pow(digits).map_or(max, |p| (p - 1).min(max));
+                        rng.random_range(lo..=hi)
+                    }
+                })
+                .collect();
+            if distribution == Distribution::Sorted {
+                values.sort_unstable();
+            }
+            values
+        };
+        Values {
+            u8: draw(u8::MAX as u128).into_iter().map(|x| x as u8).collect(),
+            u16: draw(u16::MAX as u128).into_iter().map(|x| x as u16).collect(),
+            u32: draw(u32::MAX as u128).into_iter().map(|x| x as u32).collect(),
+            u64: draw(u64::MAX as u128).into_iter().map(|x| x as u64).collect(),
+            u128: draw(u128::MAX).into_iter().collect(),
+        }
+    }
+}
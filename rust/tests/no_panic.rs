@@ -0,0 +1,156 @@
+// Links every `ilog::no_panic` function into one program, which only
+// succeeds if none of them can panic; run it with
+// `cargo test --release --features no-panic --test no_panic`. The
+// values are checked too, against the standard library.
+
+use std::hint::black_box;
+use std::num::{NonZeroU32, NonZeroU64};
+
+use ilog::no_panic as np;
+
+#[test]
+fn no_panic_functions_link_and_agree() {
+    let u32s = (0..32)
+        .flat_map(|k| [1u32 << k, (1u32 << k) | ((1u32 << k) - 1)])
+        .chain((0..10).flat_map(|k| [10u32.pow(k), 10u32.pow(k) - 1]));
+    for x in u32s.filter_map(NonZeroU32::new) {
+        let nz = black_box(x);
+        let x = nz.get();
+        let want = x.ilog10();
+        for f in [
+            np::ilog10_mul,
+            np::ilog10_mul_alt,
+            np::ilog10_mul_pow,
+            np::ilog10_mul_loop,
+            np::ilog10_mul_pow4,
+            np::ilog10_mul_packed,
+            np::log10_table_table,
+            np::ilog10_swar,
+            np::ilog10_u32,
+        ] {
+            assert_eq!(f(nz), want, "{x}");
+        }
+        for f in [
+            np::ilog10_ladder,
+            np::ilog10_debruijn,
+            np::ilog10_bytes,
+            np::ilog10_f32,
+            np::ilog10_f64,
+            np::ilog10_constant_time,
+        ] {
+            assert_eq!(f(x), want, "{x}");
+        }
+        assert_eq!(np::ilog2_and_ilog10_u32(nz), (x.ilog2(), want), "{x}");
+        assert_eq!(np::ilog10_with_flag(nz), (want, want > (x.ilog2() * 9) >> 5), "{x}");
+        assert_eq!(np::ilog1000_u32(nz), want / 3, "{x}");
+        assert_eq!(np::si_scale_u32(x), (x / 1000u32.pow(want / 3), want / 3), "{x}");
+        assert_eq!(np::digits_u32(x), want + 1, "{x}");
+        assert_eq!(np::digits_and_pow10_u32(x), (want + 1, 10u32.pow(want)), "{x}");
+        assert_eq!(np::leading_digit_u32(x), x / 10u32.pow(want), "{x}");
+        assert_eq!(np::leading_n_digits_u32(x, 3), x / 10u32.pow(want.saturating_sub(2)), "{x}");
+        assert_eq!(np::digits_u32x4([x; 4]), [want + 1; 4], "{x}");
+        assert_eq!(np::checked_ilog10_u32(x), Some(want), "{x}");
+    }
+
+    let u64s = (0..64)
+        .flat_map(|k| [1u64 << k, (1u64 << k) | ((1u64 << k) - 1)])
+        .chain((0..20).flat_map(|k| [10u64.pow(k), 10u64.pow(k) - 1]));
+    for x in u64s.filter_map(NonZeroU64::new) {
+        let nz = black_box(x);
+        let x = nz.get();
+        let want = x.ilog10();
+        for f in [
+            np::ilog10_u64_mul,
+            np::ilog10_u64_mul_pow,
+            np::ilog10_u64_mul_loop,
+            np::ilog10_u64_mul_pow4,
+            np::ilog10_u64_mul_packed,
+            np::ilog10_u64_mul20,
+            np::ilog10_u64_swar,
+            np::ilog10_u64_split,
+        ] {
+            assert_eq!(f(nz), want, "{x}");
+        }
+        for f in [
+            np::ilog10_u64_ladder,
+            np::ilog10_u64_debruijn,
+            np::ilog10_u64_bytes,
+            np::ilog10_u64_f64,
+            np::ilog10_u64_constant_time,
+        ] {
+            assert_eq!(f(x), want, "{x}");
+        }
+        assert_eq!(np::ilog2_and_ilog10_u64(nz), (x.ilog2(), want), "{x}");
+        assert_eq!(np::ilog10_u64_with_flag(nz), (want, want > (x.ilog2() * 19) >> 6), "{x}");
+        assert_eq!(np::ilog1000_u64(nz), want / 3, "{x}");
+        let ceil = want + (x != 10u64.pow(want)) as u32;
+        let nearest = want + (want < 19 && x as u128 * x as u128 > 10u128.pow(2 * want + 1)) as u32;
+        assert_eq!(np::log10_with_rounding(nz, ilog::Log10Rounding::Floor), want, "{x}");
+        assert_eq!(np::log10_with_rounding(nz, ilog::Log10Rounding::Ceil), ceil, "{x}");
+        assert_eq!(np::log10_with_rounding(nz, ilog::Log10Rounding::Nearest), nearest, "{x}");
+        assert_eq!(np::nearest_power_of_ten(x), 10u64.pow(nearest), "{x}");
+        assert_eq!(np::neg_ilog10_of_reciprocal(nz), -(ceil as i32), "{x}");
+        assert_eq!(np::ilog10_ratio(nz, nz), 0, "{x}");
+        assert_eq!(np::ilog10_ratio(NonZeroU64::MIN, nz), -(ceil as i32), "{x}");
+        assert_eq!(np::decade_bucket(x), want, "{x}");
+        let square = x as u128 * x as u128;
+        assert_eq!(np::ilog10_product(nz, nz), square.ilog10(), "{x}");
+        assert_eq!(np::digits_u128(square), square.ilog10() + 1, "{x}");
+        assert_eq!(np::si_scale_u64(x), (x / 1000u64.pow(want / 3), want / 3), "{x}");
+        let (m, e) = np::to_decimal_sci(x);
+        let zeros = x / m;
+        assert!(!m.is_multiple_of(10) && m * zeros == x && 10u64.pow(zeros.ilog10()) == zeros, "{x}");
+        assert_eq!(e, want, "{x}");
+        assert_eq!(np::digits_u64(x), want + 1, "{x}");
+        assert_eq!(np::digits_and_pow10_u64(x), (want + 1, 10u64.pow(want)), "{x}");
+        assert_eq!(np::leading_digit_u64(x) as u64, x / 10u64.pow(want), "{x}");
+        assert_eq!(np::leading_n_digits_u64(x, 3), x / 10u64.pow(want.saturating_sub(2)), "{x}");
+        let cut = 10u128.pow(want);
+        let rounded = (x as u128 + cut / 2) / cut * cut;
+        assert_eq!(
+            np::checked_round_sig_figs(x, 1, ilog::Rounding::HalfUp),
+            u64::try_from(rounded).ok(),
+            "{x}"
+        );
+        assert_eq!(np::digits_u64x2([x; 2]), [want + 1; 2], "{x}");
+        assert_eq!(np::checked_ilog10_u64(x), Some(want), "{x}");
+    }
+
+    assert_eq!(np::digits_u32(black_box(0)), 1);
+    assert_eq!(np::digits_u64(black_box(0)), 1);
+    assert_eq!(np::digits_u128(black_box(0)), 1);
+    assert_eq!(np::digits_u128(black_box(u128::MAX)), 39);
+    assert_eq!(np::digits_and_pow10_u32(black_box(0)), (1, 1));
+    assert_eq!(np::digits_and_pow10_u64(black_box(0)), (1, 1));
+    assert_eq!(np::leading_digit_u32(black_box(0)), 0);
+    assert_eq!(np::leading_digit_u64(black_box(0)), 0);
+    assert_eq!(np::si_scale_u32(black_box(0)), (0, 0));
+    assert_eq!(np::si_scale_u64(black_box(0)), (0, 0));
+    assert_eq!(np::nearest_power_of_ten(black_box(0)), 1);
+    assert_eq!(np::decade_bucket(black_box(0)), 0);
+    assert_eq!(np::to_decimal_sci(black_box(0)), (0, 0));
+    let prefixed = [
+        (0, Some(1)),
+        (9, Some(11)),
+        (99_997, Some(100_003)),
+        (u64::MAX - 20, Some(u64::MAX)),
+        (u64::MAX - 19, None),
+    ];
+    for (payload, want) in prefixed {
+        assert_eq!(np::checked_encoded_len_with_prefix(black_box(payload)), want, "{payload}");
+    }
+    let netstrings = [
+        (0, Some(3)),
+        (12, Some(16)),
+        (u64::MAX - 22, Some(u64::MAX)),
+        (u64::MAX - 21, None),
+    ];
+    for (payload, want) in netstrings {
+        assert_eq!(np::checked_netstring_len(black_box(payload)), want, "{payload}");
+    }
+    let values = [0, 9, 10, u64::MAX, 123];
+    assert_eq!(np::total_decimal_len(black_box(&values), 1), "0,9,10,18446744073709551615,123".len());
+    assert_eq!(np::total_decimal_len(black_box(&[]), 1), 0);
+    assert_eq!(np::checked_ilog10_u32(black_box(0)), None);
+    assert_eq!(np::checked_ilog10_u64(black_box(0)), None);
+}